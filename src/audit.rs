@@ -0,0 +1,498 @@
+//! Compare the built-in effect, trigger and link tables against the documentation logs that the
+//! game writes with the `script_docs` console command, for the `--audit-tables` option.
+//! The lines in the report are in the same format as the tables, so that updating the tables
+//! after a patch is mostly a matter of copying them over.
+
+use anyhow::{bail, Result};
+use fnv::{FnvHashMap, FnvHashSet};
+use std::fmt::Write;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::errors::{pause_logging, resume_logging};
+use crate::everything::Everything;
+use crate::fileset::FileKind;
+use crate::scopes::{
+    scope_from_snake_case, scope_to_scope, scope_value, Scopes, SCOPE_FROM_PREFIX, SCOPE_ITERATOR,
+    SCOPE_TO_SCOPE, SCOPE_VALUE,
+};
+use crate::tables::effects::{scope_effect, SCOPE_EFFECT, SCOPE_TRAVEL_EFFECT};
+use crate::tables::triggers::{
+    scope_trigger_target, SCOPE_TRAVEL_TRIGGER_ITEM, SCOPE_TRIGGER_BOOL, SCOPE_TRIGGER_ITEM,
+    SCOPE_TRIGGER_TARGET,
+};
+use crate::token::{Loc, Token};
+use crate::trigger::is_special_trigger;
+
+/// Triggers that `validate_trigger` handles itself before it looks at the tables
+const STRUCTURAL_TRIGGERS: &[&str] = &[
+    "trigger_if",
+    "trigger_else_if",
+    "trigger_else",
+    "calc_true_if",
+    "weighted_calc_true_if",
+    "custom_description",
+    "custom_tooltip",
+];
+
+/// The prefixes of the effects and triggers that iterate over the lists in `SCOPE_ITERATOR`
+const EFFECT_ITERATORS: &[&str] = &["every_", "random_", "ordered_"];
+const TRIGGER_ITERATORS: &[&str] = &["any_"];
+
+/// One documented effect, trigger or link from a game log
+#[derive(Debug, Default)]
+struct LogEntry {
+    name: String,
+    /// The scopes it can be used in, from `Supported Scopes` or `Input Scopes`
+    input: Option<Scopes>,
+    /// The scope a link leads to
+    output: Option<Scopes>,
+    /// The scopes a trigger compares to
+    targets: Option<Scopes>,
+    /// Whether a link needs an argument after a colon, like `title:`
+    requires_data: bool,
+    /// Whether a trigger compares a value, like `age > 16`
+    comparison: bool,
+}
+
+/// Parse the scope names in a log line, like `character, landed_title`.
+/// Names that aren't known scopes are added to `unknown`.
+fn parse_scopes(s: &str, unknown: &mut FnvHashSet<String>) -> Option<Scopes> {
+    let mut scopes = Scopes::empty();
+    for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        match scope_from_snake_case(name) {
+            Some(scope) => scopes |= scope,
+            None => {
+                unknown.insert(name.to_string());
+            }
+        }
+    }
+    if scopes.is_empty() {
+        None
+    } else {
+        Some(scopes)
+    }
+}
+
+/// Parse a game log. The entries are separated by lines of dashes, and each one starts with
+/// a line like `add_gold - Adds gold to a character`.
+fn parse_log(text: &str, unknown: &mut FnvHashSet<String>) -> Vec<LogEntry> {
+    let mut entries = Vec::new();
+    let mut entry: Option<LogEntry> = None;
+    for line in text.lines().map(str::trim) {
+        if line.len() >= 3 && line.chars().all(|c| c == '-') {
+            entries.extend(entry.take());
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        match entry {
+            None => {
+                let name = line.split(" - ").next().unwrap_or(line).trim();
+                // Skip headers like `Effect Documentation:`
+                if !name.is_empty() && !name.contains(' ') && !name.ends_with(':') {
+                    entry = Some(LogEntry {
+                        name: name.to_string(),
+                        ..LogEntry::default()
+                    });
+                }
+            }
+            Some(ref mut entry) => {
+                if let Some(s) = line
+                    .strip_prefix("Supported Scopes:")
+                    .or_else(|| line.strip_prefix("Input Scopes:"))
+                {
+                    entry.input = parse_scopes(s, unknown);
+                } else if let Some(s) = line.strip_prefix("Output Scopes:") {
+                    entry.output = parse_scopes(s, unknown);
+                } else if let Some(s) = line.strip_prefix("Supported Targets:") {
+                    entry.targets = parse_scopes(s, unknown);
+                } else if let Some(s) = line.strip_prefix("Requires Data:") {
+                    entry.requires_data = s.trim() == "yes";
+                } else if let Some(s) = line.strip_prefix("Traits:") {
+                    entry.comparison = s.contains('<') || s.contains('>');
+                }
+            }
+        }
+    }
+    entries.extend(entry);
+    entries
+}
+
+/// Write scopes the way the tables do, like `Character | LandedTitle`
+fn rust_scopes(scopes: Scopes) -> String {
+    if scopes == Scopes::all() {
+        "ALL".to_string()
+    } else if scopes == Scopes::all_but_none() {
+        "ALL_BUT_NONE".to_string()
+    } else {
+        format!("{scopes:?}")
+    }
+}
+
+fn section(out: &mut String, title: &str, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    // Writing to a String can't fail
+    let _ = writeln!(out, "{title}:");
+    for line in lines {
+        let _ = writeln!(out, "{line}");
+    }
+    out.push('\n');
+}
+
+fn mismatch(
+    name: &str,
+    what: &str,
+    table: Scopes,
+    log: Option<Scopes>,
+    logname: &str,
+) -> Option<String> {
+    match log {
+        Some(log) if log != table => Some(format!(
+            "    {}: {} {} in the tables, {} in {}",
+            name,
+            what,
+            rust_scopes(table),
+            rust_scopes(log),
+            logname
+        )),
+        _ => None,
+    }
+}
+
+/// Find which iterator an effect or trigger like `every_vassal` is, if any
+fn iterator<'a>(name: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes.iter().find_map(|prefix| name.strip_prefix(prefix))
+}
+
+fn token(name: &str, logfile: &Path) -> Token {
+    let loc = Loc::for_file(Rc::new(logfile.to_path_buf()), FileKind::Vanilla);
+    Token::new(name.to_string(), loc)
+}
+
+/// Check whether an effect or trigger like `every_vassal` iterates over one of the lists in
+/// `SCOPE_ITERATOR`, and if so compare its scopes with the list's.
+fn audit_iterator(
+    entry: &LogEntry,
+    prefixes: &[&str],
+    logname: &str,
+    seen: &mut FnvHashSet<&'static str>,
+    differ: &mut Vec<String>,
+) -> bool {
+    let Some(list) = iterator(&entry.name, prefixes) else {
+        return false;
+    };
+    let Some((from, s, _)) = SCOPE_ITERATOR.iter().find(|(_, s, _)| *s == list) else {
+        return false;
+    };
+    seen.insert(s);
+    differ.extend(mismatch(
+        &entry.name,
+        "scopes",
+        Scopes::from_bits_truncate(*from),
+        entry.input,
+        logname,
+    ));
+    true
+}
+
+/// The names from the tables that the log never mentioned, as report lines
+fn unlogged<'a>(names: impl Iterator<Item = &'a str>, seen: &FnvHashSet<&'a str>) -> Vec<String> {
+    names
+        .filter(|s| !seen.contains(s))
+        .map(|s| format!("    {s}"))
+        .collect()
+}
+
+fn audit_effects(data: &Everything, entries: &[LogEntry], logfile: &Path, out: &mut String) {
+    let logname = "effects.log";
+    let mut seen = FnvHashSet::default();
+    let mut missing = Vec::new();
+    let mut differ = Vec::new();
+    let tables = SCOPE_EFFECT.iter().chain(SCOPE_TRAVEL_EFFECT.iter());
+    let known: FnvHashMap<&str, u64> = tables.clone().map(|(from, s, _)| (*s, *from)).collect();
+
+    for entry in entries {
+        let name = entry.name.as_str();
+        if let Some((&s, &from)) = known.get_key_value(name) {
+            seen.insert(s);
+            differ.extend(mismatch(
+                name,
+                "scopes",
+                Scopes::from_bits_truncate(from),
+                entry.input,
+                logname,
+            ));
+        } else if !audit_iterator(entry, EFFECT_ITERATORS, logname, &mut seen, &mut differ)
+            && scope_effect(&token(name, logfile), data).is_none()
+        {
+            let input = rust_scopes(entry.input.unwrap_or(Scopes::all()));
+            missing.push(format!("    ({input}, \"{name}\", Unchecked),"));
+        }
+    }
+
+    let names = tables
+        .map(|(_, s, _)| *s)
+        .chain(SCOPE_ITERATOR.iter().map(|(_, s, _)| *s));
+    let unlogged = unlogged(names, &seen);
+
+    section(
+        out,
+        "Effects in effects.log but not in SCOPE_EFFECT",
+        &missing,
+    );
+    section(
+        out,
+        "Effects and iterators in the tables but not in effects.log",
+        &unlogged,
+    );
+    section(out, "Effects whose scopes differ", &differ);
+}
+
+/// The triggers in the tables, with the scopes they can be used in and, for the ones that
+/// compare to a target, the scopes of that target
+fn known_triggers() -> FnvHashMap<&'static str, (u64, Option<u64>)> {
+    let mut known = FnvHashMap::default();
+    for (from, s, to) in SCOPE_TRIGGER_TARGET {
+        known.insert(*s, (*from, Some(*to)));
+    }
+    for (from, s) in SCOPE_TRIGGER_BOOL.iter().chain(SCOPE_VALUE.iter()) {
+        known.insert(*s, (*from, None));
+    }
+    for (from, s, _) in SCOPE_TRIGGER_ITEM
+        .iter()
+        .chain(SCOPE_TRAVEL_TRIGGER_ITEM.iter())
+    {
+        known.insert(*s, (*from, None));
+    }
+    known
+}
+
+/// The table lines for triggers that are in the log but not in the tables, sorted by the
+/// table they should go in
+#[derive(Debug, Default)]
+struct MissingTriggers {
+    target: Vec<String>,
+    value: Vec<String>,
+    bool: Vec<String>,
+}
+
+impl MissingTriggers {
+    fn add(&mut self, entry: &LogEntry) {
+        let name = &entry.name;
+        let input = rust_scopes(entry.input.unwrap_or(Scopes::all()));
+        if let Some(targets) = entry.targets {
+            let targets = rust_scopes(targets);
+            self.target
+                .push(format!("    ({input}, \"{name}\", {targets}),"));
+        } else if entry.comparison {
+            self.value.push(format!("    ({input}, \"{name}\"),"));
+        } else {
+            self.bool.push(format!("    ({input}, \"{name}\"),"));
+        }
+    }
+}
+
+/// Whether `validate_trigger` knows the trigger without it being in the tables
+fn is_dynamic_trigger(name: &str, data: &Everything, logfile: &Path) -> bool {
+    let token = token(name, logfile);
+    STRUCTURAL_TRIGGERS.contains(&name)
+        || scope_trigger_target(&token, data).is_some()
+        || scope_value(&token, data).is_some()
+        || is_special_trigger(&token, data)
+}
+
+fn audit_triggers(data: &Everything, entries: &[LogEntry], logfile: &Path, out: &mut String) {
+    let logname = "triggers.log";
+    let mut seen = FnvHashSet::default();
+    let mut missing = MissingTriggers::default();
+    let mut differ = Vec::new();
+    let known = known_triggers();
+
+    for entry in entries {
+        let name = entry.name.as_str();
+        if let Some((&s, &(from, to))) = known.get_key_value(name) {
+            seen.insert(s);
+            differ.extend(mismatch(
+                name,
+                "scopes",
+                Scopes::from_bits_truncate(from),
+                entry.input,
+                logname,
+            ));
+            if let Some(to) = to {
+                differ.extend(mismatch(
+                    name,
+                    "targets",
+                    Scopes::from_bits_truncate(to),
+                    entry.targets,
+                    logname,
+                ));
+            }
+        } else if !audit_iterator(entry, TRIGGER_ITERATORS, logname, &mut seen, &mut differ)
+            && !is_dynamic_trigger(name, data, logfile)
+        {
+            missing.add(entry);
+        }
+    }
+
+    let names = SCOPE_TRIGGER_TARGET
+        .iter()
+        .map(|(_, s, _)| *s)
+        .chain(SCOPE_TRIGGER_BOOL.iter().map(|(_, s)| *s))
+        .chain(SCOPE_TRIGGER_ITEM.iter().map(|(_, s, _)| *s))
+        .chain(SCOPE_TRAVEL_TRIGGER_ITEM.iter().map(|(_, s, _)| *s))
+        .chain(SCOPE_VALUE.iter().map(|(_, s)| *s))
+        .chain(SCOPE_ITERATOR.iter().map(|(_, s, _)| *s));
+    let unlogged = unlogged(names, &seen);
+
+    section(
+        out,
+        "Triggers in triggers.log but not in SCOPE_TRIGGER_TARGET",
+        &missing.target,
+    );
+    section(
+        out,
+        "Triggers in triggers.log but not in SCOPE_VALUE",
+        &missing.value,
+    );
+    section(
+        out,
+        "Triggers in triggers.log but not in SCOPE_TRIGGER_BOOL",
+        &missing.bool,
+    );
+    section(
+        out,
+        "Triggers, values and iterators in the tables but not in triggers.log",
+        &unlogged,
+    );
+    section(out, "Triggers whose scopes differ", &differ);
+}
+
+fn audit_links(entries: &[LogEntry], out: &mut String) {
+    let logname = "event_targets.log";
+    let mut seen_links = FnvHashSet::default();
+    let mut seen_prefixes = FnvHashSet::default();
+    let mut missing_links = Vec::new();
+    let mut missing_prefixes = Vec::new();
+    let mut differ = Vec::new();
+    let prefixes: FnvHashMap<&str, (u64, u64)> = SCOPE_FROM_PREFIX
+        .iter()
+        .map(|(from, s, to)| (*s, (*from, *to)))
+        .collect();
+
+    for entry in entries {
+        let name = entry.name.as_str();
+        let found = if entry.requires_data {
+            seen_prefixes.insert(name);
+            prefixes.get(name).map(|(from, to)| {
+                (
+                    Scopes::from_bits_truncate(*from),
+                    Scopes::from_bits_truncate(*to),
+                )
+            })
+        } else {
+            seen_links.insert(name);
+            scope_to_scope(name)
+        };
+        if let Some((from, to)) = found {
+            differ.extend(mismatch(name, "input", from, entry.input, logname));
+            differ.extend(mismatch(name, "output", to, entry.output, logname));
+        } else {
+            let line = format!(
+                "    ({}, \"{}\", {}),",
+                rust_scopes(entry.input.unwrap_or(Scopes::all())),
+                name,
+                rust_scopes(entry.output.unwrap_or(Scopes::all()))
+            );
+            if entry.requires_data {
+                missing_prefixes.push(line);
+            } else {
+                missing_links.push(line);
+            }
+        }
+    }
+
+    let unlogged = SCOPE_TO_SCOPE
+        .iter()
+        .map(|(_, s, _)| *s)
+        .filter(|s| !seen_links.contains(s))
+        .chain(
+            SCOPE_FROM_PREFIX
+                .iter()
+                .map(|(_, s, _)| *s)
+                .filter(|s| !seen_prefixes.contains(s)),
+        )
+        .map(|s| format!("    {s}"))
+        .collect::<Vec<_>>();
+
+    section(
+        out,
+        "Links in event_targets.log but not in SCOPE_TO_SCOPE",
+        &missing_links,
+    );
+    section(
+        out,
+        "Prefixes in event_targets.log but not in SCOPE_FROM_PREFIX",
+        &missing_prefixes,
+    );
+    section(
+        out,
+        "Links and prefixes in the tables but not in event_targets.log",
+        &unlogged,
+    );
+    section(out, "Links whose scopes differ", &differ);
+}
+
+/// Compare the tables against the `effects.log`, `triggers.log` and `event_targets.log`
+/// files in `dir`, and describe the differences. Missing log files are skipped.
+pub fn audit_tables(everything: &Everything, dir: &Path) -> Result<String> {
+    let mut out = String::new();
+    let mut unknown = FnvHashSet::default();
+    let mut found = false;
+
+    // Looking up the dynamic names can report problems that are not about the mod
+    pause_logging();
+    for logname in ["effects.log", "triggers.log", "event_targets.log"] {
+        let logfile = dir.join(logname);
+        let Ok(text) = read_to_string(&logfile) else {
+            continue;
+        };
+        found = true;
+        let entries = parse_log(&text, &mut unknown);
+        match logname {
+            "effects.log" => audit_effects(everything, &entries, &logfile, &mut out),
+            "triggers.log" => audit_triggers(everything, &entries, &logfile, &mut out),
+            _ => audit_links(&entries, &mut out),
+        }
+    }
+    resume_logging();
+
+    if !found {
+        bail!(
+            "Found none of effects.log, triggers.log and event_targets.log in {}",
+            dir.display()
+        );
+    }
+    if !unknown.is_empty() {
+        let mut unknown = unknown.into_iter().collect::<Vec<_>>();
+        unknown.sort();
+        let lines = unknown
+            .iter()
+            .map(|s| format!("    {s}"))
+            .collect::<Vec<_>>();
+        section(
+            &mut out,
+            "Scopes in the logs that are not in `Scopes`",
+            &lines,
+        );
+    }
+    if out.is_empty() {
+        out.push_str("The tables match the logs.\n");
+    }
+    Ok(out)
+}
@@ -321,14 +321,29 @@ impl Block {
         found.cloned()
     }
 
+    /// The names of the `$PARM$` macro parameters in this block's source. A parameter can
+    /// have a default, as in `$PARM|default$`, which is not part of its name.
     pub fn macro_parms(&self) -> Vec<String> {
+        self.collect_macro_parms(false)
+    }
+
+    /// The macro parameters that are used without a default at least once, so that every
+    /// call has to supply them.
+    pub fn required_macro_parms(&self) -> Vec<String> {
+        self.collect_macro_parms(true)
+    }
+
+    fn collect_macro_parms(&self, required_only: bool) -> Vec<String> {
         let mut vec = Vec::new();
         if let Some(source) = &self.source {
             let mut odd = false;
             for part in split_macros(source) {
                 odd = !odd;
                 if !odd {
-                    vec.push(part.into_string());
+                    let (name, default) = split_macro_default(part.as_str());
+                    if !required_only || default.is_none() {
+                        vec.push(name.to_string());
+                    }
                 }
             }
             vec.sort();
@@ -346,10 +361,11 @@ impl Block {
                 if odd {
                     content.push(part);
                 } else {
-                    for (arg, val) in args {
-                        if part.is(arg) {
-                            content.push(val.clone());
-                        }
+                    let (name, default) = split_macro_default(part.as_str());
+                    if let Some((_, val)) = args.iter().find(|(arg, _)| arg == name) {
+                        content.push(val.clone());
+                    } else if let Some(default) = default {
+                        content.push(Token::new(default.to_string(), part.loc.clone()));
                     }
                 }
             }
@@ -360,10 +376,20 @@ impl Block {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+/// Split `PARM|default` into the parameter name and its default
+fn split_macro_default(parm: &str) -> (&str, Option<&str>) {
+    match parm.split_once('|') {
+        Some((name, default)) => (name, Some(default)),
+        None => (parm, None),
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Comparator {
     None,
     Eq, // Eq is also Assign
+    /// `?=`, which is like `=` but does nothing if the scope on the left doesn't exist
+    QEq,
     Lt,
     Gt,
     Le,
@@ -372,6 +398,14 @@ pub enum Comparator {
 }
 
 impl Comparator {
+    /// Whether this is one of the comparisons that only make sense for numbers and dates.
+    pub fn is_ordering(self) -> bool {
+        matches!(
+            self,
+            Comparator::Lt | Comparator::Gt | Comparator::Le | Comparator::Ge
+        )
+    }
+
     pub fn from_str(s: &str) -> Option<Self> {
         if s == "=" {
             Some(Comparator::Eq)
@@ -385,6 +419,8 @@ impl Comparator {
             Some(Comparator::Ge)
         } else if s == "!=" {
             Some(Comparator::Ne)
+        } else if s == "?=" {
+            Some(Comparator::QEq)
         } else {
             None
         }
@@ -404,6 +440,7 @@ impl Display for Comparator {
             Comparator::Le => write!(f, "<="),
             Comparator::Ge => write!(f, ">="),
             Comparator::Ne => write!(f, "!="),
+            Comparator::QEq => write!(f, "?="),
             Comparator::None => Ok(()),
         }
     }
@@ -557,10 +594,34 @@ pub struct Date {
     day: i8,
 }
 
+const DAYS_PER_MONTH: [i8; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
 impl Date {
     pub fn new(year: i16, month: i8, day: i8) -> Self {
         Date { year, month, day }
     }
+
+    pub fn year(self) -> i16 {
+        self.year
+    }
+
+    pub fn month(self) -> i8 {
+        self.month
+    }
+
+    pub fn day(self) -> i8 {
+        self.day
+    }
+
+    /// Returns the number of days in this date's month, or 0 if the month is invalid.
+    pub fn days_in_month(self) -> i8 {
+        usize::try_from(self.month)
+            .ok()
+            .and_then(|m| m.checked_sub(1))
+            .and_then(|i| DAYS_PER_MONTH.get(i))
+            .copied()
+            .unwrap_or(0)
+    }
 }
 
 impl FromStr for Date {
@@ -2,12 +2,32 @@ use crate::block::{Block, BlockOrValue, Comparator, Date, Token};
 use crate::context::ScopeContext;
 use crate::data::scriptvalues::ScriptValue;
 use crate::errorkey::ErrorKey;
-use crate::errors::{advice, error, warn};
+use crate::errors::{advice, error, error_info, warn, warn2};
 use crate::everything::Everything;
 use crate::helpers::dup_assign_error;
 use crate::item::Item;
 use crate::scopes::Scopes;
 
+/// Fields whose blocks are expected to have something in them. If one of these is empty,
+/// it does nothing, which usually means it is unfinished.
+const CONTENT_FIELDS: &[&str] = &[
+    "trigger",
+    "limit",
+    "effect",
+    "immediate",
+    "after",
+    "on_trigger_fail",
+    "is_shown",
+    "is_valid",
+    "is_valid_showing_failures_only",
+    "potential",
+    "ai_potential",
+    "on_accept",
+    "on_decline",
+    "on_send",
+    "on_auto_accept",
+];
+
 #[derive(Debug)]
 pub struct Validator<'a> {
     // The block being validated
@@ -83,6 +103,7 @@ impl<'a> Validator<'a> {
                             &format!("expected `{} =`, found `{}`", key, cmp),
                         );
                     }
+                    check_empty(key, v);
                     f(v);
                     found = Some(key);
                 }
@@ -145,10 +166,7 @@ impl<'a> Validator<'a> {
 
     pub fn field_bool(&mut self, name: &str) -> bool {
         self.field_check(name, |v| match v {
-            BlockOrValue::Token(t) if t.is("yes") || t.is("no") => (),
-            BlockOrValue::Token(t) => {
-                error(t, ErrorKey::Validation, "expected yes or no");
-            }
+            BlockOrValue::Token(t) => _ = t.expect_bool(),
             BlockOrValue::Block(s) => {
                 error(s, ErrorKey::Validation, "expected value, found block");
             }
@@ -157,11 +175,7 @@ impl<'a> Validator<'a> {
 
     pub fn field_integer(&mut self, name: &str) -> bool {
         self.field_check(name, |v| match v {
-            BlockOrValue::Token(t) => {
-                if t.as_str().parse::<i32>().is_err() {
-                    error(t, ErrorKey::Validation, "expected integer");
-                }
-            }
+            BlockOrValue::Token(t) => _ = t.expect_integer(),
             BlockOrValue::Block(s) => {
                 error(s, ErrorKey::Validation, "expected value, found block");
             }
@@ -170,11 +184,7 @@ impl<'a> Validator<'a> {
 
     pub fn field_numeric(&mut self, name: &str) -> bool {
         self.field_check(name, |v| match v {
-            BlockOrValue::Token(t) => {
-                if t.as_str().parse::<f64>().is_err() {
-                    error(t, ErrorKey::Validation, "expected number");
-                }
-            }
+            BlockOrValue::Token(t) => _ = t.expect_number(),
             BlockOrValue::Block(s) => {
                 error(s, ErrorKey::Validation, "expected value, found block");
             }
@@ -194,6 +204,34 @@ impl<'a> Validator<'a> {
         });
     }
 
+    /// Like `field_script_value`, but the field is a comparison, so `<`, `>=` and the like
+    /// are accepted as well as `=`. Only `?=` is an error, because it does not compare anything.
+    pub fn field_numeric_compared(&mut self, name: &str, sc: &mut ScopeContext) -> bool {
+        let mut found = None;
+        for (k, cmp, bv) in &self.block.v {
+            if let Some(key) = k {
+                if key.is(name) {
+                    self.known_fields.push(key.as_str());
+                    if let Some(other) = found {
+                        dup_assign_error(key, other);
+                    }
+                    if matches!(cmp, Comparator::QEq) {
+                        let msg = format!("`{key} ?=` is not a comparison");
+                        error_info(
+                            key,
+                            ErrorKey::Validation,
+                            &msg,
+                            "use `=` or `>=` and the like",
+                        );
+                    }
+                    ScriptValue::validate_bv(bv, self.data, sc);
+                    found = Some(key);
+                }
+            }
+        }
+        found.is_some()
+    }
+
     pub fn field_choice(&mut self, name: &str, choices: &[&str]) -> bool {
         self.field_check(name, |v| match v {
             BlockOrValue::Token(t) => {
@@ -318,6 +356,7 @@ impl<'a> Validator<'a> {
                     if let Some(other) = found {
                         dup_assign_error(key, other);
                     }
+                    check_empty(key, v);
                     f(v, self.data);
                     found = Some(key);
                 }
@@ -345,6 +384,7 @@ impl<'a> Validator<'a> {
                             &format!("expected `{} =`, found `{}`", key, cmp),
                         );
                     }
+                    check_empty(key, v);
                     f(v, self.data);
                     found = Some(key);
                 }
@@ -429,7 +469,10 @@ impl<'a> Validator<'a> {
                         BlockOrValue::Token(t) => {
                             error(t, ErrorKey::Validation, "expected block, found value");
                         }
-                        BlockOrValue::Block(s) => f(s, self.data),
+                        BlockOrValue::Block(s) => {
+                            check_empty(key, v);
+                            f(s, self.data);
+                        }
                     }
                     found = Some(key);
                 }
@@ -479,6 +522,71 @@ impl<'a> Validator<'a> {
         }
     }
 
+    /// Expect exactly one of the named fields to be present.
+    /// Like `mutually_exclusive`, this only checks; the fields still have to be validated.
+    pub fn req_field_one_of(&mut self, names: &[&str]) -> bool {
+        if self.present_keys(names).is_empty() {
+            let msg = format!("required one of {} missing", quoted_list(names));
+            error(self.block, ErrorKey::Validation, &msg);
+            false
+        } else {
+            self.mutually_exclusive(names)
+        }
+    }
+
+    /// Warn if more than one of the named fields is present. Returns true if there was no conflict.
+    pub fn mutually_exclusive(&mut self, names: &[&str]) -> bool {
+        let found = self.present_keys(names);
+        let mut ok = true;
+        if let Some(&first) = found.first() {
+            // Repeats of the same field are reported as duplicates elsewhere
+            for &key in found.iter().filter(|key| !key.is(first.as_str())) {
+                let msg = format!("only one of {} can be used", quoted_list(names));
+                let msg2 = format!("`{first}` is already set here");
+                warn2(key, ErrorKey::Conflict, &msg, first, &msg2);
+                ok = false;
+            }
+        }
+        ok
+    }
+
+    /// Warn if the named fields don't come first in the block, in the order given.
+    /// Fields that aren't present are skipped.
+    pub fn field_order(&mut self, names: &[&str]) -> bool {
+        let mut ok = true;
+        let mut first_other: Option<&Token> = None;
+        let mut last_named: Option<(usize, &Token)> = None;
+        for key in self.block.iter_items().filter_map(|(k, _, _)| k.as_ref()) {
+            if let Some(rank) = names.iter().position(|name| key.is(name)) {
+                let before = match (first_other, last_named) {
+                    (Some(other), _) => Some(other),
+                    (None, Some((last_rank, last))) if rank < last_rank => Some(last),
+                    _ => None,
+                };
+                if let Some(other) = before {
+                    let msg = format!("`{key}` should come before `{other}`");
+                    let msg2 = format!("`{other}` is here");
+                    warn2(key, ErrorKey::Validation, &msg, other, &msg2);
+                    ok = false;
+                }
+                if !matches!(last_named, Some((last_rank, _)) if last_rank > rank) {
+                    last_named = Some((rank, key));
+                }
+            } else if first_other.is_none() {
+                first_other = Some(key);
+            }
+        }
+        ok
+    }
+
+    fn present_keys(&self, names: &[&str]) -> Vec<&'a Token> {
+        self.block
+            .iter_items()
+            .filter_map(|(k, _, _)| k.as_ref())
+            .filter(|key| names.iter().any(|name| key.is(name)))
+            .collect()
+    }
+
     pub fn advice_field(&mut self, name: &str, msg: &str) {
         if let Some(key) = self.block.get_key(name) {
             self.known_fields.push(key.as_str());
@@ -569,6 +677,8 @@ impl<'a> Validator<'a> {
         for (k, cmp, v) in &self.block.v {
             if let Some(key) = k {
                 if let Ok(date) = Date::try_from(key) {
+                    // Check that the month and day are in range
+                    key.expect_date();
                     self.known_fields.push(key.as_str());
                     if !matches!(cmp, Comparator::Eq) {
                         error(
@@ -616,6 +726,20 @@ impl<'a> Validator<'a> {
         vec
     }
 
+    /// Like `unknown_keys`, but also return the comparator, for callers that need to check it.
+    pub fn unknown_keys_cmp(&mut self) -> Vec<(&Token, Comparator, &BlockOrValue)> {
+        self.accepted_keys = true;
+        let mut vec = Vec::new();
+        for (k, cmp, bv) in &self.block.v {
+            if let Some(key) = k {
+                if !self.known_fields.contains(&key.as_str()) {
+                    vec.push((key, *cmp, bv));
+                }
+            }
+        }
+        vec
+    }
+
     pub fn no_warn_remaining(&mut self) {
         self.accepted_keys = true;
         self.accepted_tokens = true;
@@ -670,3 +794,21 @@ impl<'a> Drop for Validator<'a> {
         self.warn_remaining();
     }
 }
+
+/// Advise about an empty block for a field that is expected to have content
+fn check_empty(key: &Token, bv: &BlockOrValue) {
+    if let BlockOrValue::Block(block) = bv {
+        if block.v.is_empty() && CONTENT_FIELDS.contains(&key.as_str()) {
+            let msg = format!("empty `{key}` does nothing");
+            advice(key, ErrorKey::EmptyBlock, &msg);
+        }
+    }
+}
+
+fn quoted_list(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,180 @@
+use fnv::{FnvHashMap, FnvHashSet};
+
+use crate::block::Block;
+use crate::errorkey::ErrorKey;
+use crate::errors::{advice_info, error, error_info};
+use crate::token::Token;
+
+/// The calls that scripted effects make to other scripted effects, or scripted triggers
+/// to other scripted triggers. Built after loading, to find calls that go around in a
+/// circle and calls that expand to more script than the game can handle well.
+pub struct CallGraph<'a> {
+    /// What is being called, for the messages, like "scripted effect"
+    what: &'static str,
+    /// The definitions by name, in order of their location so that reports are stable
+    defs: Vec<(&'a Token, &'a Block)>,
+    /// For each definition, the call sites in it and which definition they call
+    calls: Vec<Vec<(&'a Token, usize)>>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+impl<'a> CallGraph<'a> {
+    pub fn new(what: &'static str, mut defs: Vec<(&'a Token, &'a Block)>) -> Self {
+        defs.sort_unstable_by_key(|(key, _)| &key.loc);
+        let index = defs
+            .iter()
+            .enumerate()
+            .map(|(i, (key, _))| (key.as_str(), i))
+            .collect::<FnvHashMap<_, _>>();
+        let calls = defs
+            .iter()
+            .map(|(_, block)| {
+                let mut vec = Vec::new();
+                collect_calls(block, &index, &mut vec);
+                vec
+            })
+            .collect();
+        Self { what, defs, calls }
+    }
+
+    /// Report the cycles of calls. Each one is reported once, at the call that closes it.
+    /// Calls with macro arguments count too, even if the arguments change on the way around.
+    pub fn check_cycles(&self) {
+        let mut state = vec![Visit::New; self.defs.len()];
+        let mut stack = Vec::new();
+        let mut seen = FnvHashSet::default();
+        for start in 0..self.defs.len() {
+            self.visit(start, &mut state, &mut stack, &mut seen);
+        }
+    }
+
+    fn visit(
+        &self,
+        node: usize,
+        state: &mut Vec<Visit>,
+        stack: &mut Vec<usize>,
+        seen: &mut FnvHashSet<Vec<usize>>,
+    ) {
+        if state[node] != Visit::New {
+            return;
+        }
+        state[node] = Visit::Active;
+        stack.push(node);
+        for &(call, target) in &self.calls[node] {
+            match state[target] {
+                Visit::New => self.visit(target, state, stack, seen),
+                Visit::Active => {
+                    let pos = stack.iter().position(|&n| n == target).unwrap();
+                    let mut cycle = stack[pos..].to_vec();
+                    // Rotate so that the same cycle found from another call is recognized
+                    let min = cycle.iter().enumerate().min_by_key(|(_, &n)| n).unwrap().0;
+                    cycle.rotate_left(min);
+                    if seen.insert(cycle) {
+                        self.report_cycle(call, &stack[pos..]);
+                    }
+                }
+                Visit::Done => (),
+            }
+        }
+        stack.pop();
+        state[node] = Visit::Done;
+    }
+
+    fn report_cycle(&self, call: &Token, cycle: &[usize]) {
+        let first = self.defs[cycle[0]].0;
+        if cycle.len() == 1 {
+            let msg = format!("{} `{}` calls itself", self.what, first);
+            error(call, ErrorKey::Crash, &msg);
+        } else {
+            let msg = format!("{} `{}` calls itself in a cycle", self.what, first);
+            let mut path = cycle
+                .iter()
+                .map(|&n| self.defs[n].0.as_str())
+                .collect::<Vec<_>>();
+            path.push(first.as_str());
+            let info = format!("the game hangs or crashes on {}", path.join(" -> "));
+            error_info(call, ErrorKey::Crash, &msg, &info);
+        }
+    }
+
+    /// Give advice about definitions whose calls, when all filled in, add up to more than
+    /// `limit` fields.
+    pub fn check_expansion(&self, limit: usize) {
+        let mut sizes = vec![None; self.defs.len()];
+        for node in 0..self.defs.len() {
+            let size = self.expanded_size(node, &mut sizes, &mut Vec::new());
+            let key = self.defs[node].0;
+            // Only report the outermost definitions, not every one that calls them
+            let called_big = self.calls[node]
+                .iter()
+                .any(|&(_, target)| sizes[target].is_some_and(|s| s > limit));
+            if size > limit && !called_big {
+                let msg = format!(
+                    "{} `{}` expands to {} fields when all its calls are filled in",
+                    self.what, key, size
+                );
+                let info = format!(
+                    "the game builds the tooltips from the whole expansion, which gets slow; this warns above {limit}, set with `scripted_expansion` in the `limits` block of the config file"
+                );
+                advice_info(key, ErrorKey::Tooltip, &msg, &info);
+            }
+        }
+    }
+
+    fn expanded_size(
+        &self,
+        node: usize,
+        sizes: &mut Vec<Option<usize>>,
+        active: &mut Vec<usize>,
+    ) -> usize {
+        if let Some(size) = sizes[node] {
+            return size;
+        }
+        // Cycles are reported separately, so just don't count around them
+        if active.contains(&node) {
+            return 0;
+        }
+        active.push(node);
+        let mut size = count_fields(self.defs[node].1);
+        for &(_, target) in &self.calls[node] {
+            size = size.saturating_add(self.expanded_size(target, sizes, active));
+        }
+        active.pop();
+        sizes[node] = Some(size);
+        size
+    }
+}
+
+fn collect_calls<'a>(
+    block: &'a Block,
+    index: &FnvHashMap<&str, usize>,
+    vec: &mut Vec<(&'a Token, usize)>,
+) {
+    for (key, _, bv) in block.iter_items() {
+        if let Some(key) = key {
+            if let Some(&target) = index.get(key.as_str()) {
+                vec.push((key, target));
+            }
+        }
+        if let Some(block) = bv.get_block() {
+            collect_calls(block, index, vec);
+        }
+    }
+}
+
+fn count_fields(block: &Block) -> usize {
+    let mut count = 0;
+    for (_, _, bv) in block.iter_items() {
+        count += 1;
+        if let Some(block) = bv.get_block() {
+            count += count_fields(block);
+        }
+    }
+    count
+}
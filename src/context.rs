@@ -1,3 +1,4 @@
+use crate::block::{Block, BlockOrValue};
 use crate::errorkey::ErrorKey;
 use crate::errors::{warn, warn2, warn3};
 use crate::scopes::Scopes;
@@ -15,6 +16,24 @@ pub struct ScopeContext {
 
     // root is always a ScopeEntry::Scope
     root: ScopeEntry,
+
+    // Whether the whole scope stack is known, so that a `prev` that goes past its start is a
+    // mistake. Scripted effects and triggers don't know it because they inherit their caller's.
+    known_stack: bool,
+
+    // The types of the named scopes (`scope:name`) that are known to be set
+    names: Vec<(String, Scopes)>,
+
+    // Names of the triggers checked in the `limit` blocks around the block being validated.
+    // Effects that need a precondition look here for a trigger that checks it.
+    guards: Vec<String>,
+
+    // How many effect and trigger blocks deep the block being validated is
+    depth: usize,
+
+    // The title and vassal changes created in this effect tree, by the token of their
+    // `save_scope_as`, and whether they have been resolved.
+    title_changes: Vec<(Token, bool)>,
 }
 
 #[derive(Clone, Debug)]
@@ -48,6 +67,11 @@ impl ScopeContext {
             prev: None,
             this: ScopeEntry::Rootref,
             root: ScopeEntry::Scope(root, token),
+            known_stack: true,
+            names: Vec::new(),
+            guards: Vec::new(),
+            depth: 0,
+            title_changes: Vec::new(),
         }
     }
 
@@ -56,6 +80,11 @@ impl ScopeContext {
             prev: None,
             this: ScopeEntry::Scope(this, token.clone()),
             root: ScopeEntry::Scope(Scopes::all(), token),
+            known_stack: false,
+            names: Vec::new(),
+            guards: Vec::new(),
+            depth: 0,
+            title_changes: Vec::new(),
         }
     }
 
@@ -82,6 +111,14 @@ impl ScopeContext {
     }
 
     pub fn replace(&mut self, scopes: Scopes, token: Token) {
+        // If this continues a chain such as `primary_title.holder`, keep the whole chain
+        // as the reason for the scope type, so that error reports can show it.
+        let token = match self.this {
+            ScopeEntry::Scope(_, ref prev) if is_chained(prev, &token) => {
+                Token::new(format!("{prev}.{token}"), prev.loc.clone())
+            }
+            _ => token,
+        };
         self.this = ScopeEntry::Scope(scopes, token);
     }
 
@@ -90,19 +127,133 @@ impl ScopeContext {
     }
 
     pub fn replace_prev(&mut self, token: &Token) {
-        match self.this {
-            ScopeEntry::Scope(_, _) => self.this = ScopeEntry::Backref(1),
-            ScopeEntry::Backref(r) => self.this = ScopeEntry::Backref(r + 1),
+        let back = match self.this {
+            ScopeEntry::Scope(_, _) => 1,
+            ScopeEntry::Backref(r) => r + 1,
             ScopeEntry::Rootref => {
                 warn(token, ErrorKey::Scopes, "trying to take prev of root");
+                return;
             }
+        };
+        if self.known_stack && back >= self.stack_depth() {
+            warn(token, ErrorKey::Scopes, "there is no previous scope here");
+        }
+        self.this = ScopeEntry::Backref(back);
+    }
+
+    /// Whether the scope chain being built so far consists only of `this` and `prev`,
+    /// so that it can be followed by another `prev`.
+    pub fn can_chain_prev(&self) -> bool {
+        matches!(self.this, ScopeEntry::Backref(_))
+    }
+
+    /// The number of scopes in the history, not counting `this`.
+    fn stack_depth(&self) -> usize {
+        let mut depth = 0;
+        let mut ptr = &self.prev;
+        while let Some(entry) = ptr {
+            depth += 1;
+            ptr = &entry.prev;
         }
+        depth
     }
 
     pub fn replace_this(&mut self) {
         self.this = ScopeEntry::Backref(0);
     }
 
+    /// Declare that `scope:name` is set and has one of the given scope types.
+    pub fn define_name(&mut self, name: &str, scopes: Scopes) {
+        if let Some((_, s)) = self.names.iter_mut().find(|(n, _)| n == name) {
+            *s |= scopes;
+        } else {
+            self.names.push((name.to_string(), scopes));
+        }
+    }
+
+    /// Return the scope types produced by `prefix:arg`, which are `outscope` unless it refers
+    /// to a named scope whose type is known.
+    pub fn named_scope_or(&self, prefix: &Token, arg: &Token, outscope: Scopes) -> Scopes {
+        if prefix.is("scope") {
+            for (name, scopes) in &self.names {
+                if arg.is(name) {
+                    return *scopes;
+                }
+            }
+        }
+        outscope
+    }
+
+    /// Remember the triggers used in `block` as guards for the effects validated after it.
+    /// Returns a mark to pass to `drop_guards` when leaving the guarded block.
+    pub fn add_guards(&mut self, block: &Block) -> usize {
+        let mark = self.guards.len();
+        collect_guards(block, &mut self.guards);
+        mark
+    }
+
+    /// Remember a single guard, for blocks that are only run when their condition holds,
+    /// like `scope:target ?= { ... }` which is only run if the target `exists`.
+    pub fn add_guard(&mut self, name: &str) -> usize {
+        let mark = self.guards.len();
+        self.guards.push(name.to_string());
+        mark
+    }
+
+    pub fn drop_guards(&mut self, mark: usize) {
+        self.guards.truncate(mark);
+    }
+
+    /// Note that validation goes one effect or trigger block deeper, and return the new depth.
+    pub fn enter_block(&mut self) -> usize {
+        self.depth += 1;
+        self.depth
+    }
+
+    pub fn leave_block(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Remember that a title and vassal change was created and saved as `scope:name`.
+    pub fn create_title_change(&mut self, name: &Token) {
+        self.title_changes.push((name.clone(), false));
+    }
+
+    pub fn resolve_title_change(&mut self, name: &str) {
+        for (change, resolved) in &mut self.title_changes {
+            if change.is(name) {
+                *resolved = true;
+            }
+        }
+    }
+
+    /// A scripted effect sees the same named scopes, so it might resolve any of the changes.
+    pub fn resolve_title_changes(&mut self) {
+        for (_, resolved) in &mut self.title_changes {
+            *resolved = true;
+        }
+    }
+
+    /// Return the title and vassal changes that were never resolved, once the whole effect
+    /// tree has been validated. If the tree was called from elsewhere, such as a scripted
+    /// effect, the caller may resolve them so nothing is returned.
+    pub fn unresolved_title_changes(&mut self) -> Vec<Token> {
+        if self.depth > 0 || !self.known_stack {
+            return Vec::new();
+        }
+        self.title_changes
+            .drain(..)
+            .filter(|(_, resolved)| !resolved)
+            .map(|(change, _)| change)
+            .collect()
+    }
+
+    pub fn has_guard(&self, names: &[&str]) -> bool {
+        self.guards
+            .iter()
+            .any(|guard| names.contains(&guard.as_str()))
+    }
+
     pub fn can_be(&self, scopes: Scopes) -> bool {
         self.scopes().intersects(scopes)
     }
@@ -115,6 +266,12 @@ impl ScopeContext {
         self.scopes_token().0
     }
 
+    /// The token that is the reason for what the current scope is, such as the `title:b_rome`
+    /// that opened it
+    pub fn scope_token(&self) -> &Token {
+        self.scopes_token().1
+    }
+
     fn scopes_token(&self) -> (Scopes, &Token) {
         match self.this {
             ScopeEntry::Scope(s, ref t) => (s, t),
@@ -164,8 +321,9 @@ impl ScopeContext {
                         *t = token.clone();
                     }
                 } else {
-                    let msg = format!("`{}` is for {} but scope seems to be {}", token, scopes, s);
-                    let msg2 = format!("scope was deduced from `{}` here", t);
+                    let msg =
+                        format!("`{token}` requires {scopes} scope but the scope here is {s}");
+                    let msg2 = format!("scope became {s} because of `{t}` here");
                     warn2(token, ErrorKey::Scopes, &msg, &*t, &msg2);
                     // Suppress future warnings about the same problem
                     *s |= scopes;
@@ -186,9 +344,9 @@ impl ScopeContext {
                         *t = token.clone();
                     }
                 } else {
-                    let msg = format!("`{}` expects {} but scope seems to be {}", key, scopes, s);
-                    let msg2 = format!("expected scope was deduced from `{}` here", token);
-                    let msg3 = format!("actual scope was deduced from `{}` here", t);
+                    let msg = format!("`{key}` requires {scopes} scope but the scope here is {s}");
+                    let msg2 = format!("{scopes} scope is required because of `{token}` here");
+                    let msg3 = format!("scope became {s} because of `{t}` here");
                     warn3(key, ErrorKey::Scopes, &msg, token, &msg2, &*t, &msg3);
                     // Suppress future warnings about the same problem
                     *s |= scopes;
@@ -294,12 +452,12 @@ impl ScopeContext {
                             }
                         } else {
                             let msg = format!(
-                                "`{}` expects root to be {} but root scope seems to be {}",
+                                "`{}` requires root to be {} but the root here is {}",
                                 key, scopes, s
                             );
                             let msg2 =
-                                format!("expected root scope was deduced from `{}` here", token);
-                            let msg3 = format!("actual root scope was deduced from `{}` here", t);
+                                format!("{scopes} root is required because of `{token}` here");
+                            let msg3 = format!("root became {s} because of `{t}` here");
                             warn3(key, ErrorKey::Scopes, &msg, token, &msg2, &*t, &msg3);
                             // Suppress future warnings about the same problem
                             *s |= scopes;
@@ -321,6 +479,25 @@ impl ScopeContext {
     }
 }
 
+/// Whether `next` directly follows `prev` in a chain like `primary_title.holder`.
+fn is_chained(prev: &Token, next: &Token) -> bool {
+    prev.loc.pathname == next.loc.pathname
+        && prev.loc.offset + prev.as_str().len() + 1 == next.loc.offset
+}
+
+/// Collect all the keys used in a trigger block, including the parts of scope chains such as
+/// `primary_title.is_title_created`.
+fn collect_guards(block: &Block, vec: &mut Vec<String>) {
+    for (k, _, bv) in block.iter_items() {
+        if let Some(key) = k {
+            vec.extend(key.as_str().split('.').map(ToString::to_string));
+        }
+        if let BlockOrValue::Block(b) = bv {
+            collect_guards(b, vec);
+        }
+    }
+}
+
 impl Drop for ScopeContext {
     fn drop(&mut self) {
         assert!(self.prev.is_none(), "scope chain not properly unwound");
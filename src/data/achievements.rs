@@ -0,0 +1,117 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+/// The achievements in `common/achievements`. The game checks their triggers for the player
+/// character; `possible` says whether the achievement can still be earned and `happened`
+/// says whether it has been.
+#[derive(Clone, Debug, Default)]
+pub struct Achievements {
+    achievements: FnvHashMap<String, Achievement>,
+}
+
+impl Achievements {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.achievements.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "achievement");
+            }
+        }
+        self.achievements
+            .insert(key.to_string(), Achievement::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.achievements.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.achievements.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for Achievements {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.achievements.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.achievements.values().map(as_source))
+    }
+}
+
+impl FileHandler for Achievements {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/achievements")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Achievement {
+    key: Token,
+    block: Block,
+}
+
+impl Achievement {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+
+        // The game needs both, and an achievement with only one of them is never earned
+        for (field, other) in [("possible", "happened"), ("happened", "possible")] {
+            if self.block.get_key(field).is_none() && self.block.get_key(other).is_some() {
+                let msg = format!("achievement has `{other}` but no `{field}`");
+                warn(&self.key, ErrorKey::Validation, &msg);
+            }
+        }
+        vd.field_validated_block("possible", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("happened", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+    }
+}
+
+impl ItemSource for Achievement {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -0,0 +1,352 @@
+use fnv::{FnvHashMap, FnvHashSet};
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error2, error_info};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::{dup_error, suggest, Suggestion};
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::schema::{validate_schema, Field, Schema};
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::validate::{validate_cost, validate_days_weeks_months_years};
+
+/// The prefix of the named scopes that hold an activity's special guests. A special guest
+/// `honoree` is `scope:special_guest_honoree` in the activity's phases and options.
+const SPECIAL_GUEST_PREFIX: &str = "special_guest_";
+
+/// The activity types in `common/activities`, like feasts and hunts
+#[derive(Clone, Debug, Default)]
+pub struct Activities {
+    activities: FnvHashMap<String, Activity>,
+}
+
+impl Activities {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.activities.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "activity");
+            }
+        }
+        self.activities
+            .insert(key.to_string(), Activity::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.activities.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.activities.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for Activities {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.activities.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.activities.values().map(as_source))
+    }
+}
+
+impl FileHandler for Activities {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/activities")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Activity {
+    key: Token,
+    block: Block,
+}
+
+impl Activity {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    /// The names of the special guest slots declared in `special_guests`
+    fn special_guests(&self) -> Vec<&Token> {
+        self.block
+            .get_field_block("special_guests")
+            .map(|block| block.iter_pure_definitions().map(|(key, _)| key).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        data.localization.verify_exists(&self.key);
+
+        // The fields about starting the activity are from the host's point of view
+        let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+        validate_schema(&mut vd, HOST_FIELDS, &mut sc);
+
+        // The rest run in the activity, where the host and the special guests are named
+        let mut sc = ScopeContext::new_root(Scopes::Activity, self.key.clone());
+        sc.define_name("host", Scopes::Character);
+        for guest in self.special_guests() {
+            sc.define_name(&format!("{SPECIAL_GUEST_PREFIX}{guest}"), Scopes::Character);
+        }
+        validate_schema(&mut vd, ACTIVITY_FIELDS, &mut sc);
+
+        self.check_special_guest_refs(data);
+    }
+
+    /// Check that every `scope:special_guest_x` in the activity, and in the on-actions it
+    /// runs, refers to a declared slot.
+    fn check_special_guest_refs(&self, data: &Everything) {
+        let declared = self
+            .special_guests()
+            .into_iter()
+            .map(Token::as_str)
+            .collect::<FnvHashSet<_>>();
+
+        let mut refs = Vec::new();
+        collect_special_guest_refs(&self.block, &mut refs);
+        for (token, guest) in refs {
+            if !declared.contains(guest) {
+                let msg = format!(
+                    "special guest `{guest}` is not declared in activity {}",
+                    self.key
+                );
+                let info = suggest(guest, declared.iter().copied()).map_or_else(
+                    || {
+                        "special guests are declared in the `special_guests` block of the activity"
+                            .to_string()
+                    },
+                    Suggestion::info,
+                );
+                error_info(token, ErrorKey::MissingItem, &msg, &info);
+            }
+        }
+
+        // The on_actions run from the activity's effects see the same named scopes
+        let mut on_actions = Vec::new();
+        collect_on_action_calls(&self.block, &mut on_actions);
+        let mut seen = FnvHashSet::default();
+        for caller in on_actions {
+            if !seen.insert(caller.as_str()) {
+                continue;
+            }
+            let mut refs = Vec::new();
+            for block in data.on_actions.reached_blocks(caller.as_str()) {
+                collect_special_guest_refs(block, &mut refs);
+            }
+            for (token, guest) in refs {
+                if !declared.contains(guest) {
+                    let msg = format!(
+                        "special guest `{guest}` is not declared in activity {}",
+                        self.key
+                    );
+                    let msg2 = "the activity runs the on_action here";
+                    error2(token, ErrorKey::MissingItem, &msg, caller, msg2);
+                }
+            }
+        }
+    }
+}
+
+/// Collect the `scope:special_guest_x` references in `block`, with the name of the guest
+fn collect_special_guest_refs<'a>(block: &'a Block, vec: &mut Vec<(&'a Token, &'a str)>) {
+    for (key, _, bv) in block.iter_items() {
+        for token in key.iter().chain(bv.get_value()) {
+            for part in token.as_str().split('.') {
+                if let Some(guest) = part
+                    .strip_prefix("scope:")
+                    .and_then(|name| name.strip_prefix(SPECIAL_GUEST_PREFIX))
+                {
+                    vec.push((token, guest));
+                }
+            }
+        }
+        if let Some(block) = bv.get_block() {
+            collect_special_guest_refs(block, vec);
+        }
+    }
+}
+
+/// Collect the on-actions that `block` runs with `trigger_event = { on_action = ... }`
+fn collect_on_action_calls<'a>(block: &'a Block, vec: &mut Vec<&'a Token>) {
+    for (key, _, bv) in block.iter_items() {
+        if let Some(block) = bv.get_block() {
+            if matches!(key, Some(key) if key.is("trigger_event")) {
+                vec.extend(block.get_field_value("on_action"));
+            }
+            collect_on_action_calls(block, vec);
+        }
+    }
+}
+
+impl ItemSource for Activity {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+const HOST_FIELDS: &Schema = &[
+    ("is_shown", Field::Trigger(false)),
+    ("can_start", Field::Trigger(true)),
+    ("can_start_showing_failures_only", Field::Trigger(true)),
+    ("cost", Field::Validated(validate_cost)),
+    ("ai_will_do", Field::AiChance),
+    ("ai_check_interval", Field::Integer),
+    ("ai_check_interval_by_tier", Field::Any),
+    ("ai_select_num_provinces", Field::Value),
+    // Where the activity can be held
+    ("is_single_location", Field::Bool),
+    ("province_filter", Field::Any),
+    ("province_filter_radius", Field::Integer),
+    ("ai_province_filter", Field::Any),
+    ("ai_province_filter_radius", Field::Integer),
+    ("is_location_valid", Field::Trigger(true)),
+    ("province_score", Field::Value),
+    ("province_description", Field::Desc),
+    ("max_province_icons", Field::Integer),
+];
+
+const ACTIVITY_FIELDS: &Schema = &[
+    ("is_valid", Field::Trigger(false)),
+    ("on_invalidated", Field::Effect(false)),
+    ("special_guests", Field::Validated(validate_special_guests)),
+    (
+        "can_be_activity_guest",
+        Field::Rooted(Scopes::Character, &Field::Trigger(false)),
+    ),
+    ("guest_join_chance", Field::Value),
+    ("open_invite", Field::Bool),
+    ("is_grand_activity", Field::Bool),
+    ("notify_player_can_join_activity", Field::Bool),
+    ("num_pickable_phases", Field::Integer),
+    ("max_pickable_phases_per_province", Field::Integer),
+    (
+        "wait_time_before_start",
+        Field::Validated(validate_days_weeks_months_years),
+    ),
+    (
+        "max_guest_arrival_delay_time",
+        Field::Validated(validate_days_weeks_months_years),
+    ),
+    ("on_start", Field::Effect(false)),
+    ("phases", Field::Validated(validate_phases)),
+    ("options", Field::Validated(validate_option_categories)),
+    ("on_enter_travel_state", Field::Effect(false)),
+    ("on_enter_passive_state", Field::Effect(false)),
+    ("on_enter_active_state", Field::Effect(false)),
+    ("on_leave_travel_state", Field::Effect(false)),
+    ("on_leave_passive_state", Field::Effect(false)),
+    ("on_leave_active_state", Field::Effect(false)),
+    ("on_travel_state_pulse", Field::Effect(false)),
+    ("on_passive_state_pulse", Field::Effect(false)),
+    ("on_active_state_pulse", Field::Effect(false)),
+    ("on_host_death", Field::Effect(false)),
+    ("on_complete", Field::Effect(false)),
+    // These configure the gui, the intents and the invitations, and are not checked yet
+    ("host_intents", Field::Any),
+    ("guest_intents", Field::Any),
+    ("guest_invite_rules", Field::Any),
+    ("pulse_actions", Field::Any),
+    ("travel_entourage_selection", Field::Any),
+    ("ui_predicted_cost", Field::Any),
+    ("window_characters", Field::Multiple(&Field::Any)),
+    ("map_entity", Field::Any),
+    ("background", Field::Multiple(&Field::Any)),
+    ("locale_background", Field::Multiple(&Field::Any)),
+    ("activity_group_type", Field::Any),
+    ("early_locale_opening_duration", Field::Any),
+    ("sort_order", Field::Integer),
+];
+
+/// The special guest slots, each with the candidates that can fill it
+fn validate_special_guests(block: &Block, data: &Everything, _sc: &mut ScopeContext) {
+    for (key, block) in block.iter_pure_definitions_warn() {
+        let mut vd = Validator::new(block, data);
+        // `can_be_picked` runs for each candidate, with the host named
+        let mut sc = ScopeContext::new_root(Scopes::Character, key.clone());
+        sc.define_name("host", Scopes::Character);
+        validate_schema(&mut vd, SPECIAL_GUEST_FIELDS, &mut sc);
+    }
+}
+
+const SPECIAL_GUEST_FIELDS: &Schema = &[
+    ("is_required", Field::Bool),
+    ("can_be_picked", Field::Trigger(true)),
+    ("can_be_picked_in_ui", Field::Trigger(true)),
+    ("ai_will_do", Field::AiChance),
+    ("contribution", Field::Value),
+    ("on_invite", Field::Effect(false)),
+];
+
+fn validate_phases(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    for (_, block) in block.iter_pure_definitions_warn() {
+        let mut vd = Validator::new(block, data);
+        validate_schema(&mut vd, PHASE_FIELDS, sc);
+    }
+}
+
+const PHASE_FIELDS: &Schema = &[
+    ("is_predefined", Field::Bool),
+    ("number_of_picks", Field::Integer),
+    ("order", Field::Integer),
+    ("ai_will_do", Field::AiChance),
+    ("cost", Field::Validated(validate_cost)),
+    ("map_entity", Field::Any),
+    ("background", Field::Multiple(&Field::Any)),
+    ("is_shown", Field::Trigger(false)),
+    ("can_pick", Field::Trigger(true)),
+    ("on_enter_phase", Field::Effect(false)),
+    ("on_phase_active", Field::Effect(false)),
+    ("on_weekly_pulse", Field::Effect(false)),
+    ("on_monthly_pulse", Field::Effect(false)),
+    ("on_end", Field::Effect(false)),
+];
+
+/// The options are grouped in categories, like the kind of food at a feast
+fn validate_option_categories(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    for (_, block) in block.iter_pure_definitions_warn() {
+        for (_, block) in block.iter_pure_definitions_warn() {
+            let mut vd = Validator::new(block, data);
+            validate_schema(&mut vd, OPTION_FIELDS, sc);
+        }
+    }
+}
+
+const OPTION_FIELDS: &Schema = &[
+    ("is_shown", Field::Trigger(false)),
+    ("is_valid", Field::Trigger(true)),
+    ("default", Field::Bool),
+    ("blocked_intents", Field::Any),
+    ("cost", Field::Validated(validate_cost)),
+    ("reward", Field::Value),
+    ("contribution", Field::Value),
+    ("ai_will_do", Field::AiChance),
+    ("on_start", Field::Effect(false)),
+    ("on_end", Field::Effect(false)),
+];
@@ -0,0 +1,135 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::icons::{verify_icon, IconKind};
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+/// How artifacts look, in `common/artifacts/visuals`. The `court_slot` says where in the
+/// court scene the artifact is displayed.
+#[derive(Clone, Debug, Default)]
+pub struct ArtifactVisuals {
+    visuals: FnvHashMap<String, ArtifactVisual>,
+}
+
+impl ArtifactVisuals {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.visuals.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "artifact visual");
+            }
+        }
+        self.visuals
+            .insert(key.to_string(), ArtifactVisual::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.visuals.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.visuals.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for ArtifactVisuals {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.visuals.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.visuals.values().map(as_source))
+    }
+}
+
+impl FileHandler for ArtifactVisuals {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/artifacts/visuals")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ArtifactVisual {
+    key: Token,
+    block: Block,
+}
+
+impl ArtifactVisual {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        vd.field_value("default_type");
+        vd.field_value_item("court_slot", Item::CourtSceneArtifactSlot);
+        vd.field_validated_bvs("icon", |bv, data| {
+            self.validate_variant(bv, data, |icon, data| {
+                verify_icon(IconKind::Artifact, icon.as_str(), icon, data);
+            });
+        });
+        vd.field_validated_bvs("asset", |bv, data| {
+            self.validate_variant(bv, data, |asset, data| {
+                data.verify_exists(Item::Entity, asset);
+            });
+        });
+    }
+
+    /// The icon and the asset can be given directly, or as several blocks that each pick a
+    /// `reference` by trigger, like `asset = { trigger = { ... } reference = name }`.
+    fn validate_variant<F>(&self, bv: &BlockOrValue, data: &Everything, mut f: F)
+    where
+        F: FnMut(&Token, &Everything),
+    {
+        match bv {
+            BlockOrValue::Token(token) => f(token, data),
+            BlockOrValue::Block(block) => {
+                let mut vd = Validator::new(block, data);
+                vd.field_validated_block("trigger", |b, data| {
+                    let mut sc = ScopeContext::new_root(Scopes::Artifact, self.key.clone());
+                    validate_normal_trigger(b, data, &mut sc, false);
+                });
+                vd.req_field("reference");
+                if let Some(token) = vd.field_value("reference") {
+                    f(token, data);
+                }
+            }
+        }
+    }
+}
+
+impl ItemSource for ArtifactVisual {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -0,0 +1,62 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::errors::vanilla_shown;
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+
+/// The graphical entities defined in the `.asset` files under `gfx`. Other graphics
+/// definitions, like the court scene's environments and the artifact visuals, refer to
+/// them by name.
+#[derive(Clone, Debug, Default)]
+pub struct Assets {
+    entities: FnvHashMap<String, Token>,
+}
+
+impl Assets {
+    pub fn exists(&self, key: &str) -> bool {
+        self.entities.contains_key(key)
+    }
+}
+
+impl FileHandler for Assets {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("gfx")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".asset") {
+            return;
+        }
+
+        // The game's asset files are only needed for their entity names, so they are scanned
+        // instead of parsed unless their problems are to be reported
+        if entry.kind() != FileKind::Mod && !vanilla_shown() {
+            for item in PdxFile::scan(entry, fullpath).into_iter().flatten() {
+                if item.key.is("entity") {
+                    if let Some(name) = item.get_field_value("name") {
+                        self.entities.insert(name.to_string(), name.clone());
+                    }
+                }
+            }
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        // The asset files also define meshes, animations and the like, but only the
+        // entities are referred to from script
+        for (key, _, bv) in block.iter_items() {
+            if let (Some(key), Some(block)) = (key, bv.get_block()) {
+                if key.is("entity") {
+                    if let Some(name) = block.get_field_value("name") {
+                        self.entities.insert(name.to_string(), name.clone());
+                    }
+                }
+            }
+        }
+    }
+}
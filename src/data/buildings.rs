@@ -0,0 +1,148 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn2;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+#[derive(Clone, Debug, Default)]
+pub struct Buildings {
+    buildings: FnvHashMap<String, Building>,
+}
+
+impl Buildings {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.buildings.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "building");
+            }
+        }
+        self.buildings
+            .insert(key.to_string(), Building::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.buildings.contains_key(key)
+    }
+
+    /// Return true if `key` is a building of the given `type`.
+    pub fn has_type(&self, key: &str, btype: &str) -> bool {
+        self.buildings
+            .get(key)
+            .is_some_and(|building| building.btype() == btype)
+    }
+
+    /// Check that `token` names a building of the given `type`, such as `special` or
+    /// `duchy_capital`. Missing buildings are reported as missing items.
+    pub fn verify_type(&self, token: &Token, btype: &str, data: &Everything) {
+        if let Some(building) = self.buildings.get(token.as_str()) {
+            if building.btype() != btype {
+                let msg = format!("`{}` is not a {} building", token, btype.replace('_', " "));
+                let msg2 = format!("it is a {} building", building.btype().replace('_', " "));
+                warn2(token, ErrorKey::Validation, &msg, &building.key, &msg2);
+            }
+        } else {
+            data.verify_exists(Item::Building, token);
+        }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.buildings.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for Buildings {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.buildings.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.buildings.values().map(as_source))
+    }
+}
+
+impl FileHandler for Buildings {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/buildings")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Building {
+    key: Token,
+    block: Block,
+}
+
+impl Building {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    /// The building's `type`, which is `regular` if not set
+    fn btype(&self) -> &str {
+        self.block
+            .get_field_value("type")
+            .map_or("regular", Token::as_str)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        let mut sc = ScopeContext::new_root(Scopes::Province, self.key.clone());
+
+        let loca = format!("building_{}", self.key);
+        data.localization.verify_exists_implied(&loca, &self.key);
+
+        vd.field_choice("type", &["regular", "special", "duchy_capital"]);
+        vd.field_value_item("next_building", Item::Building);
+        for field in [
+            "can_construct_potential",
+            "can_construct_showing_failures_only",
+            "can_construct",
+            "is_enabled",
+            "show_disabled",
+        ] {
+            vd.field_validated_block(field, |b, data| {
+                validate_normal_trigger(b, data, &mut sc, false);
+            });
+        }
+        // TODO: validate the costs and modifiers
+        vd.no_warn_remaining();
+    }
+}
+
+impl ItemSource for Building {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -0,0 +1,261 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
+use crate::data::scriptvalues::ScriptValue;
+use crate::effect::validate_normal_effect;
+use crate::errorkey::ErrorKey;
+use crate::errors::{warn, warn_info};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_target;
+use crate::validate::validate_modifiers_with_base;
+
+#[derive(Clone, Debug, Default)]
+pub struct CharacterTemplates {
+    templates: FnvHashMap<String, CharacterTemplate>,
+}
+
+impl CharacterTemplates {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.templates.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "character template");
+            }
+        }
+        self.templates
+            .insert(key.to_string(), CharacterTemplate::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.templates.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.templates.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for CharacterTemplates {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.templates.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.templates.values().map(as_source))
+    }
+}
+
+impl FileHandler for CharacterTemplates {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/scripted_character_templates")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CharacterTemplate {
+    key: Token,
+    block: Block,
+}
+
+impl CharacterTemplate {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        // Templates are used from `create_character` in whatever scope that is in.
+        let mut sc = ScopeContext::new_unrooted(Scopes::all(), self.key.clone());
+        let mut vd = Validator::new(&self.block, data);
+        if let Some(key) = self.block.get_key("template") {
+            warn(
+                key,
+                ErrorKey::Validation,
+                "templates cannot refer to other templates",
+            );
+        }
+        validate_character_details(&self.block, data, &mut vd, &mut sc);
+    }
+}
+
+impl ItemSource for CharacterTemplate {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// The fields of `create_character` that still work when it uses a `template`.
+const TEMPLATE_COMPATIBLE: &[&str] = &[
+    "template",
+    "location",
+    "employer",
+    "save_scope_as",
+    "save_temporary_scope_as",
+    "after_creation",
+];
+
+/// Validate the `create_character` effect. It takes the same fields as a character template,
+/// or it can name a template and then only a few fields are still used.
+pub fn validate_create_character(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    if let Some(token) = block.get_field_value("template") {
+        data.verify_exists(Item::CharacterTemplate, token);
+        for (key, _, _) in block.iter_items() {
+            if let Some(key) = key {
+                if !TEMPLATE_COMPATIBLE.contains(&key.as_str()) {
+                    let msg = format!("`{key}` has no effect when `template` is used");
+                    let info = "put it in the template instead";
+                    warn_info(key, ErrorKey::Conflict, &msg, info);
+                }
+            }
+        }
+    }
+    validate_character_details(block, data, &mut vd, sc);
+}
+
+const SKILLS: &[&str] = &[
+    "diplomacy",
+    "martial",
+    "stewardship",
+    "intrigue",
+    "learning",
+    "prowess",
+];
+
+/// Validate the fields shared by `create_character` and scripted character templates.
+fn validate_character_details(
+    block: &Block,
+    data: &Everything,
+    vd: &mut Validator,
+    sc: &mut ScopeContext,
+) {
+    vd.field_value("template");
+    vd.field_value("name");
+    vd.field_validated_bv("age", |bv, data| validate_number_or_range(bv, data, sc));
+    for skill in SKILLS {
+        vd.field_validated_bv(skill, |bv, data| validate_number_or_range(bv, data, sc));
+    }
+    vd.field_validated_bv("gender", |bv, data| {
+        if let Some(token) = bv.expect_value() {
+            if !(token.is("male") || token.is("female")) {
+                // the new character gets the same gender as this character
+                validate_target(token, data, sc, Scopes::Character);
+            }
+        }
+    });
+    vd.field_script_value("gender_female_chance", sc);
+    vd.field_validated_bv("culture", |bv, data| {
+        validate_target_or_item(bv, data, sc, Scopes::Culture, Item::Culture);
+    });
+    vd.field_validated_bv("faith", |bv, data| {
+        validate_target_or_item(bv, data, sc, Scopes::Faith, Item::Faith);
+    });
+    vd.field_validated_bv("dynasty", |bv, data| {
+        if let Some(token) = bv.expect_value() {
+            if !(token.is("generate") || token.is("inherit") || token.is("none")) {
+                validate_target_or_item(bv, data, sc, Scopes::Dynasty, Item::Dynasty);
+            }
+        }
+    });
+    vd.field_validated_bv("dynasty_house", |bv, data| {
+        validate_target_or_item(bv, data, sc, Scopes::DynastyHouse, Item::House);
+    });
+    vd.field_values_items("trait", Item::Trait);
+    let traits = block.get_field_values("trait");
+    data.traits
+        .check_opposites(&traits.iter().collect::<Vec<_>>());
+    vd.field_validated_blocks("random_traits_list", |b, data| {
+        validate_random_traits_list(b, data, sc);
+    });
+    vd.field_bool("random_traits");
+    vd.field_script_value("health", sc);
+    vd.field_script_value("fertility", sc);
+    for field in ["mother", "father", "real_father", "employer", "dna"] {
+        vd.field_validated_bv(field, |bv, data| {
+            if let Some(token) = bv.expect_value() {
+                validate_target(token, data, sc, Scopes::Character);
+            }
+        });
+    }
+    vd.field_validated_bv("location", |bv, data| {
+        if let Some(token) = bv.expect_value() {
+            validate_target(token, data, sc, Scopes::Province);
+        }
+    });
+    vd.field_value("save_scope_as");
+    vd.field_value("save_temporary_scope_as");
+    vd.field_validated_block("after_creation", |b, data| {
+        let key = block.get_key("after_creation").unwrap();
+        sc.open_scope(Scopes::Character, key.clone());
+        validate_normal_effect(b, data, sc, false);
+        sc.close();
+    });
+}
+
+/// Skills and age can be a script value or a `{ min max }` range.
+fn validate_number_or_range(bv: &BlockOrValue, data: &Everything, sc: &mut ScopeContext) {
+    if let BlockOrValue::Block(block) = bv {
+        if block.iter_items().all(|(k, _, _)| k.is_none()) {
+            let mut vd = Validator::new(block, data);
+            vd.req_tokens_integers_exactly(2);
+            return;
+        }
+    }
+    ScriptValue::validate_bv(bv, data, sc);
+}
+
+fn validate_target_or_item(
+    bv: &BlockOrValue,
+    data: &Everything,
+    sc: &mut ScopeContext,
+    scopes: Scopes,
+    itype: Item,
+) {
+    if let Some(token) = bv.expect_value() {
+        if token.as_str().contains(':') || token.as_str().contains('.') {
+            validate_target(token, data, sc, scopes);
+        } else if !data.item_exists(itype, token.as_str()) {
+            // Could still be a plain scope link such as `root` or `this`
+            validate_target(token, data, sc, scopes);
+        }
+    }
+}
+
+fn validate_random_traits_list(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.field_validated_bv("count", |bv, data| validate_number_or_range(bv, data, sc));
+    for (key, bv) in vd.unknown_keys() {
+        data.verify_exists(Item::Trait, key);
+        if let Some(block) = bv.expect_block() {
+            validate_modifiers_with_base(block, data, sc);
+        }
+    }
+}
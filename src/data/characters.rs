@@ -331,7 +331,7 @@ impl Character {
         vd.req_field("name");
         vd.field_value_item("name", Item::Localization);
 
-        vd.field_value("dna");
+        vd.field_value_item("dna", Item::Dna);
         vd.field_bool("female");
         vd.field_integer("martial");
         vd.field_integer("prowess");
@@ -359,6 +359,12 @@ impl Character {
 
         vd.field_value_item("dynasty", Item::Dynasty);
         vd.field_value_item("dynasty_house", Item::House);
+        if let (Some(dynasty), Some(house)) = (
+            self.block.get_field_value("dynasty"),
+            self.block.get_field_value("dynasty_house"),
+        ) {
+            data.houses.verify_dynasty(house, dynasty);
+        }
 
         vd.field_value("give_nickname");
         vd.field_value_item("sexuality", Item::Sexuality);
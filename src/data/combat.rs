@@ -0,0 +1,209 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::effect::validate_normal_effect;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+/// The effects that change the advantage of a side in battle, such as terrain or a river
+/// crossing, in `common/combat_effects`. They are shown in the battle window.
+#[derive(Clone, Debug, Default)]
+pub struct CombatEffects {
+    effects: FnvHashMap<String, CombatEffect>,
+}
+
+impl CombatEffects {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.effects.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "combat effect");
+            }
+        }
+        self.effects
+            .insert(key.to_string(), CombatEffect::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.effects.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.effects.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for CombatEffects {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.effects.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.effects.values().map(as_source))
+    }
+}
+
+impl FileHandler for CombatEffects {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/combat_effects")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CombatEffect {
+    key: Token,
+    block: Block,
+}
+
+impl CombatEffect {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        vd.field_value_item("name", Item::Localization);
+        vd.field_value_item("desc", Item::Localization);
+        vd.field_value_item("image", Item::File);
+        vd.field_numeric("advantage");
+        vd.field_numeric("dig_in");
+    }
+}
+
+impl ItemSource for CombatEffect {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// The phases of a battle in which a combat phase event can happen
+const COMBAT_PHASES: &[&str] = &["skirmish", "main", "pursuit", "aftermath"];
+
+/// Which sides of the battle a combat phase event is for
+const COMBAT_EVENT_SIDES: &[&str] = &["attacker", "defender", "winner", "loser", "both"];
+
+/// The events that happen to the commanders and knights during the phases of a battle, in
+/// `common/combat_phase_events`. They run in the scope of a combat side.
+#[derive(Clone, Debug, Default)]
+pub struct CombatPhaseEvents {
+    events: FnvHashMap<String, CombatPhaseEvent>,
+}
+
+impl CombatPhaseEvents {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.events.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "combat phase event");
+            }
+        }
+        self.events
+            .insert(key.to_string(), CombatPhaseEvent::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.events.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.events.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for CombatPhaseEvents {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.events.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.events.values().map(as_source))
+    }
+}
+
+impl FileHandler for CombatPhaseEvents {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/combat_phase_events")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CombatPhaseEvent {
+    key: Token,
+    block: Block,
+}
+
+impl CombatPhaseEvent {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        let mut sc = ScopeContext::new_root(Scopes::CombatSide, self.key.clone());
+
+        vd.req_field("type");
+        vd.field_choice("type", COMBAT_PHASES);
+        vd.field_choice("side", COMBAT_EVENT_SIDES);
+        vd.field_validated_block("trigger", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_script_value("weight", &mut sc);
+        vd.field_validated_block("effect", |b, data| {
+            validate_normal_effect(b, data, &mut sc, false);
+        });
+    }
+}
+
+impl ItemSource for CombatPhaseEvent {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -0,0 +1,189 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::schema::{validate_schema, Field, Schema};
+use crate::scopes::Scopes;
+use crate::token::Token;
+
+/// The tasks that councillors can be given, in `common/council_tasks`
+#[derive(Clone, Debug, Default)]
+pub struct CouncilTasks {
+    tasks: FnvHashMap<String, CouncilTask>,
+}
+
+impl CouncilTasks {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.tasks.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "council task");
+            }
+        }
+        self.tasks
+            .insert(key.to_string(), CouncilTask::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.tasks.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.tasks.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for CouncilTasks {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.tasks.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.tasks.values().map(as_source))
+    }
+}
+
+impl FileHandler for CouncilTasks {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/council_tasks")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CouncilTask {
+    key: Token,
+    block: Block,
+}
+
+impl CouncilTask {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        data.localization.verify_exists(&self.key);
+
+        vd.req_field("position");
+        vd.req_field("task_type");
+        let county_task = self
+            .block
+            .get_field_value("task_type")
+            .is_some_and(|t| t.is("task_type_county"));
+        if county_task {
+            vd.req_field("county_target");
+        } else {
+            for (name, _) in COUNTY_FIELDS {
+                if let Some(key) = self.block.get_key(name) {
+                    let msg = format!("`{name}` is only used by tasks of task_type_county");
+                    warn(key, ErrorKey::Validation, &msg);
+                }
+            }
+        }
+
+        // Most of the fields are from the councillor's point of view
+        let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+        sc.define_name("councillor", Scopes::Character);
+        sc.define_name("councillor_liege", Scopes::Character);
+        validate_schema(&mut vd, TASK_FIELDS, &mut sc);
+
+        // The fields about the target county are evaluated for each county, with the
+        // councillor and the county's holder named
+        let mut county_sc = ScopeContext::new_root(Scopes::LandedTitle, self.key.clone());
+        county_sc.define_name("councillor", Scopes::Character);
+        county_sc.define_name("councillor_liege", Scopes::Character);
+        county_sc.define_name("county_owner", Scopes::Character);
+        validate_schema(&mut vd, COUNTY_FIELDS, &mut county_sc);
+
+        // The progress of a county task is per county
+        if county_task {
+            validate_schema(&mut vd, PROGRESS_FIELDS, &mut county_sc);
+        } else {
+            validate_schema(&mut vd, PROGRESS_FIELDS, &mut sc);
+        }
+    }
+}
+
+impl ItemSource for CouncilTask {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+const TASK_FIELDS: &Schema = &[
+    ("position", Field::Item(Item::CouncilPosition)),
+    (
+        "task_type",
+        Field::Choice(&["task_type_general", "task_type_county", "task_type_court"]),
+    ),
+    (
+        "task_progress",
+        Field::Choice(&[
+            "task_progress_infinite",
+            "task_progress_percentage",
+            "task_progress_value",
+        ]),
+    ),
+    ("default_task", Field::Bool),
+    ("restart_on_finish", Field::Bool),
+    ("skill", Field::Item(Item::Skill)),
+    ("effect_desc", Field::Desc),
+    ("is_shown", Field::Trigger(false)),
+    ("is_valid_showing_failures_only", Field::Trigger(true)),
+    ("on_start_task", Field::Effect(true)),
+    ("on_finish_task", Field::Effect(true)),
+    ("on_cancel_task", Field::Effect(true)),
+    ("on_monthly", Field::Effect(false)),
+    ("ai_will_do", Field::AiChance),
+];
+
+const COUNTY_TARGETS: &[&str] = &[
+    "all",
+    "realm",
+    "domain",
+    "neighbor_land",
+    "neighbor_land_or_water",
+];
+
+const COUNTY_FIELDS: &Schema = &[
+    ("county_target", Field::Choice(COUNTY_TARGETS)),
+    ("ai_county_target", Field::Choice(COUNTY_TARGETS)),
+    ("potential_county", Field::Trigger(false)),
+    ("valid_county", Field::Trigger(true)),
+    ("on_start_task_county", Field::Effect(true)),
+    ("on_finish_task_county", Field::Effect(true)),
+    ("on_cancel_task_county", Field::Effect(true)),
+    ("on_monthly_county", Field::Effect(false)),
+    ("ai_target_score", Field::AiChance),
+];
+
+const PROGRESS_FIELDS: &Schema = &[("progress", Field::Value), ("full_progress", Field::Value)];
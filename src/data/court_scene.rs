@@ -0,0 +1,327 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+
+/// The court scenes of the Royal Court, in `gfx/court_scene/scene_settings`. Each one names
+/// the environment entity that is the room, the camera it is seen through, and which of the
+/// character and artifact slots it shows.
+#[derive(Clone, Debug, Default)]
+pub struct CourtSceneSettings {
+    settings: FnvHashMap<String, CourtSceneSetting>,
+}
+
+impl CourtSceneSettings {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.settings.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "court scene");
+            }
+        }
+        self.settings
+            .insert(key.to_string(), CourtSceneSetting::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.settings.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.settings.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for CourtSceneSettings {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.settings.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.settings.values().map(as_source))
+    }
+}
+
+impl FileHandler for CourtSceneSettings {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("gfx/court_scene/scene_settings")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CourtSceneSetting {
+    key: Token,
+    block: Block,
+}
+
+impl CourtSceneSetting {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        vd.field_values_items("environment", Item::Entity);
+        vd.field_value_item("camera", Item::CourtSceneCamera);
+        if let Some(slots) = vd.field_block("character_slots") {
+            for slot in slots.get_values() {
+                data.verify_exists(Item::CourtSceneCharacterSlot, &slot);
+            }
+        }
+        if let Some(slots) = vd.field_block("artifact_slots") {
+            for slot in slots.get_values() {
+                data.verify_exists(Item::CourtSceneArtifactSlot, &slot);
+            }
+        }
+    }
+}
+
+impl ItemSource for CourtSceneSetting {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// The cameras for the court scene, in `gfx/court_scene/camera_settings`
+#[derive(Clone, Debug, Default)]
+pub struct CourtSceneCameras {
+    cameras: FnvHashMap<String, CourtSceneCamera>,
+}
+
+impl CourtSceneCameras {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.cameras.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "court scene camera");
+            }
+        }
+        self.cameras
+            .insert(key.to_string(), CourtSceneCamera::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.cameras.contains_key(key)
+    }
+}
+
+impl ItemDb for CourtSceneCameras {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.cameras.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.cameras.values().map(as_source))
+    }
+}
+
+impl FileHandler for CourtSceneCameras {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("gfx/court_scene/camera_settings")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CourtSceneCamera {
+    key: Token,
+    block: Block,
+}
+
+impl CourtSceneCamera {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+}
+
+impl ItemSource for CourtSceneCamera {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// Whether a court scene slot holds a character or an artifact. The two kinds are in
+/// different folders and have their own names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SlotKind {
+    Character,
+    Artifact,
+}
+
+impl SlotKind {
+    fn name(self) -> &'static str {
+        match self {
+            SlotKind::Character => "character slot",
+            SlotKind::Artifact => "artifact slot",
+        }
+    }
+}
+
+/// The places in the court scene where characters stand or artifacts are displayed, in
+/// `gfx/court_scene/character_slots` and `gfx/court_scene/artifact_slots`. Artifacts whose
+/// visuals name a slot that doesn't exist are not shown in the court at all.
+#[derive(Clone, Debug)]
+pub struct CourtSceneSlots {
+    kind: SlotKind,
+    slots: FnvHashMap<String, CourtSceneSlot>,
+}
+
+impl CourtSceneSlots {
+    pub fn new(kind: SlotKind) -> Self {
+        Self {
+            kind,
+            slots: FnvHashMap::default(),
+        }
+    }
+
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.slots.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, self.kind.name());
+            }
+        }
+        self.slots.insert(
+            key.to_string(),
+            CourtSceneSlot::new(key, block.clone(), self.kind),
+        );
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.slots.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.slots.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for CourtSceneSlots {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.slots.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.slots.values().map(as_source))
+    }
+}
+
+impl FileHandler for CourtSceneSlots {
+    fn subpath(&self) -> PathBuf {
+        match self.kind {
+            SlotKind::Character => PathBuf::from("gfx/court_scene/character_slots"),
+            SlotKind::Artifact => PathBuf::from("gfx/court_scene/artifact_slots"),
+        }
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CourtSceneSlot {
+    key: Token,
+    block: Block,
+    kind: SlotKind,
+}
+
+impl CourtSceneSlot {
+    pub fn new(key: Token, block: Block, kind: SlotKind) -> Self {
+        Self { key, block, kind }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        vd.field_value_item("camera", Item::CourtSceneCamera);
+        if self.kind == SlotKind::Character {
+            vd.field_value_item("court_position", Item::CourtPosition);
+        }
+        vd.field_validated_block("position", validate_position);
+        vd.field_numeric("rotation");
+        vd.field_numeric("scale");
+        if self.kind == SlotKind::Character {
+            vd.field_value("animation");
+        }
+    }
+}
+
+impl ItemSource for CourtSceneSlot {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// A position in the court scene is three coordinates, like `position = { 1.5 0 -3 }`
+fn validate_position(block: &Block, data: &Everything) {
+    let mut vd = Validator::new(block, data);
+    let mut count = 0;
+    for token in vd.values() {
+        token.expect_number();
+        count += 1;
+    }
+    if count != 3 {
+        let msg = format!("expected 3 coordinates, found {count}");
+        warn(block, ErrorKey::Validation, &msg);
+    }
+}
@@ -0,0 +1,132 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn_info;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::helpers::dup_error;
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+use crate::validate::validate_modifiers;
+
+/// The game expects all of these sections to be present, and fills the error log with complaints
+/// every time it manages the pools if one is missing.
+const REQUIRED_SECTIONS: &[&str] = &["courtiers", "guests"];
+
+#[derive(Clone, Debug, Default)]
+pub struct CourtierGuestManagement {
+    sections: FnvHashMap<String, PoolSection>,
+    /// The mod files that were loaded, for reporting missing sections
+    mod_files: Vec<FileEntry>,
+}
+
+impl CourtierGuestManagement {
+    fn load_item(&mut self, key: &Token, block: &Block) {
+        if !REQUIRED_SECTIONS.contains(&key.as_str()) {
+            let msg = format!("unknown section `{key}`");
+            let info = format!("expected one of {}", REQUIRED_SECTIONS.join(", "));
+            warn_info(key, ErrorKey::Validation, &msg, &info);
+            return;
+        }
+        if let Some(other) = self.sections.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(key, &other.key, "courtier guest management section");
+            }
+        }
+        self.sections.insert(
+            key.to_string(),
+            PoolSection::new(key.clone(), block.clone()),
+        );
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        // Only complain about missing sections if the mod is responsible for them.
+        // Vanilla is assumed to be complete.
+        if let Some(entry) = self.mod_files.last() {
+            for section in REQUIRED_SECTIONS {
+                if !self.sections.contains_key(*section) {
+                    let msg = format!("missing section `{section}`");
+                    let info = "the game requires this section and will log errors without it";
+                    warn_info(entry, ErrorKey::Validation, &msg, info);
+                }
+            }
+        }
+
+        for item in self.sections.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl FileHandler for CourtierGuestManagement {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/courtier_guest_management")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        if entry.kind() == FileKind::Mod {
+            self.mod_files.push(entry.clone());
+        }
+
+        for (key, b) in block.iter_pure_definitions_warn() {
+            self.load_item(key, b);
+        }
+    }
+}
+
+/// The rules for managing one of the character pools at court, either the courtiers or the
+/// guests. The score is calculated for each candidate character.
+#[derive(Clone, Debug)]
+pub struct PoolSection {
+    key: Token,
+    block: Block,
+}
+
+impl PoolSection {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+
+        for (key, _) in self.block.iter_pure_definitions() {
+            if key.as_str().starts_with("auto_") {
+                vd.field_validated_block(key.as_str(), |b, data| {
+                    validate_auto_block(b, data, &mut sc);
+                });
+            }
+        }
+        validate_score(&mut vd, data, &mut sc);
+    }
+}
+
+/// An `auto_` block describes something the game does to the pool by itself, such as
+/// dismissing courtiers, when its trigger is met. It has its own score.
+fn validate_auto_block(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.field_validated_block("trigger", |b, data| {
+        validate_normal_trigger(b, data, sc, false);
+    });
+    validate_score(&mut vd, data, sc);
+}
+
+fn validate_score(vd: &mut Validator, data: &Everything, sc: &mut ScopeContext) {
+    vd.field_script_value("base_value", sc);
+    validate_modifiers(vd, data, sc);
+}
@@ -162,6 +162,7 @@ impl CourtPosition {
         vd.field_value_item("custom_employer_modifier_description", Item::Localization);
         vd.field_value_item("custom_employee_modifier_description", Item::Localization);
 
+        vd.field_value_item("pool_character_selector", Item::PoolSelector);
         if let Some(block) = vd.field_block("search_for_courtier") {
             let mut sc = ScopeContext::new_root(
                 Scopes::Character,
@@ -5,16 +5,16 @@ use crate::block::validator::Validator;
 use crate::block::Block;
 use crate::context::ScopeContext;
 use crate::desc::validate_desc;
-use crate::effect::validate_normal_effect;
 use crate::errorkey::ErrorKey;
 use crate::errors::warn;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
 use crate::pdxfile::PdxFile;
+use crate::schema::{validate_schema, Field, Schema};
 use crate::scopes::Scopes;
 use crate::token::Token;
-use crate::trigger::validate_normal_trigger;
 use crate::validate::{validate_cooldown, validate_cost};
 
 #[derive(Clone, Debug, Default)]
@@ -44,6 +44,16 @@ impl Decisions {
     }
 }
 
+impl ItemDb for Decisions {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.decisions.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.decisions.values().map(as_source))
+    }
+}
+
 impl FileHandler for Decisions {
     fn subpath(&self) -> PathBuf {
         PathBuf::from("common/decisions")
@@ -84,21 +94,9 @@ impl Decision {
         if let Some(token) = vd.field_value("picture") {
             data.fileset.verify_exists(token);
         }
-        if let Some(token) = vd.field_value("extra_picture") {
-            data.fileset.verify_exists(token);
-        }
-        vd.field_bool("major");
-        vd.field_integer("sort_order");
-        vd.field_bool("is_invisible");
-        vd.field_bool("ai_goal");
-        vd.field_integer("ai_check_interval");
         if self.block.get_field_bool("ai_goal").unwrap_or(false) {
             vd.advice_field("ai_check_interval", "not needed if ai_goal = yes");
         }
-        vd.field_validated_block("cooldown", |b, data| validate_cooldown(b, data, &mut sc));
-
-        // kind of looks like a filename but it isn't.
-        vd.field_value("confirm_click_sound");
 
         if let Some(bv) = vd.field("selection_tooltip") {
             validate_desc(bv, data, &mut sc);
@@ -127,34 +125,47 @@ impl Decision {
             data.localization.verify_exists_implied(&loca, &self.key);
         }
 
-        vd.field_validated_block("is_shown", |b, data| {
-            validate_normal_trigger(b, data, &mut sc, false);
-        });
-        vd.field_validated_block("is_valid_showing_failures_only", |b, data| {
-            validate_normal_trigger(b, data, &mut sc, true);
-        });
-        vd.field_validated_block("is_valid", |b, data| {
-            validate_normal_trigger(b, data, &mut sc, true);
-        });
+        validate_schema(&mut vd, DECISION_FIELDS, &mut sc);
 
         // cost can have multiple definitions and they will be combined
         // however, two costs of the same type are not summed
-        vd.field_validated_blocks("cost", |b, data| validate_cost(b, data, &mut sc));
         check_cost(&self.block.get_field_blocks("cost"));
-        vd.field_validated_blocks("minimum_cost", |b, data| validate_cost(b, data, &mut sc));
         check_cost(&self.block.get_field_blocks("minimum_cost"));
+    }
+}
+
+const DECISION_FIELDS: &Schema = &[
+    ("extra_picture", Field::File),
+    ("major", Field::Bool),
+    ("sort_order", Field::Integer),
+    ("is_invisible", Field::Bool),
+    ("ai_goal", Field::Bool),
+    ("ai_check_interval", Field::Integer),
+    ("cooldown", Field::Validated(validate_cooldown)),
+    // kind of looks like a filename but it isn't.
+    ("confirm_click_sound", Field::Any),
+    ("is_shown", Field::Trigger(false)),
+    ("is_valid_showing_failures_only", Field::Trigger(true)),
+    ("is_valid", Field::Trigger(true)),
+    ("cost", Field::Multiple(&Field::Validated(validate_cost))),
+    (
+        "minimum_cost",
+        Field::Multiple(&Field::Validated(validate_cost)),
+    ),
+    ("effect", Field::Effect(true)),
+    ("ai_potential", Field::Trigger(false)),
+    ("ai_will_do", Field::AiChance),
+    ("should_create_alert", Field::Trigger(false)),
+    ("widget", Field::Any),
+];
+
+impl ItemSource for Decision {
+    fn key(&self) -> &Token {
+        &self.key
+    }
 
-        vd.field_validated_block("effect", |b, data| {
-            validate_normal_effect(b, data, &mut sc, true);
-        });
-        vd.field_validated_block("ai_potential", |b, data| {
-            validate_normal_trigger(b, data, &mut sc, false);
-        });
-        vd.field_block("ai_will_do");
-        vd.field_validated_block("should_create_alert", |b, data| {
-            validate_normal_trigger(b, data, &mut sc, false);
-        });
-        vd.field("widget");
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
     }
 }
 
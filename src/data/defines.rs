@@ -0,0 +1,208 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::BlockOrValue;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error2, error_info};
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::helpers::closest_key;
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+
+/// The engine defines in `common/defines`. The game's own defines files say which defines
+/// exist and what type of value they take, so the mod's overrides are checked against those
+/// instead of against a list that would need updating with every patch.
+#[derive(Clone, Debug, Default)]
+pub struct Defines {
+    /// The defines in the game files, by group and then by name
+    known: FnvHashMap<String, FnvHashMap<String, Define>>,
+    /// The defines set by the mod
+    overrides: Vec<(Token, Define)>,
+}
+
+#[derive(Clone, Debug)]
+struct Define {
+    key: Token,
+    value: BlockOrValue,
+}
+
+impl Defines {
+    fn load_define(&mut self, group: &Token, key: Token, value: &BlockOrValue) {
+        let define = Define {
+            key,
+            value: value.clone(),
+        };
+        if group.loc.kind == FileKind::Mod {
+            self.overrides.push((group.clone(), define));
+        } else {
+            self.known
+                .entry(group.to_string())
+                .or_default()
+                .insert(define.key.to_string(), define);
+        }
+    }
+
+    /// Check the argument of a `define:` or `array_define:` reference, like `NGame|START_DATE`.
+    pub fn verify_exists(&self, arg: &Token) {
+        let Some((group, name)) = arg.split_once('|') else {
+            error(arg, ErrorKey::Validation, "expected `Group|NAME`");
+            return;
+        };
+        // Without the game's defines there is nothing to compare to
+        if self.known.is_empty() {
+            return;
+        }
+        let exists = self
+            .known
+            .get(group.as_str())
+            .is_some_and(|defines| defines.contains_key(name.as_str()));
+        if !exists {
+            let msg = format!("unknown define `{arg}`");
+            error(arg, ErrorKey::MissingItem, &msg);
+        }
+    }
+
+    pub fn validate(&self) {
+        // Without the game's defines there is nothing to compare to
+        if self.known.is_empty() {
+            return;
+        }
+        for (group, define) in &self.overrides {
+            let Some(defines) = self.known.get(group.as_str()) else {
+                let msg = format!("unknown define group `{group}`");
+                match closest_key(group.as_str(), self.known.keys().map(String::as_str)) {
+                    Some(close) => {
+                        let info = format!("did you mean `{close}`?");
+                        error_info(group, ErrorKey::Validation, &msg, &info);
+                    }
+                    None => error(group, ErrorKey::Validation, &msg),
+                }
+                continue;
+            };
+            let Some(known) = defines.get(define.key.as_str()) else {
+                let msg = format!("unknown define `{}.{}`", group, define.key);
+                let info = "the game ignores defines it doesn't know";
+                match closest_key(define.key.as_str(), defines.keys().map(String::as_str)) {
+                    Some(close) => {
+                        let info = format!("{info}; did you mean `{close}`?");
+                        error_info(&define.key, ErrorKey::Validation, &msg, &info);
+                    }
+                    None => error_info(&define.key, ErrorKey::Validation, &msg, info),
+                }
+                continue;
+            };
+            define.validate_like(known);
+        }
+    }
+}
+
+impl Define {
+    /// Check that this define's value has the same type as the game's value for it.
+    fn validate_like(&self, known: &Define) {
+        let expected = value_type(&known.value);
+        let found = value_type(&self.value);
+        if expected != found {
+            let msg = format!("expected {expected} but found {found}");
+            error2(
+                &self.value,
+                ErrorKey::Validation,
+                &msg,
+                &known.key,
+                "the game's define is here",
+            );
+            return;
+        }
+        if let (BlockOrValue::Block(block), BlockOrValue::Block(known_block)) =
+            (&self.value, &known.value)
+        {
+            let expected = known_block
+                .iter_items()
+                .find_map(|(_, _, bv)| bv.get_value())
+                .map(|t| token_type(t));
+            for (key, _, bv) in block.iter_items() {
+                if let Some(key) = key {
+                    error(
+                        key,
+                        ErrorKey::Validation,
+                        "define lists can only contain values",
+                    );
+                } else if let BlockOrValue::Token(t) = bv {
+                    if let Some(expected) = expected {
+                        if token_type(t) != expected {
+                            let msg = format!("expected a list of {expected}s");
+                            error(t, ErrorKey::Validation, &msg);
+                        }
+                    }
+                } else {
+                    error(
+                        bv,
+                        ErrorKey::Validation,
+                        "define lists can't contain blocks",
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn token_type(token: &Token) -> &'static str {
+    if token.as_str().parse::<f64>().is_ok() {
+        "number"
+    } else {
+        "string"
+    }
+}
+
+fn value_type(bv: &BlockOrValue) -> &'static str {
+    match bv {
+        BlockOrValue::Token(t) => {
+            if token_type(t) == "number" {
+                "a number"
+            } else {
+                "a string"
+            }
+        }
+        BlockOrValue::Block(_) => "a list",
+    }
+}
+
+impl FileHandler for Defines {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/defines")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, _, bv) in block.iter_items() {
+            let Some(key) = key else {
+                error(bv, ErrorKey::Validation, "expected a define group");
+                continue;
+            };
+            // Either `NGame = { START_DATE = ... }` or `NDefines.NGame.START_DATE = ...`
+            let parts = key.split('.');
+            if parts.len() == 3 && parts[0].is("NDefines") {
+                self.load_define(&parts[1], parts[2].clone(), bv);
+            } else if parts.len() > 1 {
+                error(
+                    key,
+                    ErrorKey::Validation,
+                    "expected `NDefines.Group.NAME` or a define group",
+                );
+            } else if let Some(group) = bv.expect_block() {
+                for (name, _, value) in group.iter_items() {
+                    match name {
+                        Some(name) => self.load_define(key, name.clone(), value),
+                        None => error(value, ErrorKey::Validation, "expected `NAME = value`"),
+                    }
+                }
+            }
+        }
+    }
+}
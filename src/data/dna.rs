@@ -0,0 +1,222 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::data::genes::validate_dna_genes;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemIndex, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+
+/// The portrait DNA in `common/dna_data`, which the `dna` field of historical characters
+/// refers to so that they have a fixed appearance.
+#[derive(Clone, Debug, Default)]
+pub struct DnaData {
+    dna: FnvHashMap<String, Dna>,
+    /// The game's DNA entries, if they were indexed instead of loaded
+    index: ItemIndex,
+}
+
+impl DnaData {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.dna.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "dna");
+            }
+        }
+        self.dna
+            .insert(key.to_string(), Dna::new(key, block.clone()));
+    }
+
+    /// Index the game's items instead of loading them, because they don't need validating
+    pub fn index_game_files(&mut self) {
+        self.index.enable();
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.dna.contains_key(key) || self.index.contains(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.dna.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for DnaData {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.dna
+            .get(key)
+            .map(as_source)
+            .or_else(|| self.index.get(key).map(as_source))
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        let indexed = self
+            .index
+            .values()
+            .filter(|item| !self.dna.contains_key(item.key().as_str()));
+        Box::new(
+            self.dna
+                .values()
+                .map(as_source)
+                .chain(indexed.map(as_source)),
+        )
+    }
+}
+
+impl FileHandler for DnaData {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/dna_data")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+        if self.index.wants(entry) {
+            self.index.index_file(entry, fullpath);
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Dna {
+    key: Token,
+    block: Block,
+}
+
+impl Dna {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        vd.req_field("portrait_info");
+        vd.field_validated_block("portrait_info", |b, data| {
+            let mut vd = Validator::new(b, data);
+            vd.req_field("genes");
+            vd.field_validated_block("genes", validate_dna_genes);
+        });
+        vd.field_bool("enabled");
+    }
+}
+
+impl ItemSource for Dna {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// The portraits of the characters shown on the bookmark screen, in `common/bookmark_portraits`
+#[derive(Clone, Debug, Default)]
+pub struct BookmarkPortraits {
+    portraits: FnvHashMap<String, BookmarkPortrait>,
+}
+
+impl BookmarkPortraits {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.portraits.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "bookmark portrait");
+            }
+        }
+        self.portraits
+            .insert(key.to_string(), BookmarkPortrait::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.portraits.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.portraits.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for BookmarkPortraits {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.portraits.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.portraits.values().map(as_source))
+    }
+}
+
+impl FileHandler for BookmarkPortraits {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/bookmark_portraits")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BookmarkPortrait {
+    key: Token,
+    block: Block,
+}
+
+impl BookmarkPortrait {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        vd.req_field("type");
+        vd.field_choice("type", &["male", "female", "boy", "girl"]);
+        vd.field_value_item("id", Item::Character);
+        vd.field_numeric("age");
+        vd.req_field("genes");
+        vd.field_validated_block("genes", validate_dna_genes);
+        // These are the same as in the portrait files in `gfx/portraits`
+        vd.field_block("override");
+        vd.field_block("entity");
+        vd.field_value("tags");
+    }
+}
+
+impl ItemSource for BookmarkPortrait {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
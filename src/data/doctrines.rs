@@ -0,0 +1,145 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::icons::{validate_icon_bv, verify_icon, IconKind};
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::modif::{validate_modifs, ModifKinds};
+use crate::pdxfile::PdxFile;
+use crate::schema::{validate_schema, Field, Schema};
+use crate::scopes::Scopes;
+use crate::token::Token;
+
+/// The fields of a doctrine category that are blocks but not doctrines
+const CATEGORY_BLOCKS: &[&str] = &["is_available_on_create"];
+
+/// The fields of a doctrine that are evaluated for the faith
+const DOCTRINE_FIELDS: &Schema = &[
+    ("piety_cost", Field::Value),
+    ("is_shown", Field::Trigger(false)),
+    ("can_pick", Field::Trigger(true)),
+    ("name", Field::Desc),
+    ("desc", Field::Desc),
+    ("visible", Field::Bool),
+];
+
+/// The doctrines and tenets in `common/religion/doctrines`. They are defined inside their
+/// categories, such as `doctrine_core_tenets`, which decide how many can be picked.
+#[derive(Clone, Debug, Default)]
+pub struct Doctrines {
+    doctrines: FnvHashMap<String, Doctrine>,
+    /// The parameters that the doctrines set, for `has_doctrine_parameter`
+    parameters: FnvHashMap<String, Token>,
+}
+
+impl Doctrines {
+    fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.doctrines.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "doctrine");
+            }
+        }
+        if let Some(parameters) = block.get_field_block("parameters") {
+            for (parameter, _) in parameters.get_assignments() {
+                self.parameters
+                    .insert(parameter.to_string(), parameter.clone());
+            }
+        }
+        self.doctrines
+            .insert(key.to_string(), Doctrine::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.doctrines.contains_key(key)
+    }
+
+    pub fn parameter_exists(&self, key: &str) -> bool {
+        self.parameters.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.doctrines.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for Doctrines {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.doctrines.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.doctrines.values().map(as_source))
+    }
+}
+
+impl FileHandler for Doctrines {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/religion/doctrines")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (_, block) in block.iter_pure_definitions_warn() {
+            for (key, block) in block.iter_pure_definitions() {
+                if !CATEGORY_BLOCKS.contains(&key.as_str()) {
+                    self.load_item(key.clone(), block);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Doctrine {
+    key: Token,
+    block: Block,
+}
+
+impl Doctrine {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        let mut sc = ScopeContext::new_root(Scopes::Faith, self.key.clone());
+
+        // Without an `icon`, the game uses the icon named after the doctrine
+        if !vd.field_validated_bvs("icon", |bv, data| {
+            validate_icon_bv(IconKind::Doctrine, bv, data, &mut sc);
+        }) {
+            verify_icon(IconKind::Doctrine, self.key.as_str(), &self.key, data);
+        }
+        vd.field_block("parameters");
+        validate_schema(&mut vd, DOCTRINE_FIELDS, &mut sc);
+        if let Some(block) = vd.field_block("character_modifier") {
+            let vd = Validator::new(block, data);
+            let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+            validate_modifs(block, data, ModifKinds::Character, &mut sc, vd);
+        }
+    }
+}
+
+impl ItemSource for Doctrine {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -7,12 +7,15 @@ use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
 use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemIndex, ItemSource};
 use crate::pdxfile::PdxFile;
 use crate::token::Token;
 
 #[derive(Clone, Debug, Default)]
 pub struct Dynasties {
     dynasties: FnvHashMap<String, Dynasty>,
+    /// The game's dynasties, when they are only indexed
+    index: ItemIndex,
 }
 
 impl Dynasties {
@@ -26,8 +29,13 @@ impl Dynasties {
             .insert(key.to_string(), Dynasty::new(key.clone(), block.clone()));
     }
 
+    /// Index the game's items instead of loading them, because they don't need validating
+    pub fn index_game_files(&mut self) {
+        self.index.enable();
+    }
+
     pub fn exists(&self, key: &str) -> bool {
-        self.dynasties.contains_key(key)
+        self.dynasties.contains_key(key) || self.index.contains(key)
     }
 
     pub fn validate(&self, data: &Everything) {
@@ -37,6 +45,28 @@ impl Dynasties {
     }
 }
 
+impl ItemDb for Dynasties {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.dynasties
+            .get(key)
+            .map(as_source)
+            .or_else(|| self.index.get(key).map(as_source))
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        let indexed = self
+            .index
+            .values()
+            .filter(|item| !self.dynasties.contains_key(item.key().as_str()));
+        Box::new(
+            self.dynasties
+                .values()
+                .map(as_source)
+                .chain(indexed.map(as_source)),
+        )
+    }
+}
+
 impl FileHandler for Dynasties {
     fn subpath(&self) -> PathBuf {
         PathBuf::from("common/dynasties")
@@ -46,6 +76,10 @@ impl FileHandler for Dynasties {
         if !entry.filename().to_string_lossy().ends_with(".txt") {
             return;
         }
+        if self.index.wants(entry) {
+            self.index.index_file(entry, fullpath);
+            return;
+        }
 
         let block = match PdxFile::read(entry, fullpath) {
             Some(block) => block,
@@ -77,6 +111,16 @@ impl Dynasty {
         vd.field_value_item("prefix", Item::Localization);
         vd.field_value_item("motto", Item::Localization);
         vd.field_value_item("culture", Item::Culture);
-        vd.field_value("forced_coa_religiongroup");
+        vd.field_value_item("forced_coa_religiongroup", Item::ReligiousFamily);
+    }
+}
+
+impl ItemSource for Dynasty {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
     }
 }
@@ -1,26 +1,27 @@
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
+use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 
 use crate::block::validator::Validator;
-use crate::block::{Block, BlockOrValue, DefinitionItem};
+use crate::block::{Block, BlockOrValue, Comparator, DefinitionItem};
 use crate::context::ScopeContext;
 use crate::data::scripted_effects::Effect;
 use crate::data::scripted_triggers::Trigger;
 use crate::desc::validate_desc;
-use crate::effect::{validate_effect, validate_normal_effect};
+use crate::effect::{validate_effect, validate_named_effect, validate_normal_effect};
 use crate::errorkey::ErrorKey;
-use crate::errors::{error, error_info, warn, warn_info};
+use crate::errors::{advice, advice_info, error, error_info, warn, warn2, warn_info};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
 use crate::item::Item;
 use crate::pdxfile::PdxFile;
 use crate::scopes::{scope_from_snake_case, Scopes};
-use crate::token::Token;
+use crate::token::{Loc, Token};
 use crate::trigger::{validate_normal_trigger, validate_target};
 use crate::validate::{
-    validate_cooldown, validate_theme_background, validate_theme_icon, validate_theme_sound,
-    ListType,
+    validate_ai_chance, validate_cooldown, validate_modifiers_with_base, validate_theme_background,
+    validate_theme_icon, validate_theme_sound, ListType,
 };
 
 #[derive(Clone, Debug, Default)]
@@ -32,6 +33,10 @@ pub struct Events {
     // These events are known to exist, so don't warn abour them not being found,
     // but they had errors on validation.
     error_events: FnvHashMap<String, Token>,
+
+    // The events named by `trigger_event` anywhere in script, by the location of the call.
+    // Filled in during validation.
+    fired: RefCell<FnvHashMap<Loc, String>>,
 }
 
 impl Events {
@@ -109,6 +114,118 @@ impl Events {
         self.events.contains_key(key) || self.error_events.contains_key(key)
     }
 
+    /// Remember a `trigger_event` call, for `check_scope_names`
+    pub fn note_fired(&self, id: &Token) {
+        self.fired
+            .borrow_mut()
+            .insert(id.loc.clone(), id.to_string());
+    }
+
+    /// Check that the events fired from other events get the named scopes they use.
+    /// The saved scopes that an event has are the ones saved by the events that fire it,
+    /// and the ones those got from the events that fired them, and so on. When an event
+    /// is also fired from somewhere else, such as a scripted effect or an `on_action` whose
+    /// scopes are not known, nothing is reported for the events it fires, because it may
+    /// have been given any scope.
+    pub fn check_scope_names(&self, data: &Everything) {
+        let mut events = self.events.values().collect::<Vec<&Event>>();
+        events.sort_unstable_by_key(|item| &item.key.loc);
+        let index = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| (event.key.as_str(), i))
+            .collect::<FnvHashMap<_, _>>();
+        let uses = events
+            .iter()
+            .map(|event| ScopeUse::new(&event.block))
+            .collect::<Vec<_>>();
+
+        // Which events are fired from outside the events
+        let mut known_calls = FnvHashSet::default();
+        for usage in &uses {
+            for call in &usage.calls {
+                known_calls.insert(&call.loc);
+            }
+        }
+        let mut fired_elsewhere = FnvHashSet::default();
+        for (loc, id) in self.fired.borrow().iter() {
+            if !known_calls.contains(loc) {
+                fired_elsewhere.insert(id.clone());
+            }
+        }
+
+        // The names that may be set when each event starts, or `None` if it could be any
+        let mut inbound = events
+            .iter()
+            .map(|event| {
+                let key = event.key.as_str();
+                let seeded = data.on_actions.event_scope_names(key);
+                let fired = uses
+                    .iter()
+                    .any(|usage| usage.calls.iter().any(|c| c.is(key)));
+                if fired_elsewhere.contains(key)
+                    || data.on_actions.fired_from_unknown(key)
+                    || (seeded.is_none() && !fired)
+                {
+                    None
+                } else {
+                    Some(
+                        seeded
+                            .unwrap_or_default()
+                            .into_iter()
+                            .collect::<FnvHashSet<_>>(),
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (caller, usage) in uses.iter().enumerate() {
+                for call in &usage.calls {
+                    let Some(&target) = index.get(call.as_str()) else {
+                        continue;
+                    };
+                    match usage.passed_on(inbound[caller].as_ref()) {
+                        None => {
+                            if inbound[target].is_some() {
+                                inbound[target] = None;
+                                changed = true;
+                            }
+                        }
+                        Some(names) => {
+                            if let Some(target_names) = &mut inbound[target] {
+                                for name in names {
+                                    changed |= target_names.insert(name);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for (caller, usage) in uses.iter().enumerate() {
+            let Some(available) = usage.passed_on(inbound[caller].as_ref()) else {
+                continue;
+            };
+            for call in &usage.calls {
+                let Some(&target) = index.get(call.as_str()) else {
+                    continue;
+                };
+                for (name, token) in uses[target].needed() {
+                    if !available.contains(name) {
+                        let msg = format!(
+                            "event `{call}` uses `scope:{name}`, which is not set when it is fired here"
+                        );
+                        warn2(*call, ErrorKey::Scopes, &msg, *token, "it is used here");
+                    }
+                }
+            }
+        }
+    }
+
     pub fn validate(&self, data: &Everything) {
         let mut vec = self.effects.values().collect::<Vec<&Effect>>();
         vec.sort_unstable_by_key(|item| &item.key.loc);
@@ -233,6 +350,7 @@ impl Event {
         Self { key, block }
     }
 
+    #[allow(clippy::too_many_lines)]
     pub fn validate(&self, data: &Everything) {
         let mut vd = Validator::new(&self.block, data);
 
@@ -249,6 +367,10 @@ impl Event {
                 self.block.get_field_value("type").unwrap().clone(),
             );
         }
+        // The on_actions that fire this event may say more about its scopes
+        if let Some(on_action_sc) = data.on_actions.event_context(self.key.as_str()) {
+            sc = on_action_sc;
+        }
         if let Some(token) = vd.field_value("scope") {
             if let Some(scope) = scope_from_snake_case(token.as_str()) {
                 sc = ScopeContext::new_root(scope, token.clone());
@@ -257,6 +379,11 @@ impl Event {
             }
         }
 
+        // The event's trigger guards all the effects in the event
+        if let Some(b) = self.block.get_field_block("trigger") {
+            sc.add_guards(b);
+        }
+
         vd.field_bool("hidden");
         vd.field_bool("major");
         vd.field_validated_block("major_trigger", |b, data| {
@@ -264,7 +391,7 @@ impl Event {
         });
 
         vd.field_validated_block("immediate", |b, data| {
-            validate_normal_effect(b, data, &mut sc, true);
+            validate_named_effect("immediate", b, data, &mut sc, true);
         });
         vd.field_validated_block("trigger", |b, data| {
             validate_normal_trigger(b, data, &mut sc, false);
@@ -272,7 +399,9 @@ impl Event {
         vd.field_validated_block("on_trigger_fail", |b, data| {
             validate_normal_effect(b, data, &mut sc, false);
         });
-        vd.field_block("weight_multiplier"); // modifier
+        vd.field_validated_block("weight_multiplier", |b, data| {
+            validate_modifiers_with_base(b, data, &mut sc);
+        });
 
         if let Some(bv) = vd.field("title") {
             validate_desc(bv, data, &mut sc);
@@ -332,11 +461,23 @@ impl Event {
         if !self.block.get_field_bool("hidden").unwrap_or(false) {
             vd.req_field("option");
         }
-        vd.field_validated_blocks("option", |b, data| validate_event_option(b, data, &mut sc));
+        vd.field_validated_blocks("option", |b, data| {
+            validate_event_option(b, data, &mut sc, evtype);
+        });
+        self.check_options_available();
+        let options = self.block.get_field_blocks("option");
+        if evtype != "fullscreen_event" && options.len() > data.limits.event_options {
+            let msg = format!("event has {} options", options.len());
+            let info = format!(
+                "only {} fit in the event window, the rest will overflow it",
+                data.limits.event_options
+            );
+            advice_info(&self.key, ErrorKey::TooManyOptions, &msg, &info);
+        }
 
         vd.field_validated_block("after", |b, data| {
             // TODO: check if this block is tooltipped
-            validate_normal_effect(b, data, &mut sc, false);
+            validate_named_effect("after", b, data, &mut sc, false);
         });
         vd.field_validated_block("cooldown", |b, data| validate_cooldown(b, data, &mut sc));
         vd.field_value("soundeffect");
@@ -345,13 +486,62 @@ impl Event {
         vd.field("widget");
         vd.field_block("widgets");
     }
+
+    /// Warn if the event has options but none of them can ever be shown.
+    fn check_options_available(&self) {
+        let options = self.block.get_field_blocks("option");
+        let never = |b: &Block| {
+            b.get_field_block("trigger")
+                .and_then(|trigger| trigger.get_field_value("always"))
+                .is_some_and(|always| always.is("no"))
+        };
+        let fallback = |b: &Block| b.get_field_bool("fallback").unwrap_or(false);
+        if !options.is_empty() && options.iter().all(|b| never(b) && !fallback(b)) {
+            warn_info(
+                &self.key,
+                ErrorKey::Logic,
+                "none of the options of this event can ever be chosen",
+                "every option has `trigger = { always = no }` and none is a `fallback`",
+            );
+        }
+    }
 }
 
-fn validate_event_option(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+/// The fields of an event option that are not effects
+const OPTION_PROPERTIES: &[&str] = &[
+    "name",
+    "trigger",
+    "show_as_unavailable",
+    "flavor",
+    "trait",
+    "skill",
+    "ai_chance",
+    "exclusive",
+    "is_cancel_option",
+    "fallback",
+    "highlight_portrait",
+    "show_unlock_reason",
+    "add_internal_flag",
+    "custom_tooltip",
+    "reply",
+];
+
+fn validate_event_option(block: &Block, data: &Everything, sc: &mut ScopeContext, evtype: &str) {
     // TODO: warn if they use desc, first_valid, random_valid, or triggered_desc directly
     // in the name or tooltip.
 
     let mut vd = Validator::new(block, data);
+    let has_effects = block.iter_items().any(|(k, _, _)| {
+        k.as_ref()
+            .is_none_or(|key| !OPTION_PROPERTIES.contains(&key.as_str()))
+    });
+    if !has_effects && block.get_key("name").is_none() && block.get_key("ai_chance").is_none() {
+        advice(
+            block,
+            ErrorKey::EmptyBlock,
+            "option has no name, no effects, and no ai_chance",
+        );
+    }
     vd.field_validated_bvs("name", |bv, data| match bv {
         BlockOrValue::Token(t) => {
             data.localization.verify_exists(t);
@@ -396,8 +586,25 @@ fn validate_event_option(block: &Block, data: &Everything, sc: &mut ScopeContext
     vd.field_values_items("trait", Item::Trait);
     vd.field_values_items("skill", Item::Skill);
 
-    // TODO: check what this is. script value? modifier?
-    vd.field("ai_chance");
+    vd.field_validated_bv("ai_chance", |bv, data| {
+        validate_ai_chance(bv, data, sc);
+    });
+    if let Some(b) = block.get_field_block("ai_chance") {
+        // A base of 0 with nothing to change it means the option is dead weight for the AI
+        if let Some(base) = b.get_field_value("base") {
+            if base.is("0")
+                && b.iter_items()
+                    .all(|(k, _, _)| matches!(k, Some(k) if k.is("base")))
+            {
+                advice_info(
+                    block.get_key("ai_chance").unwrap(),
+                    ErrorKey::Logic,
+                    "ai will never choose this option",
+                    "`ai_chance` has a base of 0 and no modifiers",
+                );
+            }
+        }
+    }
 
     // TODO: check what this does.
     vd.field_bool("exclusive");
@@ -415,6 +622,18 @@ fn validate_event_option(block: &Block, data: &Everything, sc: &mut ScopeContext
 
     vd.field_bool("show_unlock_reason"); // TODO: what does this do?
 
+    // Marks the option as special or dangerous in the interface
+    vd.field_choice("add_internal_flag", &["special", "dangerous"]);
+
+    // The replies to a letter are options of their own
+    if evtype == "letter_event" {
+        vd.field_validated_blocks("reply", |b, data| {
+            validate_event_option(b, data, sc, evtype);
+        });
+    } else {
+        vd.advice_field("reply", "only needed for letter_event");
+    }
+
     validate_effect("option", ListType::None, block, data, sc, vd, true);
 }
 
@@ -477,12 +696,14 @@ fn validate_triggered_outfit(block: &Block, data: &Everything, sc: &mut ScopeCon
 
 fn validate_portrait(v: &BlockOrValue, data: &Everything, sc: &mut ScopeContext) {
     match v {
-        BlockOrValue::Token(_) => (),
+        BlockOrValue::Token(t) => validate_target(t, data, sc, Scopes::Character),
         BlockOrValue::Block(b) => {
             let mut vd = Validator::new(b, data);
 
             vd.req_field("character");
-            vd.field_value("character");
+            if let Some(token) = vd.field_value("character") {
+                validate_target(token, data, sc, Scopes::Character);
+            }
             vd.field_validated_block("trigger", |b, data| {
                 validate_normal_trigger(b, data, sc, false);
             });
@@ -503,3 +724,111 @@ fn validate_portrait(v: &BlockOrValue, data: &Everything, sc: &mut ScopeContext)
         }
     }
 }
+
+/// How an event uses named scopes, worked out from its script
+struct ScopeUse<'a> {
+    /// The names it reads, with the first place each is read
+    reads: Vec<(&'a str, &'a Token)>,
+    /// The names it only reads after checking that they exist
+    optional: FnvHashSet<&'a str>,
+    /// The names it saves, including the temporary ones
+    saved: FnvHashSet<&'a str>,
+    /// The names it saves that are passed on to the events it fires
+    kept: FnvHashSet<&'a str>,
+    /// The ids of the events it fires
+    calls: Vec<&'a Token>,
+}
+
+impl<'a> ScopeUse<'a> {
+    fn new(block: &'a Block) -> Self {
+        let mut usage = ScopeUse {
+            reads: Vec::new(),
+            optional: FnvHashSet::default(),
+            saved: FnvHashSet::default(),
+            kept: FnvHashSet::default(),
+            calls: Vec::new(),
+        };
+        usage.scan(block);
+        usage
+    }
+
+    fn scan(&mut self, block: &'a Block) {
+        for (key, cmp, bv) in block.iter_items() {
+            if let Some(key) = key {
+                self.scan_key(key, *cmp, bv);
+                self.note_reads(key);
+            }
+            match bv {
+                BlockOrValue::Token(token) => self.note_reads(token),
+                BlockOrValue::Block(block) => self.scan(block),
+            }
+        }
+    }
+
+    fn scan_key(&mut self, key: &'a Token, cmp: Comparator, bv: &'a BlockOrValue) {
+        let value = match bv {
+            BlockOrValue::Token(token) => Some(token),
+            BlockOrValue::Block(block) => block.get_field_value("name"),
+        };
+        if key.is("save_scope_as") || key.is("save_scope_value_as") {
+            if let Some(name) = value {
+                self.saved.insert(name.as_str());
+                self.kept.insert(name.as_str());
+            }
+        } else if key.is("save_temporary_scope_as") || key.is("save_temporary_scope_value_as") {
+            if let Some(name) = value {
+                self.saved.insert(name.as_str());
+            }
+        } else if key.is("exists") {
+            if let Some(token) = bv.get_value() {
+                self.note_optional(token);
+            }
+        } else if key.is("trigger_event") {
+            match bv {
+                BlockOrValue::Token(token) => self.calls.push(token),
+                BlockOrValue::Block(block) => {
+                    if let Some(token) = block.get_field_value("id") {
+                        self.calls.push(token);
+                    }
+                }
+            }
+        }
+        if cmp == Comparator::QEq {
+            self.note_optional(key);
+        }
+    }
+
+    fn note_reads(&mut self, token: &'a Token) {
+        for part in token.as_str().split('.') {
+            if let Some(name) = part.strip_prefix("scope:") {
+                if !self.reads.iter().any(|(n, _)| *n == name) {
+                    self.reads.push((name, token));
+                }
+            }
+        }
+    }
+
+    fn note_optional(&mut self, token: &'a Token) {
+        if let Some(part) = token.as_str().split('.').next() {
+            if let Some(name) = part.strip_prefix("scope:") {
+                self.optional.insert(name);
+            }
+        }
+    }
+
+    /// The names that must be set by whoever fires this event
+    fn needed(&self) -> impl Iterator<Item = &(&'a str, &'a Token)> {
+        self.reads
+            .iter()
+            .filter(|(name, _)| !self.saved.contains(name) && !self.optional.contains(name))
+    }
+
+    /// The names that may be set for the events this one fires, given the ones that may be
+    /// set for this one
+    fn passed_on(&self, inbound: Option<&FnvHashSet<&'a str>>) -> Option<FnvHashSet<&'a str>> {
+        let mut names = inbound?.clone();
+        names.extend(self.kept.iter().copied());
+        names.extend(self.reads.iter().map(|(name, _)| *name));
+        Some(names)
+    }
+}
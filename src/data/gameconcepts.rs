@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 use crate::block::validator::Validator;
 use crate::block::{Block, DefinitionItem};
 use crate::errorkey::ErrorKey;
-use crate::errors::{error, error_info};
+use crate::errors::{error, error_info, warn2};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
@@ -42,6 +42,53 @@ impl GameConcepts {
         for item in self.concepts.values() {
             item.validate(data);
         }
+        self.validate_aliases();
+    }
+
+    /// An alias that belongs to two concepts, or is also a concept's own name, makes links
+    /// with that name open the wrong concept's page.
+    fn validate_aliases(&self) {
+        let mut concepts = self
+            .concepts
+            .iter()
+            .map(|(key, concept)| {
+                (
+                    key,
+                    concept.block.get_field_list("alias").unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        concepts.sort_by_key(|(key, _)| *key);
+        let mut seen: FnvHashMap<&str, &Token> = FnvHashMap::default();
+        for (key, aliases) in &concepts {
+            for alias in aliases {
+                if alias.is(key) {
+                    continue;
+                }
+                if let Some(other) = self.concepts.get(alias.as_str()) {
+                    let msg = format!("alias `{alias}` is also the name of a game concept");
+                    warn2(
+                        alias,
+                        ErrorKey::NameConflict,
+                        &msg,
+                        &other.key,
+                        "that concept is here",
+                    );
+                } else if let Some(other) = seen.get(alias.as_str()) {
+                    let msg = format!("alias `{alias}` belongs to more than one game concept");
+                    warn2(
+                        alias,
+                        ErrorKey::NameConflict,
+                        &msg,
+                        *other,
+                        "the other alias is here",
+                    );
+                }
+            }
+            for alias in aliases {
+                seen.entry(alias.as_str()).or_insert(alias);
+            }
+        }
     }
 }
 
@@ -0,0 +1,248 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::{Block, BlockOrValue};
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error_info, vanilla_shown, warn};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+
+/// The kinds of genes, by the block in `common/genes` they are defined in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GeneKind {
+    /// Colors like hair and eye color, set by two coordinates in the color map
+    Color,
+    /// Face and body shapes, set by a template and its strength
+    Morph,
+    /// Clothes, hair styles and the like, set by a template and which of its accessories
+    Accessory,
+}
+
+/// The genes in `common/genes` that the portrait DNA is made of
+#[derive(Clone, Debug, Default)]
+pub struct Genes {
+    genes: FnvHashMap<String, Gene>,
+}
+
+impl Genes {
+    fn load_item(&mut self, key: Token, block: &Block, kind: GeneKind) {
+        if let Some(other) = self.genes.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "gene");
+            }
+        }
+        self.genes
+            .insert(key.to_string(), Gene::new(key, block.clone(), kind));
+    }
+
+    fn load_category(&mut self, key: &Token, block: &Block) {
+        let kind = if key.is("color_genes") {
+            GeneKind::Color
+        } else if key.is("morph_genes") {
+            GeneKind::Morph
+        } else if key.is("accessory_genes") {
+            GeneKind::Accessory
+        } else if key.is("special_genes") {
+            for (key, block) in block.iter_pure_definitions_warn() {
+                self.load_category(key, block);
+            }
+            return;
+        } else {
+            let msg = format!("unknown gene category `{key}`");
+            warn(key, ErrorKey::Validation, &msg);
+            return;
+        };
+        for (key, block) in block.iter_pure_definitions() {
+            self.load_item(key.clone(), block, kind);
+        }
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.genes.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Gene> {
+        self.genes.get(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.genes.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for Genes {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.genes.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.genes.values().map(as_source))
+    }
+}
+
+impl FileHandler for Genes {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/genes")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_category(key, block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Gene {
+    key: Token,
+    block: Block,
+    pub kind: GeneKind,
+}
+
+impl Gene {
+    pub fn new(key: Token, block: Block, kind: GeneKind) -> Self {
+        Self { key, block, kind }
+    }
+
+    /// The templates of a morph or accessory gene are the blocks in it that have an `index`
+    fn templates(&self) -> impl Iterator<Item = (&Token, &Block)> {
+        self.block
+            .iter_pure_definitions()
+            .filter(|(_, block)| block.get_field_value("index").is_some())
+    }
+
+    pub fn has_template(&self, name: &str) -> bool {
+        self.templates().any(|(key, _)| key.is(name))
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        if self.kind != GeneKind::Accessory {
+            return;
+        }
+        // The accessories are listed per age and sex with their weights, or the sex refers
+        // to another one to share its list
+        for (_, block) in self.templates() {
+            for sex in ["male", "female", "boy", "girl"] {
+                if let Some(BlockOrValue::Block(block)) = block.get_field(sex) {
+                    for (_, token) in block.get_assignments() {
+                        if !token.is("empty") {
+                            data.verify_exists(Item::Accessory, token);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ItemSource for Gene {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// The portrait accessories in `gfx/portraits/accessories`, which the accessory genes pick from
+#[derive(Clone, Debug, Default)]
+pub struct Accessories {
+    accessories: FnvHashMap<String, Token>,
+}
+
+impl Accessories {
+    pub fn exists(&self, key: &str) -> bool {
+        self.accessories.contains_key(key)
+    }
+}
+
+impl FileHandler for Accessories {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("gfx/portraits/accessories")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        // Like the asset files, the game's accessories are only needed for their names
+        if entry.kind() != FileKind::Mod && !vanilla_shown() {
+            for item in PdxFile::scan(entry, fullpath).into_iter().flatten() {
+                self.accessories.insert(item.key.to_string(), item.key);
+            }
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, _) in block.iter_pure_definitions_warn() {
+            self.accessories.insert(key.to_string(), key.clone());
+        }
+    }
+}
+
+/// A gene in portrait DNA has a dominant and a recessive pair of values
+const DNA_VALUE_COUNT: usize = 4;
+
+/// Validate the `genes` block of portrait DNA, such as in `common/dna_data` and
+/// `common/bookmark_portraits`. Color genes have two pairs of color map coordinates, and
+/// the others have two pairs of a template name and its strength.
+/// Example: `gene_chin_height = { "chin_height_pos" 120 "chin_height_pos" 120 }`
+pub fn validate_dna_genes(block: &Block, data: &Everything) {
+    for (key, _, bv) in block.iter_items() {
+        let Some(key) = key else {
+            warn(bv, ErrorKey::Validation, "expected `gene = { ... }`");
+            continue;
+        };
+        let Some(gene) = data.genes.get(key.as_str()) else {
+            data.verify_exists(Item::Gene, key);
+            continue;
+        };
+        let Some(block) = bv.expect_block() else {
+            continue;
+        };
+        let values = block.get_values();
+        if values.len() != DNA_VALUE_COUNT || values.len() != block.iter_items().len() {
+            let msg = format!("expected {DNA_VALUE_COUNT} values");
+            let info = match gene.kind {
+                GeneKind::Color => "two pairs of color coordinates",
+                _ => "a template and its strength, twice",
+            };
+            error_info(block, ErrorKey::Validation, &msg, info);
+            continue;
+        }
+        for (i, value) in values.iter().enumerate() {
+            if i % 2 == 0 && gene.kind != GeneKind::Color {
+                if !gene.has_template(value.as_str()) {
+                    let msg = format!("gene {} has no template `{}`", gene.key, value);
+                    error(value, ErrorKey::MissingItem, &msg);
+                }
+            } else if !value
+                .as_str()
+                .parse::<i64>()
+                .is_ok_and(|v| (0..=255).contains(&v))
+            {
+                error(value, ErrorKey::Range, "expected a number from 0 to 255");
+            }
+        }
+    }
+}
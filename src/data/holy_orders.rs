@@ -0,0 +1,121 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::data::men_at_arms_types::validate_maa_composition;
+use crate::data::mercenaries::validate_army_cost;
+use crate::desc::validate_desc;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+#[derive(Clone, Debug, Default)]
+pub struct HolyOrders {
+    orders: FnvHashMap<String, HolyOrder>,
+}
+
+impl HolyOrders {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.orders.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "holy order");
+            }
+        }
+        self.orders
+            .insert(key.to_string(), HolyOrder::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.orders.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.orders.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for HolyOrders {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.orders.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.orders.values().map(as_source))
+    }
+}
+
+impl FileHandler for HolyOrders {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/holy_orders")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HolyOrder {
+    key: Token,
+    block: Block,
+}
+
+impl HolyOrder {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        // Holy orders belong to a faith; the leader and patron are characters.
+        let mut sc = ScopeContext::new_root(Scopes::Faith, self.key.clone());
+
+        vd.req_field("name");
+        vd.field_validated_bv("name", |bv, data| validate_desc(bv, data, &mut sc));
+        vd.req_field("composition");
+        vd.field_validated_block("composition", |b, data| {
+            validate_maa_composition(b, data, &mut sc);
+        });
+        vd.field_validated_bv("cost", |bv, data| validate_army_cost(bv, data, &mut sc));
+        vd.field_script_value("opinion_of_liege", &mut sc);
+        vd.field_validated_block("can_create", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        for field in ["leader", "patron"] {
+            vd.field_validated_block(field, |b, data| {
+                let key = self.block.get_key(field).unwrap();
+                let mut sc = ScopeContext::new_root(Scopes::Character, key.clone());
+                validate_normal_trigger(b, data, &mut sc, false);
+            });
+        }
+    }
+}
+
+impl ItemSource for HolyOrder {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -3,16 +3,21 @@ use std::path::{Path, PathBuf};
 
 use crate::block::validator::Validator;
 use crate::block::Block;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn2;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
 use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemIndex, ItemSource};
 use crate::pdxfile::PdxFile;
 use crate::token::Token;
 
 #[derive(Clone, Debug, Default)]
 pub struct Houses {
     houses: FnvHashMap<String, House>,
+    /// The game's houses, if they were indexed instead of loaded
+    index: ItemIndex,
 }
 
 impl Houses {
@@ -26,8 +31,31 @@ impl Houses {
             .insert(key.to_string(), House::new(key.clone(), block.clone()));
     }
 
+    /// Index the game's items instead of loading them, because they don't need validating
+    pub fn index_game_files(&mut self) {
+        self.index.enable();
+    }
+
     pub fn exists(&self, key: &str) -> bool {
-        self.houses.contains_key(key)
+        self.houses.contains_key(key) || self.index.contains(key)
+    }
+
+    /// Warn if `house` is known but does not belong to `dynasty`.
+    pub fn verify_dynasty(&self, house: &Token, dynasty: &Token) {
+        if let Some(block) = self.get_item(house.as_str()).and_then(ItemSource::block) {
+            if let Some(own) = block.get_field_value("dynasty") {
+                if own.as_str() != dynasty.as_str() {
+                    let msg = format!("house {house} belongs to dynasty {own}, not {dynasty}");
+                    warn2(
+                        house,
+                        ErrorKey::Conflict,
+                        &msg,
+                        own,
+                        "the house's dynasty is set here",
+                    );
+                }
+            }
+        }
     }
 
     pub fn validate(&self, data: &Everything) {
@@ -37,6 +65,28 @@ impl Houses {
     }
 }
 
+impl ItemDb for Houses {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.houses
+            .get(key)
+            .map(as_source)
+            .or_else(|| self.index.get(key).map(as_source))
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        let indexed = self
+            .index
+            .values()
+            .filter(|item| !self.houses.contains_key(item.key().as_str()));
+        Box::new(
+            self.houses
+                .values()
+                .map(as_source)
+                .chain(indexed.map(as_source)),
+        )
+    }
+}
+
 impl FileHandler for Houses {
     fn subpath(&self) -> PathBuf {
         PathBuf::from("common/dynasty_houses")
@@ -46,6 +96,10 @@ impl FileHandler for Houses {
         if !entry.filename().to_string_lossy().ends_with(".txt") {
             return;
         }
+        if self.index.wants(entry) {
+            self.index.index_file(entry, fullpath);
+            return;
+        }
 
         let block = match PdxFile::read(entry, fullpath) {
             Some(block) => block,
@@ -79,6 +133,16 @@ impl House {
         vd.field_value_item("prefix", Item::Localization);
         vd.field_value_item("motto", Item::Localization);
         vd.field_value_item("dynasty", Item::Dynasty);
-        vd.field_value("forced_coa_religiongroup");
+        vd.field_value_item("forced_coa_religiongroup", Item::ReligiousFamily);
+    }
+}
+
+impl ItemSource for House {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
     }
 }
@@ -0,0 +1,149 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::effect::validate_normal_effect;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::tables::datafunctions::DATAFUNCTION_SCOPES;
+use crate::token::Token;
+
+/// The important actions in `common/important_actions`, which are the alerts and suggested
+/// actions shown at the top of the screen. Script creates them with
+/// `try_create_important_action`, passing the scopes that the action's texts and click
+/// effect use.
+#[derive(Clone, Debug, Default)]
+pub struct ImportantActions {
+    actions: FnvHashMap<String, ImportantAction>,
+}
+
+impl ImportantActions {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.actions.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "important action");
+            }
+        }
+        self.actions
+            .insert(key.to_string(), ImportantAction::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.actions.contains_key(key)
+    }
+
+    /// The scope types that the action's localization needs for the scopes passed to it, by
+    /// argument name, each with the data function call that needs it.
+    pub fn argument_scopes<'a>(
+        &self,
+        key: &str,
+        data: &'a Everything,
+    ) -> Vec<(&'a Token, Scopes, &'a Token)> {
+        match self.actions.get(key) {
+            Some(action) => action.argument_scopes(data),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.actions.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for ImportantActions {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.actions.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.actions.values().map(as_source))
+    }
+}
+
+impl FileHandler for ImportantActions {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/important_actions")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ImportantAction {
+    key: Token,
+    block: Block,
+}
+
+impl ImportantAction {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    /// Work out the scope types of the arguments from the data functions that the title and
+    /// description call on them, like `[actor.GetFirstName]` needing a character.
+    fn argument_scopes<'a>(&self, data: &'a Everything) -> Vec<(&'a Token, Scopes, &'a Token)> {
+        let mut vec = Vec::new();
+        for key in [self.key.to_string(), format!("{}_desc", self.key)] {
+            for (name, function) in data.localization.scope_functions(&key) {
+                if let Some((_, scopes)) = DATAFUNCTION_SCOPES.iter().find(|(f, _)| function.is(f))
+                {
+                    vec.push((name, Scopes::from_bits_truncate(*scopes), function));
+                }
+            }
+        }
+        vec
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        data.localization.verify_exists(&self.key);
+
+        vd.field_numeric("priority");
+        vd.field_value("icon");
+        vd.field_bool("is_dangerous");
+        vd.field_bool("combine_into_one");
+        vd.field_bool("unimportant");
+        // The player is the root of both
+        vd.field_validated_block("check_create_action", |b, data| {
+            let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+            validate_normal_effect(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("effect", |b, data| {
+            let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+            for (name, scopes, _) in self.argument_scopes(data) {
+                sc.define_name(name.as_str(), scopes);
+            }
+            validate_normal_effect(b, data, &mut sc, false);
+        });
+    }
+}
+
+impl ItemSource for ImportantAction {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
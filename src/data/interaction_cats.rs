@@ -4,14 +4,16 @@ use std::path::{Path, PathBuf};
 use crate::block::validator::Validator;
 use crate::block::Block;
 use crate::errorkey::ErrorKey;
-use crate::errors::error;
+use crate::errors::{error, warn2};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
 use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
 use crate::pdxfile::PdxFile;
 use crate::token::Token;
 
+/// The groups of interactions in the character menu, in `common/character_interaction_categories`
 #[derive(Clone, Debug, Default)]
 pub struct InteractionCategories {
     categories: FnvHashMap<String, Category>,
@@ -36,6 +38,35 @@ impl InteractionCategories {
         for item in self.categories.values() {
             item.validate(data);
         }
+
+        // The interactions without a category go in the default one
+        let mut defaults = self
+            .categories
+            .values()
+            .filter(|item| item.block.get_field_bool("default") == Some(true))
+            .map(|item| &item.key)
+            .collect::<Vec<_>>();
+        defaults.sort_by(|a, b| a.loc.cmp(&b.loc));
+        if defaults.len() > 1 {
+            let msg = "more than one interaction category has `default = yes`";
+            warn2(
+                defaults[1],
+                ErrorKey::Conflict,
+                msg,
+                defaults[0],
+                "the other one is here",
+            );
+        }
+    }
+}
+
+impl ItemDb for InteractionCategories {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.categories.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.categories.values().map(as_source))
     }
 }
 
@@ -61,7 +92,10 @@ impl FileHandler for InteractionCategories {
 
     fn finalize(&mut self) {
         let mut taken = vec![None; self.categories.len()];
-        for item in self.categories.values() {
+        // In file order, so that the later of two categories with the same index is reported
+        let mut items = self.categories.values().collect::<Vec<_>>();
+        items.sort_by(|a, b| a.key.loc.cmp(&b.key.loc));
+        for item in items {
             if let Some(index) = item.index {
                 if index >= (taken.len() as i64) || index < 0 {
                     error(
@@ -76,7 +110,7 @@ impl FileHandler for InteractionCategories {
                     taken[index as usize] = Some(&item.key);
                 }
             }
-            // if no index, the item will warn about that in validate
+            // if no index, the item will report that in validate
         }
     }
 }
@@ -96,8 +130,21 @@ impl Category {
 
     pub fn validate(&self, data: &Everything) {
         let mut vd = Validator::new(&self.block, data);
+        data.localization.verify_exists(&self.key);
+        // The index is the category's place in the character menu
+        vd.req_field("index");
         vd.field_integer("index");
         vd.field_value_item("desc", Item::Localization);
         vd.field_bool("default");
     }
 }
+
+impl ItemSource for Category {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
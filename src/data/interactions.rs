@@ -1,15 +1,21 @@
 use fnv::FnvHashMap;
 use std::path::{Path, PathBuf};
 
+use crate::block::validator::Validator;
 use crate::block::Block;
 use crate::context::ScopeContext;
-use crate::desc::validate_desc;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn2;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
+use crate::icons::{verify_icon, IconKind};
+use crate::item::Item;
 use crate::pdxfile::PdxFile;
+use crate::schema::{validate_schema, Field, Schema};
 use crate::scopes::Scopes;
 use crate::token::Token;
+use crate::validate::{validate_cooldown, validate_cost};
 
 #[derive(Clone, Debug, Default)]
 pub struct Interactions {
@@ -71,14 +77,16 @@ impl Interaction {
     }
 
     pub fn validate(&self, data: &Everything) {
-        // TODO: actually validate the fields
+        let mut vd = Validator::new(&self.block, data);
 
         // You're expected to use scope:actor and scope:recipient instead of root
         let mut sc = ScopeContext::new_root(Scopes::None, self.key.clone());
 
-        if let Some(name) = self.block.get_field_value("icon") {
-            let pathname = format!("gfx/interface/icons/character_interactions/{}.dds", name);
-            data.fileset.verify_exists_implied(&pathname, name);
+        if let Some(name) = vd.field_value("icon") {
+            verify_icon(IconKind::CharacterInteraction, name.as_str(), name, data);
+        }
+        if let Some(category) = vd.field_value("category") {
+            verify_category(category, data);
         }
 
         // TODO: The ai_ name check is a heuristic. It would be better to check if the
@@ -97,52 +105,245 @@ impl Interaction {
                 self.block.get_key("extra_icon").unwrap(),
             );
         }
-        if let Some(pathname) = self.block.get_field_value("extra_icon") {
-            data.fileset.verify_exists(pathname);
-        }
-        if let Some(desc) = self.block.get_field("desc") {
-            validate_desc(desc, data, &mut sc);
-        }
-        if let Some(desc) = self.block.get_field("prompt") {
-            validate_desc(desc, data, &mut sc);
-        }
-        if let Some(desc) = self.block.get_field("notification_text") {
-            validate_desc(desc, data, &mut sc);
-        }
-        if let Some(desc) = self.block.get_field("on_decline_summary") {
-            validate_desc(desc, data, &mut sc);
-        }
-        if let Some(key) = self.block.get_field_value("answer_accept_key") {
-            data.localization.verify_exists(key);
-        }
-        if let Some(key) = self.block.get_field_value("answer_reject_key") {
-            data.localization.verify_exists(key);
-        }
-        if let Some(key) = self.block.get_field_value("highlighted_reason") {
-            data.localization.verify_exists(key);
-        }
-        if let Some(key) = self.block.get_field_value("options_heading") {
-            data.localization.verify_exists(key);
-        }
-        if let Some(key) = self.block.get_field_value("pre_answer_maybe_breakdown_key") {
-            data.localization.verify_exists(key);
-        }
-        if let Some(key) = self.block.get_field_value("pre_answer_maybe_key") {
-            data.localization.verify_exists(key);
-        }
-        if let Some(key) = self.block.get_field_value("pre_answer_no_key") {
-            data.localization.verify_exists(key);
+
+        self.validate_loc_chains(data);
+
+        validate_schema(&mut vd, INTERACTION_FIELDS, &mut sc);
+    }
+
+    /// Check the texts that the game looks up by key when the field for them is not given.
+    /// This is where most blank buttons and notifications come from.
+    fn validate_loc_chains(&self, data: &Everything) {
+        let mut chains = Vec::new();
+        // Interactions that the recipient answers have buttons and a notification
+        if self.block.get_key("ai_accept").is_some() {
+            chains.extend(ANSWER_LOC_CHAINS);
         }
-        if let Some(key) = self.block.get_field_value("pre_answer_yes_key") {
-            data.localization.verify_exists(key);
+        if self.block.get_key("send_option").is_some() {
+            chains.extend(SEND_LOC_CHAINS);
         }
-        if let Some(key) = self.block.get_field_value("send_name") {
-            data.localization.verify_exists(key);
+        for (field, what, patterns) in chains {
+            if self.block.get_key(field).is_some() {
+                continue;
+            }
+            let keys = patterns
+                .iter()
+                .map(|pattern| pattern.replace('$', self.key.as_str()))
+                .collect::<Vec<_>>();
+            let what = format!("the {} of interaction {}", what, self.key);
+            data.localization
+                .verify_exists_chain(&keys, &self.key, &what);
         }
-        for b in self.block.get_field_blocks("send_option") {
-            if let Some(key) = b.get_field_value("localization") {
-                data.localization.verify_exists(key);
+
+        // A send option without `localization` is named by its flag
+        for block in self.block.get_field_blocks("send_option") {
+            if block.get_key("localization").is_some() {
+                continue;
+            }
+            if let Some(flag) = block.get_field_value("flag") {
+                let keys = vec![format!("{}_{}", self.key, flag), flag.to_string()];
+                let what = format!("send option {flag}");
+                data.localization.verify_exists_chain(&keys, flag, &what);
             }
         }
     }
 }
+
+/// The texts of an interaction that the recipient answers, by the field that sets the text,
+/// a description for the report, and the keys the game falls back to when the field is not
+/// there. `$` stands for the interaction's key. The last key of most chains is the game's
+/// generic text.
+const ANSWER_LOC_CHAINS: &[(&str, &str, &[&str])] = &[
+    (
+        "notification_text",
+        "notification",
+        &["$_notification", "$_desc"],
+    ),
+    (
+        "answer_accept_key",
+        "accept button",
+        &["$_accept", "ACCEPT"],
+    ),
+    (
+        "answer_reject_key",
+        "decline button",
+        &["$_reject", "DECLINE"],
+    ),
+    (
+        "pre_answer_yes_key",
+        "tooltip for a yes answer",
+        &["$_pre_answer_yes", "INTERACTION_PRE_ANSWER_YES"],
+    ),
+    (
+        "pre_answer_no_key",
+        "tooltip for a no answer",
+        &["$_pre_answer_no", "INTERACTION_PRE_ANSWER_NO"],
+    ),
+];
+
+/// The texts of an interaction with send options, shown in the window where they're picked
+const SEND_LOC_CHAINS: &[(&str, &str, &[&str])] = &[
+    ("prompt", "prompt", &["$_prompt", "$_desc"]),
+    (
+        "options_heading",
+        "send options heading",
+        &["$_options_heading", "INTERACTION_OPTIONS_HEADING"],
+    ),
+];
+
+/// Check the interaction's category. A mod that overrides the game's categories file without
+/// copying all of its categories makes the interactions in the missing ones vanish from the
+/// character menu, so that case gets its own report.
+fn verify_category(category: &Token, data: &Everything) {
+    if data.item_exists(Item::InteractionCategory, category.as_str()) {
+        return;
+    }
+    let definitions = data
+        .item_definitions(Item::InteractionCategory, category.as_str())
+        .unwrap_or_default();
+    if let Some(def) = definitions.iter().find(|def| def.replaced_by.is_some()) {
+        let msg = format!(
+            "interaction category {category} is only defined in a file that the mod overrides, so this interaction will not be in the character menu"
+        );
+        warn2(
+            category,
+            ErrorKey::MissingItem,
+            &msg,
+            &def.key,
+            "the overridden definition is here",
+        );
+    } else {
+        data.verify_exists(Item::InteractionCategory, category);
+    }
+}
+
+const INTERACTION_FIELDS: &Schema = &[
+    ("interface_priority", Field::Numeric),
+    ("common_interaction", Field::Bool),
+    ("hidden", Field::Bool),
+    // These name hooks in the game engine and the gui
+    ("interface", Field::Any),
+    ("special_interaction", Field::Any),
+    ("special_ai_interaction", Field::Any),
+    ("scheme", Field::Item(Item::Scheme)),
+    ("target_type", Field::Choice(&["artifact", "none", "title"])),
+    ("target_filter", Field::Any),
+    ("extra_icon", Field::File),
+    ("should_use_extra_icon", Field::Trigger(false)),
+    ("is_highlighted", Field::Trigger(false)),
+    ("highlighted_reason", Field::Localization),
+    ("desc", Field::Desc),
+    ("prompt", Field::Desc),
+    ("notification_text", Field::Desc),
+    ("on_decline_summary", Field::Desc),
+    ("answer_accept_key", Field::Localization),
+    ("answer_reject_key", Field::Localization),
+    ("options_heading", Field::Localization),
+    ("pre_answer_maybe_breakdown_key", Field::Localization),
+    ("pre_answer_maybe_key", Field::Localization),
+    ("pre_answer_no_key", Field::Localization),
+    ("pre_answer_yes_key", Field::Localization),
+    ("answer_acknowledge_key", Field::Localization),
+    ("answer_block_key", Field::Localization),
+    ("reply_item_key", Field::Localization),
+    ("send_name", Field::Localization),
+    ("send_options_exclusive", Field::Bool),
+    (
+        "send_option",
+        Field::Multiple(&Field::Block(SEND_OPTION_FIELDS)),
+    ),
+    ("is_shown", Field::Trigger(false)),
+    ("is_valid", Field::Trigger(true)),
+    ("is_valid_showing_failures_only", Field::Trigger(true)),
+    ("has_valid_target", Field::Trigger(false)),
+    (
+        "has_valid_target_showing_failures_only",
+        Field::Trigger(true),
+    ),
+    ("can_be_picked", Field::Trigger(false)),
+    ("can_be_picked_artifact", Field::Trigger(false)),
+    ("can_be_picked_title", Field::Trigger(false)),
+    ("can_send", Field::Trigger(true)),
+    ("can_be_blocked", Field::Trigger(false)),
+    ("auto_accept", Field::BoolOrTrigger(true)),
+    ("use_diplomatic_range", Field::BoolOrTrigger(false)),
+    ("can_send_despite_rejection", Field::Bool),
+    ("ignores_pending_interaction_block", Field::Bool),
+    ("needs_recipient_to_open", Field::Bool),
+    ("popup_on_receive", Field::Bool),
+    ("pause_on_receive", Field::Bool),
+    ("force_notification", Field::Bool),
+    ("show_answer_notification", Field::Bool),
+    ("show_effects_in_notification", Field::Bool),
+    ("greeting", Field::Choice(&["negative", "positive"])),
+    ("cooldown", Field::Validated(validate_cooldown)),
+    (
+        "cooldown_against_recipient",
+        Field::Validated(validate_cooldown),
+    ),
+    ("cost", Field::Validated(validate_cost)),
+    ("populate_actor_list", Field::Effect(false)),
+    ("populate_recipient_list", Field::Effect(false)),
+    ("redirect", Field::Effect(false)),
+    ("on_send", Field::Effect(true)),
+    ("on_accept", Field::Effect(true)),
+    ("on_decline", Field::Effect(true)),
+    ("on_blocked_effect", Field::Effect(false)),
+    ("on_auto_accept", Field::Effect(true)),
+    // Evaluated from the point of view of the recipient who decides
+    (
+        "ai_accept",
+        Field::Rooted(Scopes::Character, &Field::AiChance),
+    ),
+    ("ai_maybe", Field::Bool),
+    ("ai_min_reply_days", Field::Integer),
+    ("ai_max_reply_days", Field::Integer),
+    ("ai_frequency", Field::Integer),
+    (
+        "ai_targets",
+        Field::Multiple(&Field::Block(AI_TARGETS_FIELDS)),
+    ),
+    (
+        "ai_target_quick_trigger",
+        Field::Block(AI_TARGET_QUICK_TRIGGER_FIELDS),
+    ),
+    // The ai_ fields are evaluated from the point of view of the AI actor
+    (
+        "ai_potential",
+        Field::Rooted(Scopes::Character, &Field::Trigger(false)),
+    ),
+    (
+        "ai_set_target",
+        Field::Rooted(Scopes::Character, &Field::Effect(false)),
+    ),
+    (
+        "ai_will_do",
+        Field::Rooted(Scopes::Character, &Field::AiChance),
+    ),
+];
+
+/// Which characters the AI considers sending the interaction to
+const AI_TARGETS_FIELDS: &Schema = &[
+    ("ai_recipients", Field::Multiple(&Field::Any)),
+    ("max", Field::Integer),
+    ("chance", Field::Fraction),
+];
+
+/// Cheap filters on the AI's targets, checked before the triggers
+const AI_TARGET_QUICK_TRIGGER_FIELDS: &Schema = &[
+    ("adult", Field::Bool),
+    ("attracted_to_owner", Field::Bool),
+    ("owner_attracted", Field::Bool),
+    ("prison", Field::Bool),
+];
+
+const SEND_OPTION_FIELDS: &Schema = &[
+    ("flag", Field::Any),
+    ("localization", Field::Localization),
+    ("is_shown", Field::Trigger(false)),
+    ("is_valid", Field::Trigger(true)),
+    ("starts_enabled", Field::Trigger(false)),
+    ("can_be_changed", Field::Trigger(false)),
+    ("current_description", Field::Desc),
+    ("can_invalidate_interaction", Field::Bool),
+];
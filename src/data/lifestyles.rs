@@ -7,6 +7,8 @@ use crate::context::ScopeContext;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
+use crate::icons::{verify_icon, IconKind};
+use crate::itemdb::{as_source, ItemDb, ItemSource};
 use crate::pdxfile::PdxFile;
 use crate::scopes::Scopes;
 use crate::token::Token;
@@ -42,6 +44,16 @@ impl Lifestyles {
     }
 }
 
+impl ItemDb for Lifestyles {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.lifestyles.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.lifestyles.values().map(as_source))
+    }
+}
+
 impl FileHandler for Lifestyles {
     fn subpath(&self) -> PathBuf {
         PathBuf::from("common/lifestyles")
@@ -98,14 +110,22 @@ impl Lifestyle {
         }
 
         if let Some(token) = vd.field_value("icon") {
-            let pathname = format!("gfx/interface/icons/lifestyles/{}.dds", token);
-            data.fileset.verify_exists_implied(&pathname, token);
+            verify_icon(IconKind::Lifestyle, token.as_str(), token, data);
         } else {
-            let pathname = format!("gfx/interface/icons/lifestyles/{}.dds", self.key);
-            data.fileset.verify_exists_implied(&pathname, &self.key);
+            verify_icon(IconKind::Lifestyle, self.key.as_str(), &self.key, data);
         }
 
         vd.field_numeric("xp_per_level");
         vd.field_numeric("base_xp_gain");
     }
 }
+
+impl ItemSource for Lifestyle {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -1,16 +1,18 @@
 use fnv::{FnvHashMap, FnvHashSet};
+use std::cell::{OnceCell, RefCell};
 use std::ffi::OsStr;
-use std::fs::read_to_string;
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
 
 use crate::block::Block;
 use crate::data::localization::parse::{parse_loca, ValueParser};
 use crate::errorkey::ErrorKey;
-use crate::errors::{advice_info, error, error_info, warn_info};
+use crate::errors::{advice_info, error, error_info, report, warn_info, ErrorLevel, Related};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler, FileKind};
-use crate::helpers::dup_error;
+use crate::helpers::{dup_error, SuggestIndex, Suggestion};
 use crate::item::Item;
+use crate::pdxfile::PdxFile;
 use crate::token::Token;
 
 mod parse;
@@ -21,6 +23,26 @@ pub struct Localization {
     warned_dirs: Vec<String>,
     locas: FnvHashMap<&'static str, FnvHashMap<String, LocaEntry>>,
     mod_langs: Vec<&'static str>,
+    /// The language to check that localization keys exist in, if given on the command line
+    language: Option<&'static str>,
+    /// The languages to compare to the main language, for the missing translations report
+    translations: Vec<&'static str>,
+    /// Whether vanilla is being validated without a mod, in which case the languages vanilla
+    /// has are the ones to report missing keys for
+    vanilla_only: bool,
+    /// Mod keys that replace a key from the game files, outside of a `replace/` folder,
+    /// together with the game's key
+    overrides: Vec<(Token, Token)>,
+    /// Prefixes of keys that the config file says are overridden on purpose
+    override_prefixes: Vec<String>,
+    /// The keys that the mod's own items refer to
+    used: RefCell<FnvHashSet<String>>,
+    /// All keys by their `near_key` form, to explain why a key that looks right isn't found.
+    /// Made when it's first needed.
+    near_keys: OnceCell<FnvHashMap<String, String>>,
+    /// The keys of the main language by length, to suggest the key that a missing key is a
+    /// typo of. Made when it's first needed.
+    suggest_index: OnceCell<SuggestIndex>,
 }
 
 // LAST UPDATED VERSION 1.7.0
@@ -34,6 +56,25 @@ pub const KNOWN_LANGUAGES: [&str; 7] = [
     "simp_chinese",
 ];
 
+/// The `&'static str` for a language name, if it's one the game knows
+pub fn known_language(name: &str) -> Option<&'static str> {
+    KNOWN_LANGUAGES.iter().find(|lang| **lang == name).copied()
+}
+
+/// Characters that can't be seen in an editor but make a key different. They usually come
+/// from copying text out of a web page or chat program.
+const INVISIBLE_CHARS: &[char] = &['\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}', '\u{feff}'];
+
+/// The key without invisible characters and surrounding whitespace, in lowercase, so that
+/// keys that look the same to the modder map to the same string.
+fn near_key(key: &str) -> String {
+    let visible = key
+        .chars()
+        .filter(|c| !INVISIBLE_CHARS.contains(c))
+        .collect::<String>();
+    visible.trim().to_lowercase()
+}
+
 // LAST UPDATED VERSION 1.7.0
 // These are just the ones that can't be deduced from the vanilla localization files.
 pub const BUILTIN_MACROS: [&str; 2] = ["TRIGGER_AND", "TRIGGER_OR"];
@@ -151,15 +192,50 @@ fn get_file_lang(filename: &OsStr) -> Option<&'static str> {
     None
 }
 
+/// Whether the key is in a `replace/` folder, where it's meant to override the game's key.
+fn is_replace(key: &Token) -> bool {
+    key.loc
+        .pathname
+        .components()
+        .any(|c| c.as_os_str() == "replace")
+}
+
 impl Localization {
-    pub fn exists(&self, key: &str) -> bool {
-        for lang in &self.check_langs {
-            let hash = self.locas.get(lang);
-            if hash.is_none() || !hash.unwrap().contains_key(key) {
-                return false;
-            }
+    pub fn set_vanilla_only(&mut self) {
+        self.vanilla_only = true;
+    }
+
+    pub fn set_language(&mut self, lang: &'static str) {
+        self.language = Some(lang);
+    }
+
+    pub fn add_translation(&mut self, lang: &'static str) {
+        if !self.translations.contains(&lang) {
+            self.translations.push(lang);
         }
-        true
+    }
+
+    /// The language that localization keys have to exist in. Unless one was given, this is
+    /// english if the mod has it, and otherwise the first language the mod has.
+    fn main_language(&self) -> Option<&'static str> {
+        self.language.or_else(|| {
+            KNOWN_LANGUAGES
+                .iter()
+                .find(|lang| self.mod_langs.contains(lang))
+                .copied()
+        })
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        let lang = self.main_language().unwrap_or("english");
+        self.locas
+            .get(lang)
+            .is_some_and(|hash| hash.contains_key(key))
+    }
+
+    /// Whether the key is localized in at least one language
+    pub fn exists_any(&self, key: &str) -> bool {
+        self.locas.values().any(|hash| hash.contains_key(key))
     }
 
     pub fn verify_exists(&self, token: &Token) {
@@ -170,15 +246,202 @@ impl Localization {
         if key.is_empty() {
             return;
         }
-        for lang in &self.mod_langs {
+        if token.loc.kind == FileKind::Mod {
+            self.used.borrow_mut().insert(key.to_string());
+        }
+        // The other languages are compared to this one in `missing_translations`, so that
+        // their missing keys don't drown out the rest
+        if let Some(lang) = self.main_language() {
             let hash = self.locas.get(lang);
             if hash.is_none() || !hash.unwrap().contains_key(key) {
-                error(
-                    token,
-                    ErrorKey::MissingLocalization,
-                    &format!("missing {} localization key {}", lang, key),
+                let msg = format!("missing {lang} localization key {key}");
+                let info = self.near_miss(key).or_else(|| {
+                    let index = self.suggest_index.get_or_init(|| {
+                        SuggestIndex::new(
+                            hash.into_iter()
+                                .flat_map(|hash| hash.keys())
+                                .map(String::as_str),
+                        )
+                    });
+                    index.suggest(key).map(Suggestion::info)
+                });
+                match info {
+                    Some(info) => error_info(token, ErrorKey::MissingLocalization, &msg, &info),
+                    None => error(token, ErrorKey::MissingLocalization, &msg),
+                }
+            }
+        }
+    }
+
+    /// Check that at least one of `keys` exists, for texts where the game falls back from one
+    /// key to the next. If none do, the report lists all of them in the order they're tried.
+    pub fn verify_exists_chain(&self, keys: &[String], token: &Token, what: &str) {
+        if token.loc.kind == FileKind::Mod {
+            self.used.borrow_mut().extend(keys.iter().cloned());
+        }
+        if let Some(lang) = self.main_language() {
+            let hash = self.locas.get(lang);
+            if !keys
+                .iter()
+                .any(|key| hash.is_some_and(|hash| hash.contains_key(key)))
+            {
+                let msg = format!("missing {lang} localization for {what}");
+                let info = format!(
+                    "the game tries these keys in order: {}",
+                    keys.iter()
+                        .map(|key| format!("`{key}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
                 );
+                error_info(token, ErrorKey::MissingLocalization, &msg, &info);
+            }
+        }
+    }
+
+    /// Describe the keys that the main language has and the languages given with
+    /// `add_translation` don't, counted per file. With `details`, also list the keys.
+    pub fn missing_translations(&self, details: bool) -> String {
+        let mut out = String::new();
+        let Some(main) = self.main_language() else {
+            return out;
+        };
+        let empty = FnvHashMap::default();
+        let main_hash = self.locas.get(main).unwrap_or(&empty);
+        for lang in &self.translations {
+            if *lang == main {
+                continue;
+            }
+            let hash = self.locas.get(lang).unwrap_or(&empty);
+            let mut missing = main_hash
+                .values()
+                .map(|entry| &entry.key)
+                .filter(|key| key.loc.kind == FileKind::Mod || self.vanilla_only)
+                .filter(|key| !hash.contains_key(key.as_str()))
+                .collect::<Vec<_>>();
+            missing.sort_unstable_by_key(|key| &key.loc);
+            if missing.is_empty() {
+                let _ = writeln!(out, "No missing {lang} translations of {main}.\n");
+                continue;
+            }
+            let _ = writeln!(
+                out,
+                "Missing {} translations of {}: {} keys",
+                lang,
+                main,
+                missing.len()
+            );
+            let mut i = 0;
+            while i < missing.len() {
+                let pathname = &missing[i].loc.pathname;
+                let count = missing[i..]
+                    .iter()
+                    .take_while(|key| key.loc.pathname == *pathname)
+                    .count();
+                let _ = writeln!(out, "  {}: {}", pathname.display(), count);
+                if details {
+                    for key in &missing[i..i + count] {
+                        let _ = writeln!(out, "    line {} {}", key.loc.line, key);
+                    }
+                }
+                i += count;
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// If a missing key looks the same as one that exists, explain the difference.
+    fn near_miss(&self, key: &str) -> Option<String> {
+        let near_keys = self.near_keys.get_or_init(|| {
+            let mut near_keys = FnvHashMap::default();
+            for hash in self.locas.values() {
+                for k in hash.keys() {
+                    near_keys.insert(near_key(k), k.clone());
+                }
             }
+            near_keys
+        });
+        let found = near_keys.get(&near_key(key))?;
+        if found == key {
+            return None;
+        }
+        if let Some(c) = key.chars().find(|c| INVISIBLE_CHARS.contains(c)) {
+            Some(format!(
+                "the key contains the invisible character U+{:04X}; without it, it would be found",
+                c as u32
+            ))
+        } else if key.trim() != key {
+            Some("the key has spaces around it; without them, it would be found".to_string())
+        } else if let Some(c) = found.chars().find(|c| INVISIBLE_CHARS.contains(c)) {
+            Some(format!(
+                "the localization file has this key with the invisible character U+{:04X} in it",
+                c as u32
+            ))
+        } else {
+            Some(format!(
+                "the localization key is `{found}`, with different case"
+            ))
+        }
+    }
+
+    /// Give advice about mod keys that replace the game's keys without the mod using them,
+    /// which is often an accident. This must be called after all the items that refer to
+    /// localization keys have been validated.
+    pub fn validate_overrides(&self) {
+        let used = self.used.borrow();
+        let mut seen = FnvHashSet::default();
+        for (key, theirs) in &self.overrides {
+            if used.contains(key.as_str())
+                || self
+                    .override_prefixes
+                    .iter()
+                    .any(|prefix| key.as_str().starts_with(prefix))
+                || !seen.insert(key.as_str())
+            {
+                continue;
+            }
+            let msg = format!("`{key}` replaces the game's localization for this key");
+            let info = "if that's intended, put it in a `replace/` folder; otherwise give it a name of its own";
+            report(
+                key,
+                ErrorLevel::Advice,
+                ErrorKey::LocalizationOverride,
+                &msg,
+                Some(info),
+                &[Related::new(theirs, "the game's key is here")],
+            );
+        }
+    }
+
+    /// Warn about the mod's `cn_` keys that no title uses as a cultural name, which usually
+    /// means the title's `cultural_names` has a typo. This must be called after the titles
+    /// have been validated.
+    pub fn validate_cultural_names(&self) {
+        let Some(lang) = self.main_language() else {
+            return;
+        };
+        let Some(hash) = self.locas.get(lang) else {
+            return;
+        };
+        let used = self.used.borrow();
+        let mut unused = hash
+            .values()
+            .filter(|entry| {
+                entry.key.loc.kind == FileKind::Mod
+                    && entry.key.as_str().starts_with("cn_")
+                    && !used.contains(entry.key.as_str())
+                    && !self
+                        .overrides
+                        .iter()
+                        .any(|(key, _)| key.is(entry.key.as_str()))
+            })
+            .map(|entry| &entry.key)
+            .collect::<Vec<_>>();
+        unused.sort_unstable_by_key(|key| &key.loc);
+        for key in unused {
+            let msg = format!("cultural name `{key}` is not used by any title");
+            let info = "check the `cultural_names` of the title it's meant for";
+            warn_info(key, ErrorKey::Localization, &msg, info);
         }
     }
 
@@ -196,15 +459,82 @@ impl Localization {
             {
                 data.verify_exists(Item::GameConcept, &chain.codes[0].name);
             }
+            LocaValue::Code(chain, _) => Self::check_concept_functions(chain, data),
             _ => (),
         }
     }
 
+    /// Check the concepts linked with `Concept('key','text')` anywhere in a code chain.
+    fn check_concept_functions(chain: &CodeChain, data: &Everything) {
+        for code in &chain.codes {
+            if code.name.is("Concept") {
+                if let Some(CodeArg::Literal(key)) = code.arguments.first() {
+                    data.verify_exists(Item::GameConcept, key);
+                }
+            }
+            for arg in &code.arguments {
+                if let CodeArg::Chain(chain) = arg {
+                    Self::check_concept_functions(chain, data);
+                }
+            }
+        }
+    }
+
+    /// The data functions called directly on a named scope in this key's text, as pairs
+    /// of the scope name and the function, like `actor` and `GetFirstName` for
+    /// `[actor.GetFirstName]`.
+    pub fn scope_functions(&self, key: &str) -> Vec<(&Token, &Token)> {
+        let lang = self.main_language().unwrap_or("english");
+        let mut vec = Vec::new();
+        if let Some(entry) = self.locas.get(lang).and_then(|hash| hash.get(key)) {
+            Self::collect_scope_functions(&entry.value, &mut vec);
+        }
+        vec
+    }
+
+    fn collect_scope_functions<'a>(value: &'a LocaValue, vec: &mut Vec<(&'a Token, &'a Token)>) {
+        match value {
+            LocaValue::Concat(v) => {
+                for value in v {
+                    Self::collect_scope_functions(value, vec);
+                }
+            }
+            LocaValue::Code(chain, _) => Self::collect_chain_functions(chain, vec),
+            _ => (),
+        }
+    }
+
+    fn collect_chain_functions<'a>(chain: &'a CodeChain, vec: &mut Vec<(&'a Token, &'a Token)>) {
+        if let [first, second, ..] = &chain.codes[..] {
+            if first.arguments.is_empty() {
+                vec.push((&first.name, &second.name));
+            }
+        }
+        for code in &chain.codes {
+            for arg in &code.arguments {
+                if let CodeArg::Chain(chain) = arg {
+                    Self::collect_chain_functions(chain, vec);
+                }
+            }
+        }
+    }
+
     pub fn validate(&self, data: &Everything) {
         // Does every `[concept]` reference have a defined game concept?
         for hash in self.locas.values() {
             for entry in hash.values() {
                 self.check_game_concepts(&entry.value, data);
+                if let Some(orig) = &entry.orig {
+                    let len = orig.as_str().chars().count();
+                    if len > data.limits.localization_length {
+                        let msg = format!("localization value is {len} characters long");
+                        let info = format!(
+                            "the game cuts off values longer than {} characters",
+                            data.limits.localization_length
+                        );
+                        advice_info(&entry.key, ErrorKey::LongLocalization, &msg, &info);
+                    }
+                }
             }
         }
     }
@@ -225,8 +555,20 @@ impl FileHandler for Localization {
                     langs.push(lang);
                 }
             }
+            // The languages asked for on the command line are needed even if skipped here
+            for lang in self.language.iter().chain(&self.translations) {
+                if !langs.contains(lang) {
+                    langs.push(lang);
+                }
+            }
             self.check_langs = langs;
         }
+
+        if let Some(block) = config.get_field_block("localization_overrides") {
+            for prefix in block.get_field_values("prefix") {
+                self.override_prefixes.push(prefix.to_string());
+            }
+        }
     }
 
     fn subpath(&self) -> PathBuf {
@@ -277,7 +619,8 @@ impl FileHandler for Localization {
             return;
         }
 
-        if entry.kind() == FileKind::Mod && !self.mod_langs.contains(&&*lang) {
+        if (entry.kind() == FileKind::Mod || self.vanilla_only) && !self.mod_langs.contains(&&*lang)
+        {
             for known in KNOWN_LANGUAGES {
                 if known == lang {
                     self.mod_langs.push(known);
@@ -292,19 +635,32 @@ impl FileHandler for Localization {
             if filelang != lang && !warned {
                 advice_info(entry, ErrorKey::Filename, "localization file with wrong name or in wrong directory", "A localization file should be in a subdirectory corresponding to its language.");
             }
-            match read_to_string(fullpath) {
-                Ok(content) => {
-                    for loca in parse_loca(entry, &content) {
-                        let hash = self.locas.entry(filelang).or_default();
-                        if let Some(other) = hash.get(loca.key.as_str()) {
-                            if other.key.loc.kind == entry.kind() {
-                                dup_error(&loca.key, &other.key, "localization");
+            if let Some(content) = PdxFile::read_utf8(entry, fullpath) {
+                for loca in parse_loca(entry, &content) {
+                    let hash = self.locas.entry(filelang).or_default();
+                    if let Some(other) = hash.get(loca.key.as_str()) {
+                        if other.key.loc.kind == entry.kind() {
+                            dup_error(&loca.key, &other.key, "localization");
+                        } else {
+                            // The files don't come in load order, so either one can be the mod's
+                            let (ours, theirs) = if entry.kind() > other.key.loc.kind {
+                                (&loca.key, &other.key)
+                            } else {
+                                (&other.key, &loca.key)
+                            };
+                            if ours.loc.kind == FileKind::Mod
+                                && theirs.loc.kind != FileKind::Mod
+                                && !is_replace(ours)
+                            {
+                                self.overrides.push((ours.clone(), theirs.clone()));
+                            }
+                            if other.key.loc.kind > entry.kind() {
+                                continue;
                             }
                         }
-                        hash.insert(loca.key.to_string(), loca);
                     }
+                    hash.insert(loca.key.to_string(), loca);
                 }
-                Err(e) => eprintln!("{:#}", e),
             }
         } else {
             error_info(
@@ -325,7 +681,7 @@ impl FileHandler for Localization {
         builtins.extend(&BUILTIN_MACROS);
         for lang in self.locas.values() {
             for entry in lang.values() {
-                if entry.key.loc.kind != FileKind::Vanilla {
+                if entry.key.loc.kind == FileKind::Mod {
                     continue;
                 }
 
@@ -387,6 +743,14 @@ impl Default for Localization {
             warned_dirs: Vec::default(),
             locas: FnvHashMap::default(),
             mod_langs: Vec::default(),
+            language: None,
+            translations: Vec::default(),
+            vanilla_only: false,
+            overrides: Vec::default(),
+            override_prefixes: Vec::default(),
+            used: RefCell::default(),
+            near_keys: OnceCell::new(),
+            suggest_index: OnceCell::new(),
         }
     }
 }
@@ -2,13 +2,22 @@ use std::iter::Peekable;
 use std::str::Chars;
 
 use crate::data::localization::{
-    get_file_lang, Code, CodeArg, CodeChain, LocaEntry, LocaValue, MacroValue,
+    get_file_lang, Code, CodeArg, CodeChain, LocaEntry, LocaValue, MacroValue, INVISIBLE_CHARS,
 };
 use crate::errorkey::ErrorKey;
 use crate::errors::{error, warn};
 use crate::fileset::FileEntry;
 use crate::token::{Loc, Token};
 
+/// Describe an unexpected character so that it can be found even if it's invisible.
+fn describe_char(c: char) -> String {
+    if INVISIBLE_CHARS.contains(&c) {
+        format!("invisible character U+{:04X}", c as u32)
+    } else {
+        format!("character `{c}`")
+    }
+}
+
 fn is_key_char(c: char) -> bool {
     c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '\''
 }
@@ -38,7 +47,7 @@ impl<'a> LocaParser<'a> {
             loc.offset += '\u{feff}'.len_utf8();
             chars.next();
         } else {
-            warn(&loc, ErrorKey::Encoding, "Expected UTF-8 BOM encoding");
+            error(&loc, ErrorKey::Encoding, "Expected UTF-8 BOM encoding");
         }
         let value_loc = loc.clone();
         LocaParser {
@@ -127,8 +136,8 @@ impl<'a> LocaParser<'a> {
             &self.loc,
             ErrorKey::Localization,
             &format!(
-                "Unexpected character `{}`, {}",
-                self.chars.peek().unwrap_or(&' '),
+                "Unexpected {}, {}",
+                describe_char(*self.chars.peek().unwrap_or(&' ')),
                 expected
             ),
         );
@@ -437,7 +446,7 @@ impl<'a> ValueParser<'a> {
         error(
             &self.loc,
             ErrorKey::Localization,
-            &format!("Unexpected character `{}`, {}", c, expected),
+            &format!("Unexpected {}, {}", describe_char(c), expected),
         );
     }
 
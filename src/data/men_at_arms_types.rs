@@ -0,0 +1,116 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::data::scriptvalues::ScriptValue;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::icons::{verify_icon, IconKind};
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+
+#[derive(Clone, Debug, Default)]
+pub struct MenAtArmsTypes {
+    types: FnvHashMap<String, MenAtArmsType>,
+}
+
+impl MenAtArmsTypes {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.types.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "men-at-arms type");
+            }
+        }
+        self.types
+            .insert(key.to_string(), MenAtArmsType::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.types.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.types.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for MenAtArmsTypes {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.types.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.types.values().map(as_source))
+    }
+}
+
+impl FileHandler for MenAtArmsTypes {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/men_at_arms_types")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MenAtArmsType {
+    key: Token,
+    block: Block,
+}
+
+impl MenAtArmsType {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        data.localization.verify_exists(&self.key);
+        vd.req_field("type");
+        vd.field_value_item("type", Item::MenAtArmsBase);
+        if let Some(icon) = vd.field_value("icon") {
+            verify_icon(IconKind::MenAtArms, icon.as_str(), icon, data);
+        }
+        // TODO: validate the stats, costs and terrain bonuses
+        vd.no_warn_remaining();
+    }
+}
+
+impl ItemSource for MenAtArmsType {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// Validate a `composition` block, which lists men-at-arms types with the number of stacks
+/// of each. This is shared by the item types that come with their own armies.
+pub fn validate_maa_composition(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    for (key, bv) in vd.unknown_keys() {
+        data.verify_exists(Item::MenAtArms, key);
+        ScriptValue::validate_bv(bv, data, sc);
+    }
+}
@@ -0,0 +1,128 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
+use crate::data::men_at_arms_types::validate_maa_composition;
+use crate::data::scriptvalues::ScriptValue;
+use crate::desc::validate_desc;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+use crate::validate::validate_cost;
+
+#[derive(Clone, Debug, Default)]
+pub struct MercenaryCompanies {
+    companies: FnvHashMap<String, MercenaryCompany>,
+}
+
+impl MercenaryCompanies {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.companies.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "mercenary company");
+            }
+        }
+        self.companies
+            .insert(key.to_string(), MercenaryCompany::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.companies.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.companies.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for MercenaryCompanies {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.companies.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.companies.values().map(as_source))
+    }
+}
+
+impl FileHandler for MercenaryCompanies {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/mercenary_companies")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MercenaryCompany {
+    key: Token,
+    block: Block,
+}
+
+impl MercenaryCompany {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+
+        vd.req_field("name");
+        vd.field_validated_bv("name", |bv, data| validate_desc(bv, data, &mut sc));
+        vd.field_value_item("region", Item::Region);
+        vd.field_value_item("culture", Item::Culture);
+        vd.req_field("composition");
+        vd.field_validated_block("composition", |b, data| {
+            validate_maa_composition(b, data, &mut sc);
+        });
+        vd.field_validated_bv("cost", |bv, data| validate_army_cost(bv, data, &mut sc));
+        vd.field_validated_block("can_hire", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("is_valid", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+    }
+}
+
+impl ItemSource for MercenaryCompany {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+/// The hiring cost of a mercenary company or holy order is either a plain amount of gold or
+/// a cost block.
+pub fn validate_army_cost(bv: &BlockOrValue, data: &Everything, sc: &mut ScopeContext) {
+    match bv {
+        BlockOrValue::Token(_) => ScriptValue::validate_bv(bv, data, sc),
+        BlockOrValue::Block(b) => validate_cost(b, data, sc),
+    }
+}
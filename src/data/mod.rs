@@ -1,25 +1,50 @@
+pub mod achievements;
+pub mod activities;
+pub mod artifacts;
+pub mod assets;
+pub mod buildings;
+pub mod character_templates;
 pub mod characters;
+pub mod combat;
+pub mod council;
+pub mod court_scene;
+pub mod courtier_guest_management;
 pub mod courtpos;
 pub mod courtpos_categories;
 pub mod decisions;
+pub mod defines;
+pub mod dna;
+pub mod doctrines;
 pub mod dynasties;
 pub mod events;
 pub mod gameconcepts;
+pub mod genes;
+pub mod holy_orders;
 pub mod houses;
+pub mod important_actions;
 pub mod interaction_cats;
 pub mod interactions;
 pub mod lifestyles;
 pub mod localization;
+pub mod men_at_arms_types;
+pub mod mercenaries;
+pub mod named_colors;
 pub mod namelists;
+pub mod on_actions;
+pub mod pool_selectors;
 pub mod prov_history;
 pub mod provinces;
 pub mod relations;
 pub mod religions;
 pub mod scripted_effects;
 pub mod scripted_lists;
+pub mod scripted_modifiers;
+pub mod scripted_rules;
 pub mod scripted_triggers;
 pub mod scriptvalues;
 pub mod terrain;
 pub mod title_history;
 pub mod titles;
 pub mod traits;
+pub mod travel;
+pub mod vassal_contracts;
@@ -0,0 +1,86 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::Block;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+use crate::validate::validate_color;
+
+/// The colors in `common/named_colors`, which can be used by name wherever a color is expected.
+/// The files contain a `colors = { name = { r g b } ... }` block.
+#[derive(Clone, Debug, Default)]
+pub struct NamedColors {
+    colors: FnvHashMap<String, NamedColor>,
+}
+
+impl NamedColors {
+    fn load_item(&mut self, key: &Token, block: &Block) {
+        if let Some(other) = self.colors.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(key, &other.key, "named color");
+            }
+        }
+        self.colors
+            .insert(key.to_string(), NamedColor::new(key.clone(), block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.colors.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.colors.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl FileHandler for NamedColors {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/named_colors")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, bv) in block.iter_bv_definitions_warn() {
+            if !key.is("colors") {
+                let msg = format!("unknown section `{key}`");
+                warn(key, ErrorKey::Validation, &msg);
+                continue;
+            }
+            if let Some(block) = bv.expect_block() {
+                for (key, b) in block.iter_pure_definitions_warn() {
+                    self.load_item(key, b);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NamedColor {
+    key: Token,
+    block: Block,
+}
+
+impl NamedColor {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        validate_color(&self.block, data);
+    }
+}
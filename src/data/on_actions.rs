@@ -0,0 +1,413 @@
+use fnv::{FnvHashMap, FnvHashSet};
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
+use crate::data::scriptvalues::ScriptValue;
+use crate::effect::validate_named_effect;
+use crate::errorkey::ErrorKey;
+use crate::errors::warn;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::item::Item;
+use crate::pdxfile::PdxFile;
+use crate::scopes::{scope_from_snake_case, Scopes};
+use crate::tables::on_actions::ON_ACTION_SCOPES;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+use crate::validate::{validate_days_weeks_months_years, validate_modifiers_with_base};
+
+#[derive(Clone, Debug, Default)]
+pub struct OnActions {
+    /// `On_actions` with the same name are merged by the game, so keep all their definitions
+    defs: FnvHashMap<String, Vec<OnAction>>,
+    /// The known scopes of the `on_actions`, from the vanilla table or the mod's declarations,
+    /// and passed on to the `on_actions` they call
+    seeds: FnvHashMap<String, ScopeSeed>,
+    /// The known scopes of the events fired from `on_actions`
+    event_seeds: FnvHashMap<String, ScopeSeed>,
+}
+
+impl OnActions {
+    fn load_item(&mut self, key: &Token, block: &Block) {
+        self.defs
+            .entry(key.to_string())
+            .or_default()
+            .push(OnAction::new(key.clone(), block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.defs.contains_key(key) || ON_ACTION_SCOPES.iter().any(|(s, _, _)| *s == key)
+    }
+
+    /// Work out the scopes of the `on_actions` and of the events they fire, starting from the
+    /// vanilla table and the mod's own declarations and following the `on_actions` that call
+    /// other `on_actions`.
+    pub fn seed(&mut self, declared: &ScopeDeclarations) {
+        let mut pending = Vec::new();
+        for (key, defs) in &self.defs {
+            if let Some(seed) = declared.on_actions.get(key) {
+                pending.push((key.clone(), seed.clone()));
+            } else if let Some(seed) = ScopeSeed::from_table(key, &defs[0].key) {
+                pending.push((key.clone(), seed));
+            }
+        }
+
+        while let Some((key, seed)) = pending.pop() {
+            if let Some(existing) = self.seeds.get_mut(&key) {
+                if !existing.merge(&seed) {
+                    continue;
+                }
+            } else {
+                self.seeds.insert(key.clone(), seed.clone());
+            }
+            for def in self.defs.get(&key).into_iter().flatten() {
+                for called in def.called_on_actions() {
+                    pending.push((called.to_string(), seed.clone()));
+                }
+            }
+        }
+
+        for (key, seed) in &self.seeds {
+            for def in self.defs.get(key).into_iter().flatten() {
+                for event in def.fired_events() {
+                    let mut seed = seed.clone();
+                    seed.token = event.clone();
+                    if let Some(existing) = self.event_seeds.get_mut(event.as_str()) {
+                        existing.merge(&seed);
+                    } else {
+                        self.event_seeds.insert(event.to_string(), seed);
+                    }
+                }
+            }
+        }
+
+        for (key, seed) in &declared.events {
+            self.event_seeds.insert(key.clone(), seed.clone());
+        }
+    }
+
+    /// Return the scope context that an event starts with when the `on_actions` that fire it
+    /// are known.
+    pub fn event_context(&self, key: &str) -> Option<ScopeContext> {
+        self.event_seeds.get(key).map(ScopeSeed::context)
+    }
+
+    /// Return the named scopes that the `on_actions` firing an event are known to set
+    pub fn event_scope_names(&self, key: &str) -> Option<Vec<&str>> {
+        self.event_seeds
+            .get(key)
+            .map(|seed| seed.names.iter().map(|(name, _)| name.as_str()).collect())
+    }
+
+    /// Whether an event is fired from an `on_action` whose scopes are not known
+    pub fn fired_from_unknown(&self, key: &str) -> bool {
+        self.defs
+            .iter()
+            .filter(|(name, _)| !self.seeds.contains_key(name.as_str()))
+            .flat_map(|(_, defs)| defs)
+            .any(|def| def.fired_events().iter().any(|event| event.is(key)))
+    }
+
+    /// The blocks of the on-action `key` and of all the on-actions it calls, directly or
+    /// through others. Each on-action is visited once.
+    pub fn reached_blocks(&self, key: &str) -> Vec<&Block> {
+        let mut seen = FnvHashSet::default();
+        let mut pending = vec![key];
+        let mut vec = Vec::new();
+        while let Some(key) = pending.pop() {
+            if !seen.insert(key) {
+                continue;
+            }
+            for def in self.defs.get(key).into_iter().flatten() {
+                vec.push(&def.block);
+                pending.extend(def.called_on_actions().into_iter().map(Token::as_str));
+            }
+        }
+        vec
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut keys = self.defs.keys().collect::<Vec<_>>();
+        keys.sort();
+        for key in keys {
+            for item in &self.defs[key] {
+                let sc = match self.seeds.get(key) {
+                    Some(seed) => seed.context(),
+                    None => ScopeContext::new_unrooted(Scopes::all(), item.key.clone()),
+                };
+                item.validate(data, sc);
+            }
+        }
+    }
+}
+
+impl FileHandler for OnActions {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/on_action")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, b) in block.iter_pure_definitions_warn() {
+            self.load_item(key, b);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OnAction {
+    key: Token,
+    block: Block,
+}
+
+impl OnAction {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    /// The events this `on_action` can fire
+    fn fired_events(&self) -> Vec<&Token> {
+        let mut vec = Vec::new();
+        for name in ["events", "first_valid", "random_events"] {
+            for block in self.block.get_field_blocks(name) {
+                vec.extend(list_references(block));
+            }
+        }
+        vec
+    }
+
+    /// The other `on_actions` this `on_action` can call
+    fn called_on_actions(&self) -> Vec<&Token> {
+        let mut vec = Vec::new();
+        for name in [
+            "on_actions",
+            "first_valid_on_action",
+            "random_on_action",
+            "random_on_actions",
+        ] {
+            for block in self.block.get_field_blocks(name) {
+                vec.extend(list_references(block));
+            }
+        }
+        if let Some(token) = self.block.get_field_value("fallback") {
+            vec.push(token);
+        }
+        vec
+    }
+
+    pub fn validate(&self, data: &Everything, mut sc: ScopeContext) {
+        let mut vd = Validator::new(&self.block, data);
+
+        vd.field_validated_block("trigger", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("weight_multiplier", |b, data| {
+            validate_modifiers_with_base(b, data, &mut sc);
+        });
+        for name in ["events", "first_valid"] {
+            vd.field_validated_blocks(name, |b, data| {
+                validate_list(b, data, &mut sc, Item::Event, false);
+            });
+        }
+        vd.field_validated_blocks("random_events", |b, data| {
+            validate_list(b, data, &mut sc, Item::Event, true);
+        });
+        for name in ["on_actions", "first_valid_on_action"] {
+            vd.field_validated_blocks(name, |b, data| {
+                validate_list(b, data, &mut sc, Item::OnAction, false);
+            });
+        }
+        for name in ["random_on_action", "random_on_actions"] {
+            vd.field_validated_blocks(name, |b, data| {
+                validate_list(b, data, &mut sc, Item::OnAction, true);
+            });
+        }
+        vd.field_validated_block("effect", |b, data| {
+            validate_named_effect("effect", b, data, &mut sc, false);
+        });
+        vd.field_value_item("fallback", Item::OnAction);
+    }
+}
+
+/// The events or `on_actions` named in a list, or in a weighted list where they are the values
+fn list_references(block: &Block) -> Vec<&Token> {
+    let mut vec = Vec::new();
+    for (k, _, bv) in block.iter_items() {
+        if let BlockOrValue::Token(token) = bv {
+            let weighted = k
+                .as_ref()
+                .is_some_and(|k| k.as_str().parse::<f64>().is_ok());
+            if (k.is_none() || weighted) && !token.is("0") {
+                vec.push(token);
+            }
+        }
+    }
+    vec
+}
+
+/// Validate a list of events or `on_actions`. A weighted list has `weight = name` entries,
+/// where the name can be `0` for nothing.
+fn validate_list(
+    block: &Block,
+    data: &Everything,
+    sc: &mut ScopeContext,
+    itype: Item,
+    weighted: bool,
+) {
+    let mut vd = Validator::new(block, data);
+    vd.field_validated_blocks("delay", |b, data| {
+        validate_days_weeks_months_years(b, data, sc);
+    });
+    if weighted {
+        vd.field_script_value("chance_to_happen", sc);
+        vd.field_validated_bv("chance_of_no_event", |bv, data| {
+            ScriptValue::validate_bv(bv, data, sc);
+        });
+        for (key, bv) in vd.unknown_keys() {
+            if key.as_str().parse::<f64>().is_err() {
+                let msg = format!("unknown field `{key}`");
+                warn(key, ErrorKey::Validation, &msg);
+            } else if let Some(token) = bv.expect_value() {
+                if !token.is("0") {
+                    data.verify_exists(itype, token);
+                }
+            }
+        }
+    }
+    for token in vd.values() {
+        data.verify_exists(itype, token);
+    }
+}
+
+/// What is known about the scopes when an `on_action` or event starts
+#[derive(Clone, Debug)]
+pub struct ScopeSeed {
+    root: Scopes,
+    /// The reason why the root scope is known
+    token: Token,
+    /// The named scopes that are set, with their types
+    names: Vec<(String, Scopes)>,
+}
+
+impl ScopeSeed {
+    fn from_table(key: &str, token: &Token) -> Option<Self> {
+        for (name, root, names) in ON_ACTION_SCOPES {
+            if *name == key {
+                return Some(ScopeSeed {
+                    root: Scopes::from_bits_truncate(*root),
+                    token: token.clone(),
+                    names: names
+                        .iter()
+                        .map(|(n, s)| ((*n).to_string(), Scopes::from_bits_truncate(*s)))
+                        .collect(),
+                });
+            }
+        }
+        None
+    }
+
+    /// Widen this seed to also cover `other`. Return true if anything changed.
+    fn merge(&mut self, other: &ScopeSeed) -> bool {
+        let mut changed = false;
+        if !self.root.contains(other.root) {
+            self.root |= other.root;
+            changed = true;
+        }
+        for (name, scopes) in &other.names {
+            if let Some((_, s)) = self.names.iter_mut().find(|(n, _)| n == name) {
+                if !s.contains(*scopes) {
+                    *s |= *scopes;
+                    changed = true;
+                }
+            } else {
+                self.names.push((name.clone(), *scopes));
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn context(&self) -> ScopeContext {
+        let mut sc = ScopeContext::new_root(self.root, self.token.clone());
+        for (name, scopes) in &self.names {
+            sc.define_name(name, *scopes);
+        }
+        sc
+    }
+}
+
+/// The mod's own declarations of the scopes of its `on_actions` and events, for the ones that
+/// the validator can't work out by itself. They are in `.tiger/scopes.txt` in the mod, and
+/// look like `on_actions = { my_on_action = { root = character target = landed_title } }`
+/// and similar for `events`.
+#[derive(Clone, Debug, Default)]
+pub struct ScopeDeclarations {
+    on_actions: FnvHashMap<String, ScopeSeed>,
+    events: FnvHashMap<String, ScopeSeed>,
+}
+
+impl ScopeDeclarations {
+    fn load_seeds(block: &Block) -> FnvHashMap<String, ScopeSeed> {
+        let mut seeds = FnvHashMap::default();
+        for (key, block) in block.iter_pure_definitions_warn() {
+            let mut root = None;
+            let mut names = Vec::new();
+            for (name, bv) in block.iter_bv_definitions_warn() {
+                let Some(token) = bv.expect_value() else {
+                    continue;
+                };
+                let Some(scopes) = scope_from_snake_case(token.as_str()) else {
+                    warn(token, ErrorKey::Scopes, "unknown scope type");
+                    continue;
+                };
+                if name.is("root") {
+                    root = Some((scopes, token.clone()));
+                } else {
+                    names.push((name.to_string(), scopes));
+                }
+            }
+            if let Some((root, token)) = root {
+                seeds.insert(key.to_string(), ScopeSeed { root, token, names });
+            } else {
+                warn(key, ErrorKey::Scopes, "missing `root` scope type");
+            }
+        }
+        seeds
+    }
+}
+
+impl FileHandler for ScopeDeclarations {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from(".tiger")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if entry.kind() != FileKind::Mod || entry.filename() != "scopes.txt" {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, b) in block.iter_pure_definitions_warn() {
+            if key.is("on_actions") {
+                self.on_actions.extend(Self::load_seeds(b));
+            } else if key.is("events") {
+                self.events.extend(Self::load_seeds(b));
+            } else {
+                let msg = format!("unknown section `{key}`");
+                warn(key, ErrorKey::Validation, &msg);
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, warn};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::dup_error;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+use crate::validate::validate_modifiers_with_base;
+
+/// The selectors in `common/pool_character_selectors`, which decide which characters in the
+/// pool are picked when the game or script wants a new courtier.
+#[derive(Clone, Debug, Default)]
+pub struct PoolSelectors {
+    selectors: FnvHashMap<String, PoolSelector>,
+}
+
+impl PoolSelectors {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.selectors.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "pool character selector");
+            }
+        }
+        self.selectors
+            .insert(key.to_string(), PoolSelector::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.selectors.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.selectors.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for PoolSelectors {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.selectors.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.selectors.values().map(as_source))
+    }
+}
+
+impl FileHandler for PoolSelectors {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/pool_character_selectors")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PoolSelector {
+    key: Token,
+    block: Block,
+}
+
+impl PoolSelector {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        // The root is the candidate character
+        let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+        sc.define_name("province", Scopes::Province);
+        sc.define_name("employer", Scopes::Character);
+
+        vd.field_validated_block("valid_character", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_validated_bv("age", validate_age);
+        vd.field_choice("gender", &["male", "female", "any"]);
+        vd.field_validated_block("config", |b, data| {
+            validate_modifiers_with_base(b, data, &mut sc);
+        });
+    }
+}
+
+/// `age` is either a single age or a `{ min max }` range
+fn validate_age(bv: &BlockOrValue, data: &Everything) {
+    match bv {
+        BlockOrValue::Token(token) => {
+            if token.as_str().parse::<i32>().is_err() {
+                error(token, ErrorKey::Validation, "expected an age in years");
+            }
+        }
+        BlockOrValue::Block(block) => {
+            let mut vd = Validator::new(block, data);
+            vd.req_field("min");
+            vd.req_field("max");
+            vd.field_integer("min");
+            vd.field_integer("max");
+            if let (Some(min), Some(max)) = (
+                block.get_field_integer("min"),
+                block.get_field_integer("max"),
+            ) {
+                if min > max {
+                    let key = block.get_key("min").unwrap();
+                    warn(key, ErrorKey::Range, "`min` age is higher than `max`");
+                }
+            }
+        }
+    }
+}
+
+impl ItemSource for PoolSelector {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -7,7 +7,7 @@ use crate::data::provinces::ProvId;
 use crate::data::religions::Religions;
 use crate::data::titles::Titles;
 use crate::errorkey::ErrorKey;
-use crate::errors::warn;
+use crate::errors::{report, warn, warn_info, ErrorLevel, Related};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
@@ -15,6 +15,12 @@ use crate::item::Item;
 use crate::pdxfile::PdxFile;
 use crate::token::Token;
 
+/// The doctrines that decide who controls a faith's temple holdings
+const THEOCRACY_DOCTRINES: &[&str] = &[
+    "doctrine_theocracy_lay_clergy",
+    "doctrine_theocracy_temporal",
+];
+
 #[derive(Clone, Debug, Default)]
 pub struct ProvinceHistories {
     provinces: FnvHashMap<ProvId, ProvinceHistory>,
@@ -37,9 +43,70 @@ impl ProvinceHistories {
     pub fn validate(&self, data: &Everything) {
         let mut vec = self.provinces.values().collect::<Vec<&ProvinceHistory>>();
         vec.sort_unstable_by_key(|item| &item.key.loc);
-        for item in vec {
+        for item in &vec {
             item.validate(data);
         }
+        for item in vec {
+            self.check_temple_control(item, data);
+        }
+    }
+
+    /// The `holding` values this province gets, at any date
+    pub fn holdings(&self, id: ProvId) -> Vec<&Token> {
+        self.provinces
+            .get(&id)
+            .map_or_else(Vec::new, |item| item.all_values("holding"))
+    }
+
+    /// Temple holdings are controlled by the liege or by the faith, depending on the faith's
+    /// theocracy doctrine. Without one, the realms around them don't work out at game start.
+    fn check_temple_control(&self, item: &ProvinceHistory, data: &Everything) {
+        let Some(temple) = item
+            .all_values("holding")
+            .into_iter()
+            .find(|holding| holding.is("church_holding"))
+        else {
+            return;
+        };
+        let Some(county) = item
+            .key
+            .as_str()
+            .parse()
+            .ok()
+            .and_then(|id| data.titles.county_of(id))
+        else {
+            return;
+        };
+        let Some(capital) = data
+            .titles
+            .capital_province(county.as_str())
+            .and_then(|id| self.provinces.get(&id))
+        else {
+            return;
+        };
+        for faith in capital.all_values("religion") {
+            if data
+                .religions
+                .faith_has_doctrine(faith.as_str(), THEOCRACY_DOCTRINES)
+                == Some(false)
+            {
+                let msg = format!(
+                    "temple holding in {county}, whose faith {faith} has no theocracy doctrine"
+                );
+                let info = format!(
+                    "the faith needs one of {} to decide whether the liege or the faith controls its temples",
+                    THEOCRACY_DOCTRINES.join(" or ")
+                );
+                report(
+                    temple,
+                    ErrorLevel::Info,
+                    ErrorKey::History,
+                    &msg,
+                    Some(&info),
+                    &[Related::new(faith, "the county's faith is set here")],
+                );
+            }
+        }
     }
 
     pub fn check_pod_faiths(&self, religions: &Religions, titles: &Titles) {
@@ -113,7 +180,7 @@ impl ProvinceHistory {
         Self { key, block }
     }
 
-    fn validate_common(vd: &mut Validator, _data: &Everything) {
+    fn validate_common(id: ProvId, vd: &mut Validator, data: &Everything, slots: &[&Token]) {
         vd.field_value("culture");
         vd.field_value_item("religion", Item::Faith);
         vd.field_choice(
@@ -127,23 +194,66 @@ impl ProvinceHistory {
                 "auto",
             ],
         );
-        vd.field_list("buildings");
-        vd.field_values("special_building_slot");
-        vd.field_values("special_building");
-        vd.field_value("duchy_capital_building"); // TODO: check if duchy capital
+        if let Some(block) = vd.field_block("buildings") {
+            for token in block.get_values() {
+                data.buildings.verify_type(&token, "regular", data);
+            }
+        }
+        for token in vd.field_values("special_building_slot") {
+            data.buildings.verify_type(token, "special", data);
+        }
+        for token in vd.field_values("special_building") {
+            data.buildings.verify_type(token, "special", data);
+            if !slots.iter().any(|slot| slot.is(token.as_str())) {
+                let msg = format!("province has no `special_building_slot = {token}`");
+                let info = "the game only places special buildings in their own slot";
+                warn_info(token, ErrorKey::Validation, &msg, info);
+            }
+        }
+        if let Some(token) = vd.field_value("duchy_capital_building") {
+            data.buildings.verify_type(token, "duchy_capital", data);
+            if data.titles.is_duchy_capital(id) == Some(false) {
+                let msg = "province is not the capital of a duchy";
+                let info = "duchy capital buildings only work in the capital province of a duchy";
+                warn_info(token, ErrorKey::Validation, msg, info);
+            }
+        }
     }
 
-    fn validate_history(_date: Date, block: &Block, data: &Everything) {
-        let mut vd = Validator::new(block, data);
-        Self::validate_common(&mut vd, data);
+    /// All the values of this field in the province, at any date
+    fn all_values(&self, field: &str) -> Vec<&Token> {
+        let mut values = Vec::new();
+        for (key, _, bv) in self.block.iter_items() {
+            match bv {
+                BlockOrValue::Token(t) => {
+                    if key.as_ref().is_some_and(|k| k.is(field)) {
+                        values.push(t);
+                    }
+                }
+                BlockOrValue::Block(b) => {
+                    for (key, token) in b.get_assignments() {
+                        if key.is(field) {
+                            values.push(token);
+                        }
+                    }
+                }
+            }
+        }
+        values
     }
 
     fn validate(&self, data: &Everything) {
         // TODO: verify that all county-capital provinces have a culture and religion
         // This needs province mappings to be loaded too
+        // The key was checked to be a province id when loading
+        let id = self.key.as_str().parse().unwrap_or_default();
         let mut vd = Validator::new(&self.block, data);
-        Self::validate_common(&mut vd, data);
+        let slots = self.all_values("special_building_slot");
+        Self::validate_common(id, &mut vd, data, &slots);
         vd.field_value("terrain");
-        vd.validate_history_blocks(Self::validate_history);
+        vd.validate_history_blocks(|_, block, data| {
+            let mut vd = Validator::new(block, data);
+            Self::validate_common(id, &mut vd, data, &slots);
+        });
     }
 }
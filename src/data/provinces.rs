@@ -1,11 +1,12 @@
 use fnv::{FnvHashMap, FnvHashSet};
 use image::{DynamicImage, Rgb};
+use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use crate::block::{Block, BlockOrValue};
 use crate::errorkey::ErrorKey;
-use crate::errors::{error, warn};
+use crate::errors::{error, warn, warn_info};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::parse::csv::{parse_csv, read_csv};
@@ -29,7 +30,47 @@ pub struct Provinces {
 
     adjacencies: Vec<Adjacency>,
 
-    impassable: FnvHashSet<ProvId>,
+    /// The provinces that default.map says are not ordinary land
+    kinds: FnvHashMap<ProvId, ProvinceKind>,
+}
+
+/// The kinds of provinces listed in default.map. All other provinces are land.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProvinceKind {
+    Sea,
+    River,
+    Lake,
+    ImpassableMountain,
+    ImpassableSea,
+}
+
+impl ProvinceKind {
+    fn from_key(key: &Token) -> Option<Self> {
+        match key.as_str() {
+            "sea_zones" => Some(ProvinceKind::Sea),
+            "river_provinces" => Some(ProvinceKind::River),
+            "lakes" => Some(ProvinceKind::Lake),
+            "impassable_mountains" => Some(ProvinceKind::ImpassableMountain),
+            "impassable_seas" => Some(ProvinceKind::ImpassableSea),
+            _ => None,
+        }
+    }
+
+    fn is_water(self) -> bool {
+        self != ProvinceKind::ImpassableMountain
+    }
+}
+
+impl Display for ProvinceKind {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            ProvinceKind::Sea => write!(f, "a sea zone"),
+            ProvinceKind::River => write!(f, "a river province"),
+            ProvinceKind::Lake => write!(f, "a lake"),
+            ProvinceKind::ImpassableMountain => write!(f, "an impassable mountain"),
+            ProvinceKind::ImpassableSea => write!(f, "an impassable sea"),
+        }
+    }
 }
 
 impl Provinces {
@@ -46,32 +87,29 @@ impl Provinces {
         }
     }
 
-    pub fn load_impassable(&mut self, block: &Block) {
+    pub fn load_province_kinds(&mut self, block: &Block) {
         enum Expecting {
-            Range,
-            List,
+            Range(ProvinceKind),
+            List(ProvinceKind),
             Nothing,
         }
 
         let mut expecting = Expecting::Nothing;
         for (k, _, v) in block.iter_items() {
             if let Some(key) = k {
-                if key.is("sea_zones")
-                    || key.is("river_provinces")
-                    || key.is("impassable_mountains")
-                {
+                if let Some(kind) = ProvinceKind::from_key(key) {
                     if let BlockOrValue::Token(t) = v {
                         if t.is("LIST") {
-                            expecting = Expecting::List;
+                            expecting = Expecting::List(kind);
                         } else if t.is("RANGE") {
-                            expecting = Expecting::Range;
+                            expecting = Expecting::Range(kind);
                         } else {
                             expecting = Expecting::Nothing;
                         }
                     }
                 }
             } else if let BlockOrValue::Block(b) = v {
-                if matches!(expecting, Expecting::Range) {
+                if let Expecting::Range(kind) = expecting {
                     let vec = b.get_values();
                     if vec.len() != 2 {
                         error(b, ErrorKey::Validation, "invalid RANGE");
@@ -86,13 +124,13 @@ impl Provinces {
                         continue;
                     }
                     for provid in from.unwrap()..=to.unwrap() {
-                        self.impassable.insert(provid);
+                        self.kinds.insert(provid, kind);
                     }
-                } else if matches!(expecting, Expecting::List) {
+                } else if let Expecting::List(kind) = expecting {
                     for token in b.get_values() {
                         let provid = token.as_str().parse::<ProvId>();
                         if let Ok(provid) = provid {
-                            self.impassable.insert(provid);
+                            self.kinds.insert(provid, kind);
                         } else {
                             error(b, ErrorKey::Validation, "invalid LIST");
                             break;
@@ -123,6 +161,34 @@ impl Provinces {
         }
     }
 
+    pub fn kind(&self, key: &str) -> Option<ProvinceKind> {
+        key.parse::<ProvId>()
+            .ok()
+            .and_then(|provid| self.kinds.get(&provid).copied())
+    }
+
+    /// Warn about references to provinces that are water, because most of what script does
+    /// with a province only works for land.
+    pub fn verify_not_water(&self, key: &str, item: &Token) {
+        if let Some(kind) = self.kind(key) {
+            if kind.is_water() {
+                let msg = format!("province {key} is {kind}");
+                let info = "most effects and triggers only work on land provinces";
+                warn_info(item, ErrorKey::Validation, &msg, info);
+            }
+        }
+    }
+
+    /// Warn about references to provinces where nothing can be placed, such as armies.
+    /// Water provinces were already reported by `verify_not_water`.
+    pub fn verify_passable(&self, key: &str, item: &Token) {
+        if self.kind(key) == Some(ProvinceKind::ImpassableMountain) {
+            let msg = format!("province {} is {}", key, ProvinceKind::ImpassableMountain);
+            let info = "this needs a land province that can be entered";
+            warn_info(item, ErrorKey::Validation, &msg, info);
+        }
+    }
+
     #[allow(clippy::unused_self)]
     pub fn validate(&self, _data: &Everything) {
         // TODO: validate adjacencies
@@ -222,7 +288,7 @@ impl FileHandler for Provinces {
                         Some(block) => block,
                         None => return,
                     };
-                    self.load_impassable(&block);
+                    self.load_province_kinds(&block);
                 }
                 _ => (),
             }
@@ -9,8 +9,10 @@ use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
 use crate::helpers::dup_error;
 use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
 use crate::pdxfile::PdxFile;
 use crate::token::Token;
+use crate::validate::check_opinion_value;
 
 #[derive(Clone, Debug, Default)]
 pub struct Relations {
@@ -39,6 +41,16 @@ impl Relations {
     }
 }
 
+impl ItemDb for Relations {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.relations.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.relations.values().map(as_source))
+    }
+}
+
 impl FileHandler for Relations {
     fn subpath(&self) -> PathBuf {
         PathBuf::from("common/scripted_relations")
@@ -88,6 +100,9 @@ impl Relation {
             }
         }
         vd.field_integer("opinion");
+        if let Some(token) = self.block.get_field_value("opinion") {
+            check_opinion_value(token);
+        }
         vd.field_numeric("fertility");
         for (key, _) in vd.integer_values() {
             let val = key.as_str().parse::<i32>().unwrap();
@@ -100,3 +115,13 @@ impl Relation {
         vd.field_bool("hidden");
     }
 }
+
+impl ItemSource for Relation {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -2,16 +2,19 @@ use fnv::FnvHashMap;
 use std::path::{Path, PathBuf};
 
 use crate::block::validator::Validator;
-use crate::block::Block;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
 use crate::errorkey::ErrorKey;
-use crate::errors::{warn, warn_info};
+use crate::errors::{advice2, warn, warn_info};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler, FileKind};
 use crate::helpers::dup_error;
+use crate::icons::{validate_icon_bv, verify_icon, IconKind};
 use crate::item::Item;
 use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
 use crate::token::Token;
-use crate::validate::validate_color;
+use crate::validate::validate_color_bv;
 
 #[derive(Clone, Debug, Default)]
 pub struct Religions {
@@ -65,6 +68,36 @@ impl Religions {
             let religion = &self.religions[faith.religion.as_str()];
             faith.check_have_customs(religion);
         }
+        self.check_shared_colors();
+    }
+
+    /// Faiths with the same color can't be told apart on the religion map mode.
+    /// Only the mod's own faiths are checked.
+    fn check_shared_colors(&self) {
+        let mut faiths = self
+            .faiths
+            .values()
+            .filter(|f| f.key.loc.kind == FileKind::Mod)
+            .collect::<Vec<_>>();
+        faiths.sort_unstable_by_key(|f| &f.key.loc);
+
+        let mut seen: FnvHashMap<String, &Token> = FnvHashMap::default();
+        for faith in faiths {
+            if let Some(color) = faith.block.get_field("color").and_then(color_text) {
+                if let Some(other) = seen.get(&color) {
+                    let msg = format!("faith {} has the same color as {}", faith.key, other);
+                    advice2(
+                        &faith.key,
+                        ErrorKey::Conflict,
+                        &msg,
+                        *other,
+                        "the other faith is here",
+                    );
+                } else {
+                    seen.insert(color, &faith.key);
+                }
+            }
+        }
     }
 
     pub fn faith_exists(&self, key: &str) -> bool {
@@ -75,6 +108,20 @@ impl Religions {
         self.religions.contains_key(key)
     }
 
+    /// Whether the faith has one of these doctrines, either of its own or from its religion.
+    /// Returns `None` if the faith doesn't exist.
+    pub fn faith_has_doctrine(&self, faith: &str, doctrines: &[&str]) -> Option<bool> {
+        let faith = self.faiths.get(faith)?;
+        let has = |block: &Block| {
+            block
+                .get_field_values("doctrine")
+                .iter()
+                .any(|doctrine| doctrines.contains(&doctrine.as_str()))
+        };
+        let religion = self.religions.get(faith.religion.as_str());
+        Some(has(&faith.block) || religion.is_some_and(|religion| has(&religion.block)))
+    }
+
     pub fn is_modded_faith(&self, item: &Token) -> bool {
         if let Some(faith) = self.faiths.get(item.as_str()) {
             faith.key.loc.kind == FileKind::Mod
@@ -197,6 +244,22 @@ fn validate_holy_order_names(block: &Block, data: &Everything) {
     // TODO: if any items remaining, should explain the structure of the holy order blocks here
 }
 
+/// A normalized form of a color, for comparing colors
+fn color_text(bv: &BlockOrValue) -> Option<String> {
+    match bv {
+        BlockOrValue::Token(t) => Some(t.to_string()),
+        BlockOrValue::Block(b) => {
+            let format = b.tag.as_ref().map_or("rgb", Token::as_str);
+            let mut text = format.to_string();
+            for t in b.get_values() {
+                text.push(' ');
+                text.push_str(&t.as_str().parse::<f64>().ok()?.to_string());
+            }
+            Some(text)
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Faith {
     key: Token,
@@ -239,18 +302,17 @@ impl Faith {
         let mut vd = Validator::new(&self.block, data);
 
         vd.req_field("color");
-        vd.field_validated_block("color", validate_color);
-        if let Some(icon) = vd.field_value("icon") {
-            let pathname = format!("gfx/interface/icons/faith/{}.dds", icon);
-            data.fileset.verify_exists_implied(&pathname, icon);
-        } else {
-            let pathname = format!("gfx/interface/icons/faith/{}.dds", self.key);
-            data.fileset.verify_exists_implied(&pathname, &self.key);
-        }
-        if let Some(icon) = vd.field_value("reformed_icon") {
-            let pathname = format!("gfx/interface/icons/faith/{}.dds", icon);
-            data.fileset.verify_exists_implied(&pathname, icon);
+        vd.field_validated_bv("color", validate_color_bv);
+        let mut sc = ScopeContext::new_root(Scopes::Faith, self.key.clone());
+        // Without an `icon`, the game uses the icon named after the faith
+        if !vd.field_validated_bvs("icon", |bv, data| {
+            validate_icon_bv(IconKind::Faith, bv, data, &mut sc);
+        }) {
+            verify_icon(IconKind::Faith, self.key.as_str(), &self.key, data);
         }
+        vd.field_validated_bvs("reformed_icon", |bv, data| {
+            validate_icon_bv(IconKind::Faith, bv, data, &mut sc);
+        });
         vd.field_value("graphical_faith");
         vd.field_value("piety_icon_group");
 
@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 
 use crate::block::validator::Validator;
 use crate::block::Block;
+use crate::context::ScopeContext;
 use crate::errorkey::ErrorKey;
 use crate::errors::error;
 use crate::everything::Everything;
@@ -11,6 +12,7 @@ use crate::helpers::dup_error;
 use crate::pdxfile::PdxFile;
 use crate::scopes::scope_iterator;
 use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
 
 #[derive(Clone, Debug, Default)]
 pub struct ScriptedLists {
@@ -83,14 +85,21 @@ impl List {
         vd.req_field("base");
         vd.req_field("conditions");
 
+        let mut outscope = None;
         if let Some(token) = vd.field_value("base") {
-            if let Some((_, _outscope)) = scope_iterator(token, data) {
-                if let Some(_block) = vd.field_block("conditions") {
-                    // TODO: validate the trigger after we have rootless `ScopeContext`
-                }
+            if let Some((_, scopes)) = scope_iterator(token, data) {
+                outscope = Some(scopes);
             } else {
                 error(token, ErrorKey::MissingItem, "no such base list");
             }
         }
+
+        vd.field_validated_block("conditions", |b, data| {
+            // Without a valid base, we don't know what the conditions apply to.
+            if let Some(outscope) = outscope {
+                let mut sc = ScopeContext::new_unrooted(outscope, self.key.clone());
+                validate_normal_trigger(b, data, &mut sc, false);
+            }
+        });
     }
 }
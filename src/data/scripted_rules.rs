@@ -0,0 +1,157 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error_info, warn_info};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::helpers::dup_error;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::tables::scripted_rules::SCRIPTED_RULE_SCOPES;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+/// The rules in `common/scripted_rules`, which are triggers that the game asks when it makes
+/// certain decisions, such as whether two characters can marry.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedRules {
+    rules: FnvHashMap<String, ScriptedRule>,
+}
+
+impl ScriptedRules {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.rules.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "scripted rule");
+            }
+        }
+        self.rules
+            .insert(key.to_string(), ScriptedRule::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.rules.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.rules.values() {
+            item.validate(data);
+        }
+        self.validate_missing(data);
+    }
+
+    /// The game's rules are all in one file, so a mod that overrides that file or replaces the
+    /// directory has to define them all again. The ones it leaves out become empty triggers.
+    fn validate_missing(&self, data: &Everything) {
+        let subpath = PathBuf::from("common/scripted_rules");
+        let mut mod_files = data
+            .fileset
+            .get_files_under(&subpath)
+            .filter(|entry| entry.kind() == FileKind::Mod);
+        let entry = match mod_files
+            .clone()
+            .find(|entry| data.fileset.replaced_files(entry.path()).next().is_some())
+        {
+            Some(entry) => entry,
+            None if data.fileset.is_replaced("common/scripted_rules") => match mod_files.next() {
+                Some(entry) => entry,
+                None => return,
+            },
+            None => return,
+        };
+
+        let mut missing = SCRIPTED_RULE_SCOPES
+            .iter()
+            .map(|(name, _, _)| *name)
+            .filter(|name| !self.exists(name))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return;
+        }
+        missing.sort_unstable();
+        let msg = format!(
+            "this file replaces the game's scripted rules but leaves out {}",
+            missing.join(", ")
+        );
+        let info = "the game then uses an empty trigger for them";
+        warn_info(entry, ErrorKey::MissingItem, &msg, info);
+    }
+}
+
+impl ItemDb for ScriptedRules {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.rules.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.rules.values().map(as_source))
+    }
+}
+
+impl FileHandler for ScriptedRules {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/scripted_rules")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScriptedRule {
+    key: Token,
+    block: Block,
+}
+
+impl ScriptedRule {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let rule = SCRIPTED_RULE_SCOPES
+            .iter()
+            .find(|(name, _, _)| self.key.is(name));
+        if let Some((_, root, names)) = rule {
+            let mut sc =
+                ScopeContext::new_root(Scopes::from_bits_truncate(*root), self.key.clone());
+            for (name, scopes) in *names {
+                sc.define_name(name, Scopes::from_bits_truncate(*scopes));
+            }
+            validate_normal_trigger(&self.block, data, &mut sc, false);
+        } else {
+            let info = "the game only uses the rules it knows, and ignores this one";
+            error_info(
+                &self.key,
+                ErrorKey::Validation,
+                "unknown scripted rule",
+                info,
+            );
+        }
+    }
+}
+
+impl ItemSource for ScriptedRule {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -2,6 +2,7 @@ use fnv::FnvHashMap;
 use std::path::{Path, PathBuf};
 
 use crate::block::Block;
+use crate::calls::CallGraph;
 use crate::context::ScopeContext;
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
@@ -36,11 +37,28 @@ impl Triggers {
         self.triggers.get(key)
     }
 
+    pub fn iter_keys(&self) -> impl Iterator<Item = &str> {
+        self.triggers.keys().map(String::as_str)
+    }
+
     pub fn validate(&self, data: &Everything) {
         for item in self.triggers.values() {
             item.validate(data);
         }
     }
+
+    /// Look at the calls between the scripted triggers as a whole, for cycles and for ones that
+    /// grow too big when all their calls are filled in.
+    pub fn check_calls(&self, data: &Everything) {
+        let defs = self
+            .triggers
+            .values()
+            .map(|item| (&item.key, &item.block))
+            .collect();
+        let graph = CallGraph::new("scripted trigger", defs);
+        graph.check_cycles();
+        graph.check_expansion(data.limits.scripted_expansion);
+    }
 }
 
 impl FileHandler for Triggers {
@@ -109,6 +127,10 @@ impl Trigger {
         self.block.macro_parms()
     }
 
+    pub fn required_macro_parms(&self) -> Vec<String> {
+        self.block.required_macro_parms()
+    }
+
     pub fn cached_compat(
         &self,
         key: &Token,
@@ -3,7 +3,7 @@ use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 
 use crate::block::validator::Validator;
-use crate::block::{Block, BlockOrValue};
+use crate::block::{Block, BlockOrValue, Comparator};
 use crate::context::ScopeContext;
 use crate::errorkey::ErrorKey;
 use crate::errors::{error, warn, warn_info};
@@ -50,6 +50,21 @@ impl ScriptValues {
             item.validate_call(key, data, sc);
         }
     }
+
+    /// Warn if `key` names a script value that the game will show to the player,
+    /// but that has neither a `desc` in its parts nor a localization key of its own.
+    pub fn verify_shown(&self, key: &Token, data: &Everything) {
+        if let Some(item) = self.scriptvalues.get(key.as_str()) {
+            if !item.has_desc() && !data.localization.exists_any(key.as_str()) {
+                let msg =
+                    format!("script value `{key}` is shown to the player but has no localization");
+                let info = format!(
+                    "give its parts a `desc`, or add a localization key `{key}`, so the player doesn't see its raw name"
+                );
+                warn_info(key, ErrorKey::MissingLocalization, &msg, &info);
+            }
+        }
+    }
 }
 
 impl FileHandler for ScriptValues {
@@ -89,6 +104,51 @@ impl ScriptValue {
         }
     }
 
+    /// Whether any part of this script value has a `desc` for the breakdown in the tooltip
+    fn has_desc(&self) -> bool {
+        fn block_has_desc(block: &Block) -> bool {
+            block.iter_items().any(|(k, _, bv)| {
+                k.as_ref().is_some_and(|k| k.is("desc"))
+                    || bv.get_block().is_some_and(block_has_desc)
+            })
+        }
+        self.bv.get_block().is_some_and(block_has_desc)
+    }
+
+    /// Handle a key that is a scope iterator like `every_vassal`. Returns whether it was one.
+    fn validate_iterator_key(
+        key: &Token,
+        cmp: Comparator,
+        bv: &BlockOrValue,
+        data: &Everything,
+        sc: &mut ScopeContext,
+    ) -> bool {
+        if let Some((it_type, it_name)) = key.split_once('_') {
+            if it_type.is("every")
+                || it_type.is("ordered")
+                || it_type.is("random")
+                || it_type.is("any")
+            {
+                if let Some((inscopes, outscope)) = scope_iterator(&it_name, data) {
+                    if cmp == Comparator::QEq {
+                        let msg = format!("`{key} ?=` does nothing here");
+                        error(key, ErrorKey::Validation, &msg);
+                    }
+                    if it_type.is("any") {
+                        let msg = format!("cannot use `{key}` in a script value");
+                        error(key, ErrorKey::Validation, &msg);
+                    }
+                    sc.expect(inscopes, key);
+                    sc.open_scope(outscope, key.clone());
+                    Self::validate_iterator(&it_type, &it_name, bv.get_block().unwrap(), data, sc);
+                    sc.close();
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     fn validate_inner(mut vd: Validator, data: &Everything, sc: &mut ScopeContext) {
         vd.field_value_item("desc", Item::Localization);
         vd.field_value_item("format", Item::Localization);
@@ -143,36 +203,18 @@ impl ScriptValue {
             Self::validate_else(b, data, sc);
         });
 
-        'outer: for (key, bv) in vd.unknown_keys() {
+        'outer: for (key, cmp, bv) in vd.unknown_keys_cmp() {
+            if !matches!(cmp, Comparator::Eq | Comparator::QEq) {
+                let msg = format!("expected `{key} =`, found `{cmp}`");
+                error(key, ErrorKey::Validation, &msg);
+            }
             if let Some(token) = bv.get_value() {
                 error(token, ErrorKey::Validation, "expected block, found value");
                 continue;
             }
 
-            if let Some((it_type, it_name)) = key.split_once('_') {
-                if it_type.is("every")
-                    || it_type.is("ordered")
-                    || it_type.is("random")
-                    || it_type.is("any")
-                {
-                    if let Some((inscopes, outscope)) = scope_iterator(&it_name, data) {
-                        if it_type.is("any") {
-                            let msg = format!("cannot use `{}` in a script value", key);
-                            error(key, ErrorKey::Validation, &msg);
-                        }
-                        sc.expect(inscopes, key);
-                        sc.open_scope(outscope, key.clone());
-                        Self::validate_iterator(
-                            &it_type,
-                            &it_name,
-                            bv.get_block().unwrap(),
-                            data,
-                            sc,
-                        );
-                        sc.close();
-                        continue;
-                    }
-                }
+            if Self::validate_iterator_key(key, cmp, bv, data, sc) {
+                continue;
             }
 
             let mut first = true;
@@ -186,6 +228,7 @@ impl ScriptValue {
                         }
                         sc.expect(inscopes, &prefix);
                         validate_prefix_reference(&prefix, &arg, data);
+                        let outscope = sc.named_scope_or(&prefix, &arg, outscope);
                         sc.replace(outscope, part);
                     } else {
                         let msg = format!("unknown prefix `{}:`", prefix);
@@ -200,7 +243,9 @@ impl ScriptValue {
                     || part.is("PREV")
                     || part.is("THIS")
                 {
-                    if !first {
+                    // `prev.prev` is fine, but `liege.prev` is not
+                    let prev_chain = (part.is("prev") || part.is("PREV")) && sc.can_chain_prev();
+                    if !first && !prev_chain {
                         let msg = format!("`{}` makes no sense except as first part", part);
                         warn(&part, ErrorKey::Validation, &msg);
                     }
@@ -297,6 +342,9 @@ impl ScriptValue {
     pub fn validate_value(t: &Token, data: &Everything, sc: &mut ScopeContext) {
         if t.as_str().parse::<i32>().is_ok() || t.as_str().parse::<f64>().is_ok() {
             // numeric literal is always valid
+            t.check_precision();
+        } else if t.check_number_format() {
+            // already reported
         } else {
             let part_vec = t.split('.');
             sc.open_builder();
@@ -318,6 +366,7 @@ impl ScriptValue {
                         }
                         sc.expect(inscopes, &prefix);
                         validate_prefix_reference(&prefix, &arg, data);
+                        let outscope = sc.named_scope_or(&prefix, &arg, outscope);
                         sc.replace(outscope, part.clone());
                     } else {
                         let msg = format!("unknown prefix `{}:`", prefix);
@@ -332,7 +381,9 @@ impl ScriptValue {
                     || part.is("PREV")
                     || part.is("THIS")
                 {
-                    if !first {
+                    // `prev.prev` is fine, but `liege.prev` is not
+                    let prev_chain = (part.is("prev") || part.is("PREV")) && sc.can_chain_prev();
+                    if !first && !prev_chain {
                         let msg = format!("`{}` makes no sense except as first part", part);
                         warn(part, ErrorKey::Validation, &msg);
                     } else if last {
@@ -11,7 +11,7 @@ use crate::modif::{validate_modifs, ModifKinds};
 use crate::pdxfile::PdxFile;
 use crate::scopes::Scopes;
 use crate::token::Token;
-use crate::validate::validate_color;
+use crate::validate::validate_color_bv;
 
 #[derive(Clone, Debug, Default)]
 pub struct Terrains {
@@ -78,7 +78,7 @@ impl Terrain {
         vd.req_field("color");
 
         vd.field_numeric("movement_speed");
-        vd.field_validated_block("color", validate_color);
+        vd.field_validated_bv("color", validate_color_bv);
 
         vd.field_validated_block("attacker_modifier", |b, data| {
             validate_combat_modifier(b, data, &mut sc);
@@ -3,6 +3,7 @@ use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+use crate::block::validator::Validator;
 use crate::block::Block;
 use crate::data::provinces::ProvId;
 use crate::errorkey::ErrorKey;
@@ -13,11 +14,14 @@ use crate::helpers::dup_error;
 use crate::item::Item;
 use crate::pdxfile::PdxFile;
 use crate::token::Token;
+use crate::validate::validate_color_bv;
 
 #[derive(Clone, Debug, Default)]
 pub struct Titles {
     titles: FnvHashMap<String, Rc<Title>>,
     baronies: FnvHashMap<ProvId, Rc<Title>>,
+    /// The province of the capital barony of each county
+    county_capitals: FnvHashMap<String, ProvId>,
 }
 
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
@@ -69,19 +73,28 @@ impl Display for Tier {
 }
 
 impl Titles {
-    pub fn load_item(&mut self, key: Token, block: &Block, capital_of: Option<Token>) {
+    pub fn load_item(
+        &mut self,
+        key: Token,
+        block: &Block,
+        capital_of: Option<Token>,
+        county: Option<Token>,
+    ) {
         if let Some(other) = self.titles.get(key.as_str()) {
             if other.key.loc.kind >= key.loc.kind {
                 dup_error(&key, &other.key, "title");
             }
         }
-        let title = Rc::new(Title::new(key.clone(), block.clone(), capital_of));
+        let title = Rc::new(Title::new(key.clone(), block.clone(), capital_of, county));
         self.titles.insert(key.to_string(), title.clone());
 
         let parent_tier = Tier::try_from(&key).unwrap(); // guaranteed by caller
         if parent_tier == Tier::Barony {
             if let Some(provid) = block.get_field_integer("province") {
                 if let Ok(provid) = ProvId::try_from(provid) {
+                    if let Some(county) = &title.capital_of {
+                        self.county_capitals.insert(county.to_string(), provid);
+                    }
                     self.baronies.insert(provid, title);
                 } else {
                     error(
@@ -103,7 +116,12 @@ impl Titles {
                     error(k, ErrorKey::Validation, &msg);
                 }
                 let capital_of = if capital { Some(key.clone()) } else { None };
-                self.load_item(k.clone(), v, capital_of);
+                let county = if parent_tier == Tier::County {
+                    Some(key.clone())
+                } else {
+                    None
+                };
+                self.load_item(k.clone(), v, capital_of, county);
                 capital = false;
             }
         }
@@ -125,6 +143,40 @@ impl Titles {
     pub fn capital_of(&self, prov: ProvId) -> Option<&Token> {
         self.baronies.get(&prov).and_then(|b| b.capital_of.as_ref())
     }
+
+    /// The county that the barony of this province is in
+    pub fn county_of(&self, prov: ProvId) -> Option<&Token> {
+        self.baronies.get(&prov).and_then(|b| b.county.as_ref())
+    }
+
+    /// The province of the county's capital barony
+    pub fn capital_province(&self, county: &str) -> Option<ProvId> {
+        self.county_capitals.get(county).copied()
+    }
+
+    /// Whether the province is the capital of a duchy, meaning that its barony is the capital
+    /// of the county that a duchy names as its `capital`. `None` if the province has no barony.
+    pub fn is_duchy_capital(&self, prov: ProvId) -> Option<bool> {
+        let barony = self.baronies.get(&prov)?;
+        Some(barony.capital_of.as_ref().is_some_and(|county| {
+            self.titles.values().any(|title| {
+                title.tier == Tier::Duchy
+                    && title
+                        .block
+                        .get_field_value("capital")
+                        .is_some_and(|capital| capital == county)
+            })
+        }))
+    }
+
+    /// The province of a barony
+    pub fn barony_province(&self, barony: &str) -> Option<ProvId> {
+        let title = self.titles.get(barony)?;
+        if title.tier != Tier::Barony {
+            return None;
+        }
+        ProvId::try_from(title.block.get_field_integer("province")?).ok()
+    }
 }
 
 impl FileHandler for Titles {
@@ -144,7 +196,7 @@ impl FileHandler for Titles {
 
         for (key, block) in block.iter_pure_definitions_warn() {
             if Tier::try_from(key).is_ok() {
-                self.load_item(key.clone(), block, None);
+                self.load_item(key.clone(), block, None, None);
             } else {
                 warn(key, ErrorKey::Validation, "expected title");
             }
@@ -166,22 +218,38 @@ impl FileHandler for Titles {
     }
 }
 
+/// The yes/no fields of a title
+const TITLE_FLAGS: &[&str] = &[
+    "definite_form",
+    "ruler_uses_title_name",
+    "landless",
+    "destroy_if_invalid_heir",
+    "no_automatic_claims",
+    "always_follows_primary_heir",
+    "de_jure_drift_disabled",
+    "can_use_nomadic_naming",
+    "ignore_titularity_for_title_weighting",
+    "delete_on_destroy",
+];
+
 #[derive(Clone, Debug)]
 pub struct Title {
     key: Token,
     block: Block,
     tier: Tier,
     capital_of: Option<Token>, // for baronies
+    county: Option<Token>,     // for baronies
 }
 
 impl Title {
-    pub fn new(key: Token, block: Block, capital_of: Option<Token>) -> Self {
+    pub fn new(key: Token, block: Block, capital_of: Option<Token>, county: Option<Token>) -> Self {
         let tier = Tier::try_from(&key).unwrap(); // guaranteed by caller
         Self {
             key,
             block,
             tier,
             capital_of,
+            county,
         }
     }
 
@@ -192,11 +260,25 @@ impl Title {
         // The _adj key is optional
         // The _pre key is optional
 
+        for field in ["color", "color2"] {
+            if let Some(bv) = self.block.get_field(field) {
+                validate_color_bv(bv, data);
+            }
+        }
+
         if let Some(names) = self.block.get_field_block("cultural_names") {
-            for (_, t) in names.get_assignments() {
+            for (list, t) in names.get_assignments() {
+                data.verify_exists(Item::NameList, list);
                 data.localization.verify_exists(t);
                 // The _adj key is optional
             }
         }
+
+        // The nested titles are validated on their own
+        let mut vd = Validator::new(&self.block, data);
+        for field in TITLE_FLAGS {
+            vd.field_bool(field);
+        }
+        vd.no_warn_remaining();
     }
 }
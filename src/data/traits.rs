@@ -5,9 +5,12 @@ use crate::block::validator::Validator;
 use crate::block::Block;
 use crate::context::ScopeContext;
 use crate::desc::{validate_desc, validate_desc_map};
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error_info, warn, warn2, warn_info};
 use crate::everything::Everything;
 use crate::fileset::{FileEntry, FileHandler};
-use crate::helpers::dup_error;
+use crate::helpers::{closest_key, dup_error};
+use crate::icons::{verify_icon, IconKind};
 use crate::item::Item;
 use crate::modif::{validate_modifs, ModifKinds};
 use crate::pdxfile::PdxFile;
@@ -18,6 +21,8 @@ use crate::token::Token;
 pub struct Traits {
     traits: FnvHashMap<String, Trait>,
     groups: FnvHashSet<String>,
+    /// The flags that traits have, for `has_trait_with_flag`
+    flags: FnvHashSet<String>,
 }
 
 impl Traits {
@@ -33,6 +38,9 @@ impl Traits {
         if let Some(token) = block.get_field_value("group_equivalence") {
             self.groups.insert(token.to_string());
         }
+        for token in block.get_field_values("flag") {
+            self.flags.insert(token.to_string());
+        }
         self.traits
             .insert(key.to_string(), Trait::new(key.clone(), block.clone()));
     }
@@ -41,6 +49,91 @@ impl Traits {
         self.traits.contains_key(key) || self.groups.contains(key)
     }
 
+    pub fn flag_exists(&self, key: &str) -> bool {
+        self.flags.contains(key)
+    }
+
+    /// Whether either trait lists the other one in its `opposites`
+    fn are_opposites(&self, a: &str, b: &str) -> bool {
+        let lists = |this: &str, other: &str| {
+            self.traits.get(this).is_some_and(|item| {
+                item.block
+                    .get_field_list("opposites")
+                    .is_some_and(|tokens| tokens.iter().any(|t| t.is(other)))
+            })
+        };
+        lists(a, b) || lists(b, a)
+    }
+
+    /// Warn about traits that are opposites of a trait earlier in the list. The game won't give
+    /// a character a trait if they have one of its opposites, so the later one is dropped.
+    pub fn check_opposites(&self, traits: &[&Token]) {
+        for (idx, later) in traits.iter().enumerate() {
+            if let Some(earlier) = traits[..idx]
+                .iter()
+                .find(|earlier| self.are_opposites(earlier.as_str(), later.as_str()))
+            {
+                let msg = format!(
+                    "trait `{later}` is an opposite of `{earlier}`, so the character will not get it"
+                );
+                warn2(
+                    *later,
+                    ErrorKey::Conflict,
+                    &msg,
+                    *earlier,
+                    "the other one is here",
+                );
+            }
+        }
+    }
+
+    /// Check the trait and track named in `add_trait_xp` or `has_trait_xp`. The game ignores
+    /// XP for traits without tracks, and traits with several tracks need the track named.
+    pub fn verify_track(&self, key: &Token, trait_token: &Token, track: Option<&Token>) {
+        let Some(item) = self.traits.get(trait_token.as_str()) else {
+            return;
+        };
+        if let Some(tracks) = item.block.get_field_block("tracks") {
+            let names = tracks
+                .iter_items()
+                .filter_map(|(k, _, _)| k.as_ref())
+                .collect::<Vec<_>>();
+            match track {
+                Some(track) if !names.iter().any(|name| name.is(track.as_str())) => {
+                    let msg = format!("trait `{trait_token}` has no track `{track}`");
+                    match closest_key(track.as_str(), names.iter().map(|name| name.as_str())) {
+                        Some(close) => {
+                            let info = format!("did you mean `{close}`?");
+                            error_info(track, ErrorKey::MissingItem, &msg, &info);
+                        }
+                        None => error(track, ErrorKey::MissingItem, &msg),
+                    }
+                }
+                Some(_) => (),
+                None => {
+                    let msg = format!("trait `{trait_token}` has several tracks");
+                    warn_info(
+                        key,
+                        ErrorKey::Validation,
+                        &msg,
+                        "say which one with `track =`",
+                    );
+                }
+            }
+        } else if item.block.get_field_block("track").is_some() {
+            if let Some(track) = track {
+                if !track.is(trait_token.as_str()) {
+                    let msg = format!("trait `{trait_token}` has only one track");
+                    warn_info(track, ErrorKey::Validation, &msg, "leave out the `track`");
+                }
+            }
+        } else {
+            let msg = format!("trait `{trait_token}` has no XP track");
+            let info = "the game ignores XP for traits without a `track` or `tracks`";
+            warn_info(trait_token, ErrorKey::Validation, &msg, info);
+        }
+    }
+
     pub fn validate(&self, data: &Everything) {
         let mut vec = self.traits.values().collect::<Vec<&Trait>>();
         vec.sort_unstable_by_key(|item| &item.key.loc);
@@ -89,6 +182,34 @@ impl Trait {
         validate_modifs(block, data, ModifKinds::Character, sc, vd);
     }
 
+    /// A track is a list of XP thresholds with the modifiers the character has from that
+    /// much XP on.
+    fn validate_track(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+        let mut vd = Validator::new(block, data);
+        let mut previous: Option<(&Token, i64)> = None;
+        for (key, block) in vd.integer_blocks() {
+            let xp = key.as_str().parse::<i64>().unwrap_or_default();
+            if !(0..=100).contains(&xp) {
+                warn(key, ErrorKey::Range, "trait XP goes from 0 to 100");
+            }
+            if let Some((prev, prev_xp)) = previous {
+                if xp <= prev_xp {
+                    let msg = "XP thresholds should be in increasing order";
+                    warn2(
+                        key,
+                        ErrorKey::Validation,
+                        msg,
+                        prev,
+                        "the previous threshold is here",
+                    );
+                }
+            }
+            previous = Some((key, xp));
+            let vd = Validator::new(block, data);
+            validate_modifs(block, data, ModifKinds::Character, sc, vd);
+        }
+    }
+
     fn validate_triggered_opinion(block: &Block, data: &Everything) {
         let mut vd = Validator::new(block, data);
 
@@ -122,14 +243,23 @@ impl Trait {
 
         if let Some(bv) = vd.field("icon") {
             validate_desc_map(bv, data, &mut sc, |name, data| {
-                let path = format!("gfx/interface/icons/traits/{}", name);
-                data.fileset.verify_exists_implied(&path, name);
+                verify_icon(IconKind::Trait, name.as_str(), name, data);
             });
         } else {
-            let path = format!("gfx/interface/icons/traits/{}.dds", self.key);
-            data.fileset.verify_exists_implied(&path, &self.key);
+            verify_icon(IconKind::Trait, self.key.as_str(), &self.key, data);
         }
 
+        vd.field_validated_block("track", |b, data| Self::validate_track(b, data, &mut sc));
+        vd.field_validated_block("tracks", |b, data| {
+            let mut vd = Validator::new(b, data);
+            for (_, bv) in vd.unknown_keys() {
+                if let Some(block) = bv.expect_block() {
+                    Self::validate_track(block, data, &mut sc);
+                }
+            }
+        });
+        vd.mutually_exclusive(&["track", "tracks"]);
+
         vd.field_validated_blocks("culture_modifier", |b, data| {
             Self::validate_culture_modifier(b, data, &mut sc);
         });
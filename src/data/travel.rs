@@ -0,0 +1,340 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::effect::validate_normal_effect;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler, FileKind};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::modif::{validate_modifs, ModifKinds};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+use crate::validate::{validate_cost, validate_modifiers_with_base};
+
+/// The travel system was added in CK3 1.9. If the game files don't define any travel options,
+/// the installed game predates it and the travel effects and triggers don't exist either.
+#[derive(Clone, Debug, Default)]
+pub struct TravelOptions {
+    options: FnvHashMap<String, TravelOption>,
+    in_game: bool,
+}
+
+impl TravelOptions {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.options.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "travel option");
+            }
+        }
+        if key.loc.kind != FileKind::Mod {
+            self.in_game = true;
+        }
+        self.options
+            .insert(key.to_string(), TravelOption::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.options.contains_key(key)
+    }
+
+    /// Note that the installed game has the travel system even though its travel options
+    /// were not loaded.
+    pub fn set_in_game(&mut self) {
+        self.in_game = true;
+    }
+
+    /// Whether the installed game has the travel system
+    pub fn in_game(&self) -> bool {
+        self.in_game
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.options.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for TravelOptions {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.options.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.options.values().map(as_source))
+    }
+}
+
+impl FileHandler for TravelOptions {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/travel/travel_options")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TravelOption {
+    key: Token,
+    block: Block,
+}
+
+impl TravelOption {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        // The root is the owner of the travel plan
+        let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+        sc.define_name("travel_plan", Scopes::TravelPlan);
+
+        vd.field_validated_block("is_shown", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("is_valid", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, true);
+        });
+        vd.field_validated_block("cost", |b, data| validate_cost(b, data, &mut sc));
+        vd.field_validated_block("on_applied_effect", |b, data| {
+            validate_normal_effect(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("travel_plan_modifier", |b, data| {
+            let vd = Validator::new(b, data);
+            validate_modifs(b, data, ModifKinds::TravelPlan, &mut sc, vd);
+        });
+        vd.field_validated_block("owner_modifier", |b, data| {
+            let vd = Validator::new(b, data);
+            validate_modifs(b, data, ModifKinds::Character, &mut sc, vd);
+        });
+        vd.field_validated_block("ai_will_do", |b, data| {
+            validate_modifiers_with_base(b, data, &mut sc);
+        });
+    }
+}
+
+impl ItemSource for TravelOption {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PointsOfInterest {
+    types: FnvHashMap<String, PointOfInterestType>,
+}
+
+impl PointsOfInterest {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.types.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "point of interest type");
+            }
+        }
+        self.types.insert(
+            key.to_string(),
+            PointOfInterestType::new(key, block.clone()),
+        );
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.types.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.types.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for PointsOfInterest {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.types.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.types.values().map(as_source))
+    }
+}
+
+impl FileHandler for PointsOfInterest {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/travel/point_of_interest_types")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PointOfInterestType {
+    key: Token,
+    block: Block,
+}
+
+impl PointOfInterestType {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        // The root is the province being visited
+        let mut sc = ScopeContext::new_root(Scopes::Province, self.key.clone());
+        sc.define_name("travel_plan", Scopes::TravelPlan);
+        sc.define_name("traveler", Scopes::Character);
+
+        vd.field_validated_block("is_valid", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("on_visit", |b, data| {
+            validate_normal_effect(b, data, &mut sc, true);
+        });
+        vd.field_validated_block("ai_will_do", |b, data| {
+            validate_modifiers_with_base(b, data, &mut sc);
+        });
+    }
+}
+
+impl ItemSource for PointOfInterestType {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DangerTypes {
+    dangers: FnvHashMap<String, DangerType>,
+}
+
+impl DangerTypes {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.dangers.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "danger type");
+            }
+        }
+        self.dangers
+            .insert(key.to_string(), DangerType::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.dangers.contains_key(key)
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.dangers.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for DangerTypes {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.dangers.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.dangers.values().map(as_source))
+    }
+}
+
+impl FileHandler for DangerTypes {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/travel/danger_types")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DangerType {
+    key: Token,
+    block: Block,
+}
+
+impl DangerType {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        // The root is the province the travel plan is passing through
+        let mut sc = ScopeContext::new_root(Scopes::Province, self.key.clone());
+        sc.define_name("travel_plan", Scopes::TravelPlan);
+
+        vd.field_value_item("terrain", Item::Terrain);
+        vd.field_validated_block("is_valid", |b, data| {
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.field_validated_block("danger_chance", |b, data| {
+            validate_modifiers_with_base(b, data, &mut sc);
+        });
+        vd.field_validated_block("travel_plan_modifier", |b, data| {
+            let vd = Validator::new(b, data);
+            validate_modifs(b, data, ModifKinds::TravelPlan, &mut sc, vd);
+        });
+    }
+}
+
+impl ItemSource for DangerType {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
@@ -0,0 +1,243 @@
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
+use crate::desc::validate_desc;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error_info};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler};
+use crate::helpers::{closest_key, dup_error};
+use crate::itemdb::{as_source, ItemDb, ItemSource};
+use crate::pdxfile::PdxFile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+use crate::validate::{validate_ai_chance, validate_color_bv};
+
+#[derive(Clone, Debug, Default)]
+pub struct VassalContracts {
+    contracts: FnvHashMap<String, VassalContract>,
+}
+
+impl VassalContracts {
+    pub fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.contracts.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "vassal contract");
+            }
+        }
+        self.contracts
+            .insert(key.to_string(), VassalContract::new(key, block.clone()));
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.contracts.contains_key(key)
+    }
+
+    pub fn level_exists(&self, key: &str) -> bool {
+        self.contracts.values().any(|c| c.has_level(key))
+    }
+
+    pub fn flag_exists(&self, key: &str) -> bool {
+        self.contracts.values().any(|c| c.has_flag(key))
+    }
+
+    /// Check that `level` is an obligation level of the contract `contract`, either by name or
+    /// by index. A level name from a different contract is reported specially, because the
+    /// game silently ignores it.
+    pub fn verify_level(&self, contract: &Token, level: &Token) {
+        let Some(c) = self.contracts.get(contract.as_str()) else {
+            return;
+        };
+        if let Ok(index) = level.as_str().parse::<usize>() {
+            if index >= c.levels.len() {
+                let msg = format!(
+                    "`{}` has only {} obligation levels, numbered from 0",
+                    contract,
+                    c.levels.len()
+                );
+                error(level, ErrorKey::Range, &msg);
+            }
+            return;
+        }
+        if c.has_level(level.as_str()) {
+            return;
+        }
+        let msg = format!("`{level}` is not an obligation level of `{contract}`");
+        if let Some(other) = self
+            .contracts
+            .values()
+            .find(|c| c.has_level(level.as_str()))
+        {
+            let info = format!("it belongs to `{}`", other.key);
+            error_info(level, ErrorKey::MissingItem, &msg, &info);
+        } else if let Some(close) = closest_key(level.as_str(), c.levels.iter().map(Token::as_str))
+        {
+            let info = format!("did you mean `{close}`?");
+            error_info(level, ErrorKey::MissingItem, &msg, &info);
+        } else {
+            error(level, ErrorKey::MissingItem, &msg);
+        }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        for item in self.contracts.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl ItemDb for VassalContracts {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource> {
+        self.contracts.get(key).map(as_source)
+    }
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        Box::new(self.contracts.values().map(as_source))
+    }
+}
+
+impl FileHandler for VassalContracts {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/vassal_contracts")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VassalContract {
+    key: Token,
+    block: Block,
+    /// The names of the obligation levels, in order
+    levels: Vec<Token>,
+    /// The flags set by any of the obligation levels
+    flags: Vec<Token>,
+}
+
+impl VassalContract {
+    pub fn new(key: Token, block: Block) -> Self {
+        let mut levels = Vec::new();
+        let mut flags = Vec::new();
+        if let Some(obligations) = block.get_field_block("obligation_levels") {
+            for (level, b) in obligations.iter_pure_definitions() {
+                levels.push(level.clone());
+                for (k, _, bv) in b.iter_items() {
+                    if let (Some(k), BlockOrValue::Token(flag)) = (k, bv) {
+                        if k.is("flag") {
+                            flags.push(flag.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Self {
+            key,
+            block,
+            levels,
+            flags,
+        }
+    }
+
+    fn has_level(&self, key: &str) -> bool {
+        self.levels.iter().any(|level| level.is(key))
+    }
+
+    fn has_flag(&self, key: &str) -> bool {
+        self.flags.iter().any(|flag| flag.is(key))
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+        vd.field_value("display_mode");
+        vd.field_bool("uses_opinion_of_liege");
+        vd.field_validated_block("is_shown", |b, data| {
+            let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+        vd.req_field("obligation_levels");
+        vd.field_validated_block("obligation_levels", |b, data| {
+            let mut vd = Validator::new(b, data);
+            let mut defaults = 0;
+            for (level, bv) in vd.unknown_keys() {
+                if let Some(block) = bv.expect_block() {
+                    if block.get_field_bool("default").unwrap_or(false) {
+                        defaults += 1;
+                    }
+                    validate_obligation_level(level, block, data);
+                }
+            }
+            if defaults > 1 {
+                error(
+                    b,
+                    ErrorKey::Validation,
+                    "only one obligation level can be the default",
+                );
+            }
+        });
+    }
+}
+
+fn validate_obligation_level(key: &Token, block: &Block, data: &Everything) {
+    let mut vd = Validator::new(block, data);
+    // The triggers and values here get scope:liege and scope:vassal as well
+    let mut sc = ScopeContext::new_root(Scopes::Character, key.clone());
+
+    vd.field_bool("default");
+    vd.field_value("parent");
+    vd.field_value("icon");
+    vd.field_block("position");
+    vd.field_validated_bv("color", validate_color_bv);
+    vd.field_values("flag");
+
+    for field in [
+        "levies",
+        "tax",
+        "min_levies",
+        "min_tax",
+        "tax_factor",
+        "levies_factor",
+    ] {
+        vd.field_numeric(field);
+    }
+    vd.field_integer("vassal_opinion");
+    vd.field_integer("score");
+
+    vd.field_validated_block("is_shown", |b, data| {
+        validate_normal_trigger(b, data, &mut sc, false);
+    });
+    vd.field_validated_block("is_valid", |b, data| {
+        validate_normal_trigger(b, data, &mut sc, false);
+    });
+    for field in ["ai_will_do", "ai_liege_desire", "ai_vassal_desire"] {
+        vd.field_validated_bv(field, |bv, data| validate_ai_chance(bv, data, &mut sc));
+    }
+    vd.field_validated_bv("contribution_desc", |bv, data| {
+        validate_desc(bv, data, &mut sc);
+    });
+}
+
+impl ItemSource for VassalContract {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        Some(&self.block)
+    }
+}
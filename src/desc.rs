@@ -1,11 +1,13 @@
 use crate::block::{Block, BlockOrValue, DefinitionItem};
 use crate::context::ScopeContext;
 use crate::errorkey::ErrorKey;
-use crate::errors::warn;
+use crate::errors::{error, warn, warn_info};
 use crate::everything::Everything;
 use crate::token::Token;
 use crate::trigger::validate_normal_trigger;
 
+/// Validate the contents of a description block. These can be nested arbitrarily deep,
+/// with `first_valid`, `random_valid`, and `triggered_desc` blocks inside each other.
 pub fn validate_desc_map_block(
     block: &Block,
     data: &Everything,
@@ -13,10 +15,19 @@ pub fn validate_desc_map_block(
     f: &impl Fn(&Token, &Everything),
     triggered: bool,
 ) {
+    if triggered {
+        for field in &["trigger", "desc"] {
+            if block.get_key(field).is_none() {
+                let msg = format!("required field `{field}` missing");
+                error(block, ErrorKey::Validation, &msg);
+            }
+        }
+    }
+
     for def in block.iter_definitions_warn() {
         match def {
             DefinitionItem::Assignment(key, t) if key.is("desc") => {
-                if !t.as_str().contains(' ') {
+                if !t.as_str().trim().contains(' ') {
                     f(t, data);
                 }
             }
@@ -24,20 +35,61 @@ pub fn validate_desc_map_block(
                 warn(key, ErrorKey::Validation, "unexpected key in description");
             }
             DefinitionItem::Definition(key, b) => {
-                if key.is("desc") || key.is("first_valid") || key.is("random_valid") {
-                    validate_desc_map_block(b, data, sc, f, false);
-                } else if key.is("triggered_desc") {
-                    validate_desc_map_block(b, data, sc, f, true);
-                } else if triggered && key.is("trigger") {
-                    validate_normal_trigger(b, data, sc, false);
-                } else {
+                if key.is("trigger") {
+                    if triggered {
+                        validate_normal_trigger(b, data, sc, false);
+                    } else {
+                        warn(key, ErrorKey::Validation, "unexpected key in description");
+                    }
+                    continue;
+                } else if !(key.is("desc")
+                    || key.is("first_valid")
+                    || key.is("random_valid")
+                    || key.is("triggered_desc"))
+                {
                     warn(key, ErrorKey::Validation, "unexpected key in description");
+                    continue;
+                }
+
+                if b.iter_items().next().is_none() {
+                    let msg = format!("empty `{key}` block");
+                    warn(b, ErrorKey::Validation, &msg);
+                    continue;
+                }
+                if key.is("first_valid") && !has_fallback(b) {
+                    warn_info(
+                        key,
+                        ErrorKey::Validation,
+                        "`first_valid` has no unconditional entry",
+                        "if none of the triggers match, the description will be empty",
+                    );
                 }
+                validate_desc_map_block(b, data, sc, f, key.is("triggered_desc"));
             }
         }
     }
 }
 
+/// Returns true iff the block contains at least one entry that produces text regardless of triggers.
+fn has_fallback(block: &Block) -> bool {
+    block
+        .iter_items()
+        .any(|(k, _, bv)| matches!(k, Some(key) if is_unconditional(key, bv)))
+}
+
+fn is_unconditional(key: &Token, bv: &BlockOrValue) -> bool {
+    if key.is("desc") {
+        match bv {
+            BlockOrValue::Token(_) => true,
+            BlockOrValue::Block(b) => has_fallback(b),
+        }
+    } else if key.is("first_valid") || key.is("random_valid") {
+        matches!(bv.get_block(), Some(b) if has_fallback(b))
+    } else {
+        false
+    }
+}
+
 pub fn validate_desc_map(
     bv: &BlockOrValue,
     data: &Everything,
@@ -46,12 +98,16 @@ pub fn validate_desc_map(
 ) {
     match bv {
         BlockOrValue::Token(t) => {
-            if !t.as_str().contains(' ') {
+            if !t.as_str().trim().contains(' ') {
                 f(t, data);
             }
         }
         BlockOrValue::Block(b) => {
-            validate_desc_map_block(b, data, sc, &f, false);
+            if b.iter_items().next().is_none() {
+                warn(b, ErrorKey::Validation, "empty description block");
+            } else {
+                validate_desc_map_block(b, data, sc, &f, false);
+            }
         }
     }
 }
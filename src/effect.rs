@@ -1,17 +1,32 @@
 use crate::block::validator::Validator;
-use crate::block::{Block, BlockOrValue};
+use crate::block::{Block, BlockOrValue, Comparator};
 use crate::context::ScopeContext;
+use crate::data::character_templates::validate_create_character;
 use crate::data::scriptvalues::ScriptValue;
+use crate::data::titles::Tier;
 use crate::desc::validate_desc;
 use crate::errorkey::ErrorKey;
-use crate::errors::{error, error_info, warn, warn_info};
+use crate::errors::{advice, advice_info, error, error_info, warn, warn2, warn_info, ErrorLevel};
 use crate::everything::Everything;
+use crate::flags::FlagFamily;
 use crate::item::Item;
-use crate::scopes::{scope_iterator, scope_prefix, scope_to_scope, Scopes};
-use crate::tables::effects::{scope_effect, ControlEffect, Effect};
-use crate::trigger::{validate_normal_trigger, validate_target};
+use crate::scopes::{
+    iterator_names, scope_iterator, scope_prefix, scope_to_scope, unknown_link, unknown_prefix,
+    Scopes,
+};
+use crate::tables::deprecated::{report_deprecated, Command};
+use crate::tables::effects::{
+    scope_effect, ControlEffect, Effect, SpecialEffect, EFFECT_GUARDS, SCOPE_EFFECT,
+    SCOPE_TRAVEL_EFFECT,
+};
+use crate::token::Token;
+use crate::trigger::{
+    script_value_mid_chain, validate_normal_trigger, validate_switch, validate_target,
+    validate_target_produces, validate_trigger, Caller,
+};
 use crate::validate::{
-    validate_inside_iterator, validate_iterator_fields, validate_prefix_reference, ListType,
+    check_opinion_value, check_qeq_target, validate_inside_iterator, validate_iterator_fields,
+    validate_macro_args, validate_prefix_reference, ListType,
 };
 
 pub fn validate_normal_effect<'a>(
@@ -24,6 +39,24 @@ pub fn validate_normal_effect<'a>(
     validate_effect("", ListType::None, block, data, sc, vd, tooltipped);
 }
 
+/// Like `validate_normal_effect`, for an effect block that is the field `caller` of an item,
+/// like an event's `immediate`. The reports about the block can then name it.
+pub fn validate_named_effect(
+    caller: &str,
+    block: &Block,
+    data: &Everything,
+    sc: &mut ScopeContext,
+    tooltipped: bool,
+) {
+    let vd = Validator::new(block, data);
+    validate_effect(caller, ListType::None, block, data, sc, vd, tooltipped);
+}
+
+/// How to make effects depend on a condition, for the reports about `limit` and `trigger`
+/// blocks that do nothing where they are
+const CONDITIONAL_EFFECTS_INFO: &str =
+    "to make effects conditional, use `if = { limit = { ... } ... }`";
+
 pub fn validate_effect<'a>(
     caller: &str,
     list_type: ListType,
@@ -33,6 +66,8 @@ pub fn validate_effect<'a>(
     mut vd: Validator<'a>,
     mut tooltipped: bool,
 ) {
+    check_nesting(block, data, sc);
+    let mut guard_mark = None;
     if let Some(b) = vd.field_block("limit") {
         if caller == "if"
             || caller == "else_if"
@@ -41,15 +76,37 @@ pub fn validate_effect<'a>(
             || list_type != ListType::None
         {
             validate_normal_trigger(b, data, sc, tooltipped);
+            guard_mark = Some(sc.add_guards(b));
         } else {
-            warn(
+            let msg = if caller.is_empty() {
+                "`limit` can only be used in if/else_if or lists".to_string()
+            } else {
+                format!(
+                    "`limit` can only be used in if/else_if or lists, not directly in `{caller}`"
+                )
+            };
+            warn_info(
                 block.get_key("limit").unwrap(),
                 ErrorKey::Validation,
-                "`limit` can only be used in if/else_if or lists",
+                &msg,
+                CONDITIONAL_EFFECTS_INFO,
             );
         }
     }
 
+    // Event options have a `trigger` of their own, but other effect blocks don't
+    if caller != "option" {
+        if let Some(key) = block.get_key("trigger") {
+            vd.field_block("trigger");
+            let msg = if caller.is_empty() {
+                "`trigger` does nothing in an effect block".to_string()
+            } else {
+                format!("`trigger` does nothing in `{caller}`")
+            };
+            warn_info(key, ErrorKey::Validation, &msg, CONDITIONAL_EFFECTS_INFO);
+        }
+    }
+
     if validate_iterator_fields(list_type, block, data, sc, &mut vd) {
         tooltipped = false;
     }
@@ -140,9 +197,9 @@ pub fn validate_effect<'a>(
         }
     }
 
-    vd.field_validated_blocks("modifier", |_b, _data| {
+    vd.field_validated_blocks("modifier", |b, data| {
         if caller == "random" {
-            // TODO
+            validate_trigger(Caller::Modifier, b, data, sc, false);
         } else {
             warn(
                 block.get_key("modifier").unwrap(),
@@ -152,13 +209,34 @@ pub fn validate_effect<'a>(
         }
     });
 
-    'outer: for (key, bv) in vd.unknown_keys() {
+    let keys = vd.unknown_keys_cmp();
+    if tooltipped {
+        check_tooltip_quality(caller, block, &keys);
+    }
+
+    // Traits added in the same block to the same character can't be opposites
+    let added_traits = keys
+        .iter()
+        .filter(|(key, _, _)| key.is("add_trait") || key.is("add_trait_force_tooltip"))
+        .filter_map(|(_, _, bv)| bv.get_value())
+        .collect::<Vec<_>>();
+    data.traits.check_opposites(&added_traits);
+
+    'outer: for (key, cmp, bv) in keys {
+        if !matches!(cmp, Comparator::Eq | Comparator::QEq) {
+            let msg = format!("expected `{key} =`, found `{cmp}`");
+            error(key, ErrorKey::Validation, &msg);
+        }
+
         if let Some(effect) = data.get_effect(key) {
+            no_qeq(key, cmp);
+            // It might resolve the title and vassal changes made so far
+            sc.resolve_title_changes();
             match bv {
                 BlockOrValue::Token(token) => {
                     if !effect.macro_parms().is_empty() {
                         error(token, ErrorKey::Macro, "expected macro arguments");
-                    } else if !token.is("yes") {
+                    } else if !token.is("yes") && !token.check_bool_spelling() {
                         warn(token, ErrorKey::Validation, "expected just effect = yes");
                     }
                     effect.validate_call(key, data, sc, tooltipped);
@@ -173,18 +251,13 @@ pub fn validate_effect<'a>(
                             "you can just use it as effect = yes",
                         );
                     } else {
-                        let mut vec = Vec::new();
-                        let mut vd = Validator::new(block, data);
-                        for parm in &parms {
-                            vd.req_field(parm.as_str());
-                            if let Some(token) = vd.field_value(parm.as_str()) {
-                                vec.push(token.clone());
-                            } else {
-                                continue 'outer;
-                            }
+                        let required = effect.required_macro_parms();
+                        let what = "scripted effect";
+                        if let Some(args) =
+                            validate_macro_args(what, key, block, data, &parms, &required)
+                        {
+                            effect.validate_macro_expansion(key, args, data, sc, tooltipped);
                         }
-                        let args = parms.into_iter().zip(vec.into_iter()).collect();
-                        effect.validate_macro_expansion(key, args, data, sc, tooltipped);
                     }
                 }
             }
@@ -192,131 +265,10 @@ pub fn validate_effect<'a>(
         }
 
         if let Some((inscopes, effect)) = scope_effect(key, data) {
+            no_qeq(key, cmp);
             sc.expect(inscopes, key);
-            match effect {
-                Effect::Yes => {
-                    if let Some(token) = bv.expect_value() {
-                        if !token.is("yes") {
-                            let msg = format!("expected just `{} = yes`", key);
-                            warn(token, ErrorKey::Validation, &msg);
-                        }
-                    }
-                }
-                Effect::Bool => {
-                    if let Some(token) = bv.expect_value() {
-                        validate_target(token, data, sc, Scopes::Bool);
-                    }
-                }
-                Effect::Integer => {
-                    if let Some(token) = bv.expect_value() {
-                        if token.as_str().parse::<i32>().is_err() {
-                            warn(token, ErrorKey::Validation, "expected integer");
-                        }
-                    }
-                }
-                Effect::Value | Effect::ScriptValue | Effect::NonNegativeValue => {
-                    if let Some(token) = bv.get_value() {
-                        if let Ok(number) = token.as_str().parse::<i32>() {
-                            if effect == Effect::NonNegativeValue && number < 0 {
-                                if key.is("add_gold") {
-                                    let msg = "add_gold does not take negative numbers";
-                                    let info = "try remove_short_term_gold instead";
-                                    warn_info(token, ErrorKey::Range, &msg, info);
-                                } else {
-                                    let msg = format!("{} does not take negative numbers", key);
-                                    warn(token, ErrorKey::Range, &msg);
-                                }
-                            }
-                        }
-                    }
-                    ScriptValue::validate_bv(bv, data, sc);
-                }
-                Effect::Scope(outscopes) => {
-                    if let Some(token) = bv.expect_value() {
-                        validate_target(token, data, sc, outscopes);
-                    }
-                }
-                Effect::Item(itype) => {
-                    if let Some(token) = bv.expect_value() {
-                        data.verify_exists(itype, token);
-                    }
-                }
-                Effect::Target(key, outscopes) => {
-                    if let Some(block) = bv.expect_block() {
-                        let mut vd = Validator::new(block, data);
-                        vd.req_field(key);
-                        if let Some(token) = vd.field_value(key) {
-                            validate_target(token, data, sc, outscopes);
-                        }
-                    }
-                }
-                Effect::TargetValue(key, outscopes, valuekey) => {
-                    if let Some(block) = bv.expect_block() {
-                        let mut vd = Validator::new(block, data);
-                        vd.req_field(key);
-                        vd.req_field(valuekey);
-                        if let Some(token) = vd.field_value(key) {
-                            validate_target(token, data, sc, outscopes);
-                        }
-                        if let Some(bv) = vd.field(valuekey) {
-                            ScriptValue::validate_bv(bv, data, sc);
-                        }
-                    }
-                }
-                Effect::ItemTarget(ikey, itype, tkey, outscopes) => {
-                    if let Some(block) = bv.expect_block() {
-                        let mut vd = Validator::new(block, data);
-                        if let Some(token) = vd.field_value(ikey) {
-                            data.verify_exists(itype, token);
-                        }
-                        if let Some(token) = vd.field_value(tkey) {
-                            validate_target(token, data, sc, outscopes);
-                        }
-                    }
-                }
-                Effect::ItemValue(key, itype) => {
-                    if let Some(block) = bv.expect_block() {
-                        let mut vd = Validator::new(block, data);
-                        vd.req_field(key);
-                        vd.req_field("value");
-                        if let Some(token) = vd.field_value(key) {
-                            data.verify_exists(itype, token);
-                        }
-                        if let Some(bv) = vd.field("value") {
-                            ScriptValue::validate_bv(bv, data, sc);
-                        }
-                    }
-                }
-                Effect::Desc => validate_desc(bv, data, sc),
-                Effect::Gender => {
-                    if let Some(token) = bv.expect_value() {
-                        if !(token.is("male") || token.is("female") || token.is("random")) {
-                            warn(
-                                token,
-                                ErrorKey::Validation,
-                                "expected `male`, `female`, or `random`",
-                            );
-                        }
-                    }
-                }
-                Effect::Special(_special) => (), // TODO
-                Effect::Control(ControlEffect::CustomTooltip) => match bv {
-                    BlockOrValue::Token(t) => data.verify_exists(Item::Localization, t),
-                    BlockOrValue::Block(b) => validate_effect_control(
-                        ControlEffect::CustomTooltip,
-                        b,
-                        data,
-                        sc,
-                        tooltipped,
-                    ),
-                },
-                Effect::Control(control) => {
-                    if let Some(block) = bv.expect_block() {
-                        validate_effect_control(control, block, data, sc, tooltipped);
-                    }
-                }
-                Effect::Unchecked => (),
-            }
+            check_effect_guard(key, sc);
+            validate_effect_kind(key, effect, bv, data, sc, tooltipped);
             continue;
         }
 
@@ -327,6 +279,7 @@ pub fn validate_effect<'a>(
                 || it_type.is("random")
             {
                 if let Some((inscopes, outscope)) = scope_iterator(&it_name, data) {
+                    no_qeq(key, cmp);
                     if it_type.is("any") {
                         let msg = "cannot use `any_` lists in an effect";
                         error(key, ErrorKey::Validation, msg);
@@ -361,10 +314,10 @@ pub fn validate_effect<'a>(
                     }
                     sc.expect(inscopes, &prefix);
                     validate_prefix_reference(&prefix, &arg, data);
+                    let outscope = sc.named_scope_or(&prefix, &arg, outscope);
                     sc.replace(outscope, part.clone());
                 } else {
-                    let msg = format!("unknown prefix `{}:`", prefix);
-                    error(part, ErrorKey::Validation, &msg);
+                    unknown_prefix(&prefix, part);
                     sc.close();
                     continue 'outer;
                 }
@@ -375,7 +328,9 @@ pub fn validate_effect<'a>(
                 || part.is("PREV")
                 || part.is("THIS")
             {
-                if !first {
+                // `prev.prev` is fine, but `liege.prev` is not
+                let prev_chain = (part.is("prev") || part.is("PREV")) && sc.can_chain_prev();
+                if !first && !prev_chain {
                     let msg = format!("`{}` makes no sense except as first part", part);
                     warn(part, ErrorKey::Validation, &msg);
                 }
@@ -393,22 +348,444 @@ pub fn validate_effect<'a>(
                 }
                 sc.expect(inscopes, part);
                 sc.replace(outscope, part.clone());
+            } else if data.scriptvalues.exists(part.as_str()) {
+                if i + 1 < part_vec.len() {
+                    script_value_mid_chain(part);
+                } else {
+                    let msg = format!("script value `{part}` is a number, not a scope");
+                    let info = "effects can only be run in a scope like a character or a title";
+                    warn_info(part, ErrorKey::Scopes, &msg, info);
+                }
+                sc.close();
+                continue 'outer;
             // TODO: warn if trying to use iterator or effect here
+            } else if part_vec.len() == 1 {
+                let iterators = iterator_names(&["every_", "random_", "ordered_"]);
+                let others = SCOPE_EFFECT
+                    .iter()
+                    .chain(SCOPE_TRAVEL_EFFECT)
+                    .map(|(_, s, _)| *s)
+                    .chain(data.effects.iter_keys())
+                    .chain(iterators.iter().map(String::as_str));
+                if !report_deprecated(part, &[Command::Effect, Command::Link], data.game_version) {
+                    unknown_link(part, others);
+                }
+                sc.close();
+                continue 'outer;
             } else {
-                let msg = format!("unknown token `{}`", part);
-                error(part, ErrorKey::Validation, &msg);
+                if !report_deprecated(part, &[Command::Link], data.game_version) {
+                    unknown_link(part, std::iter::empty());
+                }
                 sc.close();
                 continue 'outer;
             }
         }
 
+        // `target ?= { ... }` only runs the block if the target exists
+        let mut exists_mark = None;
+        if cmp == Comparator::QEq {
+            check_qeq_target(key);
+            exists_mark = Some(sc.add_guard("exists"));
+        }
         if let Some(block) = bv.expect_block() {
             validate_normal_effect(block, data, sc, tooltipped);
         }
+        if let Some(mark) = exists_mark {
+            sc.drop_guards(mark);
+        }
         sc.close();
     }
+
+    if let Some(mark) = guard_mark {
+        sc.drop_guards(mark);
+    }
+    sc.leave_block();
+
+    for change in sc.unresolved_title_changes() {
+        let msg = format!("title and vassal change `scope:{change}` is never resolved");
+        let info = format!(
+            "without `resolve_title_and_vassal_change = scope:{change}` the changes are left half done, which can corrupt the realms involved"
+        );
+        warn_info(&change, ErrorKey::Logic, &msg, &info);
+    }
+}
+
+/// Count one more level of effect or trigger nesting, and give advice if it's too deep.
+/// The caller must call `sc.leave_block()` when it's done with the block.
+pub fn check_nesting(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    if sc.enter_block() == data.limits.nesting + 1 {
+        let msg = format!(
+            "effects and triggers are nested more than {} deep here",
+            data.limits.nesting
+        );
+        advice_info(
+            block,
+            ErrorKey::DeepNesting,
+            &msg,
+            "the game's tooltips get cut off at this depth",
+        );
+    }
+}
+
+/// Give advice if an effect that needs a precondition is not inside a `limit` that checks it.
+/// This is only advice because the check may well have been done by the caller.
+fn check_effect_guard(key: &Token, sc: &ScopeContext) {
+    for (name, guards) in EFFECT_GUARDS {
+        let matched = if name.ends_with('_') {
+            key.as_str().starts_with(name)
+        } else {
+            key.is(name)
+        };
+        if matched && !sc.has_guard(guards) {
+            let msg = format!("`{key}` is not guarded by a `limit`");
+            let guards = guards
+                .iter()
+                .map(|guard| format!("`{guard}`"))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            let info = format!(
+                "this can cause errors if its preconditions are not met; guard it with {guards} unless the caller already does"
+            );
+            advice_info(key, ErrorKey::Guard, &msg, &info);
+        }
+    }
+}
+
+/// `?=` only makes a difference when the left side is a scope that might not exist.
+fn no_qeq(key: &Token, cmp: Comparator) {
+    if cmp == Comparator::QEq {
+        let msg = format!("`{key} ?=` does nothing here");
+        error_info(
+            key,
+            ErrorKey::Validation,
+            &msg,
+            "`?=` only skips the block when the scope does not exist; use `=`",
+        );
+    }
+}
+
+/// Look for patterns in a tooltipped effect block that make for confusing tooltips.
+fn check_tooltip_quality(
+    caller: &str,
+    block: &Block,
+    keys: &[(&Token, Comparator, &BlockOrValue)],
+) {
+    if caller == "option"
+        && !keys.is_empty()
+        && keys.iter().all(|(key, _, _)| key.is("hidden_effect"))
+        && block.get_key("custom_tooltip").is_none()
+    {
+        advice_info(
+            keys[0].0,
+            ErrorKey::Tooltip,
+            "all the effects of this option are hidden, so it has no tooltip",
+            "add a `custom_tooltip` to tell the player what it does",
+        );
+    }
+
+    let has_hidden = keys.iter().any(|(key, _, _)| key.is("hidden_effect"));
+    let has_others = keys
+        .iter()
+        .any(|(key, _, _)| !key.is("hidden_effect") && !key.is("custom_tooltip"));
+    if !has_hidden && has_others {
+        for (key, _, bv) in block.iter_items() {
+            if let (Some(key), BlockOrValue::Token(_)) = (key, bv) {
+                if key.is("custom_tooltip") {
+                    advice_info(
+                        key,
+                        ErrorKey::Tooltip,
+                        "this text is shown in addition to the tooltips of the effects here",
+                        "if it describes those effects, put them in a `hidden_effect`",
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn check_random_list_tooltip(block: &Block) {
+    let mut outcomes = block.iter_items().filter_map(|(k, _, bv)| match (k, bv) {
+        (Some(_), BlockOrValue::Block(b)) => Some(b),
+        _ => None,
+    });
+    if outcomes.all(|b| b.get_key("show_chance").is_none() && b.get_key("desc").is_none()) {
+        advice_info(
+            block,
+            ErrorKey::Tooltip,
+            "the tooltip will list every outcome with its chance",
+            "use `desc` or `show_chance = no` in the outcomes to control what the player sees",
+        );
+    }
+}
+
+/// Validate an effect from the tables by its kind, which says what it takes as its value
+fn validate_effect_kind(
+    key: &Token,
+    effect: Effect,
+    bv: &BlockOrValue,
+    data: &Everything,
+    sc: &mut ScopeContext,
+    tooltipped: bool,
+) {
+    match effect {
+        Effect::Yes => {
+            if let Some(token) = bv.expect_value() {
+                if !token.is("yes") && !token.check_bool_spelling() {
+                    let msg = format!("expected just `{key} = yes`");
+                    warn(token, ErrorKey::Validation, &msg);
+                }
+            }
+        }
+        Effect::Bool => {
+            if let Some(token) = bv.expect_value() {
+                if !token.check_bool_spelling() {
+                    validate_target(token, data, sc, Scopes::Bool);
+                }
+            }
+        }
+        Effect::Integer => {
+            if let Some(token) = bv.expect_value() {
+                token.expect_integer_at(ErrorLevel::Warning);
+            }
+        }
+        Effect::Value | Effect::ScriptValue | Effect::NonNegativeValue => {
+            if let Some(token) = bv.get_value() {
+                if let Ok(number) = token.as_str().parse::<i32>() {
+                    if effect == Effect::NonNegativeValue && number < 0 {
+                        if key.is("add_gold") {
+                            let msg = "add_gold does not take negative numbers";
+                            let info = "try remove_short_term_gold instead";
+                            warn_info(token, ErrorKey::Range, msg, info);
+                        } else {
+                            let msg = format!("{key} does not take negative numbers");
+                            warn(token, ErrorKey::Range, &msg);
+                        }
+                    }
+                }
+            }
+            ScriptValue::validate_bv(bv, data, sc);
+        }
+        Effect::Scope(outscopes) => {
+            if let Some(token) = bv.expect_value() {
+                validate_target(token, data, sc, outscopes);
+            }
+        }
+        Effect::Item(itype) => {
+            if let Some(token) = bv.expect_value() {
+                data.verify_exists(itype, token);
+            }
+        }
+        Effect::Target(..)
+        | Effect::TargetValue(..)
+        | Effect::ItemTarget(..)
+        | Effect::ItemValue(..) => {
+            if let Some(block) = bv.expect_block() {
+                validate_effect_fields(effect, block, data, sc);
+            }
+        }
+        Effect::Desc => validate_desc(bv, data, sc),
+        Effect::Gender => {
+            if let Some(token) = bv.expect_value() {
+                if !(token.is("male") || token.is("female") || token.is("random")) {
+                    warn(
+                        token,
+                        ErrorKey::Validation,
+                        "expected `male`, `female`, or `random`",
+                    );
+                }
+            }
+        }
+        Effect::Special(special) => validate_special_effect(key, special, bv, data, sc),
+        Effect::Control(ControlEffect::CustomTooltip) => match bv {
+            BlockOrValue::Token(t) => data.verify_exists(Item::Localization, t),
+            BlockOrValue::Block(b) => {
+                validate_effect_control(ControlEffect::CustomTooltip, b, data, sc, tooltipped);
+            }
+        },
+        Effect::Control(control) => {
+            if let Some(block) = bv.expect_block() {
+                validate_effect_control(control, block, data, sc, tooltipped);
+            }
+        }
+        Effect::Unchecked => (),
+    }
+}
+
+/// The effects from the tables that take a block with a target or an item in it
+fn validate_effect_fields(effect: Effect, block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    match effect {
+        Effect::Target(key, outscopes) => {
+            let mut vd = Validator::new(block, data);
+            vd.req_field(key);
+            if let Some(token) = vd.field_value(key) {
+                validate_target(token, data, sc, outscopes);
+            }
+        }
+        Effect::TargetValue(key, outscopes, valuekey) => {
+            let mut vd = Validator::new(block, data);
+            vd.req_field(key);
+            vd.req_field(valuekey);
+            if let Some(token) = vd.field_value(key) {
+                validate_target(token, data, sc, outscopes);
+            }
+            if let Some(bv) = vd.field(valuekey) {
+                ScriptValue::validate_bv(bv, data, sc);
+            }
+        }
+        Effect::ItemTarget(ikey, itype, tkey, outscopes) => {
+            let mut vd = Validator::new(block, data);
+            if let Some(token) = vd.field_value(ikey) {
+                data.verify_exists(itype, token);
+            }
+            if let Some(token) = vd.field_value(tkey) {
+                validate_target(token, data, sc, outscopes);
+            }
+        }
+        Effect::ItemValue(key, itype) => {
+            let mut vd = Validator::new(block, data);
+            vd.req_field(key);
+            vd.req_field("value");
+            if let Some(token) = vd.field_value(key) {
+                data.verify_exists(itype, token);
+            }
+            if let Some(bv) = vd.field("value") {
+                ScriptValue::validate_bv(bv, data, sc);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn validate_special_effect(
+    key: &Token,
+    special: SpecialEffect,
+    bv: &BlockOrValue,
+    data: &Everything,
+    sc: &mut ScopeContext,
+) {
+    match special {
+        SpecialEffect::AddModifier => match bv {
+            BlockOrValue::Token(t) => data.verify_exists(Item::Modifier, t),
+            BlockOrValue::Block(b) => validate_add_modifier(b, data, sc),
+        },
+        SpecialEffect::AddFlag => {
+            let family = FlagFamily::from_setter(key).unwrap_or(FlagFamily::Character);
+            match bv {
+                BlockOrValue::Token(t) => data.flags.set(family, t),
+                BlockOrValue::Block(b) => validate_add_flag(family, b, data, sc),
+            }
+        }
+        SpecialEffect::TriggerEvent => match bv {
+            BlockOrValue::Token(token) => {
+                data.verify_exists(Item::Event, token);
+                data.events.note_fired(token);
+            }
+            BlockOrValue::Block(block) => validate_trigger_event(block, data, sc),
+        },
+        SpecialEffect::SetTitleName => match bv {
+            BlockOrValue::Token(t) => validate_title_name(t, data),
+            BlockOrValue::Block(b) => validate_set_title_name(b, data, sc),
+        },
+        SpecialEffect::ResolveTitleChange => {
+            if let Some(token) = bv.expect_value() {
+                validate_target(token, data, sc, Scopes::TitleAndVassalChange);
+                if let Some(name) = token.as_str().strip_prefix("scope:") {
+                    sc.resolve_title_change(name);
+                }
+            }
+        }
+        SpecialEffect::LeaseOutTo => {
+            if let Some(token) = bv.expect_value() {
+                validate_target(token, data, sc, Scopes::HolyOrder);
+                expect_leased_temple(key, sc.scope_token(), data);
+            }
+        }
+        SpecialEffect::SetCapital => {
+            if let Some(token) = bv.expect_value() {
+                validate_target(token, data, sc, Scopes::LandedTitle);
+                expect_county(key, token);
+            }
+        }
+        SpecialEffect::RelationFlag
+        | SpecialEffect::CreateCharacter
+        | SpecialEffect::BattleEvent
+        | SpecialEffect::GeneratePoolCharacter
+        | SpecialEffect::SpawnArmy
+        | SpecialEffect::StartWar
+        | SpecialEffect::CreateHolyOrder
+        | SpecialEffect::VassalContract
+        | SpecialEffect::AddTraitXp
+        | SpecialEffect::CreateTitleChange
+        | SpecialEffect::ChangeTitleHolder
+        | SpecialEffect::ChangeLiege
+        | SpecialEffect::StartTravelPlan
+        | SpecialEffect::CreateImportantAction
+        | SpecialEffect::AddOpinion => {
+            if let Some(block) = bv.expect_block() {
+                validate_special_block(key, special, block, data, sc);
+            }
+        }
+        _ => (), // TODO
+    }
+}
+
+/// The special effects that take a block, each with its own function to validate it
+fn validate_special_block(
+    key: &Token,
+    special: SpecialEffect,
+    block: &Block,
+    data: &Everything,
+    sc: &mut ScopeContext,
+) {
+    match special {
+        SpecialEffect::RelationFlag => validate_relation_flag(key, block, data, sc),
+        SpecialEffect::CreateCharacter => validate_create_character(block, data, sc),
+        SpecialEffect::BattleEvent => validate_battle_event(block, data, sc),
+        SpecialEffect::GeneratePoolCharacter => validate_generate_pool_character(block, data, sc),
+        SpecialEffect::SpawnArmy => validate_spawn_army(block, data, sc),
+        SpecialEffect::StartWar => validate_start_war(block, data, sc),
+        SpecialEffect::CreateHolyOrder => validate_create_holy_order(block, data, sc),
+        SpecialEffect::VassalContract => validate_vassal_contract(block, data),
+        SpecialEffect::AddTraitXp => validate_add_trait_xp(key, block, data, sc),
+        SpecialEffect::CreateTitleChange => validate_create_title_change(block, data, sc),
+        SpecialEffect::ChangeTitleHolder => validate_change_title_holder(block, data, sc),
+        SpecialEffect::ChangeLiege => validate_change_liege(block, data, sc),
+        SpecialEffect::StartTravelPlan => validate_start_travel_plan(block, data, sc),
+        SpecialEffect::CreateImportantAction => {
+            validate_try_create_important_action(block, data, sc);
+        }
+        SpecialEffect::AddOpinion => validate_add_opinion(block, data, sc),
+        _ => (),
+    }
+}
+
+fn validate_vassal_contract(block: &Block, data: &Everything) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("type");
+    vd.req_field("level");
+    vd.field_value_item("type", Item::VassalObligation);
+    vd.field_value("level");
+    if let (Some(contract), Some(level)) = (
+        block.get_field_value("type"),
+        block.get_field_value("level"),
+    ) {
+        data.vassal_contracts.verify_level(contract, level);
+    }
+}
+
+fn validate_change_liege(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("liege");
+    vd.req_field("change");
+    if let Some(token) = vd.field_value("liege") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    if let Some(token) = vd.field_value("change") {
+        validate_target(token, data, sc, Scopes::TitleAndVassalChange);
+    }
 }
 
+#[allow(clippy::too_many_lines)]
 fn validate_effect_control(
     control: ControlEffect,
     block: &Block,
@@ -436,6 +813,15 @@ fn validate_effect_control(
         }
         ControlEffect::If => {
             vd.req_field_warn("limit");
+            if let Some(key) = block.get_key("limit") {
+                if block.iter_items().all(|(k, _, _)| k.as_ref() == Some(key)) {
+                    advice(
+                        key,
+                        ErrorKey::EmptyBlock,
+                        "this `limit` has no effects after it",
+                    );
+                }
+            }
             validate_effect("if", ListType::None, block, data, sc, vd, tooltipped);
         }
         ControlEffect::Else => {
@@ -477,6 +863,14 @@ fn validate_effect_control(
                     Scopes::Character | Scopes::LandedTitle | Scopes::Province,
                 );
             }
+            if tooltipped {
+                advice_info(
+                    block,
+                    ErrorKey::Tooltip,
+                    "the effects in this message will also be shown in the surrounding tooltip",
+                    "wrap the `send_interface_message` in a `hidden_effect` to show them only once",
+                );
+            }
             validate_effect(
                 "send_interface_message",
                 ListType::None,
@@ -488,14 +882,35 @@ fn validate_effect_control(
             );
         }
         ControlEffect::HiddenEffect => {
+            if block.iter_items().next().is_none() {
+                advice(
+                    block,
+                    ErrorKey::EmptyBlock,
+                    "empty `hidden_effect` does nothing",
+                );
+            }
             validate_effect("hidden_effect", ListType::None, block, data, sc, vd, false);
         }
         ControlEffect::Random => {
-            // TODO: need to parse modifiers first
-            // validate_effect("random", ListType::None, block, data, sc, vd, false);
-            vd.no_warn_remaining();
+            vd.req_field("chance");
+            validate_effect("random", ListType::None, block, data, sc, vd, tooltipped);
         }
         ControlEffect::RandomList => {
+            if tooltipped {
+                check_random_list_tooltip(block);
+            }
+            let entries = block
+                .iter_items()
+                .filter(|(k, _, _)| matches!(k, Some(k) if k.as_str().parse::<f64>().is_ok()))
+                .count();
+            if entries > data.limits.random_list_entries {
+                let msg = format!("random_list has {entries} entries");
+                let info = format!(
+                    "more than {} is hard to maintain; consider a scripted list or a script value",
+                    data.limits.random_list_entries
+                );
+                advice_info(block, ErrorKey::LongRandomList, &msg, &info);
+            }
             // TODO
             vd.no_warn_remaining();
         }
@@ -503,8 +918,10 @@ fn validate_effect_control(
             validate_effect("show_as_tooltip", ListType::None, block, data, sc, vd, true);
         }
         ControlEffect::Switch => {
-            // TODO
             vd.no_warn_remaining();
+            validate_switch(block, data, sc, |b, data, sc| {
+                validate_normal_effect(b, data, sc, tooltipped);
+            });
         }
         ControlEffect::While => {
             if !(block.get_key("limit").is_some() || block.get_key("count").is_some()) {
@@ -518,3 +935,389 @@ fn validate_effect_control(
         }
     }
 }
+
+/// Validate the block form of the `add_*_modifier` effects, which can give the modifier a duration.
+fn validate_add_modifier(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("modifier");
+    vd.field_value_item("modifier", Item::Modifier);
+    // Without a duration, the modifier is permanent
+    vd.mutually_exclusive(&["days", "weeks", "months", "years"]);
+    vd.field_script_value("days", sc);
+    vd.field_script_value("weeks", sc);
+    vd.field_script_value("months", sc);
+    vd.field_script_value("years", sc);
+}
+
+/// Validate `add_opinion` and `reverse_add_opinion`, which give an opinion modifier between
+/// the scope character and the target.
+fn validate_add_opinion(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("target");
+    if let Some(token) = vd.field_value("target") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    vd.req_field("modifier");
+    vd.field_value("modifier");
+    if let Some(token) = block.get_field_value("opinion") {
+        check_opinion_value(token);
+    }
+    vd.field_script_value("opinion", sc);
+    vd.mutually_exclusive(&["days", "weeks", "months", "years"]);
+    vd.field_script_value("days", sc);
+    vd.field_script_value("weeks", sc);
+    vd.field_script_value("months", sc);
+    vd.field_script_value("years", sc);
+}
+
+/// A new name for a title needs an adjective too, or the adjective will be blank wherever the
+/// title is described.
+fn validate_title_name(name: &Token, data: &Everything) {
+    data.localization.verify_exists(name);
+    let loca = format!("{name}_adj");
+    data.localization.verify_exists_implied(&loca, name);
+}
+
+/// Validate the block form of `set_title_name`, which can rename another title than the one
+/// in scope and choose how the name is used.
+fn validate_set_title_name(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("name");
+    vd.field_validated_bv("name", |bv, data| match bv {
+        BlockOrValue::Token(token) => validate_title_name(token, data),
+        BlockOrValue::Block(block) => {
+            let msg = "expected a localization key for the new name, found a block";
+            error(block, ErrorKey::Validation, msg);
+        }
+    });
+    if let Some(token) = vd.field_value("title") {
+        validate_target(token, data, sc, Scopes::LandedTitle);
+    }
+    vd.field_bool("article");
+    vd.field_bool("definite_form");
+}
+
+/// The kinds of title and vassal changes, which decide how the change is described in the
+/// characters' histories.
+const TITLE_CHANGE_TYPES: &[&str] = &[
+    "abdication",
+    "conquest",
+    "conquest_claim",
+    "conquest_holy_war",
+    "conquest_populist",
+    "created",
+    "destroyed",
+    "faction_demand",
+    "granted",
+    "independency",
+    "inheritance",
+    "leased_out",
+    "lease_revoked",
+    "returned",
+    "revoked",
+    "stepped_down",
+    "swear_fealty",
+    "usurped",
+];
+
+fn validate_create_title_change(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("type");
+    vd.field_choice("type", TITLE_CHANGE_TYPES);
+    vd.field_bool("add_claim_on_loss");
+    match vd.field_value("save_scope_as") {
+        Some(name) => {
+            sc.define_name(name.as_str(), Scopes::TitleAndVassalChange);
+            sc.create_title_change(name);
+        }
+        None => warn_info(
+            block,
+            ErrorKey::Logic,
+            "this title and vassal change can't be resolved",
+            "add `save_scope_as` and use it with `resolve_title_and_vassal_change`",
+        ),
+    }
+}
+
+fn validate_add_trait_xp(key: &Token, block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("trait");
+    vd.req_field("value");
+    vd.field_value_item("trait", Item::Trait);
+    vd.field_value("track");
+    vd.field_script_value("value", sc);
+    if let Some(trait_token) = block.get_field_value("trait") {
+        data.traits
+            .verify_track(key, trait_token, block.get_field_value("track"));
+    }
+}
+
+fn validate_change_title_holder(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("holder");
+    if let Some(token) = vd.field_value("holder") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    if let Some(token) = vd.field_value("change") {
+        validate_target(token, data, sc, Scopes::TitleAndVassalChange);
+    }
+    vd.field_bool("take_baronies");
+    if let Some(token) = vd.field_value("government_base") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+}
+
+/// The capital effects take a county. If the title is named directly, check its tier.
+fn expect_county(key: &Token, token: &Token) {
+    if token.as_str().contains('.') {
+        return;
+    }
+    if let Some((prefix, arg)) = token.split_once(':') {
+        if prefix.is("title") {
+            if let Ok(tier) = Tier::try_from(&arg) {
+                if tier != Tier::County {
+                    let msg = format!("`{key}` needs a county, but `{arg}` is a {tier}");
+                    warn(&arg, ErrorKey::Validation, &msg);
+                }
+            }
+        }
+    }
+}
+
+/// Only baronies with temple holdings can be leased to holy orders. This can only be checked
+/// when the title is given by name.
+fn expect_leased_temple(key: &Token, title: &Token, data: &Everything) {
+    if title.as_str().contains('.') {
+        return;
+    }
+    let arg = match title.split_once(':') {
+        Some((prefix, arg)) if prefix.is("title") => arg,
+        _ => return,
+    };
+    match Tier::try_from(&arg) {
+        Ok(Tier::Barony) => (),
+        Ok(tier) => {
+            let msg = format!("`{key}` needs a barony, but `{arg}` is a {tier}");
+            warn(key, ErrorKey::Validation, &msg);
+            return;
+        }
+        Err(_) => return,
+    }
+    if let Some(id) = data.titles.barony_province(arg.as_str()) {
+        let holdings = data.province_histories.holdings(id);
+        if !holdings.is_empty() && !holdings.iter().any(|h| h.is("church_holding")) {
+            let msg = format!("`{arg}` has no temple holding in its province history");
+            let info = "only temple holdings can be leased to a holy order";
+            advice_info(key, ErrorKey::Validation, &msg, info);
+        }
+    }
+}
+
+fn validate_create_holy_order(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("leader");
+    vd.req_field("capital");
+    if let Some(token) = vd.field_value("leader") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    if let Some(token) = vd.field_value("capital") {
+        validate_target(token, data, sc, Scopes::LandedTitle);
+    }
+    vd.field_value("save_scope_as");
+    vd.field_value("save_temporary_scope_as");
+}
+
+fn validate_trigger_event(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field_one_of(&["id", "on_action"]);
+    if let Some(token) = vd.field_value("id") {
+        data.verify_exists(Item::Event, token);
+        data.events.note_fired(token);
+    }
+    vd.field_value_item("on_action", Item::OnAction);
+    vd.field_value("saved_event_id");
+    // The delay can be a range, from which the game picks at random
+    for field in ["days", "weeks", "months", "years"] {
+        vd.field_validated_bv(field, |bv, data| match bv {
+            BlockOrValue::Token(_) => ScriptValue::validate_bv(bv, data, sc),
+            BlockOrValue::Block(b) => {
+                let mut vd = Validator::new(b, data);
+                for token in vd.values() {
+                    token.expect_number();
+                }
+            }
+        });
+    }
+}
+
+/// `battle_event` adds a line with portraits to the battle summary
+fn validate_battle_event(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("key");
+    vd.field_value_item("key", Item::Localization);
+    for field in ["left_portrait", "right_portrait"] {
+        if let Some(token) = vd.field_value(field) {
+            validate_target(token, data, sc, Scopes::Character);
+        }
+    }
+    vd.field_value("type");
+    vd.field_bool("target_right");
+}
+
+fn validate_generate_pool_character(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("selector");
+    vd.field_value_item("selector", Item::PoolSelector);
+    if let Some(token) = vd.field_value("province") {
+        validate_target(token, data, sc, Scopes::Province);
+    }
+    vd.field_value("save_scope_as");
+    vd.field_value("save_temporary_scope_as");
+}
+
+fn validate_spawn_army(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    if block.get_key("levies").is_none() && block.get_key("men_at_arms").is_none() {
+        warn_info(
+            block,
+            ErrorKey::Logic,
+            "this army will be empty",
+            "add `levies` or `men_at_arms`",
+        );
+    }
+    vd.field_script_value("levies", sc);
+    vd.field_validated_blocks("men_at_arms", |b, data| {
+        let mut vd = Validator::new(b, data);
+        vd.req_field("type");
+        vd.field_value_item("type", Item::MenAtArms);
+        vd.req_field_one_of(&["stacks", "men"]);
+        vd.field_script_value("stacks", sc);
+        vd.field_script_value("men", sc);
+    });
+    vd.req_field("location");
+    for field in ["location", "origin"] {
+        if let Some(token) = vd.field_value(field) {
+            validate_target(token, data, sc, Scopes::Province);
+            if let Some(provid) = token.as_str().strip_prefix("province:") {
+                data.provinces.verify_passable(provid, token);
+            }
+        }
+    }
+    if let Some(token) = vd.field_value("war") {
+        validate_target(token, data, sc, Scopes::War);
+    }
+    if let Some(key) = block.get_key("war_keep_on_attacker_victory") {
+        if block.get_key("war").is_none() {
+            let msg = "`war_keep_on_attacker_victory` does nothing without `war`";
+            warn(key, ErrorKey::Validation, msg);
+        }
+    }
+    vd.field_bool("war_keep_on_attacker_victory");
+    vd.field_bool("inheritable");
+    vd.field_bool("uses_supply");
+    if let Some(token) = vd.field_value("army") {
+        validate_target(token, data, sc, Scopes::Army);
+    }
+    vd.field_validated_bv("name", |bv, data| validate_desc(bv, data, sc));
+    vd.field_value("save_scope_as");
+    vd.field_value("save_temporary_scope_as");
+}
+
+fn validate_start_travel_plan(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("destination");
+    for token in vd.field_values("destination") {
+        validate_target(token, data, sc, Scopes::Province);
+    }
+    for token in vd.field_values("companion") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    vd.field_bool("players_use_planner");
+    vd.field_bool("return_trip");
+    vd.field_value_item("on_start_event", Item::Event);
+    vd.field_value_item("on_start_on_action", Item::OnAction);
+    vd.field_value_item("on_arrival_event", Item::Event);
+    vd.field_value_item("on_arrival_on_action", Item::OnAction);
+    vd.field_value_item("on_travel_planner_cancel_event", Item::Event);
+    vd.field_value_item("on_travel_planner_cancel_on_action", Item::OnAction);
+}
+
+/// The arguments besides `important_action_type` are the scopes that the action's texts and
+/// click effect use. Their types are known from the data functions the texts call on them.
+fn validate_try_create_important_action(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("important_action_type");
+    vd.field_value_item("important_action_type", Item::ImportantAction);
+    let expected = match block.get_field_value("important_action_type") {
+        Some(key) => data.important_actions.argument_scopes(key.as_str(), data),
+        None => Vec::new(),
+    };
+    for (key, bv) in vd.unknown_keys() {
+        let Some(token) = bv.expect_value() else {
+            continue;
+        };
+        let uses = expected
+            .iter()
+            .filter(|(name, _, _)| name.is(key.as_str()))
+            .collect::<Vec<_>>();
+        let Some((scopes, _)) = validate_target_produces(token, data, sc) else {
+            continue;
+        };
+        for (_, needed, function) in uses {
+            if !needed.intersects(scopes) {
+                let msg = format!("`{key}` is {scopes} but the localization needs {needed}");
+                let msg2 = format!("because it calls `{function}` on it here");
+                warn2(token, ErrorKey::Scopes, &msg, *function, &msg2);
+                break;
+            }
+        }
+    }
+}
+
+fn validate_start_war(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field_one_of(&["casus_belli", "cb"]);
+    vd.field_value_item("casus_belli", Item::CasusBelli);
+    vd.field_value_item("cb", Item::CasusBelli);
+    vd.req_field("target");
+    for field in ["target", "claimant"] {
+        if let Some(token) = vd.field_value(field) {
+            validate_target(token, data, sc, Scopes::Character);
+        }
+    }
+    for token in vd.field_values("target_title") {
+        validate_target(token, data, sc, Scopes::LandedTitle);
+    }
+}
+
+/// Validate the block form of the `add_*_flag` effects, which can give the flag a duration.
+fn validate_add_flag(family: FlagFamily, block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("flag");
+    if let Some(token) = vd.field_value("flag") {
+        data.flags.set(family, token);
+    }
+    vd.mutually_exclusive(&["days", "weeks", "months", "years"]);
+    vd.field_script_value("days", sc);
+    vd.field_script_value("weeks", sc);
+    vd.field_script_value("months", sc);
+    vd.field_script_value("years", sc);
+}
+
+fn validate_relation_flag(key: &Token, block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("target");
+    vd.req_field("relation");
+    vd.req_field("flag");
+    if let Some(token) = vd.field_value("target") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    vd.field_value_item("relation", Item::Relation);
+    if let Some(token) = vd.field_value("flag") {
+        if key.is("add_relation_flag") {
+            data.flags.set(FlagFamily::Relation, token);
+        } else {
+            data.flags.read(FlagFamily::Relation, token);
+        }
+    }
+}
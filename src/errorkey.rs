@@ -1,6 +1,8 @@
-use strum_macros::{Display, EnumString};
+use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, Hash)]
+/// The kinds of reports. Each has a stable kebab-case code, such as `missing-item`, which is
+/// shown in the reports and used to refer to them in the config file and on the command line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, EnumIter, IntoStaticStr, Hash)]
 #[strum(serialize_all = "kebab-case")]
 pub enum ErrorKey {
     Config,
@@ -17,7 +19,10 @@ pub enum ErrorKey {
     EventNamespace,
     MissingLocalization,
     MissingFile,
+    FileCase,
     MissingItem,
+    Casing,
+    UnsetFlag,
     WrongGender,
     Conflict,
     ImageFormat,
@@ -32,7 +37,253 @@ pub enum ErrorKey {
     Macro,
     History,
     Logic,
+    Guard,
     Bugs,
+    EmptyBlock,
+    DeepNesting,
+    TooManyOptions,
+    LongRandomList,
+    LongLocalization,
+    LocalizationOverride,
+    Deprecated,
 
     PrincesOfDarkness,
 }
+
+impl ErrorKey {
+    /// The stable code for this key, like `missing-item`
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+
+    /// A one-line description of what this kind of report is about
+    pub fn description(self) -> &'static str {
+        self.doc().0
+    }
+
+    /// A longer explanation of this kind of report, for `--explain`
+    pub fn explanation(self) -> &'static str {
+        self.doc().1
+    }
+
+    /// An example of script that gets this kind of report, for `--explain`
+    pub fn example(self) -> &'static str {
+        self.doc().2
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn doc(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            ErrorKey::Config => (
+                "problems in the ck3-tiger.conf file",
+                "The validator's own config file could not be understood, for example because an `ignore` section names a key that doesn't exist.",
+                "ignore = { key = missing-loc }",
+            ),
+            ErrorKey::ReadError => (
+                "a file or directory could not be read",
+                "The file could not be opened or read, because of its permissions, a broken symlink, or a name the filesystem doesn't allow. The rest of the mod is still checked.",
+                "events/my_events.txt -> a symlink to a file that was deleted",
+            ),
+            ErrorKey::ParseError => (
+                "the file's syntax could not be parsed",
+                "The script file doesn't follow the basic syntax of keys, values and braces, so the game will misread it or skip part of it.",
+                "my_event = { title = \"unclosed string }",
+            ),
+            ErrorKey::BracePlacement => (
+                "braces that look mismatched from their indentation",
+                "A closing brace at the start of a line is usually the end of a top-level item. If the parser was still inside a nested block there, a brace is probably missing earlier on.",
+                "my_event = {\n	option = {\n		name = my_option\n}",
+            ),
+            ErrorKey::Packaging => (
+                "problems with how the mod is packaged",
+                "The .mod file or the mod's layout has a problem that affects how the launcher or Steam handles the mod.",
+                "replace_path = \"history/titless\"",
+            ),
+            ErrorKey::Validation => (
+                "a value or block that the game does not accept there",
+                "The most common kind of report: a field that doesn't belong, a missing required field, or a value of the wrong type.",
+                "add_gold = yes",
+            ),
+            ErrorKey::Filename => (
+                "files with the wrong name or in the wrong place",
+                "The game finds some files by their name or directory, and will ignore ones that don't follow the pattern.",
+                "localization/my_mod_l_english.yml, which should be in localization/english/",
+            ),
+            ErrorKey::Encoding => (
+                "files saved in the wrong text encoding",
+                "Localization files must be UTF-8 with a BOM, and some other files must not have one. The wrong encoding garbles special characters or makes the game skip the file.",
+                "a .yml file saved as UTF-8 without BOM",
+            ),
+            ErrorKey::Localization => (
+                "syntax problems in localization values",
+                "The text of a localization entry has markup, code or macros that the game can't make sense of.",
+                "my_key: \"Hello [ROOT.Char.GetName\"",
+            ),
+            ErrorKey::Duplicate => (
+                "items that are defined more than once",
+                "A later definition with the same name replaces the earlier one, which is usually not intended when both are in the mod.",
+                "two events both named my_mod.0001",
+            ),
+            ErrorKey::NameConflict => (
+                "different items that share a name where the game gets confused",
+                "Some kinds of items share a namespace in the game, so giving them the same name causes lookups to find the wrong one.",
+                "a faith named catholic inside a religion named catholic",
+            ),
+            ErrorKey::EventNamespace => (
+                "events that don't match the namespace of their file",
+                "Event files must declare a namespace, and the events in them should be named after it, or the game can't find them reliably.",
+                "namespace = my_mod\nother_mod.0001 = { }",
+            ),
+            ErrorKey::MissingLocalization => (
+                "localization keys that are used but not defined",
+                "The game shows the raw key instead of the text when a localization key is missing for the language being played.",
+                "title = my_event_title, with no my_event_title in the localization files",
+            ),
+            ErrorKey::MissingFile => (
+                "references to files that don't exist",
+                "A script refers to a file, such as an icon or a gui texture, that is in neither the mod nor the game.",
+                "icon = \"gfx/interface/icons/missing.dds\"",
+            ),
+            ErrorKey::FileCase => (
+                "file references that only work on case-insensitive filesystems",
+                "The file exists, but with different upper and lower case. That works on Windows but not on Linux or macOS.",
+                "icon = \"gfx/Interface/Icons/my_icon.dds\" for gfx/interface/icons/my_icon.dds",
+            ),
+            ErrorKey::MissingItem => (
+                "references to items that are not defined",
+                "A script refers to a trait, building, title, or other item by a name that doesn't exist in the mod or the game.",
+                "add_trait = bravve",
+            ),
+            ErrorKey::Casing => (
+                "references to items with the wrong upper and lower case",
+                "A script refers to an item by a name that exists, but with different upper and lower case. Item names are case sensitive, so the game doesn't find it.",
+                "add_trait = Brave",
+            ),
+            ErrorKey::UnsetFlag => (
+                "flags that are checked but never set",
+                "A trigger checks a flag that no effect ever sets, which is usually a typo in one of the two places.",
+                "has_character_flag = seen_flga, with only add_character_flag = seen_flag elsewhere",
+            ),
+            ErrorKey::WrongGender => (
+                "characters used where the other gender is required",
+                "Some history entries, such as the mother of a character, require a character of a particular gender.",
+                "mother = 163110, where character 163110 is male",
+            ),
+            ErrorKey::Conflict => (
+                "fields that cancel out or override each other",
+                "Two settings were given that can't both take effect, so one of them is silently ignored.",
+                "cooldown = { days = 10 years = 1 }",
+            ),
+            ErrorKey::ImageFormat => (
+                "images in a format the game can't use",
+                "Some images, such as the map files, must be in a specific format or the game misreads them.",
+                "map_data/rivers.png saved in RGB instead of indexed color",
+            ),
+            ErrorKey::Unneeded => (
+                "settings that have no effect",
+                "Something was written that does nothing, and can be removed, or was meant to do something else.",
+                "replace_path = \"common\", which doesn't replace the directories under it",
+            ),
+            ErrorKey::Scopes => (
+                "triggers and effects used in the wrong scope",
+                "Each trigger and effect works on particular types of scope, like characters or titles. Using it on a different type does nothing or causes errors in the game log.",
+                "title:k_france = { add_gold = 10 }",
+            ),
+            ErrorKey::Crash => (
+                "things that are known to crash the game",
+                "The game is known to crash, or to hang, when it encounters this.",
+                "a character who is their own ancestor",
+            ),
+            ErrorKey::Range => (
+                "numbers outside the range the game accepts",
+                "The value is a number, but outside the range that makes sense here. The game will clamp it, truncate it or reject it.",
+                "birth = 1066.2.30",
+            ),
+            ErrorKey::Tooltip => (
+                "effects whose tooltips will not look as intended",
+                "The effect works, but what the player sees in the tooltip will be missing, duplicated or confusing.",
+                "option = { hidden_effect = { add_gold = 10 } }",
+            ),
+            ErrorKey::Tidying => (
+                "style issues that don't affect the game",
+                "The script works as written, but could be tidier.",
+                "(no checks report this yet)",
+            ),
+            ErrorKey::Rivers => (
+                "problems in the rivers map",
+                "The river pixels in map_data/rivers.png don't connect the way the game expects.",
+                "an orphan river pixel that doesn't touch any other river pixel",
+            ),
+            ErrorKey::Modifiers => (
+                "modifiers used where they don't apply",
+                "Each modifier applies to particular things, like characters or provinces. Using it elsewhere has no effect.",
+                "a province modifier in a character modifier block",
+            ),
+            ErrorKey::Macro => (
+                "problems with scripted macros and their arguments",
+                "Scripted triggers, effects and modifiers that take $ARGUMENTS$ were called without them, or with the wrong ones.",
+                "my_scripted_effect = yes, for an effect that uses $AMOUNT$",
+            ),
+            ErrorKey::History => (
+                "history entries that don't fit the timeline",
+                "A history entry refers to something at a date when it doesn't exist, or has no effect at that date.",
+                "a character who is given a title after their death date",
+            ),
+            ErrorKey::Logic => (
+                "script that can't work the way it's written",
+                "The script is valid, but its logic means part of it can never happen.",
+                "an ai_chance of 0 on every option",
+            ),
+            ErrorKey::Guard => (
+                "effects that are missing a guard",
+                "The effect may fail or behave oddly when its target doesn't meet the conditions, and is usually wrapped in a `limit` that checks them.",
+                "remove_trait = brave, without checking has_trait = brave first",
+            ),
+            ErrorKey::Bugs => (
+                "known bugs in the game's script engine",
+                "This works differently from what one would expect, because of a bug in the game.",
+                "current_year used in an empty scope",
+            ),
+            ErrorKey::EmptyBlock => (
+                "blocks that are empty or do nothing",
+                "A trigger, limit or effect block with nothing in it, or an `if` with a limit but no effects, is valid but does nothing. It is usually unfinished work or left over from a merge.",
+                "if = { limit = { is_adult = yes } }",
+            ),
+            ErrorKey::DeepNesting => (
+                "effects and triggers that are nested too deeply",
+                "The game's tooltips for deeply nested effects and triggers get cut off, so the player can't see what will happen. The depth can be set with `nesting` in the `limits` block of the config file.",
+                "an `if` inside an `if` inside an `if`, and so on, 16 levels deep",
+            ),
+            ErrorKey::TooManyOptions => (
+                "events with more options than fit in the event window",
+                "Only a handful of options fit in the window of an event that isn't fullscreen. The rest overflow it. The number can be set with `event_options` in the `limits` block of the config file.",
+                "a character_event with 6 options",
+            ),
+            ErrorKey::LongRandomList => (
+                "`random_list` effects with a very large number of entries",
+                "A `random_list` with this many entries is hard to maintain and slow to evaluate, and is usually better done with a scripted list or a script value. The number can be set with `random_list_entries` in the `limits` block of the config file.",
+                "random_list = { 1 = { ... } 1 = { ... } ... } with 101 entries",
+            ),
+            ErrorKey::LongLocalization => (
+                "localization values that are too long",
+                "The game cuts off localization values that are this long. The length can be set with `localization_length` in the `limits` block of the config file.",
+                "my_event_desc: \"...\" with more than 2000 characters",
+            ),
+            ErrorKey::LocalizationOverride => (
+                "mod localization that replaces the game's own",
+                "The mod defines a localization key that the game already has, outside of a `replace/` folder, and doesn't use the key in its own script. Generic keys like `yes_button` are easy to take by accident, and then the game's text changes wherever it uses them. Keys that are overridden on purpose can be put in a `replace/` folder, or their prefixes listed in a `localization_overrides` block in the config file.",
+                "yes_button:0 \"Sure\"",
+            ),
+            ErrorKey::Deprecated => (
+                "script commands that were removed or renamed",
+                "The script uses an effect, trigger or scope link that a patch of the game removed or renamed. Removed commands do nothing, and renamed ones only keep working until the old name is dropped. The game version is taken from the launcher settings next to the game folder.",
+                "change_focus = diplomacy_focus",
+            ),
+            ErrorKey::PrincesOfDarkness => (
+                "checks for the Princes of Darkness mod",
+                "These are reported only with the pod profile, and check the conventions of the Princes of Darkness mod.",
+                "a faith with no religion",
+            ),
+        }
+    }
+}
@@ -1,20 +1,22 @@
 use encoding::all::{UTF_8, WINDOWS_1252};
 use encoding::{DecoderTrap, Encoding};
 use fnv::{FnvHashMap, FnvHashSet};
+use std::cell::RefCell;
 use std::fmt::{Debug, Display, Formatter};
 use std::fs::read;
 use std::io::{stdout, Stderr, Stdout, Write};
 use std::path::PathBuf;
+use std::str::FromStr;
 use unicode_width::UnicodeWidthChar;
 
 use crate::block::{Block, BlockOrValue};
 use crate::errorkey::ErrorKey;
 use crate::fileset::{FileEntry, FileKind};
-use crate::token::{Loc, Token};
+use crate::token::{EndPos, Loc, Token};
 
 static mut ERRORS: Option<Errors> = None;
 
-#[derive(Clone, Copy, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum ErrorLevel {
     #[default]
     Advice,
@@ -23,6 +25,20 @@ pub enum ErrorLevel {
     Error,
 }
 
+impl FromStr for ErrorLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(ErrorLevel::Error),
+            "warning" => Ok(ErrorLevel::Warning),
+            "info" => Ok(ErrorLevel::Info),
+            "advice" => Ok(ErrorLevel::Advice),
+            _ => Err("expected error, warning, info or advice".to_string()),
+        }
+    }
+}
+
 impl Display for ErrorLevel {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -37,6 +53,14 @@ impl Display for ErrorLevel {
 // This trait lets the error functions accept a variety of things as the error locator.
 pub trait ErrorLoc {
     fn into_loc(self) -> Loc;
+
+    /// Like `into_loc`, but also return where the reported item ends, if that is known.
+    fn into_loc_end(self) -> (Loc, Option<EndPos>)
+    where
+        Self: Sized,
+    {
+        (self.into_loc(), None)
+    }
 }
 
 impl ErrorLoc for BlockOrValue {
@@ -46,6 +70,13 @@ impl ErrorLoc for BlockOrValue {
             BlockOrValue::Block(s) => s.into_loc(),
         }
     }
+
+    fn into_loc_end(self) -> (Loc, Option<EndPos>) {
+        match self {
+            BlockOrValue::Token(t) => t.into_loc_end(),
+            BlockOrValue::Block(s) => s.into_loc_end(),
+        }
+    }
 }
 
 impl ErrorLoc for &BlockOrValue {
@@ -55,6 +86,13 @@ impl ErrorLoc for &BlockOrValue {
             BlockOrValue::Block(s) => s.into_loc(),
         }
     }
+
+    fn into_loc_end(self) -> (Loc, Option<EndPos>) {
+        match self {
+            BlockOrValue::Token(t) => t.into_loc_end(),
+            BlockOrValue::Block(s) => s.into_loc_end(),
+        }
+    }
 }
 
 impl ErrorLoc for FileEntry {
@@ -85,12 +123,21 @@ impl ErrorLoc for Token {
     fn into_loc(self) -> Loc {
         self.loc
     }
+
+    fn into_loc_end(self) -> (Loc, Option<EndPos>) {
+        let end = self.end();
+        (self.loc, Some(end))
+    }
 }
 
 impl ErrorLoc for &Token {
     fn into_loc(self) -> Loc {
         self.loc.clone()
     }
+
+    fn into_loc_end(self) -> (Loc, Option<EndPos>) {
+        (self.loc.clone(), Some(self.end()))
+    }
 }
 
 impl ErrorLoc for Block {
@@ -105,6 +152,65 @@ impl ErrorLoc for &Block {
     }
 }
 
+/// The reporting policy for the mod's files under a path, from a `files` section in the
+/// config file. Policies for longer paths override the level of the ones for shorter paths,
+/// and add to their ignored keys.
+#[derive(Clone, Debug, Default)]
+pub struct FilePolicy {
+    /// The file or directory the policy is for
+    pub path: PathBuf,
+    /// Only log reports at this level or above
+    pub level: Option<ErrorLevel>,
+    /// Skip logging reports with these keys
+    pub keys: Vec<ErrorKey>,
+    /// Skip logging all reports
+    pub ignore: bool,
+}
+
+/// A place that is reported along with the main location of a report, such as the original
+/// of a duplicate or the place where a scope was set.
+#[derive(Clone, Debug)]
+pub struct Related {
+    loc: Loc,
+    end: Option<EndPos>,
+    label: String,
+}
+
+impl Related {
+    pub fn new<E: ErrorLoc>(eloc: E, label: &str) -> Self {
+        let (loc, end) = eloc.into_loc_end();
+        Self {
+            loc,
+            end,
+            label: label.to_string(),
+        }
+    }
+}
+
+/// A place in a logged report, with the line there, for output formats other than the log
+#[derive(Clone, Debug)]
+pub struct ReportPlace {
+    pub loc: Loc,
+    /// Where the file is on disk
+    pub fullpath: PathBuf,
+    /// The line, and the marks under the reported item, if the line could be read
+    pub snippet: Option<(String, String)>,
+    /// What is at this place, for the places other than the main one
+    pub label: Option<String>,
+}
+
+/// A report as it was logged. They are kept after `collect_reports` is called, so that they
+/// can be written in other formats such as the HTML report.
+#[derive(Clone, Debug)]
+pub struct LoggedReport {
+    pub level: ErrorLevel,
+    pub key: ErrorKey,
+    pub msg: String,
+    pub info: Option<String>,
+    pub place: ReportPlace,
+    pub related: Vec<ReportPlace>,
+}
+
 #[derive(Default)]
 struct Errors {
     /// The CK3 game directory
@@ -113,6 +219,12 @@ struct Errors {
     /// The mod directory
     mod_root: PathBuf,
 
+    /// The directory of the mod given with `--base-mod`
+    base_mod_root: PathBuf,
+
+    /// The DLC directories, indexed by the number in `FileKind::Dlc`
+    loaded_dlcs: Vec<PathBuf>,
+
     /// Don't log if this is > 0,
     logging_paused: isize,
 
@@ -128,43 +240,68 @@ struct Errors {
     /// Skip logging errors for these files and directories (regardless of key)
     ignore_paths: Vec<PathBuf>,
 
+    /// The reporting policies for the mod's files under specific paths
+    file_policies: Vec<FilePolicy>,
+
+    /// Which `file_policies` apply to a file, from the shortest path to the longest.
+    /// Worked out once per file.
+    policy_cache: RefCell<FnvHashMap<PathBuf, Vec<usize>>>,
+
+    /// How many reports each of the `file_policies` kept out of the log
+    swallowed: Vec<usize>,
+
     /// Error logs are written here (initially stderr)
     outfile: Option<Box<dyn ErrorLogger>>,
 
     /// Minimum error level to log
     minimum_level: ErrorLevel,
 
+    /// Leave out the error key codes, like `(missing-item)`, from the reports
+    hide_codes: bool,
+
     /// Errors that have already been logged (to avoid duplication, which is common
     /// when validating macro expanded triggers and effects)
-    seen: FnvHashSet<(Loc, ErrorKey, String, Option<Loc>, Option<Loc>)>,
-}
+    seen: FnvHashSet<(Loc, ErrorKey, String, Vec<Loc>)>,
+
+    /// How many errors were logged, by level and key, for the summary
+    counts: FnvHashMap<(ErrorLevel, ErrorKey), usize>,
+
+    /// Whether to keep the logged reports in `reports`
+    collect: bool,
 
-// TODO: allow a message to have multiple tokens, and print the relevant lines as a stack
-// before the message. This might be implemented by letting Token have something like an
-// `Option<Token>` field to chain them.
+    /// The reports logged so far, if `collect` is set
+    reports: Vec<LoggedReport>,
+}
 
 impl Errors {
+    fn fullpath(&self, loc: &Loc) -> PathBuf {
+        match loc.kind {
+            FileKind::Vanilla => self.vanilla_root.join(&*loc.pathname),
+            FileKind::Dlc(idx) => self.loaded_dlcs[idx as usize].join(&*loc.pathname),
+            FileKind::BaseMod => self.base_mod_root.join(&*loc.pathname),
+            FileKind::Mod => self.mod_root.join(&*loc.pathname),
+        }
+    }
+
     #[allow(clippy::unused_self)] // At some point we will cache files in self
     fn get_line(&mut self, loc: &Loc) -> Option<String> {
         if loc.line == 0 {
             return None;
         }
-        let pathname = match loc.kind {
-            FileKind::Vanilla => self.vanilla_root.join(&*loc.pathname),
-            FileKind::Mod => self.mod_root.join(&*loc.pathname),
-        };
-        let bytes = read(&pathname).ok()?;
+        let bytes = read(self.fullpath(loc)).ok()?;
         let contents = match UTF_8.decode(&bytes, DecoderTrap::Strict) {
             Ok(contents) => contents,
             Err(_) => WINDOWS_1252.decode(&bytes, DecoderTrap::Strict).ok()?,
         };
-        contents.lines().nth(loc.line - 1).map(str::to_string)
+        let line = contents.lines().nth(loc.line - 1)?;
+        // The parsers count columns after the BOM, so leave it out here too
+        Some(line.strip_prefix('\u{feff}').unwrap_or(line).to_string())
     }
 
     pub fn will_log(&self, loc: &Loc, key: ErrorKey) -> bool {
         if self.logging_paused > 0
             || self.ignore_keys.contains(&key)
-            || (loc.kind == FileKind::Vanilla && !self.show_vanilla)
+            || (loc.kind != FileKind::Mod && !self.show_vanilla)
         {
             return false;
         }
@@ -181,144 +318,253 @@ impl Errors {
         true
     }
 
+    /// Return the indices of the `file_policies` that apply to this file, most specific last.
+    fn policies_for(&self, loc: &Loc) -> Vec<usize> {
+        if self.file_policies.is_empty() || loc.kind != FileKind::Mod {
+            return Vec::new();
+        }
+        if let Some(policies) = self.policy_cache.borrow().get(&*loc.pathname) {
+            return policies.clone();
+        }
+        let mut policies = (0..self.file_policies.len())
+            .filter(|&idx| loc.pathname.starts_with(&self.file_policies[idx].path))
+            .collect::<Vec<_>>();
+        policies.sort_by_key(|&idx| self.file_policies[idx].path.components().count());
+        self.policy_cache
+            .borrow_mut()
+            .insert(loc.pathname.to_path_buf(), policies.clone());
+        policies
+    }
+
+    /// Return the index of the file policy that keeps this report out of the log, if any.
+    fn policy_filter(&self, loc: &Loc, level: Option<ErrorLevel>, key: ErrorKey) -> Option<usize> {
+        let policies = self.policies_for(loc);
+        // The most specific policy that sets a level decides the level
+        for &idx in policies.iter().rev() {
+            let policy = &self.file_policies[idx];
+            if policy.ignore {
+                return Some(idx);
+            }
+            if let Some(minimum) = policy.level {
+                if level.is_some_and(|level| level < minimum) {
+                    return Some(idx);
+                }
+                break;
+            }
+        }
+        // Ignored keys are inherited from the less specific policies
+        policies
+            .into_iter()
+            .rev()
+            .find(|&idx| self.file_policies[idx].keys.contains(&key))
+    }
+
+    /// Check whether a file policy keeps this report out of the log, and count it if so.
+    fn swallowed_by_policy(&mut self, loc: &Loc, level: ErrorLevel, key: ErrorKey) -> bool {
+        match self.policy_filter(loc, Some(level), key) {
+            Some(idx) => {
+                self.swallowed[idx] += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)] // the parts of a report, all needed to write it
     pub fn log(
         &mut self,
         loc: &Loc,
+        end: Option<EndPos>,
         level: ErrorLevel,
         key: ErrorKey,
         msg: &str,
         info: Option<&str>,
+        related: &[Related],
     ) {
         if self.outfile.is_none() {
             self.outfile = Some(Box::new(stdout()));
         }
-        writeln!(self.outfile.as_mut().unwrap(), "{}", loc.file_marker()).unwrap();
-        if let Some(line) = self.get_line(loc) {
-            let line_marker = loc.line_marker();
-            if loc.line > 0 {
-                writeln!(self.outfile.as_mut().unwrap(), "{} {}", line_marker, line).unwrap();
-                let mut spacing = String::new();
-                for c in line.chars().take(loc.column.saturating_sub(1)) {
-                    if c == '\t' {
-                        spacing.push('\t');
-                    } else {
-                        for _ in 0..c.width().unwrap_or(0) {
-                            spacing.push(' ');
-                        }
-                    }
-                }
-                writeln!(
-                    self.outfile.as_mut().unwrap(),
-                    "{} {}^",
-                    line_marker,
-                    spacing
-                )
-                .unwrap();
-            }
-        }
+        self.log_snippet(loc, end, "");
         // TODO: get terminal column width and do line wrapping of msg and info
+        if self.hide_codes {
+            writeln!(self.outfile.as_mut().unwrap(), "{level}: {msg}").unwrap();
+        } else {
+            writeln!(
+                self.outfile.as_mut().unwrap(),
+                "{} ({}): {}",
+                level,
+                key.as_str(),
+                msg
+            )
+            .unwrap();
+        }
+        if let Some(info) = info {
+            writeln!(self.outfile.as_mut().unwrap(), "  {info}").unwrap();
+        }
+        for rel in related {
+            writeln!(self.outfile.as_mut().unwrap(), "  --> {}", rel.label).unwrap();
+            self.log_snippet(&rel.loc, rel.end, "    ");
+        }
+    }
+
+    /// Write the file marker and, if the line can be read, the line with the item underlined.
+    /// Each line starts with `indent`.
+    fn log_snippet(&mut self, loc: &Loc, end: Option<EndPos>, indent: &str) {
         writeln!(
             self.outfile.as_mut().unwrap(),
-            "{} ({}): {}",
-            level,
-            key,
-            msg
+            "{}{}",
+            indent,
+            loc.file_marker()
         )
         .unwrap();
-        if let Some(info) = info {
-            writeln!(self.outfile.as_mut().unwrap(), "  {}", info).unwrap();
+        if let Some((line, marks)) = self.snippet(loc, end) {
+            let line_marker = loc.line_marker();
+            writeln!(
+                self.outfile.as_mut().unwrap(),
+                "{indent}{line_marker} {line}"
+            )
+            .unwrap();
+            writeln!(
+                self.outfile.as_mut().unwrap(),
+                "{indent}{line_marker} {marks}"
+            )
+            .unwrap();
         }
     }
 
-    #[allow(clippy::similar_names)] // eloc and loc are perfectly clear
-    pub fn push<E: ErrorLoc>(
-        &mut self,
-        eloc: E,
-        level: ErrorLevel,
-        key: ErrorKey,
-        msg: &str,
-        info: Option<&str>,
-    ) {
-        if level < self.minimum_level {
-            return;
-        }
-        let loc = eloc.into_loc();
-        let index = (loc.clone(), key, msg.to_string(), None, None);
-        if self.seen.contains(&index) {
-            return;
-        }
-        self.seen.insert(index);
-        if !self.will_log(&loc, key) {
-            return;
+    /// Return the line of the location, and a line that marks the reported item in it.
+    fn snippet(&mut self, loc: &Loc, end: Option<EndPos>) -> Option<(String, String)> {
+        let line = self.get_line(loc)?;
+        let mut marks = String::new();
+        for c in line.chars().take(loc.column.saturating_sub(1)) {
+            if c == '\t' {
+                marks.push('\t');
+            } else {
+                for _ in 0..c.width().unwrap_or(0) {
+                    marks.push(' ');
+                }
+            }
         }
-        self.log(&loc, level, key, msg, info);
-        writeln!(self.outfile.as_mut().unwrap()).unwrap();
+        marks.push_str(&Self::underline(&line, loc, end));
+        Some((line, marks))
     }
 
-    #[allow(clippy::similar_names)] // eloc and loc are perfectly clear
-    pub fn push2<E: ErrorLoc, E2: ErrorLoc>(
-        &mut self,
-        eloc: E,
-        level: ErrorLevel,
-        key: ErrorKey,
-        msg: &str,
-        eloc2: E2,
-        msg2: &str,
-    ) {
-        if level < self.minimum_level {
-            return;
+    fn report_place(&mut self, loc: &Loc, end: Option<EndPos>, label: Option<&str>) -> ReportPlace {
+        ReportPlace {
+            loc: loc.clone(),
+            fullpath: self.fullpath(loc),
+            snippet: self.snippet(loc, end),
+            label: label.map(ToString::to_string),
         }
-        let loc = eloc.into_loc();
-        let loc2 = eloc2.into_loc();
-        let index = (loc.clone(), key, msg.to_string(), Some(loc2.clone()), None);
-        if self.seen.contains(&index) {
-            return;
+    }
+
+    /// Mark the reported item in its line, up to its end or the end of the line.
+    /// Items whose end isn't known get a single `^`.
+    fn underline(line: &str, loc: &Loc, end: Option<EndPos>) -> String {
+        let end_column = match end {
+            Some(end) if end.line == loc.line => end.column,
+            Some(end) if end.line > loc.line => usize::MAX,
+            _ => return "^".to_string(),
+        };
+        let mut underline = String::new();
+        let columns = end_column.saturating_sub(loc.column);
+        for c in line
+            .chars()
+            .skip(loc.column.saturating_sub(1))
+            .take(columns)
+        {
+            for _ in 0..c.width().unwrap_or(0).max(1) {
+                underline.push('^');
+            }
         }
-        self.seen.insert(index);
-        if !self.will_log(&loc, key) {
-            return;
+        if underline.is_empty() {
+            underline.push('^');
         }
-        self.log(&loc, level, key, msg, None);
-        self.log(&loc2, ErrorLevel::Info, key, msg2, None);
-        writeln!(self.outfile.as_mut().unwrap()).unwrap();
+        underline
     }
 
     #[allow(clippy::similar_names)] // eloc and loc are perfectly clear
-    pub fn push3<E: ErrorLoc, E2: ErrorLoc, E3: ErrorLoc>(
+    pub fn push<E: ErrorLoc>(
         &mut self,
         eloc: E,
         level: ErrorLevel,
         key: ErrorKey,
         msg: &str,
-        eloc2: E2,
-        msg2: &str,
-        eloc3: E3,
-        msg3: &str,
+        info: Option<&str>,
+        related: &[Related],
     ) {
         if level < self.minimum_level {
             return;
         }
-        let loc = eloc.into_loc();
-        let loc2 = eloc2.into_loc();
-        let loc3 = eloc3.into_loc();
+        let (loc, end) = eloc.into_loc_end();
         let index = (
             loc.clone(),
             key,
             msg.to_string(),
-            Some(loc2.clone()),
-            Some(loc3.clone()),
+            related.iter().map(|rel| rel.loc.clone()).collect(),
         );
         if self.seen.contains(&index) {
             return;
         }
         self.seen.insert(index);
-        if !self.will_log(&loc, key) {
+        if !self.will_log(&loc, key) || self.swallowed_by_policy(&loc, level, key) {
             return;
         }
-        self.log(&loc, level, key, msg, None);
-        self.log(&loc2, ErrorLevel::Info, key, msg2, None);
-        self.log(&loc3, ErrorLevel::Info, key, msg3, None);
+        *self.counts.entry((level, key)).or_default() += 1;
+        self.log(&loc, end, level, key, msg, info, related);
         writeln!(self.outfile.as_mut().unwrap()).unwrap();
+        if self.collect {
+            let place = self.report_place(&loc, end, None);
+            let related = related
+                .iter()
+                .map(|rel| self.report_place(&rel.loc, rel.end, Some(&rel.label)))
+                .collect();
+            self.reports.push(LoggedReport {
+                level,
+                key,
+                msg: msg.to_string(),
+                info: info.map(ToString::to_string),
+                place,
+                related,
+            });
+        }
+    }
+
+    /// Write the number of logged errors per level and key, in a stable order so that the
+    /// summaries of two runs can be diffed.
+    pub fn log_summary(&mut self) {
+        if self.outfile.is_none() {
+            self.outfile = Some(Box::new(stdout()));
+        }
+        let mut counts = self.counts.iter().collect::<Vec<_>>();
+        counts.sort_by(|((l1, k1), _), ((l2, k2), _)| {
+            l2.cmp(l1).then_with(|| k1.to_string().cmp(&k2.to_string()))
+        });
+        let out = self.outfile.as_mut().unwrap();
+        writeln!(out, "Summary:").unwrap();
+        for ((level, key), count) in &counts {
+            writeln!(out, "  {level} ({key}): {count}").unwrap();
+        }
+        let total: usize = self.counts.values().sum();
+        writeln!(out, "  total: {total}").unwrap();
+        self.log_policy_summary();
+    }
+
+    /// Write how many reports each file policy kept out of the log, if any.
+    pub fn log_policy_summary(&mut self) {
+        if self.swallowed.iter().all(|&count| count == 0) {
+            return;
+        }
+        if self.outfile.is_none() {
+            self.outfile = Some(Box::new(stdout()));
+        }
+        let out = self.outfile.as_mut().unwrap();
+        writeln!(out, "Left out by the file policies:").unwrap();
+        for (policy, count) in self.file_policies.iter().zip(&self.swallowed) {
+            if *count > 0 {
+                writeln!(out, "  {}: {}", policy.path.display(), count).unwrap();
+            }
+        }
     }
 
     pub fn get_mut() -> &'static mut Self {
@@ -348,8 +594,45 @@ impl Errors {
     }
 }
 
+/// Start a new log. Errors that were logged before can be logged again, and the summary
+/// only counts the errors in the new log.
 pub fn log_to(outfile: Box<dyn ErrorLogger>) {
-    Errors::get_mut().outfile = Some(outfile);
+    let errors = Errors::get_mut();
+    errors.outfile = Some(outfile);
+    errors.seen.clear();
+    errors.counts.clear();
+    errors.swallowed.iter_mut().for_each(|count| *count = 0);
+}
+
+/// Start checking another mod in the same run. The mod root, the ignore rules from the previous
+/// mod's config file, and the counts of what was logged are reset. The settings from the
+/// command line and the log destination are kept.
+pub fn start_mod_run(mod_root: PathBuf) {
+    let errors = Errors::get_mut();
+    errors.mod_root = mod_root;
+    errors.ignore_keys_for.clear();
+    errors.ignore_keys.clear();
+    errors.ignore_paths.clear();
+    errors.file_policies.clear();
+    errors.policy_cache.borrow_mut().clear();
+    errors.swallowed.clear();
+    errors.seen.clear();
+    errors.counts.clear();
+}
+
+/// How many reports of this level were logged since the log or mod run started
+pub fn count_at_level(level: ErrorLevel) -> usize {
+    Errors::get()
+        .counts
+        .iter()
+        .filter(|((l, _), _)| *l == level)
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// The most severe level that was logged since the log or mod run started
+pub fn worst_level() -> Option<ErrorLevel> {
+    Errors::get().counts.keys().map(|(level, _)| *level).max()
 }
 
 /// # Panics
@@ -358,6 +641,16 @@ pub fn take_log_to() -> Box<dyn ErrorLogger> {
     Errors::get_mut().outfile.take().unwrap()
 }
 
+/// Whether to keep the reports that are logged from now on, for `take_reports`
+pub fn collect_reports(v: bool) {
+    Errors::get_mut().collect = v;
+}
+
+/// Return the reports kept since `collect_reports` or the previous `take_reports`
+pub fn take_reports() -> Vec<LoggedReport> {
+    std::mem::take(&mut Errors::get_mut().reports)
+}
+
 pub fn pause_logging() {
     Errors::get_mut().logging_paused += 1;
 }
@@ -370,10 +663,30 @@ pub fn show_vanilla(v: bool) {
     Errors::get_mut().show_vanilla = v;
 }
 
+/// Whether problems in the game files are reported, which means they have to be read fully
+pub fn vanilla_shown() -> bool {
+    Errors::get().show_vanilla
+}
+
 pub fn minimum_level(lvl: ErrorLevel) {
     Errors::get_mut().minimum_level = lvl;
 }
 
+/// Leave out the error key codes from the reports
+pub fn hide_codes(v: bool) {
+    Errors::get_mut().hide_codes = v;
+}
+
+/// Log how many errors of each level and key were reported
+pub fn log_summary() {
+    Errors::get_mut().log_summary();
+}
+
+/// Log how many reports each file policy kept out of the log, if any
+pub fn log_policy_summary() {
+    Errors::get_mut().log_policy_summary();
+}
+
 /// This is an object that can pause logging as long as it's in scope.
 /// Whether it does to depends on its constructor's `pause` argument.
 #[derive(Debug)]
@@ -406,12 +719,27 @@ pub fn set_mod_root(root: PathBuf) {
     Errors::get_mut().mod_root = root;
 }
 
+pub fn set_base_mod_root(root: PathBuf) {
+    Errors::get_mut().base_mod_root = root;
+}
+
+pub fn set_loaded_dlc_roots(roots: Vec<PathBuf>) {
+    Errors::get_mut().loaded_dlcs = roots;
+}
+
 pub fn error<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Error, key, msg, None);
+    Errors::get_mut().push(eloc, ErrorLevel::Error, key, msg, None, &[]);
 }
 
 pub fn error2<E: ErrorLoc, F: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str, eloc2: F, msg2: &str) {
-    Errors::get_mut().push2(eloc, ErrorLevel::Error, key, msg, eloc2, msg2);
+    Errors::get_mut().push(
+        eloc,
+        ErrorLevel::Error,
+        key,
+        msg,
+        None,
+        &[Related::new(eloc2, msg2)],
+    );
 }
 
 pub fn error3<E: ErrorLoc, E2: ErrorLoc, E3: ErrorLoc>(
@@ -423,19 +751,27 @@ pub fn error3<E: ErrorLoc, E2: ErrorLoc, E3: ErrorLoc>(
     eloc3: E3,
     msg3: &str,
 ) {
-    Errors::get_mut().push3(eloc, ErrorLevel::Error, key, msg, eloc2, msg2, eloc3, msg3);
+    let related = [Related::new(eloc2, msg2), Related::new(eloc3, msg3)];
+    Errors::get_mut().push(eloc, ErrorLevel::Error, key, msg, None, &related);
 }
 
 pub fn error_info<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str, info: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Error, key, msg, Some(info));
+    Errors::get_mut().push(eloc, ErrorLevel::Error, key, msg, Some(info), &[]);
 }
 
 pub fn warn<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Warning, key, msg, None);
+    Errors::get_mut().push(eloc, ErrorLevel::Warning, key, msg, None, &[]);
 }
 
 pub fn warn2<E: ErrorLoc, F: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str, eloc2: F, msg2: &str) {
-    Errors::get_mut().push2(eloc, ErrorLevel::Warning, key, msg, eloc2, msg2);
+    Errors::get_mut().push(
+        eloc,
+        ErrorLevel::Warning,
+        key,
+        msg,
+        None,
+        &[Related::new(eloc2, msg2)],
+    );
 }
 
 pub fn warn3<E: ErrorLoc, E2: ErrorLoc, E3: ErrorLoc>(
@@ -447,36 +783,52 @@ pub fn warn3<E: ErrorLoc, E2: ErrorLoc, E3: ErrorLoc>(
     eloc3: E3,
     msg3: &str,
 ) {
-    Errors::get_mut().push3(
-        eloc,
-        ErrorLevel::Warning,
-        key,
-        msg,
-        eloc2,
-        msg2,
-        eloc3,
-        msg3,
-    );
+    let related = [Related::new(eloc2, msg2), Related::new(eloc3, msg3)];
+    Errors::get_mut().push(eloc, ErrorLevel::Warning, key, msg, None, &related);
 }
 
 pub fn warn_info<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str, info: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Warning, key, msg, Some(info));
+    Errors::get_mut().push(eloc, ErrorLevel::Warning, key, msg, Some(info), &[]);
+}
+
+/// Report a problem along with any number of other places that explain it, each with a
+/// label such as "first defined here".
+pub fn report<E: ErrorLoc>(
+    eloc: E,
+    level: ErrorLevel,
+    key: ErrorKey,
+    msg: &str,
+    info: Option<&str>,
+    related: &[Related],
+) {
+    Errors::get_mut().push(eloc, level, key, msg, info, related);
 }
 
 pub fn info<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Info, key, msg, None);
+    Errors::get_mut().push(eloc, ErrorLevel::Info, key, msg, None, &[]);
 }
 
 pub fn info_info<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str, info: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Info, key, msg, Some(info));
+    Errors::get_mut().push(eloc, ErrorLevel::Info, key, msg, Some(info), &[]);
 }
 
 pub fn advice<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Advice, key, msg, None);
+    Errors::get_mut().push(eloc, ErrorLevel::Advice, key, msg, None, &[]);
+}
+
+pub fn advice2<E: ErrorLoc, F: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str, eloc2: F, msg2: &str) {
+    Errors::get_mut().push(
+        eloc,
+        ErrorLevel::Advice,
+        key,
+        msg,
+        None,
+        &[Related::new(eloc2, msg2)],
+    );
 }
 
 pub fn advice_info<E: ErrorLoc>(eloc: E, key: ErrorKey, msg: &str, info: &str) {
-    Errors::get_mut().push(eloc, ErrorLevel::Advice, key, msg, Some(info));
+    Errors::get_mut().push(eloc, ErrorLevel::Advice, key, msg, Some(info), &[]);
 }
 
 pub fn ignore_key_for(path: PathBuf, key: ErrorKey) {
@@ -497,8 +849,19 @@ pub fn ignore_path(path: PathBuf) {
     Errors::get_mut().ignore_paths.push(path);
 }
 
+/// Apply this reporting policy to the mod's files under its path
+pub fn add_file_policy(policy: FilePolicy) {
+    let errors = Errors::get_mut();
+    errors.file_policies.push(policy);
+    errors.swallowed.push(0);
+    errors.policy_cache.borrow_mut().clear();
+}
+
+/// Whether reports with this key would be logged for this location, regardless of their level
 pub fn will_log<E: ErrorLoc>(eloc: E, key: ErrorKey) -> bool {
-    Errors::get().will_log(&eloc.into_loc(), key)
+    let errors = Errors::get();
+    let loc = eloc.into_loc();
+    errors.will_log(&loc, key) && errors.policy_filter(&loc, None, key).is_none()
 }
 
 pub trait ErrorLogger: Write {
@@ -1,41 +1,77 @@
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use strum::IntoEnumIterator;
 use thiserror::Error;
 
 use crate::block::Block;
+use crate::data::achievements::Achievements;
+use crate::data::activities::Activities;
+use crate::data::artifacts::ArtifactVisuals;
+use crate::data::assets::Assets;
+use crate::data::buildings::Buildings;
+use crate::data::character_templates::CharacterTemplates;
 use crate::data::characters::Characters;
+use crate::data::combat::{CombatEffects, CombatPhaseEvents};
+use crate::data::council::CouncilTasks;
+use crate::data::court_scene::{CourtSceneCameras, CourtSceneSettings, CourtSceneSlots, SlotKind};
+use crate::data::courtier_guest_management::CourtierGuestManagement;
 use crate::data::courtpos::CourtPositions;
 use crate::data::courtpos_categories::CourtPositionCategories;
 use crate::data::decisions::Decisions;
+use crate::data::defines::Defines;
+use crate::data::dna::{BookmarkPortraits, DnaData};
+use crate::data::doctrines::Doctrines;
 use crate::data::dynasties::Dynasties;
 use crate::data::events::Events;
 use crate::data::gameconcepts::GameConcepts;
+use crate::data::genes::{Accessories, Genes};
+use crate::data::holy_orders::HolyOrders;
 use crate::data::houses::Houses;
+use crate::data::important_actions::ImportantActions;
 use crate::data::interaction_cats::InteractionCategories;
 use crate::data::interactions::Interactions;
 use crate::data::lifestyles::Lifestyles;
-use crate::data::localization::Localization;
+use crate::data::localization::{known_language, Localization, KNOWN_LANGUAGES};
+use crate::data::men_at_arms_types::MenAtArmsTypes;
+use crate::data::mercenaries::MercenaryCompanies;
+use crate::data::named_colors::NamedColors;
 use crate::data::namelists::Namelists;
+use crate::data::on_actions::{OnActions, ScopeDeclarations};
+use crate::data::pool_selectors::PoolSelectors;
 use crate::data::prov_history::ProvinceHistories;
 use crate::data::provinces::Provinces;
 use crate::data::relations::Relations;
 use crate::data::religions::Religions;
 use crate::data::scripted_effects::{Effect, Effects};
 use crate::data::scripted_lists::ScriptedLists;
+use crate::data::scripted_modifiers::ScriptedModifiers;
+use crate::data::scripted_rules::ScriptedRules;
 use crate::data::scripted_triggers::{Trigger, Triggers};
 use crate::data::scriptvalues::ScriptValues;
 use crate::data::terrain::Terrains;
 use crate::data::title_history::TitleHistories;
 use crate::data::titles::Titles;
 use crate::data::traits::Traits;
+use crate::data::travel::{DangerTypes, PointsOfInterest, TravelOptions};
+use crate::data::vassal_contracts::VassalContracts;
 use crate::errorkey::ErrorKey;
-use crate::errors::{error, ignore_key, ignore_key_for, ignore_path, warn};
+use crate::errors::{
+    add_file_policy, error, error_info, ignore_key, ignore_key_for, ignore_path, pause_logging,
+    resume_logging, vanilla_shown, warn, warn_info, FilePolicy,
+};
 use crate::fileset::{FileEntry, FileKind, Fileset};
+use crate::flags::Flags;
+use crate::helpers::{suggest, Suggestion};
 use crate::item::Item;
+use crate::itemdb::{ItemDb, ItemDefinition, ItemSource};
+use crate::limits::Limits;
 use crate::pdxfile::PdxFile;
+use crate::profiles::{profile, profile_names, Profile};
+use crate::references::References;
 use crate::rivers::Rivers;
 use crate::token::{Loc, Token};
+use crate::version::GameVersion;
 
 #[derive(Debug, Error)]
 pub enum FilesError {
@@ -53,7 +89,21 @@ pub enum FilesError {
     ConfigUnreadable { path: PathBuf },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug, Error)]
+#[error("Unknown profile {name}. Known profiles are: {known}")]
+pub struct UnknownProfile {
+    name: String,
+    known: String,
+}
+
+#[derive(Debug, Error)]
+#[error("Unknown language {name}. Known languages are: {known}")]
+pub struct UnknownLanguage {
+    name: String,
+    known: String,
+}
+
+#[derive(Debug)]
 pub struct Everything {
     /// Config from file
     config: Block,
@@ -66,11 +116,18 @@ pub struct Everything {
 
     pub scripted_lists: ScriptedLists,
 
+    pub scripted_modifiers: ScriptedModifiers,
+
     /// Processed event files
     pub events: Events,
 
     /// Processed decision files
     pub decisions: Decisions,
+    pub activities: Activities,
+    pub council_tasks: CouncilTasks,
+
+    /// Engine defines
+    pub defines: Defines,
 
     /// Processed character interaction files
     pub interactions: Interactions,
@@ -82,11 +139,15 @@ pub struct Everything {
     /// Processed history/provinces data
     pub province_histories: ProvinceHistories,
 
+    /// Buildings, including the special and duchy capital buildings
+    pub buildings: Buildings,
+
     /// Processed game concepts
     pub gameconcepts: GameConcepts,
 
     /// Religions and faiths
     pub religions: Religions,
+    pub doctrines: Doctrines,
 
     /// Landed titles
     pub titles: Titles,
@@ -98,9 +159,34 @@ pub struct Everything {
     /// Cultural name lists
     pub namelists: Namelists,
 
+    /// Colors that can be used by name
+    pub named_colors: NamedColors,
+
     /// Scripted relations
     pub relations: Relations,
 
+    /// Templates for `create_character`
+    pub character_templates: CharacterTemplates,
+
+    /// Vassal contracts and their obligation levels
+    pub vassal_contracts: VassalContracts,
+
+    /// Men-at-arms regiment types
+    pub men_at_arms_types: MenAtArmsTypes,
+
+    /// Mercenary companies and holy orders, which come with their own armies
+    pub mercenary_companies: MercenaryCompanies,
+    pub holy_orders: HolyOrders,
+
+    /// Rules for managing the courtier and guest pools
+    pub courtier_guest_management: CourtierGuestManagement,
+
+    /// `On_actions`, and the scopes of the events they fire
+    pub on_actions: OnActions,
+
+    /// The mod's declarations of the scopes of its own `on_actions` and events
+    pub scope_declarations: ScopeDeclarations,
+
     pub scriptvalues: ScriptValues,
 
     pub triggers: Triggers,
@@ -115,20 +201,102 @@ pub struct Everything {
     pub courtpos_categories: CourtPositionCategories,
     pub courtpos: CourtPositions,
 
+    /// How candidates are picked from the pool of unlanded characters
+    pub pool_selectors: PoolSelectors,
+
+    /// Triggers that the game asks when it makes certain decisions
+    pub scripted_rules: ScriptedRules,
+
+    /// The alerts and suggested actions at the top of the screen
+    pub important_actions: ImportantActions,
+
+    /// What happens in battles besides the numbers
+    pub combat_effects: CombatEffects,
+    pub combat_phase_events: CombatPhaseEvents,
+
+    /// Checked for the player character
+    pub achievements: Achievements,
+
     pub title_history: TitleHistories,
+
+    /// The travel system, which is only in CK3 1.9 and later
+    pub travel_options: TravelOptions,
+    pub points_of_interest: PointsOfInterest,
+    pub danger_types: DangerTypes,
+
+    /// The graphical entities in the `.asset` files
+    pub assets: Assets,
+
+    /// The Royal Court's court scene and the artifacts shown in it
+    pub court_scene_settings: CourtSceneSettings,
+    pub court_scene_cameras: CourtSceneCameras,
+    pub court_scene_character_slots: CourtSceneSlots,
+    pub court_scene_artifact_slots: CourtSceneSlots,
+    pub artifact_visuals: ArtifactVisuals,
+
+    /// The portrait genes and the DNA made of them
+    pub genes: Genes,
+    pub accessories: Accessories,
+    pub dna_data: DnaData,
+    pub bookmark_portraits: BookmarkPortraits,
+
+    /// Thresholds for the checks on the game engine's practical limits
+    pub limits: Limits,
+
+    /// The version of the game being checked for, for the commands that changed between versions
+    pub game_version: GameVersion,
+
+    /// Flags set by effects and checked by triggers, collected during validation
+    pub flags: Flags,
+
+    /// The references to the items looked for with `--find`, collected during validation
+    pub references: References,
+
+    /// The mod profiles selected on the command line, with their extra checks
+    profiles: Vec<Box<dyn Profile>>,
 }
 
 impl Everything {
+    /// Set up the files to be validated. Without a `mod_root`, only vanilla is loaded, which is
+    /// useful as a reference for what the validator reports about the base game.
     pub fn new(
         vanilla_root: &Path,
-        mod_root: &Path,
-        replace_paths: Vec<PathBuf>,
+        mod_root: Option<&Path>,
+        replace_paths: Vec<Token>,
+        no_dlc: &[String],
+        follow_symlinks: bool,
     ) -> Result<Self, FilesError> {
+        Self::new_with_base_mod(
+            vanilla_root,
+            None,
+            mod_root,
+            replace_paths,
+            no_dlc,
+            follow_symlinks,
+        )
+    }
+
+    /// Scan the game, base mod, and mod directories into a finished `Fileset`.
+    fn scan_fileset(
+        vanilla_root: &Path,
+        base_mod: Option<(PathBuf, Vec<Token>)>,
+        mod_root: Option<&Path>,
+        replace_paths: Vec<Token>,
+        no_dlc: &[String],
+        follow_symlinks: bool,
+    ) -> Result<Fileset, FilesError> {
         let mut fileset = Fileset::new(
             vanilla_root.to_path_buf(),
-            mod_root.to_path_buf(),
+            mod_root.map_or_else(PathBuf::new, Path::to_path_buf),
             replace_paths,
         );
+        let base_root = base_mod.map(|(root, base_replace_paths)| {
+            fileset.set_base_mod(root.clone(), base_replace_paths);
+            root
+        });
+        if !follow_symlinks {
+            fileset.no_follow_symlinks();
+        }
 
         // Abort if whole directories are unreadable, because then we don't have
         // a full map of vanilla's or the mod's contents and might give bad advice.
@@ -139,14 +307,52 @@ impl Everything {
             }
         })?;
         fileset
-            .scan(mod_root, FileKind::Mod)
-            .map_err(|e| FilesError::ModUnreadable {
-                path: mod_root.to_path_buf(),
+            .scan_dlcs(no_dlc)
+            .map_err(|e| FilesError::VanillaUnreadable {
+                path: vanilla_root.join("dlc"),
                 source: e,
             })?;
+        if let Some(base_root) = base_root {
+            fileset
+                .scan(&base_root, FileKind::BaseMod)
+                .map_err(|e| FilesError::ModUnreadable {
+                    path: base_root,
+                    source: e,
+                })?;
+        }
+        if let Some(mod_root) = mod_root {
+            fileset
+                .scan(mod_root, FileKind::Mod)
+                .map_err(|e| FilesError::ModUnreadable {
+                    path: mod_root.to_path_buf(),
+                    source: e,
+                })?;
+        }
         fileset.finalize();
+        Ok(fileset)
+    }
 
-        let config_file = mod_root.join("ck3-tiger.conf");
+    /// Like `new`, but with a base mod loaded between the game and the mod. The base mod's
+    /// files are validated along with the mod's, but reports about them are hidden like the
+    /// game's. The base mod's `replace_path` entries come with its root.
+    pub fn new_with_base_mod(
+        vanilla_root: &Path,
+        base_mod: Option<(PathBuf, Vec<Token>)>,
+        mod_root: Option<&Path>,
+        replace_paths: Vec<Token>,
+        no_dlc: &[String],
+        follow_symlinks: bool,
+    ) -> Result<Self, FilesError> {
+        let mut fileset = Self::scan_fileset(
+            vanilla_root,
+            base_mod,
+            mod_root,
+            replace_paths,
+            no_dlc,
+            follow_symlinks,
+        )?;
+
+        let config_file = mod_root.map_or_else(PathBuf::new, |p| p.join("ck3-tiger.conf"));
         let config = if config_file.is_file() {
             Self::_read_config(&config_file)
                 .ok_or(FilesError::ConfigUnreadable { path: config_file })?
@@ -156,25 +362,46 @@ impl Everything {
 
         fileset.config(config.clone());
 
+        let mut localization = Localization::default();
+        if mod_root.is_none() {
+            localization.set_vanilla_only();
+        }
+
         Ok(Everything {
             fileset,
             config,
-            localization: Localization::default(),
+            localization,
             scripted_lists: ScriptedLists::default(),
+            scripted_modifiers: ScriptedModifiers::default(),
             events: Events::default(),
             decisions: Decisions::default(),
+            activities: Activities::default(),
+            council_tasks: CouncilTasks::default(),
+            defines: Defines::default(),
             interactions: Interactions::default(),
             interaction_cats: InteractionCategories::default(),
             provinces: Provinces::default(),
             province_histories: ProvinceHistories::default(),
+            buildings: Buildings::default(),
             gameconcepts: GameConcepts::default(),
             religions: Religions::default(),
+            doctrines: Doctrines::default(),
             titles: Titles::default(),
             dynasties: Dynasties::default(),
             houses: Houses::default(),
+            important_actions: ImportantActions::default(),
             characters: Characters::default(),
             namelists: Namelists::default(),
+            named_colors: NamedColors::default(),
             relations: Relations::default(),
+            character_templates: CharacterTemplates::default(),
+            vassal_contracts: VassalContracts::default(),
+            men_at_arms_types: MenAtArmsTypes::default(),
+            mercenary_companies: MercenaryCompanies::default(),
+            holy_orders: HolyOrders::default(),
+            courtier_guest_management: CourtierGuestManagement::default(),
+            on_actions: OnActions::default(),
+            scope_declarations: ScopeDeclarations::default(),
             scriptvalues: ScriptValues::default(),
             triggers: Triggers::default(),
             effects: Effects::default(),
@@ -183,10 +410,50 @@ impl Everything {
             terrains: Terrains::default(),
             courtpos_categories: CourtPositionCategories::default(),
             courtpos: CourtPositions::default(),
+            pool_selectors: PoolSelectors::default(),
+            scripted_rules: ScriptedRules::default(),
+            combat_effects: CombatEffects::default(),
+            combat_phase_events: CombatPhaseEvents::default(),
+            achievements: Achievements::default(),
             title_history: TitleHistories::default(),
+            travel_options: TravelOptions::default(),
+            points_of_interest: PointsOfInterest::default(),
+            danger_types: DangerTypes::default(),
+            assets: Assets::default(),
+            court_scene_settings: CourtSceneSettings::default(),
+            court_scene_cameras: CourtSceneCameras::default(),
+            court_scene_character_slots: CourtSceneSlots::new(SlotKind::Character),
+            court_scene_artifact_slots: CourtSceneSlots::new(SlotKind::Artifact),
+            artifact_visuals: ArtifactVisuals::default(),
+            genes: Genes::default(),
+            accessories: Accessories::default(),
+            dna_data: DnaData::default(),
+            bookmark_portraits: BookmarkPortraits::default(),
+            limits: Limits::default(),
+            game_version: GameVersion::detect(vanilla_root).unwrap_or(GameVersion::SUPPORTED),
+            flags: Flags::default(),
+            references: References::default(),
+            profiles: Vec::new(),
         })
     }
 
+    /// Check the mod as a total conversion, which leaves out the game's landed titles and
+    /// history. Mods that replace `common/landed_titles` are recognized as total conversions
+    /// without this. This must be called before `load_all`.
+    pub fn set_total_conversion(&mut self) {
+        self.fileset.set_total_conversion();
+    }
+
+    pub fn is_total_conversion(&self) -> bool {
+        self.fileset.is_total_conversion()
+    }
+
+    /// Keep the parsed game files in memory after loading them, so that the next `Everything`
+    /// can reuse them. This is for checking several mods in one run.
+    pub fn cache_game_files() {
+        PdxFile::cache_game_files();
+    }
+
     fn _read_config(path: &Path) -> Option<Block> {
         let entry = FileEntry::new(path.to_path_buf(), FileKind::Mod);
         PdxFile::read_no_bom(&entry, path)
@@ -202,14 +469,9 @@ impl Everything {
 
             let mut keys = Vec::new();
             for keyname in keynames {
-                let key = match keyname.as_str().parse() {
-                    Ok(key) => key,
-                    Err(e) => {
-                        warn(keyname, ErrorKey::Config, &format!("{:#}", e));
-                        continue;
-                    }
-                };
-                keys.push(key);
+                if let Some(key) = parse_errorkey(&keyname) {
+                    keys.push(key);
+                }
             }
 
             let pathnames = block.get_field_values("file");
@@ -229,28 +491,82 @@ impl Everything {
                 }
             }
         }
+
+        for block in self.config.get_field_blocks("files") {
+            let Some(path) = block.get_field_value("path") else {
+                warn(
+                    block,
+                    ErrorKey::Config,
+                    "expected a `path` for the files policy",
+                );
+                continue;
+            };
+            let mut policy = FilePolicy {
+                path: PathBuf::from(path.as_str()),
+                ..FilePolicy::default()
+            };
+            if let Some(level) = block.get_field_value("level") {
+                match level.as_str().parse() {
+                    Ok(level) => policy.level = Some(level),
+                    Err(e) => warn(level, ErrorKey::Config, &e),
+                }
+            }
+            for keyname in block.get_field_values("key") {
+                if let Some(key) = parse_errorkey(&keyname) {
+                    policy.keys.push(key);
+                }
+            }
+            policy.ignore = block.get_field_bool("ignore").unwrap_or(false);
+            add_file_policy(policy);
+        }
     }
 
     pub fn load_all(&mut self) {
         self.load_errorkey_config();
+        self.limits.config(&self.config);
         self.fileset.config(self.config.clone());
 
+        // The game's dynasties, houses and DNA only have to be looked up, unless they are
+        // validated to show their problems or to find the references in them
+        if !vanilla_shown() && !self.references.is_active() {
+            self.dynasties.index_game_files();
+            self.houses.index_game_files();
+            self.dna_data.index_game_files();
+        }
+
         self.fileset.handle(&mut self.localization);
         self.fileset.handle(&mut self.scripted_lists);
+        self.fileset.handle(&mut self.scripted_modifiers);
         self.fileset.handle(&mut self.events);
         self.fileset.handle(&mut self.decisions);
+        self.fileset.handle(&mut self.activities);
+        self.fileset.handle(&mut self.council_tasks);
+        self.fileset.handle(&mut self.defines);
         self.fileset.handle(&mut self.interactions);
         self.fileset.handle(&mut self.interaction_cats);
         self.fileset.handle(&mut self.provinces);
         self.fileset.handle(&mut self.province_histories);
+        self.fileset.handle(&mut self.buildings);
         self.fileset.handle(&mut self.gameconcepts);
         self.fileset.handle(&mut self.religions);
+        self.fileset.handle(&mut self.doctrines);
         self.fileset.handle(&mut self.titles);
         self.fileset.handle(&mut self.dynasties);
         self.fileset.handle(&mut self.houses);
+        self.fileset.handle(&mut self.important_actions);
         self.fileset.handle(&mut self.characters);
         self.fileset.handle(&mut self.namelists);
+        self.fileset.handle(&mut self.named_colors);
         self.fileset.handle(&mut self.relations);
+        self.fileset.handle(&mut self.character_templates);
+        self.fileset.handle(&mut self.vassal_contracts);
+        self.fileset.handle(&mut self.men_at_arms_types);
+        self.fileset.handle(&mut self.mercenary_companies);
+        self.fileset.handle(&mut self.holy_orders);
+        self.fileset.handle(&mut self.courtier_guest_management);
+        self.fileset.handle(&mut self.scope_declarations);
+        self.fileset.handle(&mut self.on_actions);
+        self.on_actions.seed(&self.scope_declarations);
         self.fileset.handle(&mut self.scriptvalues);
         self.fileset.handle(&mut self.triggers);
         self.fileset.handle(&mut self.effects);
@@ -259,7 +575,36 @@ impl Everything {
         self.fileset.handle(&mut self.terrains);
         self.fileset.handle(&mut self.courtpos_categories);
         self.fileset.handle(&mut self.courtpos);
+        self.fileset.handle(&mut self.pool_selectors);
+        self.fileset.handle(&mut self.scripted_rules);
+        self.fileset.handle(&mut self.combat_effects);
+        self.fileset.handle(&mut self.combat_phase_events);
+        self.fileset.handle(&mut self.achievements);
         self.fileset.handle(&mut self.title_history);
+        self.fileset.handle(&mut self.travel_options);
+        // A mod that replaces the travel options still has the game's travel system
+        if self.fileset.is_replaced("common/travel/travel_options") {
+            self.travel_options.set_in_game();
+        }
+        self.fileset.handle(&mut self.points_of_interest);
+        self.fileset.handle(&mut self.danger_types);
+        self.fileset.handle(&mut self.assets);
+        self.fileset.handle(&mut self.court_scene_settings);
+        self.fileset.handle(&mut self.court_scene_cameras);
+        self.fileset.handle(&mut self.court_scene_character_slots);
+        self.fileset.handle(&mut self.court_scene_artifact_slots);
+        self.fileset.handle(&mut self.artifact_visuals);
+        self.fileset.handle(&mut self.genes);
+        self.fileset.handle(&mut self.accessories);
+        self.fileset.handle(&mut self.dna_data);
+        self.fileset.handle(&mut self.bookmark_portraits);
+
+        for profile in &mut self.profiles {
+            for dir in profile.common_dirs() {
+                self.fileset.allow_common_dir(dir);
+            }
+            profile.load(&self.fileset);
+        }
     }
 
     pub fn validate_all(&mut self) {
@@ -270,26 +615,66 @@ impl Everything {
         self.scriptvalues.validate(self);
         self.triggers.validate(self);
         self.effects.validate(self);
+        self.triggers.check_calls(self);
+        self.effects.check_calls(self);
+        self.scripted_modifiers.validate(self);
         self.terrains.validate(self);
         self.events.validate(self);
         self.decisions.validate(self);
+        self.activities.validate(self);
+        self.council_tasks.validate(self);
+        self.defines.validate();
         self.interactions.validate(self);
         self.interaction_cats.validate(self);
         self.provinces.validate(self);
         self.province_histories.validate(self);
+        self.buildings.validate(self);
         self.gameconcepts.validate(self);
         self.religions.validate(self);
+        self.doctrines.validate(self);
         self.titles.validate(self);
         self.dynasties.validate(self);
         self.houses.validate(self);
+        self.important_actions.validate(self);
         self.characters.validate(self);
         self.namelists.validate(self);
+        self.named_colors.validate(self);
         self.relations.validate(self);
+        self.character_templates.validate(self);
+        self.vassal_contracts.validate(self);
+        self.men_at_arms_types.validate(self);
+        self.mercenary_companies.validate(self);
+        self.holy_orders.validate(self);
+        self.courtier_guest_management.validate(self);
+        self.on_actions.validate(self);
         self.traits.validate(self);
         self.lifestyles.validate(self);
         self.courtpos_categories.validate(self);
         self.courtpos.validate(self);
+        self.pool_selectors.validate(self);
+        self.scripted_rules.validate(self);
+        self.combat_effects.validate(self);
+        self.combat_phase_events.validate(self);
+        self.achievements.validate(self);
         self.title_history.validate(self);
+        self.travel_options.validate(self);
+        self.points_of_interest.validate(self);
+        self.danger_types.validate(self);
+        self.court_scene_settings.validate(self);
+        self.court_scene_character_slots.validate(self);
+        self.court_scene_artifact_slots.validate(self);
+        self.artifact_visuals.validate(self);
+        self.genes.validate(self);
+        self.dna_data.validate(self);
+        self.bookmark_portraits.validate(self);
+        self.validate_profiles();
+        // after all the effects that fire events
+        self.events.check_scope_names(self);
+        // after all the items that refer to localization keys
+        self.localization.validate_overrides();
+        self.localization.validate_cultural_names();
+        // must come last, after all the effects and triggers that use flags have been seen
+        self.flags.validate(self);
     }
 
     pub fn check_rivers(&mut self) {
@@ -298,42 +683,196 @@ impl Everything {
         rivers.validate(self);
     }
 
-    pub fn check_pod(&mut self) {
-        self.province_histories
-            .check_pod_faiths(&self.religions, &self.titles);
+    /// Select a mod profile by name, to do its extra checks.
+    /// This must be called before `load_all`.
+    pub fn add_profile(&mut self, name: &str) -> Result<(), UnknownProfile> {
+        let profile = profile(name).ok_or_else(|| UnknownProfile {
+            name: name.to_string(),
+            known: profile_names().join(", "),
+        })?;
+        self.profiles.push(profile);
+        Ok(())
+    }
+
+    /// Check that localization keys exist in this language, instead of in english.
+    /// This must be called before `load_all`.
+    pub fn set_language(&mut self, name: &str) -> Result<(), UnknownLanguage> {
+        let lang = Self::language(name)?;
+        self.localization.set_language(lang);
+        Ok(())
+    }
+
+    /// Compare this language to the main one, for `missing_translations`.
+    /// This must be called before `load_all`.
+    pub fn add_translation(&mut self, name: &str) -> Result<(), UnknownLanguage> {
+        let lang = Self::language(name)?;
+        self.localization.add_translation(lang);
+        Ok(())
+    }
+
+    fn language(name: &str) -> Result<&'static str, UnknownLanguage> {
+        known_language(name).ok_or_else(|| UnknownLanguage {
+            name: name.to_string(),
+            known: KNOWN_LANGUAGES.join(", "),
+        })
+    }
+
+    /// Describe the localization keys that the languages given with `add_translation` are
+    /// missing, per file. With `details`, also list the keys.
+    pub fn missing_translations(&self, details: bool) -> String {
+        self.localization.missing_translations(details)
+    }
+
+    fn validate_profiles(&self) {
+        for profile in &self.profiles {
+            for (itype, pattern) in profile.required_localization() {
+                if let Some(db) = self.item_db(*itype) {
+                    for item in db.iter_items() {
+                        let key = item.key();
+                        if key.loc.kind == FileKind::Mod {
+                            let loca = pattern.replace("$KEY$", key.as_str());
+                            self.localization.verify_exists_implied(&loca, key);
+                        }
+                    }
+                }
+            }
+            profile.validate(self);
+        }
+    }
+
+    /// Return the database that holds items of type `itype`, if it supports the `ItemDb` queries.
+    fn item_db(&self, itype: Item) -> Option<&dyn ItemDb> {
+        match itype {
+            Item::Decision => Some(&self.decisions),
+            Item::Lifestyle => Some(&self.lifestyles),
+            Item::Relation => Some(&self.relations),
+            Item::CharacterTemplate => Some(&self.character_templates),
+            Item::VassalObligation => Some(&self.vassal_contracts),
+            Item::MenAtArms => Some(&self.men_at_arms_types),
+            Item::MercenaryCompany => Some(&self.mercenary_companies),
+            Item::HolyOrder => Some(&self.holy_orders),
+            Item::Building => Some(&self.buildings),
+            Item::Dynasty => Some(&self.dynasties),
+            Item::House => Some(&self.houses),
+            Item::ImportantAction => Some(&self.important_actions),
+            Item::TravelOption => Some(&self.travel_options),
+            Item::PointOfInterestType => Some(&self.points_of_interest),
+            Item::DangerType => Some(&self.danger_types),
+            Item::PoolSelector => Some(&self.pool_selectors),
+            Item::ScriptedRule => Some(&self.scripted_rules),
+            Item::CombatEffect => Some(&self.combat_effects),
+            Item::CombatPhaseEvent => Some(&self.combat_phase_events),
+            Item::Achievement => Some(&self.achievements),
+            Item::CourtSceneSetting => Some(&self.court_scene_settings),
+            Item::CourtSceneCamera => Some(&self.court_scene_cameras),
+            Item::CourtSceneCharacterSlot => Some(&self.court_scene_character_slots),
+            Item::CourtSceneArtifactSlot => Some(&self.court_scene_artifact_slots),
+            Item::ArtifactVisual => Some(&self.artifact_visuals),
+            Item::Activity => Some(&self.activities),
+            Item::CouncilTask => Some(&self.council_tasks),
+            Item::Doctrine => Some(&self.doctrines),
+            Item::Gene => Some(&self.genes),
+            Item::Dna => Some(&self.dna_data),
+            Item::BookmarkPortrait => Some(&self.bookmark_portraits),
+            Item::InteractionCategory => Some(&self.interaction_cats),
+            _ => None,
+        }
+    }
+
+    /// Look up the definition of an item. Not all item types support this yet.
+    pub fn get_item(&self, itype: Item, key: &str) -> Option<&dyn ItemSource> {
+        self.item_db(itype)?.get_item(key)
+    }
+
+    /// Iterate over all the definitions of an item type. Not all item types support this yet,
+    /// and the ones that don't will return an empty iterator.
+    pub fn iter_items(&self, itype: Item) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_> {
+        match self.item_db(itype) {
+            Some(db) => db.iter_items(),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Whether `get_item`, `iter_items` and `item_definitions` support this item type.
+    pub fn can_query(&self, itype: Item) -> bool {
+        self.item_db(itype).is_some()
+    }
+
+    /// Find every place where an item is defined, in the order the game loads them,
+    /// including the ones in files that are replaced by a later file with the same path.
+    /// Returns `None` if this item type doesn't support lookups yet.
+    pub fn item_definitions(&self, itype: Item, key: &str) -> Option<Vec<ItemDefinition>> {
+        let used = self.get_item(itype, key).map(|item| item.key().loc.clone());
+        let find = |entry: &FileEntry, replaced_by: Option<FileKind>, vec: &mut Vec<_>| {
+            if let Some(block) = PdxFile::read(entry, &self.fileset.fullpath(entry)) {
+                for (token, _) in block.iter_pure_definitions() {
+                    if token.is(key) {
+                        vec.push(ItemDefinition {
+                            key: token.clone(),
+                            used: replaced_by.is_none() && used.as_ref() == Some(&token.loc),
+                            replaced_by,
+                        });
+                    }
+                }
+            }
+        };
+
+        self.item_db(itype)?;
+        let mut definitions = Vec::new();
+        // The files were already read once, so their problems were already reported.
+        pause_logging();
+        let subpath = PathBuf::from(itype.path());
+        for entry in self.fileset.get_files_under(&subpath) {
+            if !entry.filename().to_string_lossy().ends_with(".txt") {
+                continue;
+            }
+            for replaced in self.fileset.replaced_files(entry.path()) {
+                find(replaced, Some(entry.kind()), &mut definitions);
+            }
+            find(entry, None, &mut definitions);
+        }
+        resume_logging();
+        Some(definitions)
     }
 
     pub fn item_exists(&self, itype: Item, key: &str) -> bool {
+        if let Some(db) = self.item_db(itype) {
+            return db.get_item(key).is_some();
+        }
         match itype {
+            Item::Accessory => self.accessories.exists(key),
             Item::Character => self.characters.exists(key),
             Item::CourtPositionCategory => self.courtpos_categories.exists(key),
-            Item::Decision => self.decisions.exists(key),
-            Item::Dynasty => self.dynasties.exists(key),
+            Item::DoctrineParameter => self.doctrines.parameter_exists(key),
+            Item::Entity => self.assets.exists(key),
             Item::Event => self.events.exists(key),
             Item::Faith => self.religions.faith_exists(key),
             Item::File => self.fileset.exists(key),
             Item::GameConcept => self.gameconcepts.exists(key),
-            Item::House => self.houses.exists(key),
             Item::Holding => HOLDING_TYPES.contains(&key),
             Item::Interaction => self.interactions.exists(key),
-            Item::InteractionCategory => self.interaction_cats.exists(key),
-            Item::Lifestyle => self.lifestyles.exists(key),
             Item::Localization => self.localization.exists(key),
             Item::MenAtArmsBase => MEN_AT_ARMS_BASE.contains(&key),
             Item::NameList => self.namelists.exists(key),
+            Item::NamedColor => self.named_colors.exists(key),
+            Item::OnAction => self.on_actions.exists(key),
             Item::PrisonType => PRISON_TYPES.contains(&key),
             Item::Province => self.provinces.exists(key),
-            Item::Relation => self.relations.exists(key),
             Item::Religion => self.religions.religion_exists(key),
             Item::ScriptedEffect => self.effects.exists(key),
             Item::ScriptedList => self.scripted_lists.exists(key),
+            Item::ScriptedModifier => self.scripted_modifiers.exists(key),
             Item::ScriptedTrigger => self.triggers.exists(key),
             Item::ScriptValue => self.scriptvalues.exists(key),
             Item::Sexuality => SEXUALITIES.contains(&key),
+            Item::SpecialBuilding => self.buildings.has_type(key, "special"),
             Item::Skill => SKILLS.contains(&key),
             Item::Terrain => self.terrains.exists(key),
             Item::Title => self.titles.exists(key),
             Item::Trait => self.traits.exists(key),
+            Item::TraitFlag => self.traits.flag_exists(key),
+            Item::VassalContractFlag => self.vassal_contracts.flag_exists(key),
+            Item::VassalObligationLevel => self.vassal_contracts.level_exists(key),
             _ => true,
         }
     }
@@ -343,6 +882,7 @@ impl Everything {
     }
 
     pub fn verify_exists_implied(&self, itype: Item, key: &str, token: &Token) {
+        self.references.record(itype, key, token);
         match itype {
             Item::File => self.fileset.verify_exists_implied(key, token),
             Item::Localization => self.localization.verify_exists_implied(key, token),
@@ -350,7 +890,26 @@ impl Everything {
             _ => {
                 if !self.item_exists(itype, key) {
                     let msg = format!("{} {} not defined in {}", itype, key, itype.path());
-                    error(token, ErrorKey::MissingItem, &msg);
+                    let candidates = self.iter_items(itype).map(|item| item.key().as_str());
+                    // Numeric ids that are close together are not likely to be typos
+                    let suggestion = if key.parse::<i64>().is_ok() {
+                        None
+                    } else {
+                        suggest(key, candidates)
+                    };
+                    if let Some(suggestion @ Suggestion::Casing(_)) = suggestion {
+                        error_info(token, ErrorKey::Casing, &msg, &suggestion.info());
+                    } else if let Some(suggestion) = suggestion {
+                        error_info(token, ErrorKey::MissingItem, &msg, &suggestion.info());
+                    } else if self.fileset.is_replaced(itype.path()) {
+                        let info = format!(
+                            "the mod replaces the game's {}, so only its own are defined",
+                            itype.path()
+                        );
+                        error_info(token, ErrorKey::MissingItem, &msg, &info);
+                    } else {
+                        error(token, ErrorKey::MissingItem, &msg);
+                    }
                 }
             }
         }
@@ -411,3 +970,17 @@ const SKILLS: &[&str] = &[
 ];
 
 const SEXUALITIES: &[&str] = &["heterosexual", "homosexual", "bisexual", "asexual"];
+
+/// Parse an error key named in the config file, and warn with the closest key if it's unknown
+fn parse_errorkey(keyname: &Token) -> Option<ErrorKey> {
+    if let Ok(key) = keyname.as_str().parse() {
+        return Some(key);
+    }
+    let msg = format!("unknown error key `{keyname}`");
+    let codes = ErrorKey::iter().map(ErrorKey::as_str);
+    match suggest(keyname.as_str(), codes) {
+        Some(suggestion) => warn_info(keyname, ErrorKey::Config, &msg, &suggestion.info()),
+        None => warn(keyname, ErrorKey::Config, &msg),
+    }
+    None
+}
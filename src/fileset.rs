@@ -1,20 +1,26 @@
 use anyhow::Result;
-use fnv::FnvHashSet;
+use fnv::FnvHashMap;
 use std::ffi::OsStr;
 use std::fmt::{Display, Formatter};
+use std::fs::canonicalize;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use walkdir::WalkDir;
 
 use crate::block::Block;
 use crate::errorkey::ErrorKey;
-use crate::errors::error;
+use crate::errors::{error, error_info, info, set_loaded_dlc_roots, warn, warn_info};
 use crate::everything::Everything;
 use crate::token::{Loc, Token};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FileKind {
     Vanilla,
+    /// One of the DLC directories under the vanilla root, numbered in load order
+    Dlc(u8),
+    /// The mod given with `--base-mod`, like a total conversion that the checked mod is a
+    /// submod of. Its files are treated like the game's.
+    BaseMod,
     Mod,
 }
 
@@ -22,11 +28,25 @@ impl Display for FileKind {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
         match *self {
             FileKind::Vanilla => write!(fmt, "CK3"),
+            FileKind::Dlc(_) => write!(fmt, "DLC"),
+            FileKind::BaseMod => write!(fmt, "BASE"),
             FileKind::Mod => write!(fmt, "MOD"),
         }
     }
 }
 
+/// Whether the game loads a file, and if not then why not
+#[derive(Clone, Copy, Debug)]
+pub enum FileStatus<'a> {
+    Loaded,
+    /// A file later in the load order has the same path
+    Overridden,
+    /// The directory is in this `replace_path` of the mod or the base mod
+    ReplacePath(&'a Token),
+    /// The mod is a total conversion, which leaves out the game's map and history
+    TotalConversion,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FileEntry {
     /// Pathname components below the mod directory or the vanilla game dir
@@ -103,8 +123,25 @@ pub struct Fileset {
     /// The mod directory
     mod_root: PathBuf,
 
+    /// The directory of the mod given with `--base-mod`, if any
+    base_mod_root: PathBuf,
+
+    /// The DLC directories that were scanned, in load order.
+    /// Indexed by the number in `FileKind::Dlc`.
+    dlc_roots: Vec<PathBuf>,
+
     /// A list of directories that should not be read from vanilla.
-    replace_paths: Vec<PathBuf>,
+    /// Like in the game, only the files directly in these directories are skipped,
+    /// not the ones in subdirectories.
+    /// The first `base_replace_paths` of them are from the base mod, and only apply to
+    /// vanilla. The rest are from the mod, and apply to the base mod too.
+    replace_paths: Vec<Token>,
+
+    /// How many of the `replace_paths` are from the base mod
+    base_replace_paths: usize,
+
+    /// How many vanilla files were skipped for each of the `replace_paths`
+    replaced_counts: Vec<usize>,
 
     /// The ck3-tiger config
     config: Option<Block>,
@@ -115,44 +152,242 @@ pub struct Fileset {
     /// The CK3 and mod files in the order the game would load them
     ordered_files: Vec<FileEntry>,
 
-    /// All filenames from ordered_files, for quick lookup
-    filenames: FnvHashSet<PathBuf>,
+    /// The files that were not loaded because a later file had the same path, in load order
+    replaced_files: Vec<FileEntry>,
+
+    /// The game's files that were left out because of a `replace_path` or because the mod
+    /// is a total conversion
+    excluded_files: Vec<FileEntry>,
+
+    /// All filenames from `ordered_files`, with their index there, for quick lookup
+    filenames: FnvHashMap<PathBuf, usize>,
+
+    /// Lowercased versions of the filenames, mapped to the actual filenames.
+    /// Used to report references that only work on case-insensitive filesystems.
+    lowercase_filenames: FnvHashMap<String, PathBuf>,
+
+    /// Extra directories in common/ that the selected mod profiles know about
+    profile_common_dirs: Vec<&'static str>,
+
+    /// Whether to follow symlinked directories and files while scanning
+    follow_symlinks: bool,
+
+    /// Whether the mod is a total conversion, which replaces the game's map and titles.
+    /// Then the game's files in `TOTAL_CONVERSION_DIRS` are left out, since they only
+    /// describe the game's own map.
+    total_conversion: bool,
+
+    /// How many vanilla files were left out for each of the `TOTAL_CONVERSION_DIRS`
+    tc_counts: Vec<usize>,
 }
 
 impl Fileset {
-    pub fn new(vanilla_root: PathBuf, mod_root: PathBuf, replace_paths: Vec<PathBuf>) -> Self {
+    pub fn new(vanilla_root: PathBuf, mod_root: PathBuf, replace_paths: Vec<Token>) -> Self {
         Fileset {
             vanilla_root,
             mod_root,
+            base_mod_root: PathBuf::new(),
+            dlc_roots: Vec::new(),
+            replaced_counts: vec![0; replace_paths.len()],
             replace_paths,
+            base_replace_paths: 0,
             config: None,
             files: Vec::new(),
             ordered_files: Vec::new(),
-            filenames: FnvHashSet::default(),
+            replaced_files: Vec::new(),
+            excluded_files: Vec::new(),
+            filenames: FnvHashMap::default(),
+            lowercase_filenames: FnvHashMap::default(),
+            profile_common_dirs: Vec::new(),
+            follow_symlinks: true,
+            total_conversion: false,
+            tc_counts: vec![0; TOTAL_CONVERSION_DIRS.len()],
         }
     }
 
+    /// Load a base mod between the game and the mod, with its own `replace_path` entries.
+    /// This must be called before scanning.
+    pub fn set_base_mod(&mut self, root: PathBuf, replace_paths: Vec<Token>) {
+        self.base_mod_root = root;
+        self.base_replace_paths = replace_paths.len();
+        self.replaced_counts = vec![0; replace_paths.len() + self.replace_paths.len()];
+        self.replace_paths = replace_paths
+            .into_iter()
+            .chain(self.replace_paths.drain(..))
+            .collect();
+    }
+
+    fn has_base_mod(&self) -> bool {
+        !self.base_mod_root.as_os_str().is_empty()
+    }
+
+    /// Skip symlinks while scanning, instead of following them
+    pub fn no_follow_symlinks(&mut self) {
+        self.follow_symlinks = false;
+    }
+
+    /// Accept files in this directory in common/, because a mod profile will load them
+    pub fn allow_common_dir(&mut self, dir: &'static str) {
+        self.profile_common_dirs.push(dir);
+    }
+
     pub fn config(&mut self, config: Block) {
         self.config = Some(config);
     }
 
+    /// Collect the files under `path`. Only fail if `path` itself can't be read; problems with
+    /// the files and directories inside it are reported and those entries are skipped.
+    ///
+    /// Symlinked directories are followed unless `follow_symlinks` was turned off. Their files
+    /// get the path they have under the link, the way the game sees them. A link that leads back
+    /// up to one of its own parents is reported, and so is a link to a directory that was already
+    /// scanned, which is then skipped so that its files aren't loaded twice.
     pub fn scan(&mut self, path: &Path, kind: FileKind) -> Result<(), walkdir::Error> {
-        for entry in WalkDir::new(path) {
-            let entry = entry?;
-            if entry.depth() == 0 || !entry.file_type().is_file() {
-                continue;
-            }
+        // The DLC directories are scanned separately by `scan_dlcs`
+        let mut walker = WalkDir::new(path)
+            .follow_links(self.follow_symlinks)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| {
+                kind != FileKind::Vanilla || e.depth() != 1 || e.file_name() != "dlc"
+            });
+        // The canonical paths of the directories seen so far, mapped to where they were seen
+        let mut visited: FnvHashMap<PathBuf, PathBuf> = FnvHashMap::default();
+        while let Some(entry) = walker.next() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) if e.depth() == 0 => return Err(e),
+                Err(e) => {
+                    Self::report_scan_error(path, kind, &e);
+                    continue;
+                }
+            };
             // unwrap is safe here because WalkDir gives us paths with this prefix.
             let inner_path = entry.path().strip_prefix(path).unwrap();
-            if kind == FileKind::Vanilla && self.replace_paths.iter().any(|p| p == inner_path) {
+            if entry.file_type().is_dir() {
+                if let Ok(canonical) = canonicalize(entry.path()) {
+                    if let Some(earlier) = visited.get(&canonical) {
+                        if entry.path_is_symlink() && entry.depth() > 0 {
+                            let link = FileEntry::new(inner_path.to_path_buf(), kind);
+                            let msg = "symlinked directory was already scanned";
+                            let info = format!("its files were loaded as {}", earlier.display());
+                            warn_info(link, ErrorKey::Duplicate, msg, &info);
+                            walker.skip_current_dir();
+                        }
+                    } else {
+                        visited.insert(canonical, inner_path.to_path_buf());
+                    }
+                }
                 continue;
             }
+            if entry.depth() == 0 || !entry.file_type().is_file() {
+                continue;
+            }
+            if kind != FileKind::Mod {
+                if let Some(idx) = self.replaced_by(inner_path, kind) {
+                    self.replaced_counts[idx] += 1;
+                    self.excluded_files
+                        .push(FileEntry::new(inner_path.to_path_buf(), kind));
+                    continue;
+                }
+            }
             self.files
                 .push(FileEntry::new(inner_path.to_path_buf(), kind));
         }
         Ok(())
     }
 
+    fn report_scan_error(root: &Path, kind: FileKind, e: &walkdir::Error) {
+        let inner_path = match e.path().and_then(|p| p.strip_prefix(root).ok()) {
+            Some(inner_path) if inner_path.file_name().is_some() => inner_path,
+            _ => return,
+        };
+        let entry = FileEntry::new(inner_path.to_path_buf(), kind);
+        let info = match (e.io_error(), e.loop_ancestor()) {
+            (_, Some(ancestor)) => format!("it links back to {}", ancestor.display()),
+            (Some(io_error), _) => format!("{io_error}"),
+            (None, None) => format!("{e}"),
+        };
+        error_info(entry, ErrorKey::ReadError, "could not read path", &info);
+    }
+
+    /// Scan the DLC directories under the vanilla root, skipping the ones named in `no_dlc`.
+    /// The game loads them after the base game files, in alphabetical order.
+    pub fn scan_dlcs(&mut self, no_dlc: &[String]) -> Result<(), walkdir::Error> {
+        let dlc_dir = self.vanilla_root.join("dlc");
+        if !dlc_dir.is_dir() {
+            set_loaded_dlc_roots(Vec::new());
+            return Ok(());
+        }
+        let walker = WalkDir::new(dlc_dir)
+            .min_depth(1)
+            .max_depth(1)
+            .sort_by_file_name();
+        for entry in walker {
+            let entry = entry?;
+            if !entry.file_type().is_dir()
+                || no_dlc.iter().any(|name| entry.file_name() == name.as_str())
+            {
+                continue;
+            }
+            // There are only a few dozen DLCs, so this won't overflow in practice.
+            let Ok(idx) = u8::try_from(self.dlc_roots.len()) else {
+                break;
+            };
+            let path = entry.path().to_path_buf();
+            self.dlc_roots.push(path.clone());
+            self.scan(&path, FileKind::Dlc(idx))?;
+        }
+        set_loaded_dlc_roots(self.dlc_roots.clone());
+        Ok(())
+    }
+
+    /// Return the index of the `replace_path` that covers this file, if any.
+    /// The base mod's files are only covered by the mod's `replace_path` entries.
+    fn replaced_by(&self, inner_path: &Path, kind: FileKind) -> Option<usize> {
+        let dir = inner_path.parent()?;
+        let skip = if kind == FileKind::BaseMod {
+            self.base_replace_paths
+        } else {
+            0
+        };
+        self.replace_paths
+            .iter()
+            .skip(skip)
+            .position(|p| Path::new(p.as_str()) == dir)
+            .map(|idx| idx + skip)
+    }
+
+    /// Whether the game's (or the base mod's) files directly in this directory are not loaded, because of a
+    /// `replace_path` or because the mod is a total conversion.
+    pub fn is_replaced(&self, dir: &str) -> bool {
+        let dir = dir.trim_end_matches('/');
+        let by_path = self
+            .replace_paths
+            .iter()
+            .zip(&self.replaced_counts)
+            .any(|(path, count)| path.is(dir) && *count > 0);
+        let by_tc = TOTAL_CONVERSION_DIRS
+            .iter()
+            .zip(&self.tc_counts)
+            .any(|(tc_dir, count)| *tc_dir == dir && *count > 0);
+        by_path || by_tc
+    }
+
+    pub fn is_total_conversion(&self) -> bool {
+        self.total_conversion
+    }
+
+    /// Check the mod as a total conversion even if its `replace_path` doesn't say so.
+    /// This must be called after `finalize`.
+    pub fn set_total_conversion(&mut self) {
+        if !self.total_conversion {
+            self.total_conversion = true;
+            self.leave_out_tc_files();
+            self.index_filenames();
+        }
+    }
+
     pub fn finalize(&mut self) {
         // This places `Mod` entries after `Vanilla` entries
         self.files.sort();
@@ -161,7 +396,7 @@ impl Fileset {
         for entry in self.files.drain(..) {
             if let Some(prev) = self.ordered_files.last_mut() {
                 if entry.path == prev.path {
-                    *prev = entry;
+                    self.replaced_files.push(std::mem::replace(prev, entry));
                 } else {
                     self.ordered_files.push(entry);
                 }
@@ -170,8 +405,53 @@ impl Fileset {
             }
         }
 
-        for entry in &self.ordered_files {
-            self.filenames.insert(entry.path.clone());
+        // A mod that replaces the landed titles has its own map, and the game's history
+        // for the old map would only give confusing reports. The same goes for a mod built on
+        // a base mod that does so.
+        if self
+            .replace_paths
+            .iter()
+            .any(|p| p.is("common/landed_titles"))
+        {
+            self.total_conversion = true;
+            self.leave_out_tc_files();
+        }
+
+        self.index_filenames();
+    }
+
+    /// Leave out the game's files in the `TOTAL_CONVERSION_DIRS`, counting them per directory.
+    fn leave_out_tc_files(&mut self) {
+        let counts = &mut self.tc_counts;
+        let excluded = &mut self.excluded_files;
+        self.ordered_files.retain(|entry| {
+            if entry.kind >= FileKind::BaseMod {
+                return true;
+            }
+            let dir = entry.path.parent();
+            match TOTAL_CONVERSION_DIRS
+                .iter()
+                .position(|tc_dir| dir == Some(Path::new(tc_dir)))
+            {
+                Some(idx) => {
+                    counts[idx] += 1;
+                    excluded.push(entry.clone());
+                    false
+                }
+                None => true,
+            }
+        });
+    }
+
+    fn index_filenames(&mut self) {
+        self.filenames.clear();
+        self.lowercase_filenames.clear();
+        for (idx, entry) in self.ordered_files.iter().enumerate() {
+            self.filenames.insert(entry.path.clone(), idx);
+            self.lowercase_filenames.insert(
+                entry.path.to_string_lossy().to_lowercase(),
+                entry.path.clone(),
+            );
         }
     }
 
@@ -182,9 +462,37 @@ impl Fileset {
         }
     }
 
+    /// The files with this path that the game skips because a later file has the same path.
+    pub fn replaced_files<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a FileEntry> {
+        self.replaced_files.iter().filter(move |e| e.path == path)
+    }
+
+    /// Every file that was found, whether the game loads it or not, sorted by path and then
+    /// in load order.
+    pub fn all_files(&self) -> Vec<(&FileEntry, FileStatus<'_>)> {
+        let mut files = Vec::new();
+        for entry in &self.ordered_files {
+            files.push((entry, FileStatus::Loaded));
+        }
+        for entry in &self.replaced_files {
+            files.push((entry, FileStatus::Overridden));
+        }
+        for entry in &self.excluded_files {
+            let status = match self.replaced_by(entry.path(), entry.kind()) {
+                Some(idx) => FileStatus::ReplacePath(&self.replace_paths[idx]),
+                None => FileStatus::TotalConversion,
+            };
+            files.push((entry, status));
+        }
+        files.sort_by_key(|(entry, _)| *entry);
+        files
+    }
+
     pub fn fullpath(&self, entry: &FileEntry) -> PathBuf {
         match entry.kind {
             FileKind::Vanilla => self.vanilla_root.join(entry.path()),
+            FileKind::Dlc(idx) => self.dlc_roots[idx as usize].join(entry.path()),
+            FileKind::BaseMod => self.base_mod_root.join(entry.path()),
             FileKind::Mod => self.mod_root.join(entry.path()),
         }
     }
@@ -202,12 +510,35 @@ impl Fileset {
 
     pub fn exists(&self, key: &str) -> bool {
         let filepath = PathBuf::from(key);
-        self.filenames.contains(&filepath)
+        self.filenames.contains_key(&filepath)
+    }
+
+    /// The file that the game uses for this path, if there is one
+    pub fn get_entry(&self, path: &Path) -> Option<&FileEntry> {
+        self.filenames
+            .get(path)
+            .map(|&idx| &self.ordered_files[idx])
+    }
+
+    /// If the file exists but with different upper/lowercase, warn about it and return true.
+    /// The reference would work on Windows but not on Linux.
+    pub fn verify_case(&self, file: &str, t: &Token) -> bool {
+        if let Some(canonical) = self.lowercase_filenames.get(&file.to_lowercase()) {
+            let msg = format!(
+                "path exists but with different case: expected {}, found {}",
+                canonical.display(),
+                file
+            );
+            warn(t, ErrorKey::FileCase, &msg);
+            true
+        } else {
+            false
+        }
     }
 
     pub fn verify_exists(&self, file: &Token) {
         let filepath = PathBuf::from(file.as_str());
-        if !self.filenames.contains(&filepath) {
+        if !self.filenames.contains_key(&filepath) && !self.verify_case(file.as_str(), file) {
             error(
                 file,
                 ErrorKey::MissingFile,
@@ -218,7 +549,7 @@ impl Fileset {
 
     pub fn verify_exists_implied(&self, file: &str, t: &Token) {
         let filepath = PathBuf::from(file);
-        if !self.filenames.contains(&filepath) {
+        if !self.filenames.contains_key(&filepath) && !self.verify_case(file, t) {
             error(
                 t,
                 ErrorKey::MissingFile,
@@ -228,7 +559,42 @@ impl Fileset {
     }
 
     pub fn validate(&self, _data: &Everything) {
-        // Check the files in directories in common/ to make sure they are in known directories
+        self.validate_replace_paths();
+        self.validate_common_dirs();
+        self.validate_unloaded_files();
+    }
+
+    fn validate_replace_paths(&self) {
+        // The base mod's own replace_paths are its business, like the game's files are
+        let skip = self.base_replace_paths;
+        for (path, count) in self
+            .replace_paths
+            .iter()
+            .zip(&self.replaced_counts)
+            .skip(skip)
+        {
+            if self.vanilla_root.join(path.as_str()).is_dir()
+                || (self.has_base_mod() && self.base_mod_root.join(path.as_str()).is_dir())
+            {
+                let msg = if self.has_base_mod() {
+                    format!("replace_path excludes {count} vanilla and base mod files")
+                } else {
+                    format!("replace_path excludes {count} vanilla files")
+                };
+                info(path, ErrorKey::Packaging, &msg);
+            } else {
+                warn_info(
+                    path,
+                    ErrorKey::Packaging,
+                    "replace_path names a directory that does not exist in vanilla",
+                    "this replace_path does nothing; maybe it has a typo?",
+                );
+            }
+        }
+    }
+
+    /// Check the files in directories in common/ to make sure they are in known directories
+    fn validate_common_dirs(&self) {
         let mut warned: Vec<&Path> = Vec::new();
         'outer: for entry in &self.ordered_files {
             if !entry.path.starts_with("common") || !entry.path.to_string_lossy().ends_with(".txt")
@@ -240,16 +606,88 @@ impl Fileset {
                 continue;
             }
             // TODO: check if subdirectories are ok in the different common/ directories
-            for valid in COMMON_DIRS {
+            for valid in COMMON_DIRS.iter().chain(&self.profile_common_dirs) {
                 if entry.path.starts_with(valid) {
                     continue 'outer;
                 }
             }
-            // TODO: maybe call out specific common mistakes like scripted_values or on_actions ?
-            error(entry, ErrorKey::Filename, "file in unexpected directory");
+            let msg = "file in unexpected directory";
+            if let Some(suggest) = near_miss(&dirname.to_string_lossy(), COMMON_DIRS) {
+                let info = format!("did you mean `{suggest}`?");
+                error_info(entry, ErrorKey::Filename, msg, &info);
+            } else {
+                error(entry, ErrorKey::Filename, msg);
+            }
             warned.push(dirname);
         }
     }
+
+    /// Check for mod files that the game will not load, because they are in a directory the game
+    /// doesn't read or because they have the wrong extension for their directory.
+    fn validate_unloaded_files(&self) {
+        let mut ignore: Vec<PathBuf> = IGNORE_UNLOADED.iter().map(PathBuf::from).collect();
+        if let Some(config) = &self.config {
+            if let Some(block) = config.get_field_block("unloaded_files") {
+                for token in block.get_field_values("ignore") {
+                    ignore.push(PathBuf::from(token.as_str()));
+                }
+            }
+        }
+
+        let mut warned: Vec<&OsStr> = Vec::new();
+        for entry in &self.ordered_files {
+            if entry.kind != FileKind::Mod || ignore.iter().any(|i| entry.path.starts_with(i)) {
+                continue;
+            }
+            let mut components = entry.path.iter();
+            let topdir = components.next().unwrap();
+            // Files in the mod's top directory are things like descriptor.mod and thumbnail.png
+            if components.next().is_none() {
+                continue;
+            }
+
+            let topdir_str = topdir.to_string_lossy();
+            // `.tiger` holds this validator's own files, such as the scope declarations
+            if topdir_str == ".tiger" {
+                continue;
+            }
+            if !GAME_DIRS.contains(&&*topdir_str) {
+                if warned.contains(&topdir) {
+                    continue;
+                }
+                warned.push(topdir);
+                let msg = format!("the game does not load files from `{topdir_str}`");
+                if let Some(suggest) = near_miss(&topdir_str, GAME_DIRS) {
+                    let info = format!("did you mean `{suggest}`?");
+                    error_info(entry, ErrorKey::Filename, &msg, &info);
+                } else {
+                    warn(entry, ErrorKey::Filename, &msg);
+                }
+                continue;
+            }
+
+            for (dir, ext) in CONTENT_EXTENSIONS {
+                let ext_ok =
+                    matches!(entry.path.extension(), Some(e) if e.eq_ignore_ascii_case(ext));
+                if topdir_str == *dir && !ext_ok {
+                    let msg = format!("the game only loads .{ext} files from `{dir}`");
+                    warn(entry, ErrorKey::Filename, &msg);
+                }
+            }
+        }
+    }
+}
+
+/// Return the candidate that `name` was probably meant to be, if it differs only in
+/// upper/lowercase or by a trailing `s`.
+fn near_miss<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    candidates.iter().copied().find(|candidate| {
+        let candidate = candidate.to_lowercase();
+        name == candidate
+            || name.strip_suffix('s') == Some(&candidate)
+            || candidate.strip_suffix('s') == Some(&name)
+    })
 }
 
 #[derive(Clone, Debug)]
@@ -271,6 +709,61 @@ impl<'a> Iterator for Files<'a> {
     }
 }
 
+/// The directories in a mod that the game reads files from.
+/// The directories of the game's files that only make sense with the game's own map.
+/// Total conversions have their own map, so these are not loaded for them.
+const TOTAL_CONVERSION_DIRS: &[&str] = &[
+    "common/landed_titles",
+    "history/characters",
+    "history/provinces",
+    "history/titles",
+];
+
+const GAME_DIRS: &[&str] = &[
+    "common",
+    "content_source",
+    "data_binding",
+    "events",
+    "fonts",
+    "gfx",
+    "gui",
+    "history",
+    "localization",
+    "map_data",
+    "music",
+    "notifications",
+    "sound",
+    "tests",
+    "tweakergui_assets",
+];
+
+/// Directories where the game only loads files with a specific extension.
+const CONTENT_EXTENSIONS: &[(&str, &str)] = &[
+    ("common", "txt"),
+    ("events", "txt"),
+    ("gui", "gui"),
+    ("history", "txt"),
+    ("localization", "yml"),
+];
+
+/// Files and directories that are commonly found in a mod but are not meant for the game.
+/// More can be added with `unloaded_files` in the config.
+const IGNORE_UNLOADED: &[&str] = &[
+    ".git",
+    ".gitattributes",
+    ".github",
+    ".gitignore",
+    ".idea",
+    ".vscode",
+    "CHANGELOG.md",
+    "LICENSE",
+    "LICENSE.md",
+    "LICENSE.txt",
+    "README",
+    "README.md",
+    "README.txt",
+];
+
 /// LAST UPDATED VERSION 1.7.0
 const COMMON_DIRS: &[&str] = &[
     "common/achievements",
@@ -340,6 +833,7 @@ const COMMON_DIRS: &[&str] = &[
     "common/governments",
     "common/guest_system",
     "common/holdings",
+    "common/holy_orders",
     "common/hook_types",
     "common/important_actions",
     "common/inspirations",
@@ -349,6 +843,7 @@ const COMMON_DIRS: &[&str] = &[
     "common/lifestyle_perks",
     "common/lifestyles",
     "common/men_at_arms_types",
+    "common/mercenary_companies",
     "common/messages",
     "common/modifier_definition_formats",
     "common/modifier_icons",
@@ -385,6 +880,10 @@ const COMMON_DIRS: &[&str] = &[
     "common/suggestions",
     "common/terrain_types",
     "common/traits",
+    // The travel system is from 1.9, but its directories are accepted already
+    "common/travel/danger_types",
+    "common/travel/point_of_interest_types",
+    "common/travel/travel_options",
     "common/trigger_localization",
     "common/tutorial_lesson_chains",
     "common/tutorial_lessons",
@@ -0,0 +1,161 @@
+//! Tracking of the flags that script sets with effects like `add_character_flag`, so that
+//! triggers like `has_character_flag` can be checked against them once everything has been
+//! validated.
+
+use fnv::{FnvHashMap, FnvHashSet};
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use crate::errorkey::ErrorKey;
+use crate::errors::{warn, warn_info};
+use crate::everything::Everything;
+use crate::helpers::closest_key;
+use crate::scopes::Scopes;
+use crate::token::Token;
+
+/// The separate namespaces of flags. A flag set on a county can't be checked on a character.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FlagFamily {
+    Character,
+    Relation,
+    Realm,
+    County,
+    Province,
+    Culture,
+    Faith,
+}
+
+impl FlagFamily {
+    /// The families that are set with `add_<family>_flag = name` on their own scope, and
+    /// checked with `has_<family>_flag`. Relation flags work differently, with a block.
+    const SIMPLE: [FlagFamily; 6] = [
+        FlagFamily::Character,
+        FlagFamily::Realm,
+        FlagFamily::County,
+        FlagFamily::Province,
+        FlagFamily::Culture,
+        FlagFamily::Faith,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            FlagFamily::Character => "character flag",
+            FlagFamily::Relation => "relation flag",
+            FlagFamily::Realm => "realm flag",
+            FlagFamily::County => "county flag",
+            FlagFamily::Province => "province flag",
+            FlagFamily::Culture => "culture flag",
+            FlagFamily::Faith => "faith flag",
+        }
+    }
+
+    fn setter(self) -> &'static str {
+        match self {
+            FlagFamily::Character => "add_character_flag",
+            FlagFamily::Relation => "add_relation_flag",
+            FlagFamily::Realm => "add_realm_flag",
+            FlagFamily::County => "add_county_flag",
+            FlagFamily::Province => "add_province_flag",
+            FlagFamily::Culture => "add_culture_flag",
+            FlagFamily::Faith => "add_faith_flag",
+        }
+    }
+
+    fn reader(self) -> &'static str {
+        match self {
+            FlagFamily::Character => "has_character_flag",
+            FlagFamily::Relation => "has_relation_flag",
+            FlagFamily::Realm => "has_realm_flag",
+            FlagFamily::County => "has_county_flag",
+            FlagFamily::Province => "has_province_flag",
+            FlagFamily::Culture => "has_culture_flag",
+            FlagFamily::Faith => "has_faith_flag",
+        }
+    }
+
+    /// The scope type that this family's flags are set on. Realm flags belong to the
+    /// realm's ruler, and county flags to the county title.
+    pub fn scopes(self) -> Scopes {
+        match self {
+            FlagFamily::Character | FlagFamily::Relation | FlagFamily::Realm => Scopes::Character,
+            FlagFamily::County => Scopes::LandedTitle,
+            FlagFamily::Province => Scopes::Province,
+            FlagFamily::Culture => Scopes::Culture,
+            FlagFamily::Faith => Scopes::Faith,
+        }
+    }
+
+    /// The family whose flags the effect `key` sets, like `add_county_flag`.
+    pub fn from_setter(key: &Token) -> Option<Self> {
+        Self::SIMPLE
+            .into_iter()
+            .find(|family| key.as_str().eq_ignore_ascii_case(family.setter()))
+    }
+
+    /// The family whose flags the trigger `key` checks, like `has_county_flag`.
+    pub fn from_reader(key: &Token) -> Option<Self> {
+        Self::SIMPLE
+            .into_iter()
+            .find(|family| key.as_str().eq_ignore_ascii_case(family.reader()))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Flags {
+    /// The flag names that are set somewhere, per family.
+    set: RefCell<FnvHashMap<FlagFamily, FnvHashSet<String>>>,
+    /// Files that set a flag whose name comes from a macro parameter. Any flag of that family
+    /// might be set there, so reads in those files are not reported.
+    wildcards: RefCell<FnvHashSet<(FlagFamily, PathBuf)>>,
+    /// The flags checked by triggers, to be compared against `set` at the end.
+    reads: RefCell<Vec<(FlagFamily, Token)>>,
+}
+
+impl Flags {
+    pub fn set(&self, family: FlagFamily, flag: &Token) {
+        if flag.as_str().contains('$') {
+            let pathname = flag.loc.pathname.to_path_buf();
+            self.wildcards.borrow_mut().insert((family, pathname));
+        } else {
+            let mut set = self.set.borrow_mut();
+            set.entry(family).or_default().insert(flag.to_string());
+        }
+    }
+
+    pub fn read(&self, family: FlagFamily, flag: &Token) {
+        if !flag.as_str().contains('$') {
+            self.reads.borrow_mut().push((family, flag.clone()));
+        }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let set = self.set.borrow();
+        let wildcards = self.wildcards.borrow();
+        let empty = FnvHashSet::default();
+        for (family, flag) in self.reads.borrow().iter() {
+            let names = set.get(family).unwrap_or(&empty);
+            if names.contains(flag.as_str())
+                || wildcards.contains(&(*family, flag.loc.pathname.to_path_buf()))
+            {
+                continue;
+            }
+            let msg = format!(
+                "{} `{}` is never set with `{}`",
+                family.name(),
+                flag,
+                family.setter()
+            );
+            let candidates = names.iter().map(String::as_str);
+            // Trait flags are declared in the traits and checked with their own trigger
+            if *family == FlagFamily::Character && data.traits.flag_exists(flag.as_str()) {
+                let info = format!("`{flag}` is a trait flag; check it with `has_trait_with_flag`");
+                warn_info(flag, ErrorKey::UnsetFlag, &msg, &info);
+            } else if let Some(suggest) = closest_key(flag.as_str(), candidates) {
+                let info = format!("did you mean `{suggest}`?");
+                warn_info(flag, ErrorKey::UnsetFlag, &msg, &info);
+            } else {
+                warn(flag, ErrorKey::UnsetFlag, &msg);
+            }
+        }
+    }
+}
@@ -23,3 +23,154 @@ pub fn dup_assign_error(key: &Token, other: &Token) {
         "the other one is here",
     );
 }
+
+/// A known name that a name which wasn't found was probably meant to be
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Suggestion<'a> {
+    /// The name is known, but with different upper and lower case
+    Casing(&'a str),
+    /// The name is a typo of this one
+    Typo(&'a str),
+}
+
+impl<'a> Suggestion<'a> {
+    pub fn name(self) -> &'a str {
+        match self {
+            Suggestion::Casing(name) | Suggestion::Typo(name) => name,
+        }
+    }
+
+    /// The explanation to add to the report
+    pub fn info(self) -> String {
+        match self {
+            Suggestion::Casing(name) => format!("it is `{name}`, with different case"),
+            Suggestion::Typo(name) => format!("did you mean `{name}`?"),
+        }
+    }
+}
+
+/// The prefixes of the iterator families, in the order they are preferred as suggestions
+const ITERATOR_PREFIXES: &[&str] = &["every_", "random_", "ordered_", "any_"];
+
+/// How many edits a name of this length can be away from a candidate to still be a typo of it
+fn max_distance(key: &str) -> usize {
+    (key.chars().count() / 4).max(1)
+}
+
+/// Find the candidate that `key` was probably meant to be. A candidate that only differs in
+/// case is preferred, then the closest typo, then an iterator of the same list with another
+/// prefix or with the prefix left out (`each_vassal` or `vassal` for `every_vassal`).
+pub fn suggest<'a>(
+    key: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<Suggestion<'a>> {
+    let max = max_distance(key);
+    let key_len = key.chars().count();
+    let key_rest = key.split_once('_').map(|(_, rest)| rest);
+    let mut best: Option<(usize, usize, &str)> = None;
+    for candidate in candidates {
+        if candidate.eq_ignore_ascii_case(key) && candidate != key {
+            return Some(Suggestion::Casing(candidate));
+        }
+        let score = if candidate.chars().count().abs_diff(key_len) <= max {
+            Some((edit_distance(key, candidate), 0)).filter(|(distance, _)| *distance <= max)
+        } else {
+            None
+        };
+        let score = score.or_else(|| {
+            ITERATOR_PREFIXES
+                .iter()
+                .position(|prefix| {
+                    candidate.strip_prefix(prefix).is_some_and(|rest| {
+                        !rest.is_empty() && (key == rest || key_rest == Some(rest))
+                    })
+                })
+                .map(|order| (max + 1, order))
+        });
+        if let Some((distance, order)) = score {
+            if best.is_none_or(|best| (distance, order, candidate) < best) {
+                best = Some((distance, order, candidate));
+            }
+        }
+    }
+    best.map(|(_, _, candidate)| Suggestion::Typo(candidate))
+}
+
+/// Find the candidate closest to `key`, if one is close enough that `key` is probably a typo of it.
+pub fn closest_key<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    suggest(key, candidates).map(Suggestion::name)
+}
+
+/// The candidates of a large set of names, such as the localization keys, by their length in
+/// characters. A typo is at most a few characters longer or shorter than what it was meant
+/// to be, so only a few lengths have to be searched.
+#[derive(Clone, Debug, Default)]
+pub struct SuggestIndex {
+    by_length: Vec<Vec<String>>,
+}
+
+impl SuggestIndex {
+    /// Searching stops after this many candidates, so that a report about a short key in a
+    /// huge set doesn't take long.
+    const SEARCH_LIMIT: usize = 20_000;
+
+    pub fn new<'a>(names: impl Iterator<Item = &'a str>) -> Self {
+        let mut by_length: Vec<Vec<String>> = Vec::new();
+        for name in names {
+            let len = name.chars().count();
+            if by_length.len() <= len {
+                by_length.resize(len + 1, Vec::new());
+            }
+            by_length[len].push(name.to_string());
+        }
+        // Sorted so that the limit cuts off the same candidates every run
+        for bucket in &mut by_length {
+            bucket.sort_unstable();
+        }
+        Self { by_length }
+    }
+
+    pub fn suggest(&self, key: &str) -> Option<Suggestion<'_>> {
+        let len = key.chars().count();
+        let max = max_distance(key);
+        // The key's own length first, then the lengths around it
+        let mut lengths = vec![len];
+        for diff in 1..=max {
+            lengths.push(len + diff);
+            if let Some(shorter) = len.checked_sub(diff) {
+                lengths.push(shorter);
+            }
+        }
+        let candidates = lengths
+            .into_iter()
+            .filter_map(|len| self.by_length.get(len))
+            .flatten()
+            .map(String::as_str)
+            .take(Self::SEARCH_LIMIT);
+        suggest(key, candidates)
+    }
+}
+
+/// The optimal string alignment distance between two strings: the number of single-character
+/// insertions, deletions, substitutions, or swaps of neighboring characters needed to turn one
+/// into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for i in 0..a.len() {
+        cur[0] = i + 1;
+        for j in 0..b.len() {
+            let cost = usize::from(a[i] != b[j]);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                cur[j + 1] = cur[j + 1].min(before[j - 1] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
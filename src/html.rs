@@ -0,0 +1,256 @@
+//! A standalone HTML page with the reports of a run, for the `--html` option.
+//! The page has no external assets, and filters the reports with a bit of embedded script.
+
+use fnv::FnvHashMap;
+use std::env::current_dir;
+use std::fmt::Write;
+
+use crate::errors::{ErrorLevel, LoggedReport, ReportPlace};
+
+/// Browsers get slow with pages much bigger than this, so the reports after these are left out.
+const MAX_REPORTS: usize = 20_000;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 1em 2em; }
+#filters { position: sticky; top: 0; background: #f4f4f4; padding: 0.5em; border: 1px solid #ccc; }
+#filters label { margin-right: 1em; }
+details.file { margin: 0.5em 0; border: 1px solid #ddd; }
+details.file > summary { cursor: pointer; padding: 0.3em; background: #fafafa; font-family: monospace; }
+.count { color: #666; font-family: sans-serif; }
+.report { margin: 0.5em 1em; padding-left: 0.5em; border-left: 4px solid #999; }
+.report.error { border-color: #c00; }
+.report.warning { border-color: #e80; }
+.report.info { border-color: #08c; }
+.report.advice { border-color: #6a6; }
+.level { font-weight: bold; }
+.key { color: #666; }
+pre { margin: 0.2em 0; padding: 0.2em; background: #f8f8f8; tab-size: 4; overflow-x: auto; }
+.related { margin-left: 1.5em; }
+.note { color: #a00; }
+";
+
+const SCRIPT: &str = "
+function applyFilters() {
+  const levels = new Set([...document.querySelectorAll('input.level:checked')].map(e => e.value));
+  const key = document.getElementById('key').value;
+  const path = document.getElementById('path').value.toLowerCase();
+  let shown = 0;
+  for (const file of document.querySelectorAll('details.file')) {
+    const pathMatches = file.dataset.path.toLowerCase().includes(path);
+    let count = 0;
+    for (const report of file.querySelectorAll('.report')) {
+      const show = pathMatches && levels.has(report.dataset.level)
+        && (key === '' || report.dataset.key === key);
+      report.style.display = show ? '' : 'none';
+      if (show) { count += 1; }
+    }
+    file.style.display = count > 0 ? '' : 'none';
+    file.querySelector('.count').textContent = '(' + count + ')';
+    shown += count;
+  }
+  document.getElementById('shown').textContent = shown + ' reports shown';
+}
+for (const e of document.querySelectorAll('#filters input, #filters select')) {
+  e.addEventListener('input', applyFilters);
+}
+applyFilters();
+";
+
+/// Make the page for the reports of each mod that was checked, given with the name of the mod.
+pub fn html_report(runs: &[(String, Vec<LoggedReport>)]) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>ck3-tiger report</title>\n");
+    let _ = writeln!(out, "<style>{STYLE}</style>\n</head>\n<body>");
+    out.push_str("<h1>ck3-tiger report</h1>\n");
+
+    let mut budget = MAX_REPORTS;
+    let mut left_out = 0;
+    let mut level_counts: FnvHashMap<ErrorLevel, usize> = FnvHashMap::default();
+    let mut key_counts: FnvHashMap<&str, usize> = FnvHashMap::default();
+    let mut body = String::new();
+    for (name, reports) in runs {
+        if runs.len() > 1 {
+            let _ = writeln!(body, "<h2>{}</h2>", escape(name));
+        }
+        if reports.is_empty() {
+            body.push_str("<p>No reports.</p>\n");
+        }
+        let mut reports = reports.iter().collect::<Vec<_>>();
+        reports.sort_by(|a, b| a.place.loc.cmp(&b.place.loc));
+        let kept = reports.len().min(budget);
+        left_out += reports.len() - kept;
+        budget -= kept;
+        for file in group_by_file(&reports[..kept]) {
+            write_file(&mut body, file);
+        }
+        for report in &reports[..kept] {
+            *level_counts.entry(report.level).or_default() += 1;
+            *key_counts.entry(report.key.as_str()).or_default() += 1;
+        }
+    }
+
+    out.push_str("<div id=\"filters\">\n");
+    for level in [
+        ErrorLevel::Error,
+        ErrorLevel::Warning,
+        ErrorLevel::Info,
+        ErrorLevel::Advice,
+    ] {
+        let _ = writeln!(
+            out,
+            "<label><input type=\"checkbox\" class=\"level\" value=\"{}\" checked> {} ({})</label>",
+            level_class(level),
+            level,
+            level_counts.get(&level).copied().unwrap_or(0)
+        );
+    }
+    out.push_str("<label>Code <select id=\"key\"><option value=\"\">all</option>");
+    let mut keys = key_counts.into_iter().collect::<Vec<_>>();
+    keys.sort_unstable();
+    for (key, count) in keys {
+        let _ = write!(out, "<option value=\"{key}\">{key} ({count})</option>");
+    }
+    out.push_str("</select></label>\n");
+    out.push_str("<label>Path <input type=\"search\" id=\"path\"></label>\n");
+    out.push_str("<span id=\"shown\"></span>\n</div>\n");
+    if left_out > 0 {
+        let _ = writeln!(
+            out,
+            "<p class=\"note\">{left_out} more reports were left out to keep this page usable. See the text output for all of them.</p>"
+        );
+    }
+
+    out.push_str(&body);
+    let _ = writeln!(out, "<script>{SCRIPT}</script>\n</body>\n</html>");
+    out
+}
+
+/// Split reports that are sorted by location into the runs of reports about the same file
+fn group_by_file<'a>(reports: &'a [&'a LoggedReport]) -> Vec<&'a [&'a LoggedReport]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for idx in 1..=reports.len() {
+        if idx == reports.len() || !same_file(&reports[idx].place, &reports[start].place) {
+            groups.push(&reports[start..idx]);
+            start = idx;
+        }
+    }
+    groups
+}
+
+fn same_file(a: &ReportPlace, b: &ReportPlace) -> bool {
+    a.loc.kind == b.loc.kind && a.loc.pathname == b.loc.pathname
+}
+
+/// Write a collapsible section with the reports about one file
+fn write_file(out: &mut String, reports: &[&LoggedReport]) {
+    let place = &reports[0].place;
+    let path = place.loc.pathname.display().to_string();
+    let _ = writeln!(
+        out,
+        "<details class=\"file\" open data-path=\"{}\">\n<summary>[{}] <a href=\"{}\">{}</a> <span class=\"count\">({})</span></summary>",
+        escape(&path),
+        place.loc.kind,
+        file_url(place),
+        escape(&path),
+        reports.len()
+    );
+    for report in reports {
+        let _ = writeln!(
+            out,
+            "<div class=\"report {}\" data-level=\"{}\" data-key=\"{}\">",
+            level_class(report.level),
+            level_class(report.level),
+            report.key.as_str()
+        );
+        let _ = writeln!(
+            out,
+            "<div><span class=\"level\">{}</span> <span class=\"key\">({})</span> {}</div>",
+            report.level,
+            report.key.as_str(),
+            escape(&report.msg)
+        );
+        write_snippet(out, &report.place);
+        if let Some(info) = &report.info {
+            let _ = writeln!(out, "<div class=\"info\">{}</div>", escape(info));
+        }
+        for rel in &report.related {
+            let _ = writeln!(
+                out,
+                "<div class=\"related\">&rarr; {}<br>[{}] <a href=\"{}\">{}</a>",
+                escape(rel.label.as_deref().unwrap_or_default()),
+                rel.loc.kind,
+                file_url(rel),
+                escape(&rel.loc.pathname.display().to_string())
+            );
+            write_snippet(out, rel);
+            out.push_str("</div>\n");
+        }
+        out.push_str("</div>\n");
+    }
+    out.push_str("</details>\n");
+}
+
+fn write_snippet(out: &mut String, place: &ReportPlace) {
+    if let Some((line, marks)) = &place.snippet {
+        let marker = place.loc.line_marker();
+        let _ = writeln!(
+            out,
+            "<pre>{} {}\n{} {}</pre>",
+            marker,
+            escape(line),
+            marker,
+            escape(marks)
+        );
+    }
+}
+
+fn level_class(level: ErrorLevel) -> &'static str {
+    match level {
+        ErrorLevel::Error => "error",
+        ErrorLevel::Warning => "warning",
+        ErrorLevel::Info => "info",
+        ErrorLevel::Advice => "advice",
+    }
+}
+
+/// A `file:` link to the place's file, which opens it from the browser
+fn file_url(place: &ReportPlace) -> String {
+    let fullpath = match current_dir() {
+        Ok(dir) if place.fullpath.is_relative() => dir.join(&place.fullpath),
+        _ => place.fullpath.clone(),
+    };
+    let mut url = "file:///".to_string();
+    for c in fullpath
+        .display()
+        .to_string()
+        .trim_start_matches('/')
+        .chars()
+    {
+        match c {
+            '\\' => url.push('/'),
+            ' ' => url.push_str("%20"),
+            '#' => url.push_str("%23"),
+            '%' => url.push_str("%25"),
+            '?' => url.push_str("%3F"),
+            _ => url.push(c),
+        }
+    }
+    escape(&url)
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
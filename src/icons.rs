@@ -0,0 +1,153 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::block::validator::Validator;
+use crate::block::BlockOrValue;
+use crate::context::ScopeContext;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error_info, warn_info};
+use crate::everything::Everything;
+use crate::fileset::FileKind;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+/// The kinds of icons that script gives by name rather than by path. The game looks for
+/// them in the folders for their kind, in order, and adds `.dds` if the name has no extension.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IconKind {
+    Artifact,
+    CharacterInteraction,
+    Doctrine,
+    Faith,
+    Lifestyle,
+    MenAtArms,
+    Trait,
+}
+
+impl IconKind {
+    fn folders(self) -> &'static [&'static str] {
+        match self {
+            IconKind::Artifact => &["gfx/interface/icons/artifacts"],
+            IconKind::CharacterInteraction => &["gfx/interface/icons/character_interactions"],
+            IconKind::Doctrine => &["gfx/interface/icons/faith_doctrines"],
+            IconKind::Faith => &["gfx/interface/icons/faith"],
+            IconKind::Lifestyle => &["gfx/interface/icons/lifestyles"],
+            IconKind::MenAtArms => &["gfx/interface/icons/regimenttypes"],
+            IconKind::Trait => &["gfx/interface/icons/traits"],
+        }
+    }
+
+    /// The paths the game tries for an icon name, in order
+    fn candidates(self, name: &str) -> Vec<String> {
+        // A full path is used as is
+        if name.starts_with("gfx/") {
+            return vec![name.to_string()];
+        }
+        let filename = icon_filename(name);
+        self.folders()
+            .iter()
+            .map(|folder| format!("{folder}/{filename}"))
+            .collect()
+    }
+}
+
+/// Subfolders with the same icons in other sizes, for other parts of the interface. The
+/// game doesn't look in them for icons given by name.
+const SIZE_VARIANTS: &[&str] = &["small", "medium", "large", "32", "64", "128"];
+
+fn icon_filename(name: &str) -> String {
+    if name.contains('.') {
+        name.to_string()
+    } else {
+        format!("{name}.dds")
+    }
+}
+
+/// Check that the game can find an icon that is given by name, and that the mod's icon
+/// files really are DDS images.
+pub fn verify_icon(kind: IconKind, name: &str, token: &Token, data: &Everything) {
+    let candidates = kind.candidates(name);
+    for candidate in &candidates {
+        if let Some(entry) = data.fileset.get_entry(candidate.as_ref()) {
+            if entry.kind() == FileKind::Mod {
+                verify_dds(&data.fileset.fullpath(entry), candidate, token);
+            }
+            return;
+        }
+    }
+    for candidate in &candidates {
+        if data.fileset.verify_case(candidate, token) {
+            return;
+        }
+    }
+    if !name.starts_with("gfx/") {
+        let filename = icon_filename(name);
+        for folder in kind.folders() {
+            for variant in SIZE_VARIANTS {
+                let path = format!("{folder}/{variant}/{filename}");
+                if data.fileset.exists(&path) {
+                    let msg = format!("icon `{name}` is only in {path}");
+                    let info = format!("the game looks for it in {}", candidates.join(", "));
+                    warn_info(token, ErrorKey::MissingFile, &msg, &info);
+                    return;
+                }
+            }
+        }
+    }
+    if candidates.len() == 1 {
+        let msg = format!("file {} does not exist", candidates[0]);
+        error(token, ErrorKey::MissingFile, &msg);
+    } else {
+        let msg = format!("icon file for `{name}` does not exist");
+        let info = format!("looked for {}", candidates.join(", "));
+        error_info(token, ErrorKey::MissingFile, &msg, &info);
+    }
+}
+
+/// Validate an `icon` field that is either the icon's name or a block that picks it by
+/// trigger, like `icon = { trigger = { ... } icon = name }`. The field can be repeated for
+/// several of those blocks.
+pub fn validate_icon_bv(
+    kind: IconKind,
+    bv: &BlockOrValue,
+    data: &Everything,
+    sc: &mut ScopeContext,
+) {
+    match bv {
+        BlockOrValue::Token(token) => verify_icon(kind, token.as_str(), token, data),
+        BlockOrValue::Block(block) => {
+            let mut vd = Validator::new(block, data);
+            vd.field_validated_block("trigger", |b, data| {
+                validate_normal_trigger(b, data, sc, false);
+            });
+            vd.req_field("icon");
+            if let Some(icon) = vd.field_value("icon") {
+                verify_icon(kind, icon.as_str(), icon, data);
+            }
+        }
+    }
+}
+
+/// Check the magic bytes at the start of the file. An image in another format with a `.dds`
+/// name only works with some graphics cards.
+fn verify_dds(fullpath: &Path, path: &str, token: &Token) {
+    let is_dds = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("dds"));
+    if !is_dds {
+        return;
+    }
+    let mut magic = [0u8; 4];
+    let read = File::open(fullpath).and_then(|mut f| f.read_exact(&mut magic));
+    if read.is_err() || &magic == b"DDS " {
+        return;
+    }
+    let msg = format!("{path} is not a DDS file");
+    let info = if &magic == b"\x89PNG" {
+        "it is a PNG image with a .dds name, which some graphics cards can't show"
+    } else {
+        "the game needs icons in DDS format"
+    };
+    error_info(token, ErrorKey::ImageFormat, &msg, info);
+}
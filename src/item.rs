@@ -1,10 +1,14 @@
 use std::fmt::{Display, Formatter};
+use strum_macros::EnumIter;
 
 /// "items" are all the things that can be looked up in string-indexed databases.
 /// There is some overlap with scopes, but the difference is that scopes are runtime values
 /// while items are always strings.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter)]
 pub enum Item {
+    Accessory,
+    Achievement,
+    Activity,
     Amenity,
     Artifact,
     ArtifactCategory,
@@ -13,44 +17,59 @@ pub enum Item {
     ArtifactModifier,
     ArtifactRarity,
     ArtifactSlot,
+    ArtifactVisual,
+    BookmarkPortrait,
     Building,
     BuildingFlag,
     BuildingGfx,
     CasusBelli,
     Catalyst,
     Character,
+    CharacterTemplate,
     ClothingGfx,
     CoaGfx,
+    CombatEffect,
+    CombatPhaseEvent,
     CouncilPosition,
     CouncilTask,
     CourtPosition,
     CourtPositionCategory,
+    CourtSceneArtifactSlot,
+    CourtSceneCamera,
+    CourtSceneCharacterSlot,
+    CourtSceneSetting,
     CourtType,
     Culture,
     CultureEra,
     CultureParameter,
     CulturePillar,
     CultureTradition,
+    DangerType,
     Decision,
+    Dna,
     Doctrine,
     DoctrineParameter,
     Dynasty,
     DynastyLegacy,
     DynastyPerk,
     EducationFocus,
+    Entity,
     Event,
     Faction,
     Faith,
     FaithIcon,
     File,
     GameConcept,
+    Gene,
     Government,
     GraphicalFaith,
     Holding,
+    HolyOrder,
     HolySite,
     HolySiteFlag,
     Hook,
     House,
+    ImportantAction,
     Innovation,
     InnovationFlag,
     Inspiration,
@@ -62,11 +81,16 @@ pub enum Item {
     Localization,
     MenAtArms,
     MenAtArmsBase,
+    MercenaryCompany,
     Modifier,
     Music,
     NameList,
+    NamedColor,
     Nickname,
+    OnAction,
     Perk,
+    PointOfInterestType,
+    PoolSelector,
     PrisonType,
     Province,
     Region,
@@ -76,6 +100,8 @@ pub enum Item {
     Scheme,
     ScriptedEffect,
     ScriptedList,
+    ScriptedModifier,
+    ScriptedRule,
     ScriptedTrigger,
     ScriptValue,
     Secret,
@@ -93,16 +119,24 @@ pub enum Item {
     TitleLawFlag,
     Tradition,
     Trait,
+    TraitFlag,
+    TravelOption,
     UnitGfx,
+    VassalContractFlag,
     VassalObligation,
+    VassalObligationLevel,
 }
 
 use crate::item::Item::*;
 
 impl Item {
+    #[allow(clippy::too_many_lines)]
     pub fn path(self) -> &'static str {
         #[allow(clippy::match_same_arms)]
         match self {
+            Accessory => "gfx/portraits/accessories/",
+            Achievement => "common/achievements/",
+            Activity => "common/activities/",
             Amenity => "common/court_amenities/",
             Artifact => "common/artifacts/types",
             ArtifactCategory => "common/artifacts/",
@@ -111,44 +145,59 @@ impl Item {
             ArtifactModifier => "common/artifacts/",
             ArtifactRarity => "common/artifacts/",
             ArtifactSlot => "common/artifacts/",
+            ArtifactVisual => "common/artifacts/visuals/",
+            BookmarkPortrait => "common/bookmark_portraits/",
             Building => "common/buildings/",
             BuildingFlag => "common/buildings/",
             BuildingGfx => "common/culture/cultures/",
             CasusBelli => "common/casus_belli_types",
             Catalyst => "common/struggle/catalysts/",
             Character => "history/characters/",
+            CharacterTemplate => "common/scripted_character_templates/",
             ClothingGfx => "common/culture/cultures/",
             CoaGfx => "common/culture/cultures/",
+            CombatEffect => "common/combat_effects/",
+            CombatPhaseEvent => "common/combat_phase_events/",
             CouncilPosition => "common/council_positions/",
             CouncilTask => "common/council_tasks/",
             CourtPosition => "common/court_positions/types/",
             CourtPositionCategory => "common/court_positions/categories/",
+            CourtSceneArtifactSlot => "gfx/court_scene/artifact_slots/",
+            CourtSceneCamera => "gfx/court_scene/camera_settings/",
+            CourtSceneCharacterSlot => "gfx/court_scene/character_slots/",
+            CourtSceneSetting => "gfx/court_scene/scene_settings/",
             CourtType => "common/court_types/",
             Culture => "common/culture/cultures/",
             CultureEra => "common/culture/eras/",
             CultureParameter => "common/culture/cultures/",
             CulturePillar => "common/culture/pillars/",
             CultureTradition => "common/culture/traditions/",
+            DangerType => "common/travel/danger_types/",
             Decision => "common/decisions/",
+            Dna => "common/dna_data/",
             Doctrine => "common/religion/doctrines/",
             DoctrineParameter => "common/religion/doctrines/",
             Dynasty => "common/dynasties/",
             DynastyLegacy => "common/dynasty_legacies/",
             DynastyPerk => "common/dynasty_perks/",
             EducationFocus => "common/focuses/",
+            Entity => "gfx/models/",
             Event => "events/",
             Faith => "common/religion/religions/",
             FaithIcon => "common/religion/religions/",
             Faction => "common/factions/",
             File => "",
             GameConcept => "common/game_concepts/",
+            Gene => "common/genes/",
             Government => "common/governments/",
             GraphicalFaith => "common/religion/religions/",
             Holding => "",
+            HolyOrder => "common/holy_orders/",
             HolySite => "common/religion/holy_sites/",
             HolySiteFlag => "common/religion/holy_sites/",
             Hook => "common/hook_types/",
             House => "common/dynasty_houses/",
+            ImportantAction => "common/important_actions/",
             Innovation => "common/culture/innovations/",
             InnovationFlag => "common/culture/innovations/",
             Inspiration => "common/inspirations/",
@@ -160,11 +209,16 @@ impl Item {
             Localization => "localization/",
             MenAtArms => "common/men_at_arms_types/",
             MenAtArmsBase => "common/men_at_arms_types/",
+            MercenaryCompany => "common/mercenary_companies/",
             Modifier => "common/modifiers/",
             Music => "music/",
             NameList => "common/culture/name_lists/",
+            NamedColor => "common/named_colors/",
             Nickname => "common/nicknames/",
+            OnAction => "common/on_action/",
             Perk => "common/lifestyle_perks/",
+            PointOfInterestType => "common/travel/point_of_interest_types/",
+            PoolSelector => "common/pool_character_selectors/",
             PrisonType => "",
             Province => "map_data/definition.csv",
             Region => "map_data/geographical_regions/",
@@ -174,6 +228,8 @@ impl Item {
             Scheme => "common/schemes/",
             ScriptedEffect => "common/scripted_effects/",
             ScriptedList => "common/scripted_lists/",
+            ScriptedModifier => "common/scripted_modifiers/",
+            ScriptedRule => "common/scripted_rules/",
             ScriptedTrigger => "common/scripted_triggers/",
             ScriptValue => "common/script_values/",
             Secret => "common/secret_types/",
@@ -191,15 +247,23 @@ impl Item {
             TitleLawFlag => "common/laws/",
             Tradition => "common/culture/traditions/",
             Trait => "common/traits/",
+            TraitFlag => "common/traits/",
+            TravelOption => "common/travel/travel_options/",
             UnitGfx => "common/culture/cultures/",
+            VassalContractFlag => "common/vassal_contracts/",
             VassalObligation => "common/vassal_contracts/",
+            VassalObligationLevel => "common/vassal_contracts/",
         }
     }
 }
 
 impl Display for Item {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
         match *self {
+            Accessory => write!(f, "accessory"),
+            Achievement => write!(f, "achievement"),
+            Activity => write!(f, "activity"),
             Amenity => write!(f, "amenity"),
             Artifact => write!(f, "artifact"),
             ArtifactCategory => write!(f, "artifact category"),
@@ -208,44 +272,59 @@ impl Display for Item {
             ArtifactModifier => write!(f, "artifact modifier"),
             ArtifactRarity => write!(f, "artifact rarity"),
             ArtifactSlot => write!(f, "artifact slot"),
+            ArtifactVisual => write!(f, "artifact visual"),
+            BookmarkPortrait => write!(f, "bookmark portrait"),
             Building => write!(f, "building"),
             BuildingFlag => write!(f, "building flag"),
             BuildingGfx => write!(f, "building gfx"),
             CasusBelli => write!(f, "casus belli"),
             Catalyst => write!(f, "catalyst"),
             Character => write!(f, "character"),
+            CharacterTemplate => write!(f, "character template"),
             ClothingGfx => write!(f, "clothing gfx"),
             CoaGfx => write!(f, "coa gfx"),
+            CombatEffect => write!(f, "combat effect"),
+            CombatPhaseEvent => write!(f, "combat phase event"),
             CouncilPosition => write!(f, "council position"),
             CouncilTask => write!(f, "council task"),
             CourtPosition => write!(f, "court position"),
             CourtPositionCategory => write!(f, "court position category"),
+            CourtSceneArtifactSlot => write!(f, "court scene artifact slot"),
+            CourtSceneCamera => write!(f, "court scene camera"),
+            CourtSceneCharacterSlot => write!(f, "court scene character slot"),
+            CourtSceneSetting => write!(f, "court scene"),
             CourtType => write!(f, "court type"),
             Culture => write!(f, "culture"),
             CultureEra => write!(f, "culture era"),
             CultureParameter => write!(f, "culture parameter"),
             CulturePillar => write!(f, "culture pillar"),
             CultureTradition => write!(f, "culture tradition"),
+            DangerType => write!(f, "danger type"),
             Decision => write!(f, "decision"),
+            Dna => write!(f, "dna"),
             Doctrine => write!(f, "doctrine"),
             DoctrineParameter => write!(f, "doctrine parameter"),
             Dynasty => write!(f, "dynasty"),
             DynastyLegacy => write!(f, "dynasty legacy"),
             DynastyPerk => write!(f, "dynasty perk"),
             EducationFocus => write!(f, "education focus"),
+            Entity => write!(f, "entity"),
             Event => write!(f, "event"),
             Faction => write!(f, "faction"),
             Faith => write!(f, "faith"),
             FaithIcon => write!(f, "faith icon"),
             File => write!(f, "file"),
             GameConcept => write!(f, "game concept"),
+            Gene => write!(f, "gene"),
             Government => write!(f, "government"),
             GraphicalFaith => write!(f, "graphical faith"),
             Holding => write!(f, "holding"),
+            HolyOrder => write!(f, "holy order"),
             HolySite => write!(f, "holy site"),
             HolySiteFlag => write!(f, "holy site flag"),
             Hook => write!(f, "hook"),
             House => write!(f, "house"),
+            ImportantAction => write!(f, "important action"),
             Innovation => write!(f, "innovation"),
             InnovationFlag => write!(f, "innovation flag"),
             Inspiration => write!(f, "inspiration"),
@@ -257,11 +336,16 @@ impl Display for Item {
             Localization => write!(f, "localization"),
             MenAtArms => write!(f, "men at arms"),
             MenAtArmsBase => write!(f, "men at arms base"),
+            MercenaryCompany => write!(f, "mercenary company"),
             Modifier => write!(f, "modifier"),
             Music => write!(f, "music"),
             NameList => write!(f, "name list"),
+            NamedColor => write!(f, "named color"),
             Nickname => write!(f, "nickname"),
+            OnAction => write!(f, "on_action"),
             Perk => write!(f, "perk"),
+            PointOfInterestType => write!(f, "point of interest type"),
+            PoolSelector => write!(f, "pool character selector"),
             PrisonType => write!(f, "prison type"),
             Province => write!(f, "province"),
             Region => write!(f, "region"),
@@ -271,6 +355,8 @@ impl Display for Item {
             Scheme => write!(f, "scheme"),
             ScriptedEffect => write!(f, "effect"),
             ScriptedList => write!(f, "scripted list"),
+            ScriptedModifier => write!(f, "scripted modifier"),
+            ScriptedRule => write!(f, "scripted rule"),
             ScriptedTrigger => write!(f, "trigger"),
             ScriptValue => write!(f, "script value"),
             Secret => write!(f, "secret"),
@@ -288,8 +374,12 @@ impl Display for Item {
             TitleLawFlag => write!(f, "title law flag"),
             Tradition => write!(f, "tradition"),
             Trait => write!(f, "trait"),
+            TraitFlag => write!(f, "trait flag"),
+            TravelOption => write!(f, "travel option"),
             UnitGfx => write!(f, "unit gfx"),
+            VassalContractFlag => write!(f, "vassal contract flag"),
             VassalObligation => write!(f, "vassal obligation"),
+            VassalObligationLevel => write!(f, "vassal obligation level"),
         }
     }
 }
@@ -0,0 +1,141 @@
+use fnv::FnvHashMap;
+use std::cell::OnceCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::block::Block;
+use crate::fileset::{FileEntry, FileKind};
+use crate::pdxfile::PdxFile;
+use crate::token::Token;
+
+/// A single item definition in one of the databases in `Everything`.
+pub trait ItemSource {
+    /// The token where the item is defined. Its text is the item's key.
+    fn key(&self) -> &Token;
+
+    /// The item's definition block, if it has one.
+    fn block(&self) -> Option<&Block> {
+        None
+    }
+}
+
+/// A database that holds the items of one `Item` type.
+/// This lets code look up and enumerate items without knowing which database holds them.
+pub trait ItemDb {
+    fn get_item(&self, key: &str) -> Option<&dyn ItemSource>;
+
+    fn iter_items(&self) -> Box<dyn Iterator<Item = &dyn ItemSource> + '_>;
+}
+
+/// Convenience function for implementing `ItemDb` on a map of item definitions.
+pub fn as_source<T: ItemSource>(item: &T) -> &dyn ItemSource {
+    item
+}
+
+/// One place where an item is defined, as found by `Everything::item_definitions`.
+#[derive(Clone, Debug)]
+pub struct ItemDefinition {
+    /// The token where the item is defined
+    pub key: Token,
+    /// Whether this is the definition the game ends up using
+    pub used: bool,
+    /// If the whole file was skipped because a later file has the same path,
+    /// this is the kind of that later file.
+    pub replaced_by: Option<FileKind>,
+}
+
+/// A game file whose definitions were only indexed. It is parsed the first time one of its
+/// items is needed, and then kept for the other items in it.
+#[derive(Debug)]
+struct IndexedFile {
+    entry: FileEntry,
+    fullpath: PathBuf,
+    block: OnceCell<Option<Block>>,
+}
+
+impl IndexedFile {
+    fn block(&self) -> Option<&Block> {
+        self.block
+            .get_or_init(|| PdxFile::read(&self.entry, &self.fullpath))
+            .as_ref()
+    }
+}
+
+/// An item from a game file that was only indexed. Its block is parsed on demand.
+#[derive(Clone, Debug)]
+pub struct IndexedItem {
+    key: Token,
+    file: Rc<IndexedFile>,
+    block: OnceCell<Option<Block>>,
+}
+
+impl ItemSource for IndexedItem {
+    fn key(&self) -> &Token {
+        &self.key
+    }
+
+    fn block(&self) -> Option<&Block> {
+        self.block
+            .get_or_init(|| {
+                self.file
+                    .block()?
+                    .iter_pure_definitions()
+                    .find(|(key, _)| {
+                        key.loc.line == self.key.loc.line && key.loc.column == self.key.loc.column
+                    })
+                    .map(|(_, block)| block.clone())
+            })
+            .as_ref()
+    }
+}
+
+/// The items of one type from the game files, found with a quick scan instead of parsing.
+/// Databases use this when the game's items only have to be looked up, not validated,
+/// which is when problems in the game files are not being reported anyway.
+#[derive(Clone, Debug, Default)]
+pub struct ItemIndex {
+    /// Whether the game files are indexed instead of loaded
+    enabled: bool,
+    items: FnvHashMap<String, IndexedItem>,
+}
+
+impl ItemIndex {
+    pub fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// Whether this file should be indexed instead of loaded.
+    pub fn wants(&self, entry: &FileEntry) -> bool {
+        self.enabled && entry.kind() != FileKind::Mod
+    }
+
+    pub fn index_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if let Some(scanned) = PdxFile::scan(entry, fullpath) {
+            let file = Rc::new(IndexedFile {
+                entry: entry.clone(),
+                fullpath: fullpath.to_path_buf(),
+                block: OnceCell::new(),
+            });
+            for item in scanned {
+                let indexed = IndexedItem {
+                    key: item.key,
+                    file: Rc::clone(&file),
+                    block: OnceCell::new(),
+                };
+                self.items.insert(indexed.key.to_string(), indexed);
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&IndexedItem> {
+        self.items.get(key)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.items.contains_key(key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &IndexedItem> {
+        self.items.values()
+    }
+}
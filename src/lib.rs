@@ -19,26 +19,38 @@
 // warning about struct fields.
 #![allow(dead_code)]
 
+pub mod audit;
 pub mod errorkey;
 pub mod errors;
 pub mod everything;
+pub mod html;
 pub mod modfile;
+pub mod query;
 
 mod block;
+mod calls;
 mod context;
 mod data;
 mod desc;
 mod effect;
 mod fileset;
+mod flags;
 mod helpers;
+mod icons;
 mod item;
+mod itemdb;
+mod limits;
 mod macrocache;
 mod modif;
 mod parse;
 mod pdxfile;
+mod profiles;
+mod references;
 mod rivers;
+mod schema;
 mod scopes;
 mod tables;
 mod token;
 mod trigger;
 mod validate;
+mod version;
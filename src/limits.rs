@@ -0,0 +1,63 @@
+use crate::block::Block;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, warn};
+
+/// Thresholds for the checks on script that is valid but runs into the game engine's
+/// practical limits. They can be changed in a `limits` block in the config file.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// How deeply effects and triggers can be nested before their tooltips get cut off
+    pub nesting: usize,
+    /// How many options fit in the window of an event that isn't fullscreen
+    pub event_options: usize,
+    /// How many entries a `random_list` can have before it gets unwieldy
+    pub random_list_entries: usize,
+    /// How many characters a localization value can have before the game cuts it off
+    pub localization_length: usize,
+    /// How many fields a scripted effect or trigger can expand to, with all its calls
+    /// filled in, before building its tooltips gets slow
+    pub scripted_expansion: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            nesting: 15,
+            event_options: 5,
+            random_list_entries: 100,
+            localization_length: 2000,
+            scripted_expansion: 10000,
+        }
+    }
+}
+
+impl Limits {
+    pub fn config(&mut self, config: &Block) {
+        let Some(block) = config.get_field_block("limits") else {
+            return;
+        };
+        for (key, _, bv) in block.iter_items() {
+            let Some(key) = key else {
+                warn(bv, ErrorKey::Config, "expected `name = number`");
+                continue;
+            };
+            let field = match key.as_str() {
+                "nesting" => &mut self.nesting,
+                "event_options" => &mut self.event_options,
+                "random_list_entries" => &mut self.random_list_entries,
+                "localization_length" => &mut self.localization_length,
+                "scripted_expansion" => &mut self.scripted_expansion,
+                _ => {
+                    warn(key, ErrorKey::Config, "unknown limit");
+                    continue;
+                }
+            };
+            if let Some(token) = bv.expect_value() {
+                match token.as_str().parse() {
+                    Ok(value) => *field = value,
+                    Err(_) => error(token, ErrorKey::Config, "expected a whole number"),
+                }
+            }
+        }
+    }
+}
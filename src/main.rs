@@ -1,17 +1,28 @@
 use anyhow::{bail, Result};
 use clap::Parser;
 use home::home_dir;
-use std::fs::read_to_string;
-use std::path::PathBuf;
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 
 #[cfg(windows)]
 use winreg::enums::HKEY_LOCAL_MACHINE;
 #[cfg(windows)]
 use winreg::RegKey;
 
-use ck3_tiger::errors::{minimum_level, set_mod_root, set_vanilla_root, show_vanilla, ErrorLevel};
+use ck3_tiger::audit::audit_tables;
+use ck3_tiger::errorkey::ErrorKey;
+use ck3_tiger::errors::{
+    collect_reports, count_at_level, hide_codes, log_policy_summary, log_summary, minimum_level,
+    pause_logging, set_base_mod_root, set_vanilla_root, show_vanilla, start_mod_run, take_reports,
+    worst_level, ErrorLevel, LoggedReport,
+};
 use ck3_tiger::everything::Everything;
+use ck3_tiger::html::html_report;
 use ck3_tiger::modfile::ModFile;
+use ck3_tiger::query::{dump_filelist, find_references, list_items, parse_find, where_item};
 
 /// Steam's code for Crusader Kings 3
 const CK3_APP_ID: &str = "1158310";
@@ -28,10 +39,25 @@ const CK3_GAME_DIR: &str = "steamapps/common/Crusader Kings III/game";
 /// A file that should be present if this is a CK3 game directory
 const CK3_SIGNATURE_FILE: &str = "events/witch_events.txt";
 
+/// Exit codes for when the checks found problems. Exit code 1 means the checks couldn't be done.
+const EXIT_WARNINGS: i32 = 2;
+const EXIT_ERRORS: i32 = 3;
+
 #[derive(Parser)]
 struct Cli {
-    /// Path to .mod file of mod to check.
-    modpath: PathBuf,
+    /// Path to .mod file of mod to check, or to the mod's directory, or the number of a
+    /// Steam workshop mod. Give more than one to check several mods in one run, which reads
+    /// the CK3 game files only once.
+    modpath: Vec<PathBuf>,
+    /// Path to .mod file or directory of a mod that the checked mods are built on, like a
+    /// total conversion for its submods. It is loaded between the game and the checked mod,
+    /// and reports about its own files are hidden like the game's.
+    #[clap(long, value_name = "PATH")]
+    base_mod: Option<PathBuf>,
+    /// Check only the base CK3 script code, without a mod. Implies --show-vanilla.
+    /// Ends with a summary of the error counts, for comparing runs.
+    #[clap(long)]
+    vanilla_only: bool,
     /// Path to CK3 game directory.
     #[clap(long)]
     ck3: Option<PathBuf>,
@@ -41,9 +67,71 @@ struct Cli {
     /// Show advice in addition to warnings and errors
     #[clap(long)]
     advice: bool,
-    /// Do checks specific to the Princes of Darkness mod
+    /// Do the extra checks for a particular mod. Can be given more than once.
+    #[clap(long, value_name = "PROFILE")]
+    profile: Vec<String>,
+    /// Do checks specific to the Princes of Darkness mod. Same as `--profile pod`.
     #[clap(long)]
     pod: bool,
+    /// Don't follow symlinks (or junctions) in the mod and game directories.
+    #[clap(long)]
+    no_follow_symlinks: bool,
+    /// Leave out the codes, like `(missing-item)`, from the reports.
+    #[clap(long)]
+    no_codes: bool,
+    /// Explain what the reports with this code, like `missing-item`, are about, and exit.
+    #[clap(long, value_name = "CODE")]
+    explain: Option<String>,
+    /// Show where an item, given as `type:key` like `decision:my_decision`, is defined
+    /// in the game, the DLC, the base mod and the mod, and which definition is used. Skips the checks.
+    /// Can be given more than once.
+    #[clap(long = "where", value_name = "TYPE:KEY")]
+    where_item: Vec<String>,
+    /// List the items of a type, like `building`, with the files they come from.
+    /// Skips the checks. Can be given more than once.
+    #[clap(long, value_name = "TYPE")]
+    list: Vec<String>,
+    /// Show every place that refers to an item, given as `type:key` like `trait:brave`,
+    /// including from localization and history. Skips the other checks.
+    /// Can be given more than once.
+    #[clap(long, value_name = "TYPE:KEY")]
+    find: Vec<String>,
+    /// Like `--find`, but for all the items of the type whose keys match a regular
+    /// expression, like `trait:^education_`.
+    #[clap(long, value_name = "TYPE:REGEX")]
+    find_regex: Vec<String>,
+    /// For developers of this validator: compare its effect, trigger and link tables against
+    /// the effects.log, triggers.log and event_targets.log files in this directory.
+    /// The game writes them with the `script_docs` console command. Skips the checks.
+    #[clap(long, value_name = "DIR")]
+    audit_tables: Option<PathBuf>,
+    /// List the files of the game, the DLC, the base mod and the mod in the order the game loads them,
+    /// marking the ones it skips because they are overridden or left out by a `replace_path`.
+    /// Give a path like `common/decisions` to list only the files under it. Skips the checks.
+    #[clap(long, value_name = "PATH")]
+    dump_filelist: Option<Option<String>>,
+    /// Check the mod as a total conversion with its own map, leaving out the game's landed
+    /// titles and history. Mods with `replace_path = "common/landed_titles"` get this anyway.
+    #[clap(long)]
+    tc: bool,
+    /// Pretend this DLC is not installed. Can be given more than once.
+    #[clap(long, value_name = "DLC")]
+    no_dlc: Vec<String>,
+    /// The language that localization keys must exist in. Defaults to english if the mod
+    /// has it, and otherwise to the first language the mod has.
+    #[clap(long, value_name = "LANGUAGE")]
+    language: Option<String>,
+    /// After the checks, report how many keys of the main language are missing from these
+    /// languages, per file. Give them separated by commas, like `german,french`.
+    #[clap(long, value_name = "LANGUAGES", use_value_delimiter = true)]
+    check_languages: Vec<String>,
+    /// List the missing keys in the --check-languages report, not just how many there are.
+    #[clap(long)]
+    translation_details: bool,
+    /// Also write the reports to this file as a web page, which can filter them by level,
+    /// code and path.
+    #[clap(long, value_name = "FILE")]
+    html: Option<PathBuf>,
 }
 
 fn find_steamapps_directory() -> Option<PathBuf> {
@@ -98,9 +186,40 @@ fn find_ck3_directory() -> Option<PathBuf> {
     None
 }
 
+fn explain(code: &str) -> Result<()> {
+    let key = match ErrorKey::from_str(code) {
+        Ok(key) => key,
+        Err(_) => {
+            let codes = ErrorKey::iter().map(ErrorKey::as_str).collect::<Vec<_>>();
+            bail!(
+                "Unknown code `{}`. The codes are: {}",
+                code,
+                codes.join(", ")
+            );
+        }
+    };
+    println!("{}: {}", key.as_str(), key.description());
+    println!();
+    println!("{}", key.explanation());
+    println!();
+    println!("Example:");
+    for line in key.example().lines() {
+        println!("    {}", line);
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     let mut args = Cli::parse();
 
+    if let Some(code) = &args.explain {
+        return explain(code);
+    }
+
+    if args.modpath.is_empty() && !args.vanilla_only && args.audit_tables.is_none() {
+        bail!("Please supply the path to the mod, or use --vanilla-only.");
+    }
+
     eprintln!("This validator was made for Crusader Kings version 1.7.0.");
     eprintln!("If you are using a newer version of Crusader Kings, it may be inaccurate.");
     eprintln!("!! Currently it's inaccurate anyway because it's in alpha state.");
@@ -130,33 +249,216 @@ fn main() -> Result<()> {
 
     set_vanilla_root(args.ck3.as_ref().unwrap().clone());
 
-    if args.show_vanilla {
+    if args.show_vanilla || args.vanilla_only {
         show_vanilla(true);
     }
 
+    if args.no_codes {
+        hide_codes(true);
+    }
+
     if !args.advice {
         minimum_level(ErrorLevel::Info);
     }
 
-    if args.modpath.is_dir() {
-        args.modpath.push("descriptor.mod");
+    let mut modfiles = Vec::new();
+    let mut base_modfile = None;
+    if !args.vanilla_only {
+        let steamapps = find_steamapps_directory();
+        if let Some(path) = &args.base_mod {
+            let modfile = ModFile::locate(path, steamapps.as_deref())?;
+            let path = modfile.locate_modpath()?;
+            eprintln!("Using base mod directory: {}", path.display());
+            set_base_mod_root(path);
+            base_modfile = Some(modfile);
+        }
+        for path in &args.modpath {
+            let modfile = ModFile::locate(path, steamapps.as_deref())?;
+            let path = modfile.locate_modpath()?;
+            eprintln!("Using mod directory: {}", path.display());
+            modfiles.push(modfile);
+        }
+    }
+
+    let ck3 = args.ck3.clone().unwrap();
+    for dlc in &args.no_dlc {
+        if !ck3.join("dlc").join(dlc).is_dir() {
+            eprintln!(
+                "Warning: --no-dlc {}: no such directory under {}",
+                dlc,
+                ck3.join("dlc").display()
+            );
+        }
+    }
+
+    if args.pod {
+        args.profile.push("pod".to_string());
+    }
+
+    let querying = !args.where_item.is_empty()
+        || !args.list.is_empty()
+        || !args.find.is_empty()
+        || !args.find_regex.is_empty()
+        || args.audit_tables.is_some()
+        || args.dump_filelist.is_some();
+    if querying {
+        // Only the answers are wanted, not the reports
+        pause_logging();
+    }
+    if args.html.is_some() {
+        collect_reports(true);
+    }
+
+    if modfiles.is_empty() {
+        check(&args, &ck3, None, None)?;
+        if !querying {
+            log_summary();
+        }
+        if let Some(path) = &args.html {
+            write_html(path, &[("CK3".to_string(), take_reports())])?;
+        }
+        return Ok(());
+    }
+
+    let several = modfiles.len() > 1;
+    if several {
+        Everything::cache_game_files();
+    }
+    let mut results = Vec::new();
+    let mut html_runs = Vec::new();
+    for modfile in &modfiles {
+        let name = modfile.name().map_or_else(
+            || modfile.modpath().display().to_string(),
+            ToString::to_string,
+        );
+        if several {
+            println!("===== {} =====", name);
+            println!();
+        }
+        start_mod_run(modfile.modpath());
+        check(&args, &ck3, base_modfile.as_ref(), Some(modfile))?;
+        if !querying {
+            log_policy_summary();
+        }
+        if args.html.is_some() {
+            html_runs.push((name.clone(), take_reports()));
+        }
+        results.push((name, worst_level(), mod_counts()));
+    }
+    if let Some(path) = &args.html {
+        write_html(path, &html_runs)?;
+    }
+
+    if several && !querying {
+        println!("===== Summary =====");
+        for (name, _, counts) in &results {
+            println!("{}: {}", name, counts);
+        }
     }
-    let modfile = ModFile::read(&args.modpath)?;
-    let modpath = modfile.modpath();
-    if !modpath.exists() {
-        eprintln!("Looking for mod in {}", modpath.display());
-        bail!("Cannot find mod directory. Please make sure the .mod file is correct.");
+
+    match results.iter().filter_map(|(_, worst, _)| *worst).max() {
+        Some(ErrorLevel::Error) => exit(EXIT_ERRORS),
+        Some(ErrorLevel::Warning) => exit(EXIT_WARNINGS),
+        _ => Ok(()),
     }
-    eprintln!("Using mod directory: {}", modpath.display());
-    set_mod_root(modpath.clone());
+}
 
-    let mut everything = Everything::new(&args.ck3.unwrap(), &modpath, modfile.replace_paths())?;
+/// Load the game files, the base mod and the mod, if any, and check them or answer the
+/// queries about them.
+fn check(
+    args: &Cli,
+    ck3: &Path,
+    base_modfile: Option<&ModFile>,
+    modfile: Option<&ModFile>,
+) -> Result<()> {
+    let mut everything = Everything::new_with_base_mod(
+        ck3,
+        base_modfile.map(|base| (base.modpath(), base.replace_paths())),
+        modfile.map(ModFile::modpath).as_deref(),
+        modfile.map(ModFile::replace_paths).unwrap_or_default(),
+        &args.no_dlc,
+        !args.no_follow_symlinks,
+    )?;
+    for name in &args.profile {
+        everything.add_profile(name)?;
+    }
+    if let Some(lang) = &args.language {
+        everything.set_language(lang)?;
+    }
+    for lang in &args.check_languages {
+        everything.add_translation(lang)?;
+    }
+    if modfile.is_some() {
+        if args.tc {
+            everything.set_total_conversion();
+        }
+        if everything.is_total_conversion() {
+            eprintln!("Checking as a total conversion: the game's landed titles and history are left out.");
+        }
+    }
+    if let Some(prefix) = &args.dump_filelist {
+        print!("{}", dump_filelist(&everything, prefix.as_deref()));
+        return Ok(());
+    }
+    for query in &args.find {
+        everything.references.add_query(parse_find(query, false)?);
+    }
+    for query in &args.find_regex {
+        everything.references.add_query(parse_find(query, true)?);
+    }
     everything.load_all();
+    if let Some(dir) = &args.audit_tables {
+        print!("{}", audit_tables(&everything, dir)?);
+        return Ok(());
+    }
+    if everything.references.is_active() {
+        // The references are seen while validating
+        everything.validate_all();
+        print!("{}", find_references(&everything));
+        return Ok(());
+    }
+    if !args.where_item.is_empty() || !args.list.is_empty() {
+        for query in &args.where_item {
+            print!("{}", where_item(&everything, query)?);
+        }
+        for itype in &args.list {
+            print!("{}", list_items(&everything, itype)?);
+        }
+        return Ok(());
+    }
     everything.validate_all();
     everything.check_rivers();
-    if args.pod {
-        everything.check_pod();
+    if !args.check_languages.is_empty() {
+        print!(
+            "{}",
+            everything.missing_translations(args.translation_details)
+        );
     }
+    Ok(())
+}
 
+fn write_html(path: &Path, runs: &[(String, Vec<LoggedReport>)]) -> Result<()> {
+    if let Err(e) = write(path, html_report(runs)) {
+        bail!(
+            "Could not write the HTML report to {}: {}",
+            path.display(),
+            e
+        );
+    }
+    eprintln!("Wrote the HTML report to {}", path.display());
     Ok(())
 }
+
+/// Describe how many reports of each level were logged for the current mod.
+fn mod_counts() -> String {
+    [
+        (ErrorLevel::Error, "errors"),
+        (ErrorLevel::Warning, "warnings"),
+        (ErrorLevel::Info, "infos"),
+        (ErrorLevel::Advice, "advice"),
+    ]
+    .iter()
+    .map(|(level, name)| format!("{} {}", count_at_level(*level), name))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
@@ -1,12 +1,25 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use home::home_dir;
+use std::fs::read_dir;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use crate::block::Block;
 use crate::errorkey::ErrorKey;
-use crate::errors::{advice_info, error_info, warn};
+use crate::errors::{advice_info, error_info, info, warn};
 use crate::fileset::{FileEntry, FileKind};
 use crate::pdxfile::PdxFile;
-use crate::token::Token;
+use crate::token::{Loc, Token};
+
+/// Where Steam puts the workshop mods for Crusader Kings 3, under the steamapps directory
+const WORKSHOP_DIR: &str = "workshop/content/1158310";
+
+/// Where the game keeps the user's mods and settings, under the home directory. A relative
+/// `path` in a .mod file is relative to this directory.
+const USER_DIRS: &[&str] = &[
+    "Documents/Paradox Interactive/Crusader Kings III",
+    ".local/share/Paradox Interactive/Crusader Kings III",
+];
 
 #[derive(Clone, Debug)]
 #[allow(dead_code)] // remove when TODO are fixed
@@ -14,7 +27,7 @@ pub struct ModFile {
     block: Block,
     name: Option<Token>,
     path: Option<Token>,
-    // TODO: implement this in Fileset
+    archive: Option<Token>,
     replace_path: Vec<Token>,
     version: Option<Token>,
     // TODO: check that these are tags accepted by steam ?
@@ -31,6 +44,7 @@ fn validate_modfile(block: &Block) -> ModFile {
         block: block.clone(),
         name: block.get_field_value("name").cloned(),
         path: block.get_field_value("path").cloned(),
+        archive: block.get_field_value("archive").cloned(),
         replace_path: block.get_field_values("replace_path"),
         version: block.get_field_value("version").cloned(),
         tags: block.get_field_list("tags"),
@@ -77,34 +91,203 @@ impl ModFile {
         Ok(validate_modfile(&block))
     }
 
-    pub fn modpath(&self) -> PathBuf {
-        let mut dirpath = self
+    /// Find and read the .mod file for a mod given on the command line. The argument can be
+    /// a .mod file, a mod directory with a `descriptor.mod` in it, or the number of a Steam
+    /// workshop mod, which is looked for under `steamapps`.
+    pub fn locate(arg: &Path, steamapps: Option<&Path>) -> Result<Self> {
+        let mut pathname = arg.to_path_buf();
+        if !pathname.exists() && is_workshop_id(arg) {
+            match steamapps {
+                Some(steamapps) => pathname = steamapps.join(WORKSHOP_DIR).join(arg),
+                None => bail!(
+                    "{} looks like a Steam workshop mod, but the Steam directory was not found",
+                    arg.display()
+                ),
+            }
+            if !pathname.is_dir() {
+                bail!(
+                    "Steam workshop mod {} is not downloaded; looked in {}",
+                    arg.display(),
+                    pathname.display()
+                );
+            }
+        }
+        if pathname.is_dir() {
+            let descriptor = pathname.join("descriptor.mod");
+            if descriptor.is_file() {
+                pathname = descriptor;
+            } else {
+                // Some mods only have a .mod file named after the mod
+                let mut modfiles = mod_files_in(&pathname);
+                if modfiles.len() != 1 {
+                    bail!(
+                        "No descriptor.mod in mod directory {}, and {} other .mod files in it",
+                        arg.display(),
+                        if modfiles.is_empty() { "no" } else { "several" }
+                    );
+                }
+                pathname = modfiles.remove(0);
+                let loc = Loc::for_file(Rc::new(pathname.clone()), FileKind::Mod);
+                let msg = format!(
+                    "no descriptor.mod in mod directory {}, so using this .mod file instead",
+                    arg.display()
+                );
+                info(loc, ErrorKey::Packaging, &msg);
+            }
+        }
+        Self::read(&pathname)
+    }
+
+    /// Whether this is the `descriptor.mod` inside a mod directory, rather than the .mod
+    /// file that the launcher puts next to the mod directories
+    fn is_descriptor(&self) -> bool {
+        self.block.loc.pathname.ends_with("descriptor.mod")
+    }
+
+    /// The directory the .mod file is in
+    fn dirpath(&self) -> &Path {
+        let dirpath = self
             .block
             .loc
             .pathname
             .parent()
             .unwrap_or_else(|| Path::new("."));
         if dirpath.components().count() == 0 {
-            dirpath = Path::new(".");
+            Path::new(".")
+        } else {
+            dirpath
         }
+    }
 
-        let modpath = if let Some(path) = &self.path {
-            dirpath.join(path.as_str())
-        } else {
-            dirpath.to_path_buf()
+    /// The places where the directory named by the `path` field could be, in the order they
+    /// are tried. The path can use forward or backward slashes, and if it is relative it can
+    /// be relative to the .mod file or to the game's user directory that holds the `mod` folder.
+    fn path_candidates(&self) -> Vec<PathBuf> {
+        let path = match &self.path {
+            Some(path) => path.as_str().replace('\\', "/"),
+            None => return Vec::new(),
         };
+        let path = PathBuf::from(path.trim_end_matches('/'));
+        if path.is_absolute() {
+            return vec![path];
+        }
+        let dirpath = self.dirpath();
+        let mut candidates = vec![dirpath.join(&path)];
+        // The launcher's .mod files are in the `mod` folder, with paths like `mod/name`
+        if let Some(parent) = dirpath.parent() {
+            candidates.push(parent.join(&path));
+        }
+        if let Some(home) = home_dir() {
+            for user_dir in USER_DIRS {
+                candidates.push(home.join(user_dir).join(&path));
+            }
+        }
+        candidates
+    }
 
-        if modpath.exists() {
-            modpath
-        } else {
-            dirpath.to_path_buf()
+    /// The directory named by the `path` field, if it exists
+    fn path_dir(&self) -> Option<PathBuf> {
+        self.path_candidates()
+            .into_iter()
+            .find(|path| path.is_dir())
+    }
+
+    /// The directory next to the launcher's .mod file that has the same name as it, which is
+    /// where the launcher puts the mod when it creates the .mod file
+    fn sibling_dir(&self) -> Option<PathBuf> {
+        if self.is_descriptor() {
+            return None;
         }
+        let stem = self.block.loc.pathname.file_stem()?;
+        Some(self.dirpath().join(stem)).filter(|path| path.is_dir())
     }
 
-    pub fn replace_paths(&self) -> Vec<PathBuf> {
-        self.replace_path
-            .iter()
-            .map(|t| PathBuf::from(t.as_str()))
-            .collect()
+    pub fn modpath(&self) -> PathBuf {
+        self.path_dir()
+            .or_else(|| self.sibling_dir())
+            .unwrap_or_else(|| self.dirpath().to_path_buf())
+    }
+
+    /// Like `modpath`, but fail with an explanation if the mod's files can't be checked
+    /// where the .mod file says they are. The fallbacks it uses are reported as info.
+    pub fn locate_modpath(&self) -> Result<PathBuf> {
+        if let Some(modpath) = self.path_dir() {
+            return Ok(modpath);
+        }
+        if let Some(archive) = &self.archive {
+            bail!(
+                "The mod is packed in the archive {}. Archives are not supported; please extract it first.",
+                archive
+            );
+        }
+        if let Some(path) = &self.path {
+            // A descriptor.mod inside the mod directory often has a path that doesn't
+            // fit where the mod is now
+            if self.is_descriptor() {
+                let dirpath = self.dirpath().to_path_buf();
+                let msg = format!(
+                    "mod directory {} was not found, so using the directory of this descriptor.mod: {}",
+                    path,
+                    dirpath.display()
+                );
+                info(path, ErrorKey::Packaging, &msg);
+                return Ok(dirpath);
+            }
+            // The launcher's .mod file has a stale path, but the mod may still be in the
+            // directory next to it
+            if let Some(sibling) = self.sibling_dir() {
+                let msg = format!(
+                    "mod directory {} was not found, so using the directory next to this .mod file: {}",
+                    path,
+                    sibling.display()
+                );
+                info(path, ErrorKey::Packaging, &msg);
+                return Ok(sibling);
+            }
+            let mut tried = self
+                .path_candidates()
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>();
+            if let Some(stem) = self.block.loc.pathname.file_stem() {
+                tried.push(self.dirpath().join(stem).display().to_string());
+            }
+            bail!(
+                "Cannot find mod directory {}, from `path` on line {} of {}. Please make sure the .mod file is correct.\nLooked in: {}",
+                path,
+                path.loc.line,
+                path.loc.pathname.display(),
+                tried.join(", ")
+            );
+        }
+        Ok(self.dirpath().to_path_buf())
     }
+
+    /// The name of the mod as shown in the launcher
+    pub fn name(&self) -> Option<&Token> {
+        self.name.as_ref()
+    }
+
+    pub fn replace_paths(&self) -> Vec<Token> {
+        self.replace_path.clone()
+    }
+}
+
+/// Steam workshop mods are known by their number
+fn is_workshop_id(arg: &Path) -> bool {
+    arg.to_str()
+        .is_some_and(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// The .mod files directly inside a directory
+fn mod_files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut modfiles = match read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "mod"))
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    modfiles.sort();
+    modfiles
 }
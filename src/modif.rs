@@ -21,6 +21,7 @@ bitflags! {
         const Terrain = 0x08;
         const Culture = 0x10;
         const Scheme = 0x20;
+        const TravelPlan = 0x40;
     }
 }
 
@@ -54,6 +55,9 @@ impl Display for ModifKinds {
         if self.contains(ModifKinds::Scheme) {
             vec.push("scheme");
         }
+        if self.contains(ModifKinds::TravelPlan) {
+            vec.push("travel plan");
+        }
         write!(f, "{}", vec.join(", "))
     }
 }
@@ -492,6 +496,13 @@ pub fn validate_modifs<'a>(
         vd.field_script_value("scheme_success_chance", sc);
     }
 
+    if kinds.intersects(ModifKinds::TravelPlan) {
+        vd.field_script_value("travel_safety", sc);
+        vd.field_script_value("travel_safety_mult", sc);
+        vd.field_script_value("travel_speed", sc);
+        vd.field_script_value("travel_speed_mult", sc);
+    }
+
     'outer: for (token, bv) in vd.unknown_keys() {
         for terrain_sfx in &[
             "_advantage",
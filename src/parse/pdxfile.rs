@@ -4,7 +4,7 @@ use std::mem::take;
 
 use crate::block::{Block, BlockOrValue, Comparator};
 use crate::errorkey::ErrorKey;
-use crate::errors::{error, warn, warn_info};
+use crate::errors::{advice_info, error, error_info, warn, warn_info};
 use crate::fileset::FileEntry;
 use crate::token::{Loc, Token};
 
@@ -15,22 +15,23 @@ enum State {
     Id,
     Comparator,
     Calculation,
-    CalculationId,
     Comment,
 }
 
-#[derive(Copy, Clone, Debug)]
-enum CalculationOp {
-    Add,
-    Divide,
-}
-
 #[allow(clippy::wrong_self_convention)]
 trait CharExt {
     fn is_id_char(self) -> bool;
     fn is_comparator_char(self) -> bool;
 }
 
+/// Whether `s` is the start of a number, possibly written with thousands separators
+fn is_number_like(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+}
+
 impl CharExt for char {
     fn is_id_char(self) -> bool {
         self.is_alphabetic()
@@ -46,7 +47,7 @@ impl CharExt for char {
     }
 
     fn is_comparator_char(self) -> bool {
-        self == '<' || self == '>' || self == '!' || self == '='
+        self == '<' || self == '>' || self == '!' || self == '=' || self == '?'
     }
 }
 
@@ -63,52 +64,67 @@ struct Parser {
     stack: Vec<ParseLevel>,
     brace_error: bool,
     local_macros: FnvHashMap<String, f64>,
-    calculation_op: CalculationOp,
-    calculation: f64,
 }
 
 impl Parser {
     fn unknown_char(c: char, loc: Loc) {
         let token = Token::new(c.to_string(), loc);
-        error(
+        if c == '\u{feff}' {
+            // The one at the start of the file was already taken off
+            advice_info(
+                token,
+                ErrorKey::Encoding,
+                "UTF-8 BOM in the middle of the file",
+                "this usually comes from joining two files, such as in a merge; it is ignored but can be removed",
+            );
+        } else if c.is_control() {
+            let msg = format!("Unrecognized control character U+{:04X}", c as u32);
+            let info = "it can't be seen in most editors; delete the text around it and retype it";
+            error_info(token, ErrorKey::ParseError, &msg, info);
+        } else {
+            error(
+                token,
+                ErrorKey::ParseError,
+                &format!("Unrecognized character {c}"),
+            );
+        }
+    }
+
+    fn semicolon(loc: Loc) {
+        let token = Token::new(";".to_string(), loc);
+        warn_info(
             token,
             ErrorKey::ParseError,
-            &format!("Unrecognized character {}", c),
+            "unexpected semicolon",
+            "script files do not use semicolons; it will be ignored",
         );
     }
 
-    fn calculation_start(&mut self) {
-        self.calculation = 0.0;
-        self.calculation_op = CalculationOp::Add;
-    }
-
-    fn calculation_op(&mut self, op: CalculationOp) {
-        self.calculation_op = op;
+    fn quote_in_string(loc: Loc) {
+        let token = Token::new("\"".to_string(), loc);
+        error_info(
+            token,
+            ErrorKey::ParseError,
+            "quoted string ends here but the text continues",
+            "quotes cannot be used inside quoted strings",
+        );
     }
 
-    fn calculation_next(&mut self, local_macro: &Token) {
-        if let Some(value) = self
-            .local_macros
-            .get(local_macro.as_str())
-            .copied()
-            .or_else(|| local_macro.as_str().parse::<f64>().ok())
-        {
-            match self.calculation_op {
-                CalculationOp::Add => self.calculation += value,
-                CalculationOp::Divide => self.calculation /= value,
-            }
-        } else {
-            error(local_macro, ErrorKey::ParseError, "local value not defined");
+    fn calculation(&mut self, expr: &Token, loc: Loc) {
+        if let Some(value) = Calculator::new(expr, &self.local_macros).evaluate() {
+            self.token(Token::new(value.to_string(), loc));
+        } else if self.current.comp.is_some() {
+            // The error has been reported already. Drop the assignment rather than
+            // inventing a value for it.
+            self.current.key = None;
+            self.current.comp = None;
         }
     }
 
-    fn calculation_result(&mut self) -> f64 {
-        take(&mut self.calculation)
-    }
-
     fn token(&mut self, token: Token) {
-        // Special case parsing of color = hsv { ... }
-        if token.is("hsv") {
+        // Special case parsing of color = hsv { ... } and the other color formats
+        if (token.is("hsv") || token.is("hsv360") || token.is("rgb")) && self.current.comp.is_some()
+        {
             self.current.tag = Some(token);
             return;
         }
@@ -123,19 +139,15 @@ impl Parser {
         if let Some(key) = self.current.key.take() {
             if let Some((comp, _)) = self.current.comp.take() {
                 if let Some(local_macro) = key.as_str().strip_prefix('@') {
-                    if let Ok(value) = token.as_str().parse::<f64>() {
+                    if let Some(value) = self.local_value(&token) {
                         self.local_macros.insert(local_macro.to_string(), value);
-                    } else {
-                        error(token, ErrorKey::ParseError, "can't parse local value");
                     }
-                } else if let Some(local_macro) = token.as_str().strip_prefix('@') {
-                    if let Some(value) = self.local_macros.get(local_macro) {
+                } else if token.as_str().starts_with('@') {
+                    if let Some(value) = self.local_value(&token) {
                         let token = Token::new(value.to_string(), token.loc);
                         self.current
                             .block
                             .add_key_value(key, comp, BlockOrValue::Token(token));
-                    } else {
-                        error(token, ErrorKey::ParseError, "local value not defined");
                     }
                 } else {
                     self.current
@@ -151,6 +163,23 @@ impl Parser {
         }
     }
 
+    /// Look up a `@name` reference, or parse a plain number for a `@name = value` definition.
+    fn local_value(&self, token: &Token) -> Option<f64> {
+        if let Some(name) = token.as_str().strip_prefix('@') {
+            let value = self.local_macros.get(name).copied();
+            if value.is_none() {
+                let msg = format!("`@{name}` is not defined in this file");
+                error(token, ErrorKey::ParseError, &msg);
+            }
+            value
+        } else if let Ok(value) = token.as_str().parse::<f64>() {
+            Some(value)
+        } else {
+            error(token, ErrorKey::ParseError, "can't parse local value");
+            None
+        }
+    }
+
     fn block_value(&mut self, mut block: Block) {
         // Like token(), but block values cannot become keys
         if let Some(tag) = self.current.tag.take() {
@@ -162,8 +191,16 @@ impl Parser {
                     .block
                     .add_key_value(key, comp, BlockOrValue::Block(block));
             } else {
-                self.current.block.add_value(BlockOrValue::Token(key));
-                self.current.block.add_value(BlockOrValue::Block(block));
+                // Recover by treating it as `key = {`, which is almost always what was meant.
+                error_info(
+                    &key,
+                    ErrorKey::ParseError,
+                    "missing `=` between key and block",
+                    "without the `=`, the game will not treat this as a key",
+                );
+                self.current
+                    .block
+                    .add_key_value(key, Comparator::Eq, BlockOrValue::Block(block));
             }
         } else {
             self.current.block.add_value(BlockOrValue::Block(block));
@@ -237,11 +274,13 @@ impl Parser {
                 );
             }
         } else {
-            self.brace_error = true;
-            error(
+            // A stray } at top level doesn't change the structure of the items around it,
+            // so just skip it and keep parsing.
+            error_info(
                 &Token::new("}".to_string(), loc),
                 ErrorKey::ParseError,
                 "Unexpected }",
+                "this closing brace has no matching opening brace and will be ignored",
             );
         }
     }
@@ -274,6 +313,137 @@ impl Parser {
     }
 }
 
+/// Evaluator for `@[ ... ]` expressions, which can use `+`, `-`, `*`, `/` and parentheses
+/// on numbers and on the `@` values defined earlier in the same file.
+struct Calculator<'a> {
+    expr: &'a Token,
+    tokens: Vec<Token>,
+    pos: usize,
+    local_macros: &'a FnvHashMap<String, f64>,
+}
+
+impl<'a> Calculator<'a> {
+    fn new(expr: &'a Token, local_macros: &'a FnvHashMap<String, f64>) -> Self {
+        let mut tokens = Vec::new();
+        let mut current = String::new();
+        let mut loc = expr.loc.clone();
+        let mut start = loc.clone();
+        for (i, c) in expr.as_str().char_indices() {
+            loc.offset = expr.loc.offset + i;
+            if c.is_whitespace() || "+-*/()".contains(c) {
+                if !current.is_empty() {
+                    tokens.push(Token::new(take(&mut current), start.clone()));
+                }
+                if !c.is_whitespace() {
+                    tokens.push(Token::new(c.to_string(), loc.clone()));
+                }
+            } else {
+                if current.is_empty() {
+                    start = loc.clone();
+                }
+                current.push(c);
+            }
+            loc.column += 1;
+        }
+        if !current.is_empty() {
+            tokens.push(Token::new(current, start));
+        }
+        Self {
+            expr,
+            tokens,
+            pos: 0,
+            local_macros,
+        }
+    }
+
+    fn evaluate(mut self) -> Option<f64> {
+        let value = self.expression()?;
+        if let Some(token) = self.tokens.get(self.pos) {
+            let msg = format!("unexpected `{token}` in calculation");
+            error(token, ErrorKey::ParseError, &msg);
+            return None;
+        }
+        Some(value)
+    }
+
+    fn next_is(&self, op: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(token) if token.is(op))
+    }
+
+    fn expression(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        loop {
+            if self.next_is("+") {
+                self.pos += 1;
+                value += self.term()?;
+            } else if self.next_is("-") {
+                self.pos += 1;
+                value -= self.term()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.factor()?;
+        loop {
+            if self.next_is("*") {
+                self.pos += 1;
+                value *= self.factor()?;
+            } else if self.next_is("/") {
+                let op = self.tokens[self.pos].clone();
+                self.pos += 1;
+                let divisor = self.factor()?;
+                if divisor == 0.0 {
+                    error(op, ErrorKey::ParseError, "division by zero in calculation");
+                    return None;
+                }
+                value /= divisor;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Option<f64> {
+        let token = if let Some(token) = self.tokens.get(self.pos) {
+            token.clone()
+        } else {
+            error(self.expr, ErrorKey::ParseError, "incomplete calculation");
+            return None;
+        };
+        self.pos += 1;
+        if token.is("-") {
+            Some(-self.factor()?)
+        } else if token.is("(") {
+            let value = self.expression()?;
+            if self.next_is(")") {
+                self.pos += 1;
+                Some(value)
+            } else {
+                error(token, ErrorKey::ParseError, "unclosed `(` in calculation");
+                None
+            }
+        } else if token.is(")") || token.is("+") || token.is("*") || token.is("/") {
+            let msg = format!("unexpected `{token}` in calculation");
+            error(token, ErrorKey::ParseError, &msg);
+            None
+        } else {
+            let name = token.as_str().strip_prefix('@').unwrap_or(token.as_str());
+            if let Some(value) = self.local_macros.get(name) {
+                Some(*value)
+            } else if let Ok(value) = token.as_str().parse::<f64>() {
+                Some(value)
+            } else {
+                let msg = format!("`@{name}` is not defined in this file");
+                error(token, ErrorKey::ParseError, &msg);
+                None
+            }
+        }
+    }
+}
+
 #[allow(clippy::too_many_lines)] // many lines are natural for state machines
 fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
     let mut parser = Parser {
@@ -287,13 +457,14 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
         stack: Vec::new(),
         brace_error: false,
         local_macros: FnvHashMap::default(),
-        calculation: 0.0,
-        calculation_op: CalculationOp::Add,
     };
     let mut state = State::Neutral;
     let mut token_start = blockloc.clone();
     let mut calculation_start = blockloc;
     let mut current_id = String::new();
+    // The location of the closing quote of the most recent quoted string,
+    // if nothing has been parsed after it yet.
+    let mut quote_end = None;
 
     for token in inputs {
         let content = token.as_str();
@@ -330,6 +501,8 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
                         parser.open_brace(loc.clone());
                     } else if c == '}' {
                         parser.close_brace(loc.clone(), content);
+                    } else if c == ';' {
+                        Parser::semicolon(loc.clone());
                     } else {
                         Parser::unknown_char(c, loc.clone());
                     }
@@ -342,15 +515,29 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
                 }
                 State::QString => {
                     if c == '"' {
+                        quote_end = Some(loc.clone());
                         state = State::Id;
+                    } else if c == '\r' && content[i + 1..].starts_with('\n') {
+                        // Leave out the carriage return of a CRLF line ending
                     } else if c == '\n' {
-                        let token = Token::new(take(&mut current_id), token_start.clone());
+                        let token =
+                            Token::new_with_end(take(&mut current_id), token_start.clone(), &loc);
                         warn(token, ErrorKey::ParseError, "Quoted string not closed");
+                        // Whatever follows on the next line is reported from where it starts
+                        token_start = loc.clone();
+                        token_start.line += 1;
+                        token_start.column = 1;
+                        token_start.offset += 1;
                     } else {
                         current_id.push(c);
                     }
                 }
                 State::Id => {
+                    if let Some(end) = quote_end.take() {
+                        if c == '"' || c.is_id_char() {
+                            Parser::quote_in_string(end);
+                        }
+                    }
                     if c == '"' {
                         // The quoted string actually becomes part of this id
                         state = State::QString;
@@ -359,11 +546,19 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
                         current_id.push(c);
                     } else if c.is_id_char() {
                         current_id.push(c);
+                    } else if (c == '%' || c == ',') && is_number_like(&current_id) {
+                        // Keep `10%` and `1,000` together so that the validators can explain
+                        // what's wrong with them
+                        current_id.push(c);
                     } else if c == '[' && loc.offset == token_start.offset + 1 {
                         state = State::Calculation;
-                        parser.calculation_start();
+                        current_id.clear();
+                        token_start = loc.clone();
+                        token_start.offset += 1;
+                        token_start.column += 1;
                     } else {
-                        let token = Token::new(take(&mut current_id), token_start.clone());
+                        let token =
+                            Token::new_with_end(take(&mut current_id), token_start.clone(), &loc);
                         parser.token(token);
 
                         if c.is_comparator_char() {
@@ -379,6 +574,9 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
                         } else if c == '}' {
                             parser.close_brace(loc.clone(), content);
                             state = State::Neutral;
+                        } else if c == ';' {
+                            Parser::semicolon(loc.clone());
+                            state = State::Neutral;
                         } else {
                             Parser::unknown_char(c, loc.clone());
                             state = State::Neutral;
@@ -387,58 +585,21 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
                     }
                 }
                 State::Calculation => {
-                    current_id.clear();
-                    if c.is_whitespace() {
-                    } else if c == '+' {
-                        parser.calculation_op(CalculationOp::Add);
-                    } else if c == '/' {
-                        parser.calculation_op(CalculationOp::Divide);
-                    } else if c.is_id_char() {
-                        state = State::CalculationId;
-                        current_id.push(c);
-                    } else if c == ']' {
-                        let token = Token::new(
-                            parser.calculation_result().to_string(),
-                            calculation_start.clone(),
-                        );
-                        parser.token(token);
-                        state = State::Neutral;
-                    }
-                    token_start = loc.clone();
-                }
-                State::CalculationId => {
-                    if c.is_id_char() {
-                        current_id.push(c);
-                    } else if c.is_whitespace() || c == '+' || c == '/' {
-                        let token = Token::new(take(&mut current_id), token_start.clone());
-                        parser.calculation_next(&token);
-                        state = State::Calculation;
-                        if c == '+' {
-                            parser.calculation_op(CalculationOp::Add);
-                        } else if c == '/' {
-                            parser.calculation_op(CalculationOp::Divide);
-                        }
-                    } else if c == ']' {
-                        let token = Token::new(take(&mut current_id), token_start.clone());
-                        parser.calculation_next(&token);
-
-                        let token = Token::new(
-                            parser.calculation_result().to_string(),
-                            calculation_start.clone(),
-                        );
-                        parser.token(token);
+                    if c == ']' {
+                        let expr =
+                            Token::new_with_end(take(&mut current_id), token_start.clone(), &loc);
+                        parser.calculation(&expr, calculation_start.clone());
                         state = State::Neutral;
                     } else {
-                        Parser::unknown_char(c, loc.clone());
-                        current_id.clear();
-                        state = State::Neutral;
+                        current_id.push(c);
                     }
                 }
                 State::Comparator => {
                     if c.is_comparator_char() {
                         current_id.push(c);
                     } else {
-                        let token = Token::new(take(&mut current_id), token_start.clone());
+                        let token =
+                            Token::new_with_end(take(&mut current_id), token_start.clone(), &loc);
                         parser.comparator(token);
 
                         if c == '"' {
@@ -465,6 +626,9 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
                         } else if c == '}' {
                             parser.close_brace(loc.clone(), content);
                             state = State::Neutral;
+                        } else if c == ';' {
+                            Parser::semicolon(loc.clone());
+                            state = State::Neutral;
                         } else {
                             Parser::unknown_char(c, loc.clone());
                             state = State::Neutral;
@@ -498,6 +662,14 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
             let token = Token::new(current_id, token_start);
             parser.comparator(token);
         }
+        State::Calculation => {
+            let token = Token::new("@[".to_string(), calculation_start);
+            error(
+                token,
+                ErrorKey::ParseError,
+                "calculation was never closed with `]`",
+            );
+        }
         _ => (),
     }
 
@@ -506,6 +678,15 @@ fn parse(blockloc: Loc, inputs: &[Token]) -> Option<Block> {
 
 #[allow(clippy::module_name_repetitions)]
 pub fn parse_pdx(entry: &FileEntry, content: &str) -> Option<Block> {
+    if content.contains('\0') {
+        error_info(
+            entry,
+            ErrorKey::ParseError,
+            "file contains null bytes",
+            "this is not a script file; it may be a binary file that was saved or committed by mistake",
+        );
+        return None;
+    }
     let blockloc = Loc::for_entry(entry);
     let mut loc = blockloc.clone();
     loc.line = 1;
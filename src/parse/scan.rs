@@ -0,0 +1,153 @@
+//! A quick pass over a script file that finds its definitions without building blocks.
+//! It's for game files that only have to be indexed. It reports no errors, because the
+//! full parser does that for the files that are really read.
+
+use crate::fileset::FileEntry;
+use crate::token::{Loc, Token};
+
+/// A definition at the top level of a file, with the `field = value` pairs directly inside it.
+/// Fields with a block as value are skipped, and so is everything inside those blocks.
+#[derive(Clone, Debug)]
+pub struct ScanItem {
+    pub key: Token,
+    pub fields: Vec<(Token, Token)>,
+}
+
+impl ScanItem {
+    pub fn get_field_value(&self, name: &str) -> Option<&Token> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field.is(name))
+            .map(|(_, value)| value)
+    }
+}
+
+/// The words are kept as ranges in the content, to only make tokens of the ones needed
+#[derive(Debug)]
+enum Lex {
+    Word(usize, usize, Loc),
+    Open,
+    Close,
+    Assign,
+}
+
+/// Split the content into words, braces and `=`. The `<` of `<=` and the like end up as
+/// words of their own, which is fine because they don't start definitions.
+fn lex(entry: &FileEntry, content: &str) -> Vec<Lex> {
+    let mut vec = Vec::new();
+    let mut loc = Loc::for_entry(entry);
+    loc.line = 1;
+    loc.column = 1;
+    let mut chars = content.char_indices().peekable();
+    let mut word: Option<(usize, Loc)> = None;
+
+    let advance = |loc: &mut Loc, c: char, i: usize| {
+        loc.offset = i + c.len_utf8();
+        if c == '\n' {
+            loc.line += 1;
+            loc.column = 1;
+        } else {
+            loc.column += 1;
+        }
+    };
+    let end_word = |word: &mut Option<(usize, Loc)>, i: usize, vec: &mut Vec<Lex>| {
+        if let Some((start, loc)) = word.take() {
+            vec.push(Lex::Word(start, i, loc));
+        }
+    };
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '#' => {
+                end_word(&mut word, i, &mut vec);
+                advance(&mut loc, c, i);
+                while let Some(&(i, c)) = chars.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    advance(&mut loc, c, i);
+                    chars.next();
+                }
+                continue;
+            }
+            '"' => {
+                end_word(&mut word, i, &mut vec);
+                advance(&mut loc, c, i);
+                let start = i + 1;
+                let start_loc = loc.clone();
+                let mut end = content.len();
+                for (i, c) in chars.by_ref() {
+                    advance(&mut loc, c, i);
+                    if c == '"' || c == '\n' {
+                        end = i;
+                        break;
+                    }
+                }
+                vec.push(Lex::Word(start, end, start_loc));
+                continue;
+            }
+            '{' | '}' | '=' => {
+                end_word(&mut word, i, &mut vec);
+                vec.push(match c {
+                    '{' => Lex::Open,
+                    '}' => Lex::Close,
+                    _ => Lex::Assign,
+                });
+            }
+            c if c.is_whitespace() => end_word(&mut word, i, &mut vec),
+            _ => {
+                if word.is_none() {
+                    word = Some((i, loc.clone()));
+                }
+            }
+        }
+        advance(&mut loc, c, i);
+    }
+    end_word(&mut word, content.len(), &mut vec);
+    vec
+}
+
+/// Find the definitions in the content of a script file
+pub fn scan_pdx(entry: &FileEntry, content: &str) -> Vec<ScanItem> {
+    let lexed = lex(entry, content);
+    let token = |start: usize, end: usize, loc: &Loc| {
+        Token::new(content[start..end].to_string(), loc.clone())
+    };
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < lexed.len() {
+        match (&lexed[i], lexed.get(i + 1), lexed.get(i + 2)) {
+            (Lex::Word(start, end, loc), Some(Lex::Assign), Some(Lex::Open)) if depth == 0 => {
+                items.push(ScanItem {
+                    key: token(*start, *end, loc),
+                    fields: Vec::new(),
+                });
+                depth = 1;
+                i += 3;
+            }
+            (
+                Lex::Word(start, end, loc),
+                Some(Lex::Assign),
+                Some(Lex::Word(vstart, vend, vloc)),
+            ) if depth == 1 => {
+                if let Some(item) = items.last_mut() {
+                    let field = token(*start, *end, loc);
+                    let value = token(*vstart, *vend, vloc);
+                    item.fields.push((field, value));
+                }
+                i += 3;
+            }
+            (Lex::Open, _, _) => {
+                depth += 1;
+                i += 1;
+            }
+            (Lex::Close, _, _) => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    items
+}
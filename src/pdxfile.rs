@@ -1,24 +1,74 @@
 use encoding::all::WINDOWS_1252;
 use encoding::{DecoderTrap, Encoding};
-use std::fs::{read, read_to_string};
-use std::path::Path;
+use fnv::FnvHashMap;
+use std::cell::RefCell;
+use std::fs::read;
+use std::path::{Path, PathBuf};
 
 use crate::block::Block;
 use crate::errorkey::ErrorKey;
-use crate::errors::{advice_info, error_info, warn};
-use crate::fileset::FileEntry;
+use crate::errors::{advice_info, error_info};
+use crate::fileset::{FileEntry, FileKind};
 use crate::parse::pdxfile::parse_pdx;
+use crate::parse::scan::{scan_pdx, ScanItem};
+use crate::token::Loc;
 
 /// If a windows-1252 file mistakenly starts with a UTF-8 BOM, this is
 /// what it will look like after decoding
 const BOM_FROM_1252: &str = "\u{00ef}\u{00bb}\u{00bf}";
 
+thread_local! {
+    /// The parsed game and DLC files, by full path. This is `None` unless caching was turned on
+    /// with `PdxFile::cache_game_files`.
+    static GAME_FILES: RefCell<Option<FnvHashMap<PathBuf, Option<Block>>>> = const { RefCell::new(None) };
+}
+
 pub struct PdxFile;
 
 impl PdxFile {
-    fn read_utf8(entry: &FileEntry, fullpath: &Path) -> Option<String> {
-        match read_to_string(fullpath) {
-            Ok(contents) => Some(contents),
+    /// Keep the parsed game and DLC files, so that checking several mods in one run only
+    /// parses them once. Problems in those files are reported only the first time.
+    pub fn cache_game_files() {
+        GAME_FILES.with(|cache| {
+            cache.borrow_mut().get_or_insert_with(FnvHashMap::default);
+        });
+    }
+
+    fn cached(
+        entry: &FileEntry,
+        fullpath: &Path,
+        parse: impl FnOnce() -> Option<Block>,
+    ) -> Option<Block> {
+        if entry.kind() == FileKind::Mod {
+            return parse();
+        }
+        let found = GAME_FILES.with(|cache| {
+            cache
+                .borrow()
+                .as_ref()
+                .map(|files| files.get(fullpath).cloned())
+        });
+        match found {
+            // Caching is off
+            None => parse(),
+            Some(Some(block)) => block,
+            Some(None) => {
+                let block = parse();
+                GAME_FILES.with(|cache| {
+                    if let Some(files) = cache.borrow_mut().as_mut() {
+                        files.insert(fullpath.to_path_buf(), block.clone());
+                    }
+                });
+                block
+            }
+        }
+    }
+
+    /// Read a file that should be in UTF-8 encoding.
+    /// If it contains invalid UTF-8, report where and carry on with the invalid bytes replaced.
+    pub fn read_utf8(entry: &FileEntry, fullpath: &Path) -> Option<String> {
+        let bytes = match read(fullpath) {
+            Ok(bytes) => bytes,
             Err(e) => {
                 error_info(
                     entry,
@@ -26,7 +76,28 @@ impl PdxFile {
                     "could not read file",
                     &format!("{:#}", e),
                 );
-                None
+                return None;
+            }
+        };
+        match String::from_utf8(bytes) {
+            Ok(contents) => Some(contents),
+            Err(e) => {
+                let offset = e.utf8_error().valid_up_to();
+                let bytes = e.into_bytes();
+                // The bytes up to the offset are valid by definition
+                let prefix = std::str::from_utf8(&bytes[..offset]).unwrap_or_default();
+                let mut loc = Loc::for_entry(entry);
+                loc.line = prefix.matches('\n').count() + 1;
+                loc.column = prefix.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+                loc.offset = offset;
+                let info = if WINDOWS_1252.decode(&bytes, DecoderTrap::Strict).is_ok() {
+                    "This file looks like it was saved in Windows-1252 encoding. Special characters will be garbled in the game. Save it as UTF-8 instead."
+                } else {
+                    "The file must be saved in UTF-8 encoding."
+                };
+                let msg = format!("invalid UTF-8 byte at offset {offset}");
+                error_info(loc, ErrorKey::Encoding, &msg, info);
+                Some(String::from_utf8_lossy(&bytes).into_owned())
             }
         }
     }
@@ -53,20 +124,39 @@ impl PdxFile {
     }
 
     pub fn read(entry: &FileEntry, fullpath: &Path) -> Option<Block> {
+        Self::cached(entry, fullpath, || Self::read_uncached(entry, fullpath))
+    }
+
+    fn read_uncached(entry: &FileEntry, fullpath: &Path) -> Option<Block> {
         let contents = Self::read_utf8(entry, fullpath)?;
         if let Some(bomless) = contents.strip_prefix('\u{feff}') {
-            parse_pdx(entry, bomless)
-        } else {
-            warn(
+            advice_info(
                 entry,
                 ErrorKey::Encoding,
-                "file must start with a UTF-8 BOM",
+                "file starts with a UTF-8 BOM",
+                "The game doesn't need it in script files, and some tools have trouble with it",
             );
+            parse_pdx(entry, bomless)
+        } else {
             parse_pdx(entry, &contents)
         }
     }
 
+    /// Find the definitions in a file without parsing it fully. Nothing is reported except
+    /// when the file can't be read.
+    pub fn scan(entry: &FileEntry, fullpath: &Path) -> Option<Vec<ScanItem>> {
+        let contents = Self::read_utf8(entry, fullpath)?;
+        let bomless = contents.strip_prefix('\u{feff}').unwrap_or(&contents);
+        Some(scan_pdx(entry, bomless))
+    }
+
     pub fn read_cp1252(entry: &FileEntry, fullpath: &Path) -> Option<Block> {
+        Self::cached(entry, fullpath, || {
+            Self::read_cp1252_uncached(entry, fullpath)
+        })
+    }
+
+    fn read_cp1252_uncached(entry: &FileEntry, fullpath: &Path) -> Option<Block> {
         let contents = Self::read_1252(entry, fullpath)?;
 
         if let Some(bomless) = contents.strip_prefix(BOM_FROM_1252) {
@@ -0,0 +1,113 @@
+//! Checks for the A Game of Thrones mod, which adds dragons through its own framework folder
+
+use fnv::FnvHashMap;
+use std::path::{Path, PathBuf};
+
+use crate::block::validator::Validator;
+use crate::block::Block;
+use crate::context::ScopeContext;
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileHandler, Fileset};
+use crate::helpers::dup_error;
+use crate::item::Item;
+use crate::pdxfile::PdxFile;
+use crate::profiles::Profile;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+
+#[derive(Clone, Debug, Default)]
+pub struct GameOfThrones {
+    dragons: Dragons,
+}
+
+impl Profile for GameOfThrones {
+    fn name(&self) -> &'static str {
+        "agot"
+    }
+
+    fn common_dirs(&self) -> &[&'static str] {
+        &["common/agot_dragons"]
+    }
+
+    fn required_localization(&self) -> &[(Item, &'static str)] {
+        // The mod's army screen shows the flavor text of every regiment type
+        &[(Item::MenAtArms, "$KEY$_flavor")]
+    }
+
+    fn load(&mut self, fileset: &Fileset) {
+        fileset.handle(&mut self.dragons);
+    }
+
+    fn validate(&self, data: &Everything) {
+        self.dragons.validate(data);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Dragons {
+    dragons: FnvHashMap<String, Dragon>,
+}
+
+impl Dragons {
+    fn load_item(&mut self, key: Token, block: &Block) {
+        if let Some(other) = self.dragons.get(key.as_str()) {
+            if other.key.loc.kind >= key.loc.kind {
+                dup_error(&key, &other.key, "dragon");
+            }
+        }
+        self.dragons
+            .insert(key.to_string(), Dragon::new(key, block.clone()));
+    }
+
+    fn validate(&self, data: &Everything) {
+        for item in self.dragons.values() {
+            item.validate(data);
+        }
+    }
+}
+
+impl FileHandler for Dragons {
+    fn subpath(&self) -> PathBuf {
+        PathBuf::from("common/agot_dragons")
+    }
+
+    fn handle_file(&mut self, entry: &FileEntry, fullpath: &Path) {
+        if !entry.filename().to_string_lossy().ends_with(".txt") {
+            return;
+        }
+
+        let Some(block) = PdxFile::read(entry, fullpath) else {
+            return;
+        };
+
+        for (key, block) in block.iter_pure_definitions_warn() {
+            self.load_item(key.clone(), block);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Dragon {
+    key: Token,
+    block: Block,
+}
+
+impl Dragon {
+    pub fn new(key: Token, block: Block) -> Self {
+        Self { key, block }
+    }
+
+    pub fn validate(&self, data: &Everything) {
+        let mut vd = Validator::new(&self.block, data);
+
+        data.localization.verify_exists(&self.key);
+        vd.req_field("trait");
+        vd.field_value_item("trait", Item::Trait);
+        vd.field_integer("max_riders");
+        vd.field_validated_block("rider_trigger", |b, data| {
+            let mut sc = ScopeContext::new_root(Scopes::Character, self.key.clone());
+            validate_normal_trigger(b, data, &mut sc, false);
+        });
+    }
+}
@@ -0,0 +1,58 @@
+//! Mod profiles hold the checks that only make sense for one particular mod, usually a big
+//! total conversion with its own framework on top of the base game.
+//! A profile is selected on the command line with `--profile`.
+
+use std::fmt::Debug;
+
+use crate::everything::Everything;
+use crate::fileset::Fileset;
+use crate::item::Item;
+
+pub mod agot;
+pub mod pod;
+
+/// The hooks a mod profile can provide. All of them are optional.
+pub trait Profile: Debug {
+    /// The name used to select this profile with `--profile`
+    fn name(&self) -> &'static str;
+
+    /// Directories under `common/` that belong to the mod's own framework, so that the game
+    /// doesn't know them but the mod does load them.
+    fn common_dirs(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Localization keys that the mod needs for every item of the given type.
+    /// `$KEY$` in the pattern is replaced by the item's key.
+    fn required_localization(&self) -> &[(Item, &'static str)] {
+        &[]
+    }
+
+    /// Load the profile's own item types, after the game's items have been loaded.
+    fn load(&mut self, _fileset: &Fileset) {}
+
+    /// Do the profile's own checks, after the normal validation.
+    fn validate(&self, _data: &Everything) {}
+}
+
+/// A function that creates a fresh profile
+type MakeProfile = fn() -> Box<dyn Profile>;
+
+/// All the known profiles, by name
+const PROFILES: &[(&str, MakeProfile)] = &[
+    ("pod", || Box::<pod::PrincesOfDarkness>::default()),
+    ("agot", || Box::<agot::GameOfThrones>::default()),
+];
+
+/// Create the profile with the given name, or return None if there's no such profile.
+pub fn profile(name: &str) -> Option<Box<dyn Profile>> {
+    PROFILES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, create)| create())
+}
+
+/// The names of all the known profiles, for telling the user what to choose from
+pub fn profile_names() -> Vec<&'static str> {
+    PROFILES.iter().map(|(n, _)| *n).collect()
+}
@@ -0,0 +1,18 @@
+//! Checks for the Princes of Darkness mod
+
+use crate::everything::Everything;
+use crate::profiles::Profile;
+
+#[derive(Clone, Debug, Default)]
+pub struct PrincesOfDarkness {}
+
+impl Profile for PrincesOfDarkness {
+    fn name(&self) -> &'static str {
+        "pod"
+    }
+
+    fn validate(&self, data: &Everything) {
+        data.province_histories
+            .check_pod_faiths(&data.religions, &data.titles);
+    }
+}
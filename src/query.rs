@@ -0,0 +1,214 @@
+//! Answers to questions about where items are defined, for the `--where` and `--list` options,
+//! about where they are referenced, for the `--find` and `--find-regex` options,
+//! and about which files are loaded, for the `--dump-filelist` option.
+
+use anyhow::{bail, Result};
+use fnv::FnvHashMap;
+use regex::Regex;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use strum::IntoEnumIterator;
+
+use crate::block::Block;
+use crate::errors::{pause_logging, resume_logging};
+use crate::everything::Everything;
+use crate::fileset::{FileEntry, FileStatus};
+use crate::item::Item;
+use crate::itemdb::ItemSource;
+use crate::pdxfile::PdxFile;
+use crate::references::{FindPattern, FindQuery};
+use crate::token::{Loc, Token};
+
+/// Find an item type by the name the user would write for it, like `decision` or `men_at_arms`.
+fn item_type_by_name(s: &str) -> Option<Item> {
+    let normalize = |s: &str| s.replace(['_', '-'], " ");
+    let wanted = normalize(s);
+    Item::iter().find(|itype| normalize(&itype.to_string()) == wanted)
+}
+
+/// Parse an item type as the user would write it, like `decision` or `men_at_arms`.
+fn parse_item_type(everything: &Everything, s: &str) -> Result<Item> {
+    if let Some(itype) = item_type_by_name(s) {
+        if !everything.can_query(itype) {
+            bail!("Looking up items of type `{}` is not supported yet", s);
+        }
+        return Ok(itype);
+    }
+    let known = Item::iter()
+        .filter(|itype| everything.can_query(*itype))
+        .map(|itype| itype.to_string().replace(' ', "_"))
+        .collect::<Vec<_>>();
+    bail!(
+        "Unknown item type `{}`. The types that can be looked up are: {}",
+        s,
+        known.join(", ")
+    );
+}
+
+fn format_loc(loc: &Loc) -> String {
+    format!(
+        "{} {}:{}:{}",
+        loc.kind,
+        loc.pathname.display(),
+        loc.line,
+        loc.column
+    )
+}
+
+/// Describe every definition of the item given as `type:key`, in load order,
+/// and mark the one that the game uses.
+pub fn where_item(everything: &Everything, query: &str) -> Result<String> {
+    let (itype, key) = match query.split_once(':') {
+        Some((itype, key)) => (parse_item_type(everything, itype)?, key),
+        None => bail!("Expected `type:key` but got `{}`", query),
+    };
+    let mut out = format!("{query}:\n");
+    let definitions = everything.item_definitions(itype, key).unwrap_or_default();
+    if definitions.is_empty() {
+        out.push_str("  not defined anywhere\n");
+    }
+    for def in definitions {
+        let note = if def.used {
+            " (used)".to_string()
+        } else if let Some(kind) = def.replaced_by {
+            format!(" (file replaced by {kind} file)")
+        } else {
+            String::new()
+        };
+        // Writing to a String can't fail
+        let _ = writeln!(out, "  {}{}", format_loc(&def.key.loc), note);
+    }
+    Ok(out)
+}
+
+/// List all the items of a type, sorted by key, with where the used definition is.
+pub fn list_items(everything: &Everything, itype: &str) -> Result<String> {
+    let itype = parse_item_type(everything, itype)?;
+    let mut items = everything
+        .iter_items(itype)
+        .map(ItemSource::key)
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    let mut out = String::new();
+    for key in items {
+        let _ = writeln!(out, "{} {}", key, format_loc(&key.loc));
+    }
+    Ok(out)
+}
+
+/// List every file in the game, the DLC, the base mod and the mod, or only the ones under `prefix`,
+/// sorted by path and then in load order, and mark the ones that the game doesn't load.
+pub fn dump_filelist(everything: &Everything, prefix: Option<&str>) -> String {
+    let files = everything
+        .fileset
+        .all_files()
+        .into_iter()
+        .filter(|(entry, _)| prefix.is_none_or(|prefix| entry.path().starts_with(prefix)))
+        .collect::<Vec<_>>();
+    let mut out = String::new();
+    for (idx, (entry, status)) in files.iter().enumerate() {
+        let note = match status {
+            FileStatus::Loaded => String::new(),
+            FileStatus::Overridden => {
+                // The file that overrides it is the next one with this path that was not left out
+                let by = files[idx + 1..]
+                    .iter()
+                    .take_while(|(other, _)| other.path() == entry.path())
+                    .find(|(_, status)| {
+                        matches!(status, FileStatus::Loaded | FileStatus::Overridden)
+                    });
+                match by {
+                    Some((other, _)) => format!(" (overridden by {} file)", other.kind()),
+                    None => " (overridden)".to_string(),
+                }
+            }
+            FileStatus::ReplacePath(path) => format!(" (excluded by replace_path {path})"),
+            FileStatus::TotalConversion => " (excluded by total conversion)".to_string(),
+        };
+        let _ = writeln!(out, "{} {}{}", entry.kind(), entry.path().display(), note);
+    }
+    out
+}
+
+/// Parse a `--find` query like `trait:brave`, or with `regex` a `--find-regex` query like
+/// `trait:^education_`. Every item type can be looked for, since the references to all of
+/// them are checked during validation.
+pub fn parse_find(query: &str, regex: bool) -> Result<FindQuery> {
+    let Some((itype, key)) = query.split_once(':') else {
+        bail!("Expected `type:key` but got `{}`", query)
+    };
+    let Some(itype) = item_type_by_name(itype) else {
+        bail!(
+            "Unknown item type `{}`. The types are: {}",
+            itype,
+            Item::iter()
+                .map(|itype| itype.to_string().replace(' ', "_"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    let pattern = if regex {
+        match Regex::new(key) {
+            Ok(regex) => FindPattern::Regex(regex),
+            Err(e) => bail!("Bad regular expression in `{}`: {}", query, e),
+        }
+    } else {
+        FindPattern::Key(key.to_string())
+    };
+    Ok(FindQuery {
+        text: query.to_string(),
+        itype,
+        pattern,
+    })
+}
+
+/// Describe every reference to the items of the `--find` queries that was seen during
+/// validation, with the item whose definition it is in.
+pub fn find_references(everything: &Everything) -> String {
+    let mut out = String::new();
+    let mut files = FnvHashMap::default();
+    for (query, tokens) in everything.references.results() {
+        let _ = writeln!(out, "{}:", query.text);
+        if tokens.is_empty() {
+            out.push_str("  not referenced anywhere\n");
+        }
+        for token in tokens {
+            let place = referencing_item(everything, &token.loc, &mut files)
+                .map_or_else(String::new, |key| format!(" in {key}"));
+            let key = match &query.pattern {
+                FindPattern::Key(_) => String::new(),
+                FindPattern::Regex(_) => format!(" {token}"),
+            };
+            let _ = writeln!(out, "  {}{}{}", format_loc(&token.loc), key, place);
+        }
+    }
+    out
+}
+
+/// The key of the top-level definition in a script file that contains `loc`.
+/// The files are read again here, and kept in `files` for the next lookup.
+fn referencing_item(
+    everything: &Everything,
+    loc: &Loc,
+    files: &mut FnvHashMap<Rc<PathBuf>, Option<Block>>,
+) -> Option<Token> {
+    if !loc.pathname.to_string_lossy().ends_with(".txt") {
+        return None;
+    }
+    let block = files.entry(loc.pathname.clone()).or_insert_with(|| {
+        let entry = FileEntry::new(loc.pathname.to_path_buf(), loc.kind);
+        // The files were already read once, so their problems were already reported.
+        pause_logging();
+        let block = PdxFile::read(&entry, &everything.fileset.fullpath(&entry));
+        resume_logging();
+        block
+    });
+    block
+        .as_ref()?
+        .iter_pure_definitions()
+        .map(|(key, _)| key)
+        .take_while(|key| key.loc.line <= loc.line)
+        .last()
+        .cloned()
+}
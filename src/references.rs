@@ -0,0 +1,81 @@
+//! Collecting the places where script refers to certain items, for the `--find` and
+//! `--find-regex` options. The references are seen while validating, where every item
+//! reference is checked against the item's definitions.
+
+use regex::Regex;
+use std::cell::RefCell;
+
+use crate::item::Item;
+use crate::token::Token;
+
+/// What to look for: the keys of an item type that are either exactly `key` or match a regex
+#[derive(Clone, Debug)]
+pub enum FindPattern {
+    Key(String),
+    Regex(Regex),
+}
+
+#[derive(Clone, Debug)]
+pub struct FindQuery {
+    /// The query as given on the command line, for the output
+    pub text: String,
+    pub itype: Item,
+    pub pattern: FindPattern,
+}
+
+impl FindQuery {
+    fn matches(&self, itype: Item, key: &str) -> bool {
+        self.itype == itype
+            && match &self.pattern {
+                FindPattern::Key(wanted) => wanted == key,
+                FindPattern::Regex(regex) => regex.is_match(key),
+            }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct References {
+    queries: Vec<FindQuery>,
+    /// The references found, by the index of the query they match
+    found: RefCell<Vec<(usize, Token)>>,
+}
+
+impl References {
+    pub fn add_query(&mut self, query: FindQuery) {
+        self.queries.push(query);
+    }
+
+    /// Whether any items are being looked for
+    pub fn is_active(&self) -> bool {
+        !self.queries.is_empty()
+    }
+
+    /// Note a reference to an item, if it's one of the items being looked for
+    pub fn record(&self, itype: Item, key: &str, token: &Token) {
+        for (idx, query) in self.queries.iter().enumerate() {
+            if query.matches(itype, key) {
+                self.found.borrow_mut().push((idx, token.clone()));
+            }
+        }
+    }
+
+    /// The queries, each with the references to it in the order of their locations.
+    /// A reference that was checked more than once is only listed once.
+    pub fn results(&self) -> Vec<(&FindQuery, Vec<Token>)> {
+        let found = self.found.borrow();
+        self.queries
+            .iter()
+            .enumerate()
+            .map(|(idx, query)| {
+                let mut tokens = found
+                    .iter()
+                    .filter(|(i, _)| *i == idx)
+                    .map(|(_, token)| token.clone())
+                    .collect::<Vec<_>>();
+                tokens.sort_by(|a, b| a.loc.cmp(&b.loc));
+                tokens.dedup_by(|a, b| a.loc == b.loc && a.as_str() == b.as_str());
+                (query, tokens)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,161 @@
+//! A module for declaring the fields of an item type, so that a generic driver can validate them.
+//! This keeps item validators from having to spell out which fields are triggers, effects,
+//! and so on, and from getting it wrong.
+
+use crate::block::validator::Validator;
+use crate::block::{Block, BlockOrValue};
+use crate::context::ScopeContext;
+use crate::data::scriptvalues::ScriptValue;
+use crate::desc::validate_desc;
+use crate::effect::validate_normal_effect;
+use crate::errorkey::ErrorKey;
+use crate::errors::error;
+use crate::everything::Everything;
+use crate::item::Item;
+use crate::scopes::Scopes;
+use crate::token::Token;
+use crate::trigger::validate_normal_trigger;
+use crate::validate::{check_fraction_hint, validate_ai_chance};
+
+/// The kinds of field that can be declared in a `Schema`.
+#[derive(Clone, Copy, Debug)]
+pub enum Field {
+    /// Anything is accepted. For fields that are checked elsewhere or that can't be checked.
+    Any,
+    Bool,
+    Integer,
+    Numeric,
+    /// One of a fixed set of values
+    Choice(&'static [&'static str]),
+    /// The key of an item of this type
+    Item(Item),
+    /// A localization key
+    Localization,
+    /// A pathname relative to the game or mod directory
+    File,
+    /// A localization key or a `desc` block
+    Desc,
+    /// A script value
+    Value,
+    /// A script value that is normally from 0 to 1, like a chance or a factor
+    Fraction,
+    /// A trigger block. The flag is whether it's shown in tooltips.
+    Trigger(bool),
+    /// Either `yes` or `no`, or a trigger block that decides it
+    BoolOrTrigger(bool),
+    /// An effect block. The flag is whether it's shown in tooltips.
+    Effect(bool),
+    /// A weight like `ai_will_do`: a value, or a block with a base value and modifiers
+    AiChance,
+    /// A block whose fields are declared by another schema. It uses the same scope context.
+    Block(&'static Schema),
+    /// A block that is validated by a custom function
+    Validated(fn(&Block, &Everything, &mut ScopeContext)),
+    /// The inner field, validated with its own scope context instead of the item's.
+    /// The new scope context has its root in the given scopes.
+    Rooted(Scopes, &'static Field),
+    /// The inner field, which may be given more than once
+    Multiple(&'static Field),
+}
+
+/// A list of field names and what they contain.
+/// The fields are validated in the order they are listed, which matters for the scope context
+/// because effects can set named scopes that are used in later fields.
+pub type Schema = [(&'static str, Field)];
+
+/// Validate the fields declared in `schema`.
+/// Fields that are not in the schema are left for the caller to handle with `vd`.
+pub fn validate_schema(vd: &mut Validator, schema: &Schema, sc: &mut ScopeContext) {
+    for (name, field) in schema {
+        if let Field::Multiple(field) = field {
+            vd.field_validated_bvs(name, |bv, data| validate_field(bv, field, data, sc));
+        } else {
+            vd.field_validated_bv(name, |bv, data| validate_field(bv, field, data, sc));
+        }
+    }
+}
+
+fn validate_field(bv: &BlockOrValue, field: &Field, data: &Everything, sc: &mut ScopeContext) {
+    match field {
+        Field::Any => (),
+        Field::Bool => {
+            if let Some(token) = bv.expect_value() {
+                token.expect_bool();
+            }
+        }
+        Field::Integer => {
+            if let Some(token) = bv.expect_value() {
+                token.expect_integer();
+            }
+        }
+        Field::Numeric => {
+            if let Some(token) = bv.expect_value() {
+                token.expect_number();
+            }
+        }
+        Field::Choice(choices) => {
+            if let Some(token) = bv.expect_value() {
+                if !choices.contains(&token.as_str()) {
+                    let msg = format!("expected one of {}", choices.join(", "));
+                    error(token, ErrorKey::Validation, &msg);
+                }
+            }
+        }
+        Field::Item(itype) => {
+            if let Some(token) = bv.expect_value() {
+                data.verify_exists(*itype, token);
+            }
+        }
+        Field::Localization => {
+            if let Some(token) = bv.expect_value() {
+                data.localization.verify_exists(token);
+            }
+        }
+        Field::File => {
+            if let Some(token) = bv.expect_value() {
+                data.fileset.verify_exists(token);
+            }
+        }
+        Field::Desc => validate_desc(bv, data, sc),
+        Field::Value => ScriptValue::validate_bv(bv, data, sc),
+        Field::Fraction => {
+            ScriptValue::validate_bv(bv, data, sc);
+            check_fraction_hint(bv);
+        }
+        Field::Trigger(tooltipped) => {
+            if let Some(block) = bv.expect_block() {
+                validate_normal_trigger(block, data, sc, *tooltipped);
+            }
+        }
+        Field::BoolOrTrigger(tooltipped) => match bv {
+            BlockOrValue::Token(token) => _ = token.expect_bool(),
+            BlockOrValue::Block(block) => validate_normal_trigger(block, data, sc, *tooltipped),
+        },
+        Field::Effect(tooltipped) => {
+            if let Some(block) = bv.expect_block() {
+                validate_normal_effect(block, data, sc, *tooltipped);
+            }
+        }
+        Field::AiChance => validate_ai_chance(bv, data, sc),
+        Field::Block(schema) => {
+            if let Some(block) = bv.expect_block() {
+                let mut vd = Validator::new(block, data);
+                validate_schema(&mut vd, schema, sc);
+            }
+        }
+        Field::Validated(f) => {
+            if let Some(block) = bv.expect_block() {
+                f(block, data, sc);
+            }
+        }
+        Field::Rooted(scopes, field) => {
+            let token = match bv {
+                BlockOrValue::Token(t) => t.clone(),
+                BlockOrValue::Block(b) => Token::from(&b.loc),
+            };
+            let mut sc = ScopeContext::new_root(*scopes, token);
+            validate_field(bv, field, data, &mut sc);
+        }
+        Field::Multiple(field) => validate_field(bv, field, data, sc),
+    }
+}
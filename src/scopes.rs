@@ -4,15 +4,16 @@ use bitflags::bitflags;
 use std::fmt::{Display, Formatter};
 
 use crate::errorkey::ErrorKey;
-use crate::errors::warn;
+use crate::errors::{error, error_info, warn};
 use crate::everything::Everything;
+use crate::helpers::{closest_key, suggest};
 use crate::token::Token;
 
 bitflags! {
     /// LAST UPDATED VERSION 1.7.0
     /// See `event_scopes.log` from the game data dumps.
     /// Keep in sync with the module constants below.
-    pub struct Scopes: u32 {
+    pub struct Scopes: u64 {
         const None = 0x0000_0001;
         const Value = 0x0000_0002;
         const Bool = 0x0000_0004;
@@ -44,6 +45,11 @@ bitflags! {
         const Inspiration = 0x1000_0000;
         const Struggle = 0x2000_0000;
         const CharacterMemory = 0x4000_0000;
+        const TravelPlan = 0x8000_0000;
+        const ActivityType = 0x0001_0000_0000;
+        const Accolade = 0x0002_0000_0000;
+        const AccoladeType = 0x0004_0000_0000;
+        const Decision = 0x0008_0000_0000;
     }
 }
 
@@ -68,39 +74,48 @@ impl Scopes {
 
 /// LAST UPDATED VERSION 1.7.0
 /// See `event_scopes.log` from the game data dumps.
-pub const None: u32 = 0x0000_0001;
-pub const Value: u32 = 0x0000_0002;
-pub const Bool: u32 = 0x0000_0004;
-pub const Flag: u32 = 0x0000_0008;
-pub const Character: u32 = 0x0000_0010;
-pub const LandedTitle: u32 = 0x0000_0020;
-pub const Activity: u32 = 0x0000_0040;
-pub const Secret: u32 = 0x0000_0080;
-pub const Province: u32 = 0x0000_0100;
-pub const Scheme: u32 = 0x0000_0200;
-pub const Combat: u32 = 0x0000_0400;
-pub const CombatSide: u32 = 0x0000_0800;
-pub const TitleAndVassalChange: u32 = 0x0000_1000;
-pub const Faith: u32 = 0x0000_2000;
-pub const GreatHolyWar: u32 = 0x0000_4000;
-pub const Religion: u32 = 0x0000_8000;
-pub const War: u32 = 0x0001_0000;
-pub const StoryCycle: u32 = 0x0002_0000;
-pub const CasusBelli: u32 = 0x0004_0000;
-pub const Dynasty: u32 = 0x0008_0000;
-pub const DynastyHouse: u32 = 0x0010_0000;
-pub const Faction: u32 = 0x0020_0000;
-pub const Culture: u32 = 0x0040_0000;
-pub const Army: u32 = 0x0080_0000;
-pub const HolyOrder: u32 = 0x0100_0000;
-pub const CouncilTask: u32 = 0x0200_0000;
-pub const MercenaryCompany: u32 = 0x0400_0000;
-pub const Artifact: u32 = 0x0800_0000;
-pub const Inspiration: u32 = 0x1000_0000;
-pub const Struggle: u32 = 0x2000_0000;
-pub const CharacterMemory: u32 = 0x4000_0000;
-pub const ALL: u32 = 0x7fff_ffff;
-pub const ALL_BUT_NONE: u32 = 0x7fff_fffe;
+pub const None: u64 = 0x0000_0001;
+pub const Value: u64 = 0x0000_0002;
+pub const Bool: u64 = 0x0000_0004;
+pub const Flag: u64 = 0x0000_0008;
+pub const Character: u64 = 0x0000_0010;
+pub const LandedTitle: u64 = 0x0000_0020;
+pub const Activity: u64 = 0x0000_0040;
+pub const Secret: u64 = 0x0000_0080;
+pub const Province: u64 = 0x0000_0100;
+pub const Scheme: u64 = 0x0000_0200;
+pub const Combat: u64 = 0x0000_0400;
+pub const CombatSide: u64 = 0x0000_0800;
+pub const TitleAndVassalChange: u64 = 0x0000_1000;
+pub const Faith: u64 = 0x0000_2000;
+pub const GreatHolyWar: u64 = 0x0000_4000;
+pub const Religion: u64 = 0x0000_8000;
+pub const War: u64 = 0x0001_0000;
+pub const StoryCycle: u64 = 0x0002_0000;
+pub const CasusBelli: u64 = 0x0004_0000;
+pub const Dynasty: u64 = 0x0008_0000;
+pub const DynastyHouse: u64 = 0x0010_0000;
+pub const Faction: u64 = 0x0020_0000;
+pub const Culture: u64 = 0x0040_0000;
+pub const Army: u64 = 0x0080_0000;
+pub const HolyOrder: u64 = 0x0100_0000;
+pub const CouncilTask: u64 = 0x0200_0000;
+pub const MercenaryCompany: u64 = 0x0400_0000;
+pub const Artifact: u64 = 0x0800_0000;
+pub const Inspiration: u64 = 0x1000_0000;
+pub const Struggle: u64 = 0x2000_0000;
+pub const CharacterMemory: u64 = 0x4000_0000;
+pub const TravelPlan: u64 = 0x8000_0000;
+pub const ActivityType: u64 = 0x0001_0000_0000;
+pub const Accolade: u64 = 0x0002_0000_0000;
+pub const AccoladeType: u64 = 0x0004_0000_0000;
+pub const Decision: u64 = 0x0008_0000_0000;
+pub const ALL: u64 = Scopes::all().bits();
+pub const ALL_BUT_NONE: u64 = ALL ^ None;
+
+/// A table entry for something the game runs with known scopes: its name, its root scope, and
+/// the named scopes that the game sets for it
+pub type NamedScopesEntry = (&'static str, u64, &'static [(&'static str, u64)]);
 
 pub fn scope_from_snake_case(s: &str) -> Option<Scopes> {
     Some(match s {
@@ -135,6 +150,11 @@ pub fn scope_from_snake_case(s: &str) -> Option<Scopes> {
         "inspiration" => Scopes::Inspiration,
         "struggle" => Scopes::Struggle,
         "character_memory" => Scopes::CharacterMemory,
+        "travel_plan" => Scopes::TravelPlan,
+        "activity_type" => Scopes::ActivityType,
+        "accolade" => Scopes::Accolade,
+        "accolade_type" => Scopes::AccoladeType,
+        "decision" => Scopes::Decision,
         _ => return std::option::Option::None,
     })
 }
@@ -163,6 +183,45 @@ pub fn scope_prefix(prefix: &str) -> Option<(Scopes, Scopes)> {
     std::option::Option::None
 }
 
+/// Report a part of a scope chain that is not a known link, with the closest name among the
+/// links and `others` if it looks like a typo. `others` are the effects or triggers that the
+/// part could also have been, if it's the whole key.
+pub fn unknown_link<'a>(part: &Token, others: impl Iterator<Item = &'a str>) {
+    let msg = format!("unknown token `{part}`");
+    let candidates = SCOPE_TO_SCOPE.iter().map(|(_, s, _)| *s).chain(others);
+    match suggest(part.as_str(), candidates) {
+        Some(suggestion) => error_info(part, ErrorKey::Validation, &msg, &suggestion.info()),
+        std::option::Option::None => error(part, ErrorKey::Validation, &msg),
+    }
+}
+
+/// The names of the iterators with each of these prefixes, like `every_vassal` for `every_`
+pub fn iterator_names(prefixes: &[&str]) -> Vec<String> {
+    let mut vec = Vec::new();
+    for (_, s, _) in SCOPE_ITERATOR {
+        for prefix in prefixes {
+            vec.push(format!("{prefix}{s}"));
+        }
+    }
+    vec
+}
+
+/// Report a prefix like `titel:` that is not known, with the closest prefix if it looks
+/// like a typo. `part` is the whole part of the chain that the prefix is in.
+pub fn unknown_prefix(prefix: &Token, part: &Token) {
+    let msg = format!("unknown prefix `{prefix}:`");
+    match closest_key(
+        prefix.as_str(),
+        SCOPE_FROM_PREFIX.iter().map(|(_, s, _)| *s),
+    ) {
+        Some(close) => {
+            let info = format!("did you mean `{close}:`?");
+            error_info(part, ErrorKey::Validation, &msg, &info);
+        }
+        std::option::Option::None => error(part, ErrorKey::Validation, &msg),
+    }
+}
+
 pub fn scope_value(name: &Token, data: &Everything) -> Option<Scopes> {
     for (from, s) in SCOPE_VALUE {
         if name.is(s) {
@@ -320,6 +379,21 @@ impl Display for Scopes {
             if self.contains(Scopes::CharacterMemory) {
                 vec.push("character memory");
             }
+            if self.contains(Scopes::TravelPlan) {
+                vec.push("travel plan");
+            }
+            if self.contains(Scopes::ActivityType) {
+                vec.push("activity type");
+            }
+            if self.contains(Scopes::Accolade) {
+                vec.push("accolade");
+            }
+            if self.contains(Scopes::AccoladeType) {
+                vec.push("accolade type");
+            }
+            if self.contains(Scopes::Decision) {
+                vec.push("decision");
+            }
             for i in 0..vec.len() {
                 write!(f, "{}", vec[i])?;
                 if i + 1 == vec.len() {
@@ -337,9 +411,13 @@ impl Display for Scopes {
 /// LAST UPDATED VERSION 1.7.0
 /// See `event_targets.log` from the game data dumps
 /// These are scope transitions that can be chained like `root.joined_faction.faction_leader`
-const SCOPE_TO_SCOPE: &[(u32, &str, u32)] = &[
+pub const SCOPE_TO_SCOPE: &[(u64, &str, u64)] = &[
+    (Accolade, "acclaimed_knight", Character),
+    (Accolade, "accolade_owner", Character),
+    (Accolade, "accolade_type", AccoladeType),
     (Character, "activity", Activity),
     (Activity, "activity_owner", Character),
+    (Activity, "activity_type", ActivityType),
     (Activity, "activity_province", Province),
     (Army, "army_commander", Character),
     (Army, "army_owner", Character),
@@ -373,6 +451,7 @@ const SCOPE_TO_SCOPE: &[(u32, &str, u32)] = &[
     (Character | LandedTitle | Province, "culture", Culture),
     (Culture, "culture_head", Character),
     (LandedTitle, "current_heir", Character),
+    (Character, "current_travel_plan", TravelPlan),
     (LandedTitle, "de_facto_liege", LandedTitle),
     (LandedTitle, "de_jure_liege", LandedTitle),
     (Character, "designated_heir", Character),
@@ -380,7 +459,7 @@ const SCOPE_TO_SCOPE: &[(u32, &str, u32)] = &[
     (None, "dummy_female", Character),
     (None, "dummy_male", Character),
     (Dynasty, "dynast", Character),
-    (Character, "dynasty", Dynasty),
+    (Character | DynastyHouse, "dynasty", Dynasty),
     (LandedTitle | Province, "empire", LandedTitle),
     (Character, "employer", Character),
     (CombatSide, "enemy_side", CombatSide),
@@ -470,6 +549,7 @@ const SCOPE_TO_SCOPE: &[(u32, &str, u32)] = &[
     (LandedTitle, "title_capital_county", LandedTitle),
     (LandedTitle, "title_province", Province),
     (Character, "top_liege", Character),
+    (TravelPlan, "travel_plan_owner", Character),
     // "value" special
     (CasusBelli, "war", War),
     (None, "yes", Bool),
@@ -479,7 +559,9 @@ const SCOPE_TO_SCOPE: &[(u32, &str, u32)] = &[
 /// See `event_targets.log` from the game data dumps
 /// These are absolute scopes (like character:100000) and scope transitions that require
 /// a key (like `root.cp:councillor_steward`)
-const SCOPE_FROM_PREFIX: &[(u32, &str, u32)] = &[
+pub const SCOPE_FROM_PREFIX: &[(u64, &str, u64)] = &[
+    (None, "accolade_type", AccoladeType),
+    (None, "activity_type", ActivityType),
     (Character, "aptitude", Value),
     (None, "array_define", Value),
     (None, "character", Character),
@@ -487,6 +569,7 @@ const SCOPE_FROM_PREFIX: &[(u32, &str, u32)] = &[
     (Character, "court_position", Character),
     (Character, "cp", Character), // councillor
     (None, "culture", Culture),
+    (None, "decision", Decision),
     (None, "define", Value),
     (None, "dynasty", Dynasty),
     (None, "event_id", Flag),
@@ -494,6 +577,7 @@ const SCOPE_FROM_PREFIX: &[(u32, &str, u32)] = &[
     (None, "flag", Flag),
     (None, "global_var", ALL),
     (None, "house", DynastyHouse),
+    (None, "list_size", Value),
     (None, "local_var", ALL),
     (CharacterMemory, "memory_participant", Character),
     (None, "province", Province),
@@ -508,7 +592,8 @@ const SCOPE_FROM_PREFIX: &[(u32, &str, u32)] = &[
 /// LAST UPDATED VERSION 1.7.0
 /// See `triggers.log` from the game data dumps
 /// These are 'triggers' that return a value.
-const SCOPE_VALUE: &[(u32, &str)] = &[
+pub const SCOPE_VALUE: &[(u64, &str)] = &[
+    (Accolade, "accolade_rank"),
     (LandedTitle, "active_de_jure_drift_progress"),
     (Character, "age"),
     (Character, "ai_boldness"),
@@ -734,7 +819,9 @@ const SCOPE_VALUE: &[(u32, &str)] = &[
 /// See `effects.log` from the game data dumps
 /// These are the list iterators. Every entry represents
 /// a every_, ordered_, random_, and any_ version.
-const SCOPE_ITERATOR: &[(u32, &str, u32)] = &[
+pub const SCOPE_ITERATOR: &[(u64, &str, u64)] = &[
+    (Character, "accolade", Accolade),
+    (Character, "active_accolade", Accolade),
     (Activity, "activity_declined", Character),
     (Activity, "activity_invited", Character),
     (Character, "alert_creatable_title", LandedTitle),
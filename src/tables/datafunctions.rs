@@ -0,0 +1,40 @@
+use crate::scopes::*;
+
+/// Data functions in localization that only exist for one type of scope, so that a scope
+/// they are called on must have that type. Functions like `GetName` that many types have are
+/// left out, since they don't tell anything.
+/// Taken from `data_types` logs from the game.
+/// LAST UPDATED VERSION 1.9.0
+pub const DATAFUNCTION_SCOPES: &[(&str, u64)] = &[
+    ("GetAge", Character),
+    ("GetDaughterSon", Character),
+    ("GetFirstName", Character),
+    ("GetFirstNameNoTooltip", Character),
+    ("GetHerHim", Character),
+    ("GetHerHis", Character),
+    ("GetHerselfHimself", Character),
+    ("GetHersHis", Character),
+    ("GetLadyLord", Character),
+    ("GetLiege", Character),
+    ("GetMotherFather", Character),
+    ("GetPrimaryTitle", Character),
+    ("GetQueenKing", Character),
+    ("GetSheHe", Character),
+    ("GetShortUIName", Character),
+    ("GetShortUINameNoTooltip", Character),
+    ("GetSisterBrother", Character),
+    ("GetTitledFirstName", Character),
+    ("GetTitledFirstNameNoTooltip", Character),
+    ("GetWifeHusband", Character),
+    ("GetWomanMan", Character),
+    ("GetBaseName", LandedTitle),
+    ("GetBaseNameNoTooltip", LandedTitle),
+    ("GetHolder", LandedTitle),
+    ("GetNameNoTier", LandedTitle),
+    ("GetNameNoTierNoTooltip", LandedTitle),
+    ("GetAdherentName", Faith),
+    ("GetAdherentNamePlural", Faith),
+    ("GetDevilName", Faith),
+    ("GetHighGodName", Faith),
+    ("GetReligiousHeadTitleName", Faith),
+];
@@ -0,0 +1,85 @@
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error_info, warn_info};
+use crate::token::Token;
+use crate::version::GameVersion;
+
+use Change::*;
+use Command::*;
+
+/// The kinds of script commands that can be renamed or removed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Effect,
+    Trigger,
+    /// A link in a scope chain, like `liege`
+    Link,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Change {
+    /// The command is gone, with the command that does its job now if there is one
+    Removed(Option<&'static str>),
+    /// The command has a new name, and the old name still works for now
+    Renamed(&'static str),
+}
+
+const V1_5: GameVersion = GameVersion::new(1, 5);
+const V1_6: GameVersion = GameVersion::new(1, 6);
+const V1_7: GameVersion = GameVersion::new(1, 7);
+
+/// LAST UPDATED VERSION 1.7.0
+/// The script commands that were removed or renamed, with the version that changed them.
+/// New entries go at the end of their version.
+const DEPRECATED: &[(GameVersion, Command, &str, Change)] = &[
+    (V1_5, Effect, "change_focus", Removed(Some("set_focus"))),
+    (V1_5, Trigger, "has_focus_type", Renamed("has_focus")),
+    (
+        V1_6,
+        Trigger,
+        "has_doctrine_parameter_value",
+        Removed(Some("has_doctrine_parameter")),
+    ),
+    (V1_6, Link, "realm_capital", Renamed("capital_province")),
+    (
+        V1_7,
+        Effect,
+        "add_opinion_with_memory",
+        Removed(Some("add_opinion")),
+    ),
+];
+
+/// Report `part` if it's a command of one of these kinds that was removed or renamed by
+/// `version`. Returns whether it was in the table, in which case it needs no other report.
+pub fn report_deprecated(part: &Token, kinds: &[Command], version: GameVersion) -> bool {
+    for &(changed, kind, name, change) in DEPRECATED {
+        if !kinds.contains(&kind) || !part.is(name) {
+            continue;
+        }
+        // The command still works in the version being checked
+        if version < changed {
+            return true;
+        }
+        match change {
+            Removed(replacement) => {
+                let msg =
+                    format!("`{part}` was removed in {changed}, and you are targeting {version}");
+                match replacement {
+                    Some(replacement) => {
+                        let info = format!("use `{replacement}` instead");
+                        error_info(part, ErrorKey::Deprecated, &msg, &info);
+                    }
+                    None => error(part, ErrorKey::Deprecated, &msg),
+                }
+            }
+            Renamed(new_name) => {
+                let msg = format!("`{part}` was renamed to `{new_name}` in {changed}");
+                let info = format!(
+                    "the old name still works in {version}, but may be removed in a later version"
+                );
+                warn_info(part, ErrorKey::Deprecated, &msg, &info);
+            }
+        }
+        return true;
+    }
+    false
+}
@@ -0,0 +1,94 @@
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error_info, warn_info};
+use crate::helpers::closest_key;
+use crate::token::Token;
+
+/// The DLC names accepted by `has_dlc`, paired with the `has_dlc_feature` flag they enable
+/// where there is a direct equivalent.
+const DLC: &[(&str, Option<&str>)] = &[
+    (
+        "Fashion of the Abbasid Court",
+        Some("fashion_of_the_abbasid_court"),
+    ),
+    ("The Northern Lords", Some("the_northern_lords")),
+    (
+        "Garments of the Holy Roman Empire",
+        Some("garments_of_the_hre"),
+    ),
+    ("Royal Court", Some("royal_court")),
+    ("Fate of Iberia", Some("the_fate_of_iberia")),
+    ("Friends and Foes", Some("friends_and_foes")),
+    ("Tours and Tournaments", Some("tours_and_tournaments")),
+    ("Elegance of the Empire", Some("elegance_of_the_empire")),
+    ("Wards and Wardens", Some("wards_and_wardens")),
+    ("Couture of the Capets", Some("couture_of_the_capets")),
+    ("Legacy of Persia", Some("legacy_of_persia")),
+    ("Expansion Pass", None),
+];
+
+/// The flags accepted by `has_dlc_feature` that are not tied to a single DLC name.
+const DLC_FEATURES: &[&str] = &[
+    "hybridize_culture",
+    "diverge_culture",
+    "court_artifacts",
+    "royal_court_court_type",
+    "struggle",
+    "advanced_activities",
+    "accolades",
+];
+
+fn dlc_features() -> impl Iterator<Item = &'static str> {
+    DLC.iter()
+        .filter_map(|(_, feature)| *feature)
+        .chain(DLC_FEATURES.iter().copied())
+}
+
+pub fn validate_has_dlc(token: &Token) {
+    if DLC.iter().any(|(name, _)| token.is(name)) {
+        return;
+    }
+    if let Some((name, _)) = DLC
+        .iter()
+        .find(|(_, feature)| *feature == Some(token.as_str()))
+    {
+        let msg = format!("`{token}` is a feature flag, not a DLC name");
+        let info = format!("use `has_dlc_feature = {token}` or `has_dlc = \"{name}\"`");
+        warn_info(token, ErrorKey::Validation, &msg, &info);
+    } else if DLC_FEATURES.contains(&token.as_str()) {
+        let msg = format!("`{token}` is a feature flag, not a DLC name");
+        let info = format!("use `has_dlc_feature = {token}`");
+        warn_info(token, ErrorKey::Validation, &msg, &info);
+    } else {
+        let msg = format!("unknown DLC `{token}`");
+        if let Some(name) = closest_key(token.as_str(), DLC.iter().map(|(name, _)| *name)) {
+            let info = format!("did you mean `{name}`?");
+            error_info(token, ErrorKey::Validation, &msg, &info);
+        } else {
+            error(token, ErrorKey::Validation, &msg);
+        }
+    }
+}
+
+pub fn validate_has_dlc_feature(token: &Token) {
+    if dlc_features().any(|feature| token.is(feature)) {
+        return;
+    }
+    if let Some((_, feature)) = DLC.iter().find(|(name, _)| token.is(name)) {
+        let msg = format!("`{token}` is a DLC name, not a feature flag");
+        let info = match feature {
+            Some(feature) => {
+                format!("use `has_dlc = \"{token}\"` or `has_dlc_feature = {feature}`")
+            }
+            None => format!("use `has_dlc = \"{token}\"`"),
+        };
+        warn_info(token, ErrorKey::Validation, &msg, &info);
+    } else {
+        let msg = format!("unknown DLC feature `{token}`");
+        if let Some(feature) = closest_key(token.as_str(), dlc_features()) {
+            let info = format!("did you mean `{feature}`?");
+            error_info(token, ErrorKey::Validation, &msg, &info);
+        } else {
+            error(token, ErrorKey::Validation, &msg);
+        }
+    }
+}
@@ -27,7 +27,7 @@ pub enum SpecialEffect {
     ActivateCatalyst,
     ArtifactHistory,
     ArtifactTitleHistory,
-    AddCharacterFlag,
+    AddFlag,
     AddModifier,
     AddFromContribution,
     AddHook,
@@ -36,6 +36,7 @@ pub enum SpecialEffect {
     RelationFlag,
     AddSchemeCooldown,
     AddToList,
+    AddTraitXp,
     AddTruce,
     AssignCouncilTask,
     AssignCouncillor,
@@ -66,8 +67,10 @@ pub enum SpecialEffect {
     FactionStartWar,
     AddToScheme,
     ForceVote,
+    GeneratePoolCharacter,
     Imprison,
     JoinFactionForced,
+    LeaseOutTo,
     MakePregnant,
     MoveBudget,
     OpenInteraction,
@@ -77,12 +80,15 @@ pub enum SpecialEffect {
     RemoveGuest,
     RemoveOpinion,
     ReplaceCourtPosition,
+    ResolveTitleChange,
     RoundVariable,
     RunInteraction,
     SaveOpinion,
     SaveValue,
+    SetCapital,
     SetCoa,
     SetCultureName,
+    SetTitleName,
     SetVariable,
     SetGhwTarget,
     SetRelation,
@@ -99,6 +105,7 @@ pub enum SpecialEffect {
     TriggerEvent,
     CreateImportantAction,
     CreateSuggestion,
+    StartTravelPlan,
     VassalContract,
 }
 
@@ -131,6 +138,13 @@ pub fn scope_effect(name: &Token, data: &Everything) -> Option<(Scopes, Effect)>
             return Some((Scopes::from_bits_truncate(*from), *effect));
         }
     }
+    if data.travel_options.in_game() {
+        for (from, s, effect) in SCOPE_TRAVEL_EFFECT {
+            if lwname == *s {
+                return Some((Scopes::from_bits_truncate(*from), *effect));
+            }
+        }
+    }
     if let Some(x) = lwname.strip_suffix("_perk_points") {
         if let Some(lifestyle) = x.strip_prefix("add_") {
             data.verify_exists_implied(Item::Lifestyle, lifestyle, name);
@@ -157,9 +171,22 @@ pub fn scope_effect(name: &Token, data: &Everything) -> Option<(Scopes, Effect)>
     std::option::Option::None
 }
 
+/// Effects that log errors or crash when their preconditions are not met, together with the
+/// triggers that check those preconditions. A name ending in `_` is a prefix.
+pub const EFFECT_GUARDS: &[(&str, &[&str])] = &[
+    ("destroy_title", &["is_title_created", "primary_title"]),
+    ("divorce", &["is_married", "is_spouse_of", "is_consort_of"]),
+    (
+        "make_pregnant",
+        &["is_female", "is_male", "sex_opposite_of", "sex_same_as"],
+    ),
+    ("remove_trait", &["has_trait"]),
+    ("set_relation_", &["exists", "is_alive"]),
+];
+
 /// LAST UPDATED VERSION 1.7.0
 /// See `effects.log` from the game data dumps
-const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
+pub const SCOPE_EFFECT: &[(u64, &str, Effect)] = &[
     (
         Activity,
         "accept_invitation_for_character",
@@ -186,10 +213,12 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (War, "add_attacker", Scope(Scopes::Character)),
     (Province, "add_building", Item(Item::Building)),
     (Province, "add_building_slot", Integer),
-    (Character, "add_character_flag", Special(AddCharacterFlag)),
+    (Character, "add_character_flag", Special(AddFlag)),
     (Character, "add_character_modifier", Special(AddModifier)),
+    (LandedTitle, "add_county_flag", Special(AddFlag)),
     (LandedTitle, "add_county_modifier", Special(AddModifier)),
     (Character, "add_courtier", Scope(Scopes::Character)),
+    (Culture, "add_culture_flag", Special(AddFlag)),
     (Culture, "add_culture_tradition", Item(Item::Tradition)),
     (War, "add_defender", Scope(Scopes::Character)),
     (Character, "add_diplomacy_skill", Effect::Value),
@@ -201,6 +230,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (Dynasty, "add_dynasty_prestige", Effect::Value),
     (Dynasty, "add_dynasty_prestige_level", Effect::Value),
     (Faction, "add_faction_discontent", Effect::Value),
+    (Faith, "add_faith_flag", Special(AddFlag)),
     (Character, "add_focus_progress", Effect::Value),
     (
         CasusBelli,
@@ -247,6 +277,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (Character, "add_prestige_experience", Effect::Value),
     (Character, "add_prestige_level", Effect::Value),
     (Character, "add_prestige_no_experience", Effect::Value),
+    (Province, "add_province_flag", Special(AddFlag)),
     (Province, "add_province_modifier", Special(AddModifier)),
     (Character, "add_prowess_skill", Effect::Value),
     (
@@ -264,6 +295,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
         "add_random_valid_tradition_replace_if_necessary",
         Scope(Scopes::Character),
     ),
+    (Character, "add_realm_flag", Special(AddFlag)),
     (Character, "add_realm_law", Item(Item::Law)),
     (Character, "add_realm_law_skip_effects", Item(Item::Law)),
     (Character, "add_relation_flag", Special(RelationFlag)),
@@ -305,6 +337,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (None, "add_to_variable_list", Special(AddToList)),
     (Character, "add_trait", Item(Item::Trait)),
     (Character, "add_trait_force_tooltip", Item(Item::Trait)),
+    (Character, "add_trait_xp", Special(AddTraitXp)),
     (Character, "add_truce_both_ways", Special(AddTruce)),
     (Character, "add_truce_one_way", Special(AddTruce)),
     (Character, "add_tyranny", Effect::Value),
@@ -562,6 +595,11 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (Character, "force_character_skill_recalculation", Yes),
     (Character, "force_vote_as", Special(ForceVote)),
     (Province, "generate_building", Yes),
+    (
+        Character,
+        "generate_pool_character",
+        Special(GeneratePoolCharacter),
+    ),
     // not sure what the argument to generate_coa means
     (
         LandedTitle | Dynasty | DynastyHouse,
@@ -601,7 +639,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
         "learn_language_of_culture",
         Scope(Scopes::Culture),
     ),
-    (LandedTitle, "lease_out_to", Scope(Scopes::HolyOrder)),
+    (LandedTitle, "lease_out_to", Special(LeaseOutTo)),
     (Culture, "leave_era", Item(Item::CultureEra)),
     (Character, "leave_faction", Scope(Scopes::Faction)),
     (CombatSide, "lose_combat", Effect::Bool),
@@ -716,11 +754,14 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (Character, "remove_claim", Scope(Scopes::LandedTitle)),
     (Army, "remove_commanded", Yes),
     (Character, "remove_concubine", Scope(Scopes::Character)),
+    (LandedTitle, "remove_county_flag", Unchecked),
     (LandedTitle, "remove_county_modifier", Item(Item::Modifier)),
     (Character, "remove_courtier_or_guest", Special(RemoveGuest)),
+    (Culture, "remove_culture_flag", Unchecked),
     (Culture, "remove_culture_tradition", Item(Item::Tradition)),
     (Character, "remove_decision_cooldown", Item(Item::Decision)),
     (Faith, "remove_doctrine", Item(Item::Doctrine)),
+    (Faith, "remove_faith_flag", Unchecked),
     (Dynasty, "remove_dynasty_modifier", Item(Item::Modifier)),
     (Dynasty, "remove_dynasty_perk", Item(Item::DynastyPerk)),
     (ALL_BUT_NONE, "remove_from_list", Unchecked),
@@ -768,8 +809,10 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
         "remove_personal_artifact_claim",
         Scope(Scopes::Artifact),
     ),
+    (Province, "remove_province_flag", Unchecked),
     (Province, "remove_province_modifier", Item(Item::Modifier)),
     (Culture, "remove_random_culture_tradition", Yes),
+    (Character, "remove_realm_flag", Unchecked),
     (Character, "remove_realm_law", Item(Item::Law)),
     (Character, "remove_relation_flag", Special(RelationFlag)),
     (Faith, "remove_religious_head_title", Yes),
@@ -804,7 +847,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (
         None,
         "resolve_title_and_vassal_change",
-        Scope(Scopes::TitleAndVassalChange),
+        Special(ResolveTitleChange),
     ),
     (Character, "return_to_court", Yes),
     (Secret, "reveal_to", Scope(Scopes::Character)),
@@ -868,11 +911,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (War, "set_called_to", Scope(Scopes::Character)),
     (LandedTitle, "set_can_be_named_after_dynasty", Effect::Bool),
     (LandedTitle, "set_capital_barony", Yes),
-    (
-        LandedTitle,
-        "set_capital_county",
-        Scope(Scopes::LandedTitle),
-    ),
+    (LandedTitle, "set_capital_county", Special(SetCapital)),
     (War, "set_casus_belli", Item(Item::CasusBelli)),
     (Character, "set_character_faith", Scope(Scopes::Faith)),
     (
@@ -1003,7 +1042,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
         Scope(Scopes::LandedTitle),
     ),
     (Character, "set_real_father", Scope(Scopes::Character)),
-    (Character, "set_realm_capital", Scope(Scopes::LandedTitle)),
+    (Character, "set_realm_capital", Special(SetCapital)),
     (
         Faith,
         "set_religious_head_title",
@@ -1015,7 +1054,7 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     (Artifact, "set_should_decay", Effect::Bool),
     (Faction, "set_special_character", Scope(Scopes::Character)),
     (Faction, "set_special_title", Scope(Scopes::LandedTitle)),
-    (LandedTitle, "set_title_name", Item(Item::Localization)),
+    (LandedTitle, "set_title_name", Special(SetTitleName)),
     (LandedTitle, "set_title_prefix", Item(Item::Localization)),
     (Character, "set_to_lowborn", Yes),
     (Character, "set_trait_rank", Special(SetTraitRank)),
@@ -1101,3 +1140,12 @@ const SCOPE_EFFECT: &[(u32, &str, Effect)] = &[
     // TODO special: remove_relation_<relation>
     // TODO special: set_relation_<relation>
 ];
+
+/// LAST UPDATED VERSION 1.9.0
+/// The effects of the travel system. They are only known if the game has travel options,
+/// because older versions of the game don't have them.
+pub const SCOPE_TRAVEL_EFFECT: &[(u64, &str, Effect)] = &[
+    (TravelPlan, "add_travel_option", Item(Item::TravelOption)),
+    (TravelPlan, "remove_travel_option", Item(Item::TravelOption)),
+    (Character, "start_travel_plan", Special(StartTravelPlan)),
+];
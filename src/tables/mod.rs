@@ -1,2 +1,7 @@
+pub mod datafunctions;
+pub mod deprecated;
+pub mod dlc;
 pub mod effects;
+pub mod on_actions;
+pub mod scripted_rules;
 pub mod triggers;
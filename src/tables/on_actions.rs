@@ -0,0 +1,124 @@
+use crate::scopes::*;
+
+/// The root scopes of the vanilla `on_actions`, and the named scopes the game sets when firing
+/// them. Named scopes that are only sometimes set (such as `scope:killer` for `on_death`) are
+/// included, because their type is still known when they do exist.
+/// Taken from `common/on_action/_on_actions.info` and the vanilla `on_action` files.
+/// LAST UPDATED VERSION 1.7.0
+pub const ON_ACTION_SCOPES: &[NamedScopesEntry] = &[
+    ("on_game_start", None, &[]),
+    ("on_game_start_after_lobby", None, &[]),
+    ("yearly_global_pulse", None, &[]),
+    ("yearly_playable_pulse", Character, &[]),
+    ("quarterly_playable_pulse", Character, &[]),
+    ("three_year_playable_pulse", Character, &[]),
+    ("five_year_playable_pulse", Character, &[]),
+    ("random_yearly_playable_pulse", Character, &[]),
+    ("random_yearly_everyone_pulse", Character, &[]),
+    (
+        "on_birth_child",
+        Character,
+        &[
+            ("mother", Character),
+            ("father", Character),
+            ("real_father", Character),
+        ],
+    ),
+    (
+        "on_birth_mother",
+        Character,
+        &[
+            ("child", Character),
+            ("father", Character),
+            ("real_father", Character),
+        ],
+    ),
+    (
+        "on_birth_father",
+        Character,
+        &[
+            ("child", Character),
+            ("mother", Character),
+            ("real_father", Character),
+        ],
+    ),
+    (
+        "on_birth_real_father",
+        Character,
+        &[
+            ("child", Character),
+            ("mother", Character),
+            ("father", Character),
+        ],
+    ),
+    ("on_pregnancy_mother", Character, &[("father", Character)]),
+    ("on_pregnancy_father", Character, &[("mother", Character)]),
+    ("on_16th_birthday", Character, &[]),
+    ("on_death", Character, &[("killer", Character)]),
+    ("on_natural_death_second_chance", Character, &[]),
+    ("on_marriage", Character, &[("spouse", Character)]),
+    ("on_divorce", Character, &[("spouse", Character)]),
+    ("on_concubinage", Character, &[("concubine", Character)]),
+    ("on_join_court", Character, &[]),
+    ("on_leave_court", Character, &[]),
+    ("on_imprison", Character, &[("imprisoner", Character)]),
+    (
+        "on_release_from_prison",
+        Character,
+        &[("imprisoner", Character)],
+    ),
+    (
+        "on_title_gain",
+        Character,
+        &[("title", LandedTitle), ("previous_holder", Character)],
+    ),
+    (
+        "on_title_gain_inheritance",
+        Character,
+        &[("title", LandedTitle), ("previous_holder", Character)],
+    ),
+    (
+        "on_title_gain_usurpation",
+        Character,
+        &[("title", LandedTitle), ("previous_holder", Character)],
+    ),
+    (
+        "on_title_lost",
+        Character,
+        &[("title", LandedTitle), ("new_holder", Character)],
+    ),
+    ("on_title_destroyed", Character, &[("title", LandedTitle)]),
+    ("on_realm_capital_change", Character, &[]),
+    (
+        "on_character_faith_change",
+        Character,
+        &[("old_faith", Faith)],
+    ),
+    (
+        "on_character_culture_change",
+        Character,
+        &[("old_culture", Culture)],
+    ),
+    (
+        "on_county_faith_change",
+        LandedTitle,
+        &[("old_faith", Faith)],
+    ),
+    (
+        "on_county_culture_change",
+        LandedTitle,
+        &[("old_culture", Culture)],
+    ),
+    ("on_prestige_level_gain", Character, &[]),
+    ("on_prestige_level_loss", Character, &[]),
+    ("on_piety_level_gain", Character, &[]),
+    ("on_piety_level_loss", Character, &[]),
+    ("on_war_started", Character, &[("war", War)]),
+    ("on_war_won_attacker", Character, &[("war", War)]),
+    ("on_war_won_defender", Character, &[("war", War)]),
+    ("on_war_white_peace", Character, &[("war", War)]),
+    ("on_join_war_as_secondary", Character, &[("war", War)]),
+    ("on_leave_war_as_secondary", Character, &[("war", War)]),
+    ("on_combat_end_winner", CombatSide, &[]),
+    ("on_combat_end_loser", CombatSide, &[]),
+];
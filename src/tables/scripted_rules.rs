@@ -0,0 +1,44 @@
+use crate::scopes::*;
+
+/// The scripted rules that the game asks about, with the root scope it evaluates them in and
+/// the named scopes it sets. A rule with a name that is not in this list is never used.
+/// Taken from the comments in the vanilla `common/scripted_rules` files.
+/// LAST UPDATED VERSION 1.9.0
+pub const SCRIPTED_RULE_SCOPES: &[NamedScopesEntry] = &[
+    ("can_marry", Character, &[("spouse", Character)]),
+    ("can_be_betrothed", Character, &[("betrothed", Character)]),
+    ("can_take_concubine", Character, &[("concubine", Character)]),
+    (
+        "is_valid_heir",
+        Character,
+        &[("holder", Character), ("title", LandedTitle)],
+    ),
+    (
+        "is_valid_designated_heir",
+        Character,
+        &[("holder", Character)],
+    ),
+    (
+        "can_be_granted_title",
+        Character,
+        &[("liege", Character), ("title", LandedTitle)],
+    ),
+    ("can_be_guardian", Character, &[("ward", Character)]),
+    ("can_be_knight", Character, &[("liege", Character)]),
+    ("can_be_commander", Character, &[("liege", Character)]),
+    (
+        "can_be_councillor",
+        Character,
+        &[("liege", Character), ("council_task", CouncilTask)],
+    ),
+    ("can_be_invited_to_court", Character, &[("host", Character)]),
+    ("can_leave_court", Character, &[("liege", Character)]),
+    ("can_be_imprisoned", Character, &[("imprisoner", Character)]),
+    ("can_join_activity", Character, &[("activity", Activity)]),
+    ("can_travel", Character, &[]),
+    (
+        "can_join_faction",
+        Character,
+        &[("faction", Faction), ("target", Character)],
+    ),
+];
@@ -34,19 +34,26 @@ pub fn scope_trigger_bool(name: &str) -> Option<Scopes> {
     std::option::Option::None
 }
 
-pub fn scope_trigger_item(name: &str) -> Option<(Scopes, Item)> {
+pub fn scope_trigger_item(name: &str, data: &Everything) -> Option<(Scopes, Item)> {
     for (from, s, item) in SCOPE_TRIGGER_ITEM {
         if *s == name {
             return Some((Scopes::from_bits_truncate(*from), *item));
         }
     }
+    if data.travel_options.in_game() {
+        for (from, s, item) in SCOPE_TRAVEL_TRIGGER_ITEM {
+            if *s == name {
+                return Some((Scopes::from_bits_truncate(*from), *item));
+            }
+        }
+    }
     std::option::Option::None
 }
 
 /// LAST UPDATED VERSION 1.7.0
 /// See `triggers.log` from the game data dumps
 /// These are the triggers that do a simple comparison with a target scope item
-const SCOPE_TRIGGER_TARGET: &[(u32, &str, u32)] = &[
+pub const SCOPE_TRIGGER_TARGET: &[(u64, &str, u64)] = &[
     (Character, "can_attack_in_hierarchy", Character),
     (Character, "can_be_child_of", Character),
     (Artifact, "can_be_claimed_by", Character),
@@ -229,7 +236,8 @@ const SCOPE_TRIGGER_TARGET: &[(u32, &str, u32)] = &[
 /// LAST UPDATED VERSION 1.7.0
 /// See `triggers.log` from the game data dumps
 /// These are the triggers that take a simple yes or no
-const SCOPE_TRIGGER_BOOL: &[(u32, &str)] = &[
+pub const SCOPE_TRIGGER_BOOL: &[(u64, &str)] = &[
+    (Accolade, "is_accolade_active"),
     (Activity, "activity_has_been_activated"),
     (Character, "allowed_concubines"),
     (Character, "allowed_more_concubines"),
@@ -404,7 +412,7 @@ const SCOPE_TRIGGER_BOOL: &[(u32, &str)] = &[
 /// LAST UPDATED VERSION 1.7.0
 /// See `triggers.log` from the game data dumps
 /// These are the triggers that compare to an item type
-const SCOPE_TRIGGER_ITEM: &[(u32, &str, Item)] = &[
+pub const SCOPE_TRIGGER_ITEM: &[(u64, &str, Item)] = &[
     (Artifact, "artifact_slot_type", Item::ArtifactSlot),
     (Artifact, "artifact_type", Item::Artifact),
     (Character, "can_execute_decision", Item::Decision),
@@ -419,6 +427,7 @@ const SCOPE_TRIGGER_ITEM: &[(u32, &str, Item)] = &[
     ),
     (Faction, "faction_is_type", Item::Faction),
     (Province, "geographical_region", Item::Region),
+    (Activity, "has_activity_type", Item::Activity),
     (Artifact, "has_artifact_feature", Item::ArtifactFeature),
     (
         Artifact,
@@ -472,6 +481,7 @@ const SCOPE_TRIGGER_ITEM: &[(u32, &str, Item)] = &[
     (Culture, "has_innovation_flag", Item::InnovationFlag),
     (Inspiration, "has_inspiration_type", Item::Inspiration),
     (Character, "has_lifestyle", Item::Lifestyle),
+    (CombatSide, "has_combat_effect", Item::CombatEffect),
     (CombatSide, "has_maa_of_type", Item::MenAtArms),
     (Culture, "has_name_list", Item::NameList),
     (
@@ -494,9 +504,11 @@ const SCOPE_TRIGGER_ITEM: &[(u32, &str, Item)] = &[
         Item::StrugglePhaseParameter,
     ),
     (Character, "has_trait", Item::Trait),
+    (Character, "has_trait_with_flag", Item::TraitFlag),
     (LandedTitle, "has_title_law", Item::TitleLaw),
     (LandedTitle, "has_title_law_flag", Item::TitleLawFlag),
     (Culture, "has_unit_gfx", Item::UnitGfx),
+    (Character, "in_activity_type", Item::Activity),
     (Character, "is_decision_on_cooldown", Item::Decision),
     (Character, "is_leading_faction_type", Item::Faction),
     (Struggle, "is_struggle_phase", Item::StrugglePhase),
@@ -515,5 +527,25 @@ const SCOPE_TRIGGER_ITEM: &[(u32, &str, Item)] = &[
     (Province, "terrain", Item::Terrain),
     (Faith, "trait_is_sin", Item::Trait),
     (Faith, "trait_is_virtue", Item::Trait),
+    (
+        Character,
+        "vassal_contract_has_flag",
+        Item::VassalContractFlag,
+    ),
+    (
+        Character,
+        "vassal_contract_obligation_level_can_be_decreased",
+        Item::VassalObligation,
+    ),
+    (
+        Character,
+        "vassal_contract_obligation_level_can_be_increased",
+        Item::VassalObligation,
+    ),
     (War, "using_cb", Item::CasusBelli),
 ];
+
+/// LAST UPDATED VERSION 1.9.0
+/// The item triggers of the travel system, which are only known if the game has it
+pub const SCOPE_TRAVEL_TRIGGER_ITEM: &[(u64, &str, Item)] =
+    &[(TravelPlan, "has_travel_option", Item::TravelOption)];
@@ -1,9 +1,15 @@
+use fnv::FnvHashSet;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::ffi::OsStr;
 use std::fmt::{Display, Error, Formatter};
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use crate::block::Date;
+use crate::errorkey::ErrorKey;
+use crate::errors::{error, error_info, report, warn, warn_info, ErrorLevel};
 use crate::fileset::{FileEntry, FileKind};
 
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -12,6 +18,8 @@ pub struct Loc {
     pub kind: FileKind,
     /// line 0 means the loc applies to the file as a whole.
     pub line: usize,
+    /// Columns count Unicode scalar values (`char`s) starting from 1, so a tab or a
+    /// multi-byte character is one column.
     pub column: usize,
     pub offset: usize,
     /// Used in macro expansions to point to the macro invocation
@@ -64,15 +72,113 @@ impl Loc {
     }
 }
 
+/// The line and column just past the last character of a token.
+/// Used to underline the whole token when reporting it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct EndPos {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl EndPos {
+    /// Find the end of the text `s` if it starts at `loc`
+    fn after(loc: &Loc, s: &str) -> Self {
+        let mut end = EndPos {
+            line: loc.line,
+            column: loc.column,
+        };
+        for c in s.chars() {
+            if c == '\n' {
+                end.line += 1;
+                end.column = 1;
+            } else {
+                end.column += 1;
+            }
+        }
+        end
+    }
+}
+
+/// The text of all the live tokens, so that each different string is stored only once.
+/// Script repeats the same few keys and values, like `trigger` and `yes`, very often.
+#[derive(Debug, Default)]
+struct Interner {
+    strings: FnvHashSet<Rc<str>>,
+    /// The size of the set after the last time it was pruned.
+    pruned_len: usize,
+}
+
+impl Interner {
+    /// Return the stored copy of `s`, storing it first if it's new.
+    fn intern(&mut self, s: &str) -> Rc<str> {
+        if let Some(interned) = self.strings.get(s) {
+            return Rc::clone(interned);
+        }
+        if self.strings.len() >= 2 * self.pruned_len.max(4096) {
+            self.prune();
+        }
+        let interned: Rc<str> = Rc::from(s);
+        self.strings.insert(Rc::clone(&interned));
+        interned
+    }
+
+    /// Drop the strings that no token uses anymore, such as those of files that were
+    /// loaded and then thrown away. Done whenever the set has doubled in size, so that the
+    /// work stays in proportion to the number of strings stored.
+    fn prune(&mut self) {
+        self.strings.retain(|s| Rc::strong_count(s) > 1);
+        self.pruned_len = self.strings.len();
+    }
+}
+
+thread_local! {
+    static STRINGS: RefCell<Interner> = RefCell::default();
+}
+
+fn intern(s: &str) -> Rc<str> {
+    STRINGS.with(|strings| strings.borrow_mut().intern(s))
+}
+
 #[derive(Clone, Debug)]
 pub struct Token {
-    s: String,
+    s: Rc<str>,
     pub loc: Loc,
+    end: EndPos,
 }
 
 impl Token {
+    /// Create a token whose text is exactly what appears in the file at `loc`.
+    #[allow(clippy::needless_pass_by_value)] // callers have a String to give anyway
     pub fn new(s: String, loc: Loc) -> Self {
-        Token { s, loc }
+        Self::from_str(&s, loc)
+    }
+
+    fn from_str(s: &str, loc: Loc) -> Self {
+        let end = EndPos::after(&loc, s);
+        Token {
+            s: intern(s),
+            loc,
+            end,
+        }
+    }
+
+    /// Create a token whose text differs from what appears in the file, such as a quoted string
+    /// without its quotes, by giving where it ends in the file.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new_with_end(s: String, loc: Loc, end: &Loc) -> Self {
+        let end = EndPos {
+            line: end.line,
+            column: end.column,
+        };
+        Token {
+            s: intern(&s),
+            loc,
+            end,
+        }
+    }
+
+    pub fn end(&self) -> EndPos {
+        self.end
     }
 
     pub fn as_str(&self) -> &str {
@@ -80,49 +186,206 @@ impl Token {
     }
 
     pub fn is(&self, s: &str) -> bool {
-        self.s == s
+        &*self.s == s
     }
 
     pub fn split(&self, ch: char) -> Vec<Token> {
         let mut pos = 0;
         let mut vec = Vec::new();
         let mut loc = self.loc.clone();
-        let mut lines = 0;
-        for (cols, (i, c)) in self.s.char_indices().enumerate() {
+        let mut here = self.loc.clone();
+        for (i, c) in self.s.char_indices() {
+            if c == '\n' {
+                here.line += 1;
+                here.column = 1;
+            } else {
+                here.column += 1;
+            }
             if c == ch {
-                vec.push(Token::new(self.s[pos..i].to_string(), loc.clone()));
+                vec.push(Token::from_str(&self.s[pos..i], loc.clone()));
                 pos = i + 1;
                 loc.offset = self.loc.offset + i + 1;
-                loc.column = self.loc.column + cols + 1;
-                loc.line = self.loc.line + lines;
-            }
-            if c == '\n' {
-                lines += 1;
+                loc.column = here.column;
+                loc.line = here.line;
             }
         }
-        vec.push(Token::new(self.s[pos..].to_string(), loc));
+        vec.push(Token::from_str(&self.s[pos..], loc));
         vec
     }
 
     pub fn split_once(&self, ch: char) -> Option<(Token, Token)> {
-        for (cols, (i, c)) in self.s.char_indices().enumerate() {
-            if c == ch {
-                let token1 = Token::new(self.s[..i].to_string(), self.loc.clone());
-                let mut loc = self.loc.clone();
-                loc.offset += i + 1;
-                loc.column += cols + 1;
-                let token2 = Token::new(self.s[i + 1..].to_string(), loc);
-                return Some((token1, token2));
+        let i = self.s.find(ch)?;
+        let token1 = Token::from_str(&self.s[..i], self.loc.clone());
+        let mut loc = self.loc.clone();
+        let end = EndPos::after(&loc, &self.s[..=i]);
+        loc.offset += i + ch.len_utf8();
+        loc.line = end.line;
+        loc.column = end.column;
+        let token2 = Token::from_str(&self.s[i + ch.len_utf8()..], loc);
+        Some((token1, token2))
+    }
+
+    pub fn into_string(self) -> String {
+        self.s.to_string()
+    }
+
+    /// Parse the token as an integer, or report an error if it isn't one.
+    pub fn expect_integer(&self) -> Option<i64> {
+        self.expect_integer_at(ErrorLevel::Error)
+    }
+
+    /// Like `expect_integer`, but report at `level` if the token isn't an integer.
+    pub fn expect_integer_at(&self, level: ErrorLevel) -> Option<i64> {
+        let value = self.s.parse::<i64>().ok();
+        if value.is_none() {
+            report(
+                self,
+                level,
+                ErrorKey::Validation,
+                "expected integer",
+                None,
+                &[],
+            );
+        }
+        value
+    }
+
+    /// Parse the token as a number, or report an error if it isn't one.
+    pub fn expect_number(&self) -> Option<f64> {
+        let s = self.s.strip_prefix('-').unwrap_or(&self.s);
+        let valid = !s.is_empty()
+            && s.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && s.chars().filter(|&c| c == '.').count() <= 1;
+        if let (true, Ok(value)) = (valid, self.s.parse::<f64>()) {
+            self.check_precision();
+            Some(value)
+        } else {
+            if !self.check_number_format() {
+                error(self, ErrorKey::Validation, "expected number");
             }
+            None
         }
-        None
     }
 
-    pub fn into_string(self) -> String {
-        self.s
+    /// Warn if the token is a number with more decimals than the game keeps.
+    /// The game stores numbers as fixed-point values with 5 decimals,
+    /// so any further precision is silently lost.
+    pub fn check_precision(&self) {
+        if let Some((_, decimals)) = self.s.split_once('.') {
+            if decimals.len() > 5 && decimals.chars().all(|c| c.is_ascii_digit()) {
+                warn_info(
+                    self,
+                    ErrorKey::Range,
+                    "too many decimal places",
+                    "the game only uses 5 decimals and will truncate the rest",
+                );
+            }
+        }
+    }
+
+    /// Report numbers written the way they are written outside of script, like `10%` or
+    /// `1,000`, which the game does not understand. Return true if one was reported.
+    pub fn check_number_format(&self) -> bool {
+        if let Some(number) = self.s.strip_suffix('%') {
+            if let Ok(value) = number.parse::<f64>() {
+                let info = format!("write {} for {}", value / 100.0, self);
+                error_info(
+                    self,
+                    ErrorKey::Validation,
+                    "percent signs are not valid",
+                    &info,
+                );
+                return true;
+            }
+        }
+        if self.s.contains(',') {
+            let number = self.s.replace(',', "");
+            if !self.s.starts_with(',') && number.parse::<f64>().is_ok() {
+                let info = format!("write {number} for {self}");
+                let msg = "thousands separators are not valid";
+                error_info(self, ErrorKey::Validation, msg, &info);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Parse the token as a number and check that it is within the given range.
+    pub fn expect_number_range(&self, range: RangeInclusive<f64>) -> Option<f64> {
+        let value = self.expect_number()?;
+        if !range.contains(&value) {
+            let msg = format!("expected a value from {} to {}", range.start(), range.end());
+            warn(self, ErrorKey::Range, &msg);
+        }
+        Some(value)
+    }
+
+    /// Parse the token as a date in `year.month.day` format, and check that the month and day
+    /// actually exist. The game does not have leap years.
+    pub fn expect_date(&self) -> Option<Date> {
+        if let Ok(date) = Date::try_from(self) {
+            if !(1..=12).contains(&date.month()) {
+                error(self, ErrorKey::Range, "month must be from 1 to 12");
+                None
+            } else if !(1..=date.days_in_month()).contains(&date.day()) {
+                let msg = format!("day must be from 1 to {}", date.days_in_month());
+                error(self, ErrorKey::Range, &msg);
+                None
+            } else {
+                Some(date)
+            }
+        } else {
+            error(self, ErrorKey::Validation, "expected date");
+            None
+        }
+    }
+
+    /// Parse the token as `yes` or `no`, or report an error if it isn't one of those.
+    pub fn expect_bool(&self) -> Option<bool> {
+        self.expect_bool_at(ErrorLevel::Error)
+    }
+
+    /// Like `expect_bool`, but report at `level` if the token isn't `yes` or `no`.
+    /// Misspellings like `true` are still errors, because the game reads them as `no`.
+    pub fn expect_bool_at(&self, level: ErrorLevel) -> Option<bool> {
+        if self.is("yes") {
+            Some(true)
+        } else if self.is("no") {
+            Some(false)
+        } else {
+            if !self.check_bool_spelling() {
+                report(
+                    self,
+                    level,
+                    ErrorKey::Validation,
+                    "expected yes or no",
+                    None,
+                    &[],
+                );
+            }
+            None
+        }
+    }
+
+    /// Report an error if the token is a boolean spelled the way other games (or other
+    /// programming languages) spell it, such as `true` or `Yes`. Return true if it was.
+    pub fn check_bool_spelling(&self) -> bool {
+        if BOOL_MISSPELLINGS.contains(&self.as_str()) {
+            let msg = format!("expected yes or no, found `{self}`");
+            let info = "CK3 requires lowercase yes or no, and treats anything else as no";
+            error_info(self, ErrorKey::Validation, &msg, info);
+            true
+        } else {
+            false
+        }
     }
 }
 
+/// Spellings of booleans that the game does not understand
+const BOOL_MISSPELLINGS: &[&str] = &[
+    "true", "false", "True", "False", "TRUE", "FALSE", "Yes", "No", "YES", "NO", "1", "0",
+];
+
 /// Tokens are compared for equality regardless of their loc.
 impl PartialEq for Token {
     fn eq(&self, other: &Self) -> bool {
@@ -133,9 +396,11 @@ impl Eq for Token {}
 
 impl From<Loc> for Token {
     fn from(loc: Loc) -> Self {
+        let end = EndPos::after(&loc, "");
         Token {
-            s: String::new(),
+            s: intern(""),
             loc,
+            end,
         }
     }
 }
@@ -143,8 +408,9 @@ impl From<Loc> for Token {
 impl From<&Loc> for Token {
     fn from(loc: &Loc) -> Self {
         Token {
-            s: String::new(),
+            s: intern(""),
             loc: loc.clone(),
+            end: EndPos::after(loc, ""),
         }
     }
 }
@@ -1,15 +1,31 @@
 use crate::block::validator::Validator;
-use crate::block::{Block, BlockOrValue, Comparator, Date};
+use crate::block::{Block, BlockOrValue, Comparator};
 use crate::context::ScopeContext;
 use crate::data::scriptvalues::ScriptValue;
+use crate::desc::validate_desc;
+use crate::effect::check_nesting;
 use crate::errorkey::ErrorKey;
-use crate::errors::{advice_info, error, warn, warn_info};
+use crate::errors::{
+    advice_info, error, pause_logging, resume_logging, warn, warn_info, ErrorLevel,
+};
 use crate::everything::Everything;
+use crate::flags::FlagFamily;
 use crate::item::Item;
-use crate::scopes::{scope_iterator, scope_prefix, scope_to_scope, scope_value, Scopes};
-use crate::tables::triggers::{scope_trigger_bool, scope_trigger_item, scope_trigger_target};
+use crate::scopes::{
+    iterator_names, scope_iterator, scope_prefix, scope_to_scope, scope_value, unknown_link,
+    unknown_prefix, Scopes,
+};
+use crate::tables::deprecated::{report_deprecated, Command};
+use crate::tables::dlc::{validate_has_dlc, validate_has_dlc_feature};
+use crate::tables::triggers::{
+    scope_trigger_bool, scope_trigger_item, scope_trigger_target, SCOPE_TRIGGER_BOOL,
+    SCOPE_TRIGGER_ITEM, SCOPE_TRIGGER_TARGET,
+};
 use crate::token::Token;
-use crate::validate::{validate_days_weeks_months_years, validate_prefix_reference};
+use crate::validate::{
+    always_exists, check_qeq_target, validate_days_weeks_months_years, validate_macro_args,
+    validate_prefix_reference,
+};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum Caller {
@@ -18,6 +34,7 @@ pub enum Caller {
     CustomDescription,
     CustomTooltip,
     CalcTrueIf,
+    Modifier,
 
     // All lists should be below this entry, all non-lists above.
     AnyList,
@@ -40,6 +57,7 @@ pub fn validate_normal_trigger(
     validate_trigger(Caller::Normal, block, data, sc, tooltipped);
 }
 
+#[allow(clippy::too_many_lines)]
 pub fn validate_trigger(
     caller: Caller,
     block: &Block,
@@ -47,11 +65,13 @@ pub fn validate_trigger(
     sc: &mut ScopeContext,
     tooltipped: bool,
 ) {
+    check_nesting(block, data, sc);
     let mut seen_if = false;
 
     'outer: for (key, cmp, bv) in block.iter_items() {
         if let Some(key) = key {
             if key.is("limit") {
+                expect_eq(key, *cmp);
                 if caller == Caller::If {
                     if let Some(block) = bv.expect_block() {
                         validate_normal_trigger(block, data, sc, tooltipped);
@@ -62,12 +82,14 @@ pub fn validate_trigger(
                 continue;
             }
             if key.is("trigger_if") {
+                expect_eq(key, *cmp);
                 if let Some(block) = bv.expect_block() {
                     validate_trigger(Caller::If, block, data, sc, tooltipped);
                 }
                 seen_if = true;
                 continue;
             } else if key.is("trigger_else_if") {
+                expect_eq(key, *cmp);
                 if !seen_if {
                     error(
                         key,
@@ -80,6 +102,7 @@ pub fn validate_trigger(
                 }
                 continue;
             } else if key.is("trigger_else") {
+                expect_eq(key, *cmp);
                 if !seen_if {
                     error(
                         key,
@@ -95,6 +118,21 @@ pub fn validate_trigger(
             }
             seen_if = false;
 
+            if key.is("add") || key.is("factor") || key.is("desc") {
+                expect_eq(key, *cmp);
+                if caller == Caller::Modifier {
+                    if key.is("desc") {
+                        validate_desc(bv, data, sc);
+                    } else {
+                        ScriptValue::validate_bv(bv, data, sc);
+                    }
+                } else {
+                    let msg = format!("can only use `{key} =` in a `modifier` block");
+                    warn(key, ErrorKey::Validation, &msg);
+                }
+                continue;
+            }
+
             if key.is("percent") {
                 if caller < Caller::AnyList {
                     warn(
@@ -105,14 +143,9 @@ pub fn validate_trigger(
                     continue;
                 }
                 if let Some(token) = bv.get_value() {
-                    if let Ok(num) = token.as_str().parse::<f64>() {
-                        if num > 1.0 {
-                            warn(
-                                token,
-                                ErrorKey::Range,
-                                "'percent' here needs to be between 0 and 1",
-                            );
-                        }
+                    if token.as_str().parse::<f64>().is_ok() {
+                        token.expect_number_range(0.0..=1.0);
+                        continue;
                     }
                 }
                 ScriptValue::validate_bv(bv, data, sc);
@@ -137,6 +170,7 @@ pub fn validate_trigger(
             }
 
             if key.is("list") || key.is("variable") {
+                expect_eq(key, *cmp);
                 if caller != Caller::AnyInList {
                     let msg = format!("can only use `{} =` in `any_in_list`, `any_in_global_list`, or `any_in_local_list`", key);
                     warn(key, ErrorKey::Validation, &msg);
@@ -147,6 +181,7 @@ pub fn validate_trigger(
             }
 
             if key.is("type") {
+                expect_eq(key, *cmp);
                 if caller == Caller::AnyRelationType {
                     if let Some(token) = bv.expect_value() {
                         data.verify_exists(Item::Relation, token);
@@ -161,6 +196,7 @@ pub fn validate_trigger(
             }
 
             if key.is("province") {
+                expect_eq(key, *cmp);
                 if caller != Caller::AnyProvince {
                     let msg = format!("can only use `{} =` in `any_pool_character` list", key);
                     warn(key, ErrorKey::Validation, &msg);
@@ -173,6 +209,7 @@ pub fn validate_trigger(
             }
 
             if key.is("even_if_dead") || key.is("only_if_dead") {
+                expect_eq(key, *cmp);
                 if caller < Caller::AnyList || !sc.can_be(Scopes::Character) {
                     let msg = format!("can only use `{} =` in a character list", key);
                     warn(key, ErrorKey::Validation, &msg);
@@ -184,6 +221,7 @@ pub fn validate_trigger(
             }
 
             if key.is("involvement") {
+                expect_eq(key, *cmp);
                 if caller != Caller::AnyInvolvement {
                     let msg = format!("can only use `{} =` in `any_character_struggle` list", key);
                     warn(key, ErrorKey::Validation, &msg);
@@ -194,6 +232,7 @@ pub fn validate_trigger(
             }
 
             if key.is("region") {
+                expect_eq(key, *cmp);
                 if caller != Caller::AnyRegion {
                     let msg = format!("can only use `{} =` in `any_county_in_region` list", key);
                     warn(key, ErrorKey::Validation, &msg);
@@ -206,6 +245,7 @@ pub fn validate_trigger(
             }
 
             if key.is("filter") || key.is("continue") {
+                expect_eq(key, *cmp);
                 if caller != Caller::AnyHierarchy {
                     let msg = format!("can only use `{} =` in `..._hierarchy` list", key);
                     warn(key, ErrorKey::Validation, &msg);
@@ -218,6 +258,7 @@ pub fn validate_trigger(
             }
 
             if key.is("pressed") || key.is("explicit") {
+                expect_eq(key, *cmp);
                 if caller != Caller::AnyClaim {
                     let msg = format!("can only use `{} =` in `any_claim` list", key);
                     warn(key, ErrorKey::Validation, &msg);
@@ -228,6 +269,7 @@ pub fn validate_trigger(
             }
 
             if key.is("text") {
+                expect_eq(key, *cmp);
                 if caller == Caller::CustomDescription {
                     // TODO: validate trigger_localization
                     bv.expect_value();
@@ -246,6 +288,7 @@ pub fn validate_trigger(
             }
 
             if key.is("subject") {
+                expect_eq(key, *cmp);
                 if caller == Caller::CustomDescription || caller == Caller::CustomTooltip {
                     if let Some(token) = bv.expect_value() {
                         validate_target(token, data, sc, Scopes::non_primitive());
@@ -260,6 +303,7 @@ pub fn validate_trigger(
                 continue;
             }
             if key.is("object") {
+                expect_eq(key, *cmp);
                 if caller == Caller::CustomDescription {
                     if let Some(token) = bv.expect_value() {
                         validate_target(token, data, sc, Scopes::non_primitive());
@@ -283,9 +327,7 @@ pub fn validate_trigger(
             if key.is("amount") {
                 if caller == Caller::CalcTrueIf {
                     if let Some(token) = bv.expect_value() {
-                        if token.as_str().parse::<i32>().is_err() {
-                            warn(token, ErrorKey::Validation, "expected a number");
-                        }
+                        token.expect_integer_at(ErrorLevel::Warning);
                     }
                 } else {
                     let msg = format!("can only use `{} =` in `calc_true_if`", key);
@@ -301,6 +343,7 @@ pub fn validate_trigger(
                     || it_type.is("random")
                 {
                     if let Some((inscopes, outscope)) = scope_iterator(&it_name, data) {
+                        expect_eq(key, *cmp);
                         if !it_type.is("any") {
                             let msg = format!("cannot use `{}_` list in a trigger", key);
                             error(key, ErrorKey::Validation, &msg);
@@ -320,6 +363,7 @@ pub fn validate_trigger(
             }
 
             if key.is("custom_description") {
+                expect_eq(key, *cmp);
                 if let Some(block) = bv.expect_block() {
                     validate_trigger(Caller::CustomDescription, block, data, sc, false);
                 }
@@ -327,6 +371,7 @@ pub fn validate_trigger(
             }
 
             if key.is("custom_tooltip") {
+                expect_eq(key, *cmp);
                 match bv {
                     BlockOrValue::Token(t) => data.verify_exists(Item::Localization, t),
                     BlockOrValue::Block(b) => {
@@ -337,36 +382,39 @@ pub fn validate_trigger(
             }
 
             if key.is("calc_true_if") {
+                expect_eq(key, *cmp);
                 if let Some(block) = bv.expect_block() {
+                    check_calc_true_if(key, block);
                     validate_trigger(Caller::CalcTrueIf, block, data, sc, tooltipped);
                 }
                 continue;
             }
             if key.is("weighted_calc_true_if") {
+                expect_eq(key, *cmp);
                 bv.expect_block();
                 // TODO
                 continue;
             }
 
-            if let Some((inscopes, item)) = scope_trigger_item(key.as_str()) {
+            if let Some((inscopes, item)) = scope_trigger_item(key.as_str(), data) {
+                expect_eq(key, *cmp);
                 sc.expect(inscopes, key);
                 if let Some(token) = bv.expect_value() {
-                    data.verify_exists(item, token);
+                    validate_trigger_item(token, item, data, sc);
                 }
                 continue;
             }
 
-            let handled = validate_trigger_keys(key, bv, data, sc, tooltipped);
+            let handled = validate_trigger_keys(key, *cmp, bv, data, sc, tooltipped);
             if handled {
                 continue;
             }
 
             if let Some(trigger) = data.get_trigger(key) {
+                expect_eq(key, *cmp);
                 match bv {
                     BlockOrValue::Token(token) => {
-                        if !(token.is("yes") || token.is("no")) {
-                            warn(token, ErrorKey::Validation, "expected yes or no");
-                        }
+                        token.expect_bool();
                         if !trigger.macro_parms().is_empty() {
                             error(token, ErrorKey::Macro, "expected macro arguments");
                         }
@@ -381,24 +429,30 @@ pub fn validate_trigger(
                                 "trigger does not need macro arguments",
                             );
                         } else {
-                            let mut vec = Vec::new();
-                            let mut vd = Validator::new(block, data);
-                            for parm in &parms {
-                                vd.req_field(parm);
-                                if let Some(token) = vd.field_value(parm.as_str()) {
-                                    vec.push(token.clone());
-                                } else {
-                                    continue 'outer;
-                                }
+                            let required = trigger.required_macro_parms();
+                            let what = "scripted trigger";
+                            if let Some(args) =
+                                validate_macro_args(what, key, block, data, &parms, &required)
+                            {
+                                trigger.validate_macro_expansion(key, args, data, sc, tooltipped);
                             }
-                            let args = parms.into_iter().zip(vec.into_iter()).collect();
-                            trigger.validate_macro_expansion(key, args, data, sc, tooltipped);
                         }
                     }
                 }
                 continue;
             }
 
+            if let Some(token) = bv.get_value() {
+                if token.as_str() == key.as_str() && !matches!(cmp, Comparator::None) {
+                    let always = matches!(
+                        cmp,
+                        Comparator::Eq | Comparator::QEq | Comparator::Le | Comparator::Ge
+                    );
+                    let msg = format!("`{key}` is compared to itself, so this is always {always}");
+                    warn(key, ErrorKey::Logic, &msg);
+                }
+            }
+
             // `10 < scriptvalue` is a valid trigger
             if key.as_str().parse::<f64>().is_ok() {
                 ScriptValue::validate_bv(bv, data, sc);
@@ -421,10 +475,10 @@ pub fn validate_trigger(
                         }
                         sc.expect(inscopes, &prefix);
                         validate_prefix_reference(&prefix, &arg, data);
+                        let outscope = sc.named_scope_or(&prefix, &arg, outscope);
                         sc.replace(outscope, part.clone());
                     } else {
-                        let msg = format!("unknown prefix `{}:`", prefix);
-                        error(part, ErrorKey::Validation, &msg);
+                        unknown_prefix(&prefix, part);
                         sc.close();
                         continue 'outer;
                     }
@@ -435,7 +489,9 @@ pub fn validate_trigger(
                     || part.is("PREV")
                     || part.is("THIS")
                 {
-                    if !first {
+                    // `prev.prev` is fine, but `liege.prev` is not
+                    let prev_chain = (part.is("prev") || part.is("PREV")) && sc.can_chain_prev();
+                    if !first && !prev_chain {
                         let msg = format!("`{}` makes no sense except as first part", part);
                         warn(part, ErrorKey::Validation, &msg);
                     }
@@ -502,23 +558,38 @@ pub fn validate_trigger(
                     sc.replace(Scopes::Bool, part.clone());
                 } else if data.scriptvalues.exists(part.as_str()) {
                     if !last {
-                        let msg = "script value should be the last part";
-                        warn(part, ErrorKey::Validation, msg);
+                        script_value_mid_chain(part);
                         sc.close();
                         continue 'outer;
                     }
                     data.scriptvalues.validate_call(part, data, sc);
                     sc.replace(Scopes::Value, part.clone());
                 // TODO: warn if trying to use iterator here
+                } else if part_vec.len() == 1 {
+                    let iterators = iterator_names(&["any_"]);
+                    let others = SCOPE_TRIGGER_TARGET
+                        .iter()
+                        .map(|(_, s, _)| *s)
+                        .chain(SCOPE_TRIGGER_BOOL.iter().map(|(_, s)| *s))
+                        .chain(SCOPE_TRIGGER_ITEM.iter().map(|(_, s, _)| *s))
+                        .chain(data.triggers.iter_keys())
+                        .chain(iterators.iter().map(String::as_str));
+                    let kinds = &[Command::Trigger, Command::Link];
+                    if !report_deprecated(part, kinds, data.game_version) {
+                        unknown_link(part, others);
+                    }
+                    sc.close();
+                    continue 'outer;
                 } else {
-                    let msg = format!("unknown token `{}`", part);
-                    error(part, ErrorKey::Validation, &msg);
+                    if !report_deprecated(part, &[Command::Link], data.game_version) {
+                        unknown_link(part, std::iter::empty());
+                    }
                     sc.close();
                     continue 'outer;
                 }
             }
 
-            if matches!(cmp, Comparator::Eq) {
+            if matches!(cmp, Comparator::Eq | Comparator::QEq) {
                 if let Some(token) = warn_against_eq {
                     let msg = format!("`{} =` means exactly equal to that amount, which is usually not what you want", token);
                     warn(token, ErrorKey::Logic, &msg);
@@ -527,6 +598,24 @@ pub fn validate_trigger(
                 if sc.can_be(Scopes::Value) {
                     sc.close();
                     ScriptValue::validate_bv(bv, data, sc);
+                } else if cmp.is_ordering() {
+                    let msg = format!("`{cmp}` can only be used to compare numbers");
+                    error(key, ErrorKey::Validation, &msg);
+                    sc.close();
+                } else if let BlockOrValue::Token(t) = bv {
+                    if sc.must_be(Scopes::Bool) && *cmp == Comparator::Ne {
+                        let msg = format!("`{key} != {t}` works, but is usually a mistake");
+                        let info = match t.as_str() {
+                            "yes" => format!("if it is intended, `{key} = no` is clearer"),
+                            "no" => format!("if it is intended, `{key} = yes` is clearer"),
+                            _ => "compare with `=` instead".to_string(),
+                        };
+                        warn_info(key, ErrorKey::Logic, &msg, &info);
+                    }
+                    // `scope:a != scope:b` is a valid comparison
+                    let scopes = sc.scopes();
+                    sc.close();
+                    validate_target(t, data, sc, scopes);
                 } else {
                     let msg = format!("unexpected comparator {}", cmp);
                     warn(key, ErrorKey::Validation, &msg);
@@ -537,8 +626,10 @@ pub fn validate_trigger(
 
             if sc.must_be(Scopes::Bool) {
                 sc.close();
-                if let Some(_token) = bv.expect_value() {
-                    ScriptValue::validate_bv(bv, data, sc);
+                if let Some(token) = bv.expect_value() {
+                    if !token.check_bool_spelling() {
+                        ScriptValue::validate_bv(bv, data, sc);
+                    }
                     // TODO: get outscope from ScriptValue because it can be either Value or Bool.
                     // Then check if it's Bool here.
                 }
@@ -553,6 +644,9 @@ pub fn validate_trigger(
                         validate_target(t, data, sc, scopes);
                     }
                     BlockOrValue::Block(b) => {
+                        if *cmp == Comparator::QEq {
+                            check_qeq_target(key);
+                        }
                         validate_normal_trigger(b, data, sc, tooltipped);
                         sc.close();
                     }
@@ -575,6 +669,83 @@ pub fn validate_trigger(
             }
         }
     }
+    sc.leave_block();
+}
+
+/// Report an error if a key that only takes `=` was used with a different comparator.
+fn expect_eq(key: &Token, cmp: Comparator) {
+    if cmp != Comparator::Eq {
+        let msg = format!("expected `{key} =`, found `{cmp}`");
+        error(key, ErrorKey::Validation, &msg);
+    }
+}
+
+/// Warn about logical operators whose result doesn't depend on what's in them, or that
+/// don't do what their name suggests. `match_key` is the lowercased operator.
+fn check_logic_block(key: &Token, match_key: &str, block: &Block) {
+    let items = block.iter_items().collect::<Vec<_>>();
+    // Whether the operator is true when none of its triggers are true, as is the case for
+    // an empty block
+    let true_if_none = matches!(match_key, "and" | "not" | "nor" | "all_false");
+    if items.is_empty() {
+        let msg = format!("`{key} = {{ }}` is empty, so it is always {true_if_none}");
+        let info = if true_if_none {
+            "there is no trigger in it that could fail"
+        } else {
+            "there is no trigger in it that could succeed"
+        };
+        warn_info(key, ErrorKey::Logic, &msg, info);
+        return;
+    }
+    if items.len() != 1 {
+        return;
+    }
+
+    let (inner_key, _, inner_bv) = items[0];
+    let Some(inner_key) = inner_key else { return };
+    let negates = matches!(
+        match_key,
+        "not" | "nor" | "nand" | "all_false" | "any_false"
+    );
+    if inner_key.is("always") {
+        if let Some(value) = inner_bv.get_value() {
+            if value.is("yes") || value.is("no") {
+                let msg = format!(
+                    "`{} = {{ always = {} }}` is always {}",
+                    key,
+                    value,
+                    value.is("yes") != negates
+                );
+                warn(key, ErrorKey::Logic, &msg);
+            }
+        }
+    } else if match_key == "not" && inner_key.as_str().eq_ignore_ascii_case("not") {
+        let msg = format!("`{key} = {{ {inner_key} = {{ ... }} }}` cancels itself out");
+        let info = "use the triggers inside the inner block directly";
+        warn_info(key, ErrorKey::Logic, &msg, info);
+    } else if match_key == "nand" || match_key == "nor" {
+        let msg = format!(
+            "`{key}` with only one trigger in it is the same as `NOT`, which may not be what was intended"
+        );
+        warn_info(key, ErrorKey::Logic, &msg, "use `NOT` if that is intended");
+    }
+}
+
+/// Warn about a `calc_true_if` that needs more of its triggers to be true than it has
+fn check_calc_true_if(key: &Token, block: &Block) {
+    let Some(amount) = block.get_field_integer("amount") else {
+        return;
+    };
+    let count = block
+        .iter_items()
+        .filter(|(k, _, _)| !k.as_ref().is_some_and(|k| k.is("amount")))
+        .count();
+    if i64::try_from(count).is_ok_and(|count| amount > count) {
+        let msg = format!(
+            "`{key}` needs {amount} of its triggers to be true, but it only has {count}, so it is always false"
+        );
+        warn(key, ErrorKey::Logic, &msg);
+    }
 }
 
 fn validate_trigger_iterator(
@@ -607,7 +778,22 @@ pub fn validate_target(token: &Token, data: &Everything, sc: &mut ScopeContext,
         }
         return;
     }
-    let part_vec = token.split('.');
+    if let Some((scopes, part)) = validate_target_produces(token, data, sc) {
+        if !outscopes.intersects(scopes | Scopes::None) {
+            let msg = format!("`{part}` produces {scopes} but expected {outscopes}");
+            warn(&part, ErrorKey::Scopes, &msg);
+        }
+    }
+}
+
+/// Validate a target like `scope:actor.primary_title` and return the scope types it
+/// produces along with its last part, or `None` if it is not a valid target.
+pub fn validate_target_produces(
+    token: &Token,
+    data: &Everything,
+    sc: &mut ScopeContext,
+) -> Option<(Scopes, Token)> {
+    let mut part_vec = token.split('.');
     sc.open_builder();
     for i in 0..part_vec.len() {
         let first = i == 0;
@@ -622,12 +808,12 @@ pub fn validate_target(token: &Token, data: &Everything, sc: &mut ScopeContext,
                 }
                 sc.expect(inscopes, &prefix);
                 validate_prefix_reference(&prefix, &arg, data);
+                let outscope = sc.named_scope_or(&prefix, &arg, outscope);
                 sc.replace(outscope, part.clone());
             } else {
-                let msg = format!("unknown prefix `{}:`", prefix);
-                error(part, ErrorKey::Validation, &msg);
+                unknown_prefix(&prefix, part);
                 sc.close();
-                return;
+                return None;
             }
         } else if part.is("root")
             || part.is("prev")
@@ -636,7 +822,9 @@ pub fn validate_target(token: &Token, data: &Everything, sc: &mut ScopeContext,
             || part.is("PREV")
             || part.is("THIS")
         {
-            if !first {
+            // `prev.prev` is fine, but `liege.prev` is not
+            let prev_chain = (part.is("prev") || part.is("PREV")) && sc.can_chain_prev();
+            if !first && !prev_chain {
                 let msg = format!("`{}` makes no sense except as first part", part);
                 warn(part, ErrorKey::Validation, &msg);
             }
@@ -659,7 +847,7 @@ pub fn validate_target(token: &Token, data: &Everything, sc: &mut ScopeContext,
                 let msg = format!("`{}` only makes sense as the last part", part);
                 warn(part, ErrorKey::Scopes, &msg);
                 sc.close();
-                return;
+                return None;
             }
             if inscopes == Scopes::None && !first {
                 let msg = format!("`{}` makes no sense except as first part", part);
@@ -677,33 +865,117 @@ pub fn validate_target(token: &Token, data: &Everything, sc: &mut ScopeContext,
             }
             sc.replace(Scopes::Value, part.clone());
         } else if data.scriptvalues.exists(part.as_str()) {
-            // TODO: validate inscope of the script value against sc
             if !last {
-                let msg = format!("`{}` only makes sense as the last part", part);
-                warn(part, ErrorKey::Scopes, &msg);
+                script_value_mid_chain(part);
                 sc.close();
-                return;
+                return None;
             }
+            data.scriptvalues.validate_call(part, data, sc);
             sc.replace(Scopes::Value, part.clone());
         // TODO: warn if trying to use iterator here
         } else {
-            let msg = format!("unknown token `{}`", part);
-            error(part, ErrorKey::Validation, &msg);
+            if !report_deprecated(part, &[Command::Link], data.game_version) {
+                unknown_link(part, std::iter::empty());
+            }
             sc.close();
-            return;
+            return None;
         }
     }
-    if !outscopes.intersects(sc.scopes() | Scopes::None) {
-        let part = &part_vec[part_vec.len() - 1];
-        let msg = format!(
-            "`{}` produces {} but expected {}",
-            part,
-            sc.scopes(),
-            outscopes
-        );
-        warn(part, ErrorKey::Scopes, &msg);
-    }
+    let scopes = sc.scopes();
     sc.close();
+    part_vec.pop().map(|part| (scopes, part))
+}
+
+/// Report a script value that is used in the middle of a chain, like `root.my_value.liege`.
+/// A script value is a number, so nothing can follow it.
+pub fn script_value_mid_chain(part: &Token) {
+    let msg = format!("script value `{part}` can only be the last part of a chain");
+    let info = "a script value is a number, so it has no scopes to go to from there";
+    warn_info(part, ErrorKey::Scopes, &msg, info);
+}
+
+/// Whether `validate_trigger_keys` knows this trigger by name, instead of through the tables.
+/// This is for the table audit, which has no other way to see those names.
+pub fn is_special_trigger(key: &Token, data: &Everything) -> bool {
+    let mut sc = ScopeContext::new_root(Scopes::all(), key.clone());
+    let bv = BlockOrValue::Token(Token::new("yes".to_string(), key.loc.clone()));
+    pause_logging();
+    let handled = validate_trigger_keys(key, Comparator::Eq, &bv, data, &mut sc, false);
+    resume_logging();
+    handled
+}
+
+/// How the case keys of a `switch` are checked, depending on its `trigger`.
+enum SwitchCases {
+    Item(Item),
+    Target(Scopes),
+    Bool,
+    Number,
+    Unchecked,
+}
+
+/// Validate the value of a trigger that compares to an item. The items that are also scope
+/// types can be given as a target instead, like `is_decision_on_cooldown = decision:my_decision`.
+fn validate_trigger_item(token: &Token, item: Item, data: &Everything, sc: &mut ScopeContext) {
+    let scopes = match item {
+        Item::Activity => Scopes::ActivityType,
+        Item::Decision => Scopes::Decision,
+        _ => Scopes::empty(),
+    };
+    if !scopes.is_empty() && (token.as_str().contains(':') || token.as_str().contains('.')) {
+        validate_target(token, data, sc, scopes);
+    } else {
+        data.verify_exists(item, token);
+    }
+}
+
+/// Validate a `switch` block, which works the same in effects and triggers except for what
+/// goes in its cases. The `trigger` is compared to each case key in turn, and the first case
+/// that matches is used, or else the `fallback`.
+pub fn validate_switch<F>(block: &Block, data: &Everything, sc: &mut ScopeContext, mut f: F)
+where
+    F: FnMut(&Block, &Everything, &mut ScopeContext),
+{
+    let mut vd = Validator::new(block, data);
+    vd.req_field("trigger");
+    let mut cases = SwitchCases::Unchecked;
+    if let Some(trigger) = vd.field_value("trigger") {
+        if let Some((inscopes, item)) = scope_trigger_item(trigger.as_str(), data) {
+            sc.expect(inscopes, trigger);
+            cases = SwitchCases::Item(item);
+        } else if let Some((inscopes, outscopes)) = scope_trigger_target(trigger, data) {
+            sc.expect(inscopes, trigger);
+            cases = SwitchCases::Target(outscopes);
+        } else if let Some(inscopes) = scope_trigger_bool(trigger.as_str()) {
+            sc.expect(inscopes, trigger);
+            cases = SwitchCases::Bool;
+        } else if let Some(inscopes) = scope_value(trigger, data) {
+            sc.expect(inscopes, trigger);
+            cases = SwitchCases::Number;
+        } else if !is_special_trigger(trigger, data) {
+            let msg = format!("`{trigger}` can't be the trigger of a `switch`");
+            let info = "it needs a trigger that compares to a value, like `has_trait`";
+            warn_info(trigger, ErrorKey::Validation, &msg, info);
+        }
+    }
+    vd.field_validated_block("fallback", |b, data| f(b, data, sc));
+    for (key, cmp, bv) in vd.unknown_keys_cmp() {
+        expect_eq(key, cmp);
+        match cases {
+            SwitchCases::Item(item) => data.verify_exists(item, key),
+            SwitchCases::Target(outscopes) => validate_target(key, data, sc, outscopes),
+            SwitchCases::Bool => {
+                key.expect_bool();
+            }
+            SwitchCases::Number => {
+                key.expect_number();
+            }
+            SwitchCases::Unchecked => (),
+        }
+        if let Some(block) = bv.expect_block() {
+            f(block, data, sc);
+        }
+    }
 }
 
 /// Validate the keys that don't follow a consistent pattern in what they require from their
@@ -714,6 +986,7 @@ pub fn validate_target(token: &Token, data: &Everything, sc: &mut ScopeContext,
 #[allow(clippy::match_same_arms)] // many of these "same arms" just need further coding
 fn validate_trigger_keys(
     key: &Token,
+    cmp: Comparator,
     bv: &BlockOrValue,
     data: &Everything,
     sc: &mut ScopeContext,
@@ -742,6 +1015,7 @@ fn validate_trigger_keys(
 
         "and" | "or" | "not" | "nor" | "nand" | "all_false" | "any_false" => {
             if let Some(block) = bv.expect_block() {
+                check_logic_block(key, match_key, block);
                 validate_normal_trigger(block, data, sc, tooltipped);
             }
         }
@@ -838,11 +1112,7 @@ fn validate_trigger_keys(
         }
 
         "current_computer_date" | "current_date" | "game_start_date" => {
-            if let Some(token) = bv.expect_value() {
-                if Date::try_from(token).is_err() {
-                    error(token, ErrorKey::Validation, "expected date");
-                }
-            }
+            validate_trigger_date(key, cmp, bv);
         }
 
         "de_jure_drift_progress" => {
@@ -886,25 +1156,7 @@ fn validate_trigger_keys(
 
         "exists" => {
             if let Some(token) = bv.expect_value() {
-                if token.is("yes") || token.is("no") {
-                    // TODO: check scope is not none?
-                } else {
-                    validate_target(token, data, sc, Scopes::non_primitive());
-
-                    if tooltipped {
-                        if let Some(firstpart) = token.as_str().strip_suffix(".holder") {
-                            advice_info(
-                                key,
-                                ErrorKey::Tooltip,
-                                &format!(
-                                    "could rewrite this as `{} = {{ is_title_created = yes }}`",
-                                    firstpart
-                                ),
-                                "it gives a nicer tooltip",
-                            );
-                        }
-                    }
-                }
+                validate_trigger_exists(key, token, data, sc, tooltipped);
             }
         }
 
@@ -962,21 +1214,32 @@ fn validate_trigger_keys(
             }
         }
 
-        "has_character_flag"
-        | "has_council_position"
+        "has_character_flag" | "has_county_flag" | "has_culture_flag" | "has_faith_flag"
+        | "has_province_flag" | "has_realm_flag" => {
+            validate_trigger_has_flag(key, bv, data, sc);
+        }
+
+        "has_council_position"
         | "has_councillor_for_skill"
         | "has_court_language"
         | "has_court_position"
         | "has_court_type"
-        | "has_trait_with_flag"
         | "highest_skill" => {
             sc.expect(Scopes::Character, key);
             bv.expect_value();
         }
 
-        "has_dlc"
-        | "has_dlc_feature"
-        | "has_game_rule"
+        "has_dlc" | "has_dlc_feature" => {
+            if let Some(token) = bv.expect_value() {
+                if match_key == "has_dlc" {
+                    validate_has_dlc(token);
+                } else {
+                    validate_has_dlc_feature(token);
+                }
+            }
+        }
+
+        "has_game_rule"
         | "has_global_variable"
         | "has_global_variable_list"
         | "has_local_variable"
@@ -1076,6 +1339,13 @@ fn validate_trigger_keys(
             bv.expect_value();
         }
 
+        "has_trait_xp" => {
+            sc.expect(Scopes::Character, key);
+            if let Some(block) = bv.expect_block() {
+                validate_trigger_has_trait_xp(key, block, data, sc);
+            }
+        }
+
         "has_trait_rank" => {
             sc.expect(Scopes::Character, key);
             if let Some(block) = bv.expect_block() {
@@ -1083,9 +1353,7 @@ fn validate_trigger_keys(
             }
         }
 
-        "important_action_is_valid_but_invisible"
-        | "important_action_is_visible"
-        | "in_activity_type" => {
+        "important_action_is_valid_but_invisible" | "important_action_is_visible" => {
             sc.expect(Scopes::Character, key);
             bv.expect_value();
         }
@@ -1291,6 +1559,14 @@ fn validate_trigger_keys(
             }
         }
 
+        "switch" => {
+            if let Some(block) = bv.expect_block() {
+                validate_switch(block, data, sc, |b, data, sc| {
+                    validate_normal_trigger(b, data, sc, tooltipped);
+                });
+            }
+        }
+
         "tier_difference" => {
             sc.expect(Scopes::Character, key);
             if let Some(block) = bv.expect_block() {
@@ -1318,13 +1594,6 @@ fn validate_trigger_keys(
             }
         }
 
-        "vassal_contract_has_flag"
-        | "vassal_contract_obligation_level_can_be_decreased"
-        | "vassal_contract_obligation_level_can_be_increased" => {
-            sc.expect(Scopes::Character, key);
-            bv.expect_value();
-        }
-
         "war_contribution" => {
             sc.expect(Scopes::War, key);
             if let Some(block) = bv.expect_block() {
@@ -1359,9 +1628,7 @@ fn validate_trigger_target_value(
     if let Some(token) = vd.field_value("target") {
         validate_target(token, data, sc, outscopes);
     }
-    if let Some(bv) = vd.field_any_cmp("value") {
-        ScriptValue::validate_bv(bv, data, sc);
-    }
+    vd.field_numeric_compared("value", sc);
 }
 
 fn validate_trigger_type_value(
@@ -1376,9 +1643,7 @@ fn validate_trigger_type_value(
     vd.req_field(field);
     vd.req_field("value");
     vd.field_value_item(field, itype);
-    if let Some(bv) = vd.field_any_cmp("value") {
-        ScriptValue::validate_bv(bv, data, sc);
-    }
+    vd.field_numeric_compared("value", sc);
 }
 
 fn validate_trigger_type_target(
@@ -1550,8 +1815,91 @@ fn validate_trigger_has_opinion_modifier(
     // TODO
 }
 
-fn validate_trigger_has_relation_flag(_block: &Block, _data: &Everything, _sc: &mut ScopeContext) {
-    // TODO
+fn validate_trigger_date(key: &Token, cmp: Comparator, bv: &BlockOrValue) {
+    if let Some(token) = bv.expect_value() {
+        token.expect_date();
+    }
+    if key.is("current_date") && cmp == Comparator::Eq {
+        let msg = "`current_date =` is only true on that exact day";
+        let info = "you probably want `current_date >=` or `current_date <`";
+        warn_info(key, ErrorKey::Logic, msg, info);
+    }
+}
+
+fn validate_trigger_exists(
+    key: &Token,
+    token: &Token,
+    data: &Everything,
+    sc: &mut ScopeContext,
+    tooltipped: bool,
+) {
+    if token.is("yes") || token.is("no") {
+        // TODO: check scope is not none?
+        return;
+    }
+    validate_target(token, data, sc, Scopes::non_primitive());
+    if always_exists(token) {
+        let msg = format!("`{token}` always exists, so this is always true");
+        warn(key, ErrorKey::Logic, &msg);
+    }
+
+    if tooltipped {
+        if let Some(firstpart) = token.as_str().strip_suffix(".holder") {
+            advice_info(
+                key,
+                ErrorKey::Tooltip,
+                &format!("could rewrite this as `{firstpart} = {{ is_title_created = yes }}`"),
+                "it gives a nicer tooltip",
+            );
+        }
+    }
+}
+
+/// `has_character_flag` and the other flag triggers, which each read one family of flags
+fn validate_trigger_has_flag(
+    key: &Token,
+    bv: &BlockOrValue,
+    data: &Everything,
+    sc: &mut ScopeContext,
+) {
+    if let Some(family) = FlagFamily::from_reader(key) {
+        sc.expect(family.scopes(), key);
+        if let Some(token) = bv.expect_value() {
+            data.flags.read(family, token);
+        }
+    }
+}
+
+fn validate_trigger_has_trait_xp(
+    key: &Token,
+    block: &Block,
+    data: &Everything,
+    sc: &mut ScopeContext,
+) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("trait");
+    vd.req_field("value");
+    vd.field_value_item("trait", Item::Trait);
+    vd.field_value("track");
+    vd.field_numeric_compared("value", sc);
+    if let Some(trait_token) = block.get_field_value("trait") {
+        data.traits
+            .verify_track(key, trait_token, block.get_field_value("track"));
+    }
+}
+
+fn validate_trigger_has_relation_flag(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+    vd.req_field("target");
+    vd.req_field("relation");
+    vd.req_field("flag");
+    if let Some(token) = vd.field_value("target") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    vd.field_value_item("relation", Item::Relation);
+    if let Some(token) = vd.field_value("flag") {
+        data.flags.read(FlagFamily::Relation, token);
+    }
 }
 
 fn validate_trigger_has_trait_rank(_block: &Block, _data: &Everything, _sc: &mut ScopeContext) {
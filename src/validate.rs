@@ -5,13 +5,15 @@ use crate::block::{Block, BlockOrValue};
 /// A module for validation functions that are useful for more than one data module.
 use crate::context::ScopeContext;
 use crate::data::scriptvalues::ScriptValue;
+use crate::desc::validate_desc;
 use crate::errorkey::ErrorKey;
-use crate::errors::{error, warn};
+use crate::errors::{advice_info, error, warn, warn2, warn_info, ErrorLevel};
 use crate::everything::Everything;
+use crate::helpers::closest_key;
 use crate::item::Item;
 use crate::scopes::Scopes;
 use crate::token::Token;
-use crate::trigger::{validate_normal_trigger, validate_target};
+use crate::trigger::{validate_normal_trigger, validate_target, validate_trigger, Caller};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ListType {
@@ -80,62 +82,43 @@ pub fn validate_theme_sound(block: &Block, data: &Everything) {
 
 pub fn validate_days_weeks_months_years(block: &Block, data: &Everything, sc: &mut ScopeContext) {
     let mut vd = Validator::new(block, data);
-    let mut count = 0;
 
-    if let Some(bv) = vd.field_any_cmp("days") {
-        ScriptValue::validate_bv(bv, data, sc);
-        count += 1;
-    }
-    if let Some(bv) = vd.field_any_cmp("weeks") {
-        ScriptValue::validate_bv(bv, data, sc);
-        count += 1;
-    }
-    if let Some(bv) = vd.field_any_cmp("months") {
-        ScriptValue::validate_bv(bv, data, sc);
-        count += 1;
-    }
-    if let Some(bv) = vd.field_any_cmp("years") {
-        ScriptValue::validate_bv(bv, data, sc);
-        count += 1;
-    }
-
-    if count != 1 {
-        error(
-            block,
-            ErrorKey::Validation,
-            "must have 1 of days, weeks, months, or years",
-        );
+    vd.req_field_one_of(&["days", "weeks", "months", "years"]);
+    for name in ["days", "weeks", "months", "years"] {
+        vd.field_numeric_compared(name, sc);
     }
 }
 
 // Very similar to validate_years_months_days, but requires = instead of allowing comparators
 pub fn validate_cooldown(block: &Block, data: &Everything, sc: &mut ScopeContext) {
     let mut vd = Validator::new(block, data);
-    let mut count = 0;
 
-    if let Some(bv) = vd.field("days") {
-        ScriptValue::validate_bv(bv, data, sc);
-        count += 1;
-    }
-    if let Some(bv) = vd.field("months") {
-        ScriptValue::validate_bv(bv, data, sc);
-        count += 1;
-    }
-    if let Some(bv) = vd.field("years") {
-        ScriptValue::validate_bv(bv, data, sc);
-        count += 1;
-    }
+    vd.req_field_one_of(&["days", "months", "years"]);
+    vd.field_script_value("days", sc);
+    vd.field_script_value("months", sc);
+    vd.field_script_value("years", sc);
+}
 
-    if count != 1 {
-        error(
-            block,
-            ErrorKey::Validation,
-            "must have 1 of days, months, or years",
-        );
+/// Validate a color that can be either a color block or the name of a color from
+/// `common/named_colors`.
+pub fn validate_color_bv(bv: &BlockOrValue, data: &Everything) {
+    match bv {
+        BlockOrValue::Token(t) => data.verify_exists(Item::NamedColor, t),
+        BlockOrValue::Block(b) => validate_color(b, data),
     }
 }
 
+/// Validate a color block. It can be `{ r g b }` or `rgb { r g b }`, with the components
+/// either integers from 0 to 255 or fractions from 0.0 to 1.0, or `hsv { h s v }` with
+/// fractions, or `hsv360 { h s v }` with hue from 0 to 360 and the others from 0 to 100.
 pub fn validate_color(block: &Block, _data: &Everything) {
+    let format = block.tag.as_ref().map_or("rgb", Token::as_str);
+    if !["rgb", "hsv", "hsv360"].contains(&format) {
+        let msg = format!("unknown color format `{format}`");
+        error(block, ErrorKey::Validation, &msg);
+        return;
+    }
+
     let mut count = 0;
     for (k, _, v) in block.iter_items() {
         if let Some(key) = k {
@@ -143,25 +126,7 @@ pub fn validate_color(block: &Block, _data: &Everything) {
         } else {
             match v {
                 BlockOrValue::Token(t) => {
-                    if let Ok(i) = t.as_str().parse::<isize>() {
-                        if !(0..=255).contains(&i) {
-                            error(
-                                t,
-                                ErrorKey::Validation,
-                                "color values should be between 0 and 255",
-                            );
-                        }
-                    } else if let Ok(f) = t.as_str().parse::<f64>() {
-                        if !(0.0..=1.0).contains(&f) {
-                            error(
-                                t,
-                                ErrorKey::Validation,
-                                "color values should be between 0.0 and 1.0",
-                            );
-                        }
-                    } else {
-                        error(t, ErrorKey::Validation, "expected color value");
-                    }
+                    validate_color_component(t, format, count);
                     count += 1;
                 }
                 BlockOrValue::Block(b) => {
@@ -175,17 +140,69 @@ pub fn validate_color(block: &Block, _data: &Everything) {
     }
 }
 
+/// Check one component of a color. `idx` is the component's position in the color.
+fn validate_color_component(t: &Token, format: &str, idx: usize) {
+    let Ok(value) = t.as_str().parse::<f64>() else {
+        error(t, ErrorKey::Validation, "expected color value");
+        return;
+    };
+    let is_int = t.as_str().parse::<isize>().is_ok();
+    let (max, msg) = match format {
+        "rgb" if is_int => (255.0, "color values should be between 0 and 255"),
+        "hsv360" if idx == 0 => (360.0, "hue should be between 0 and 360"),
+        "hsv360" => (100.0, "saturation and value should be between 0 and 100"),
+        _ => (1.0, "color values should be between 0.0 and 1.0"),
+    };
+    if !(0.0..=max).contains(&value) {
+        warn_info(
+            t,
+            ErrorKey::Range,
+            msg,
+            "the game will silently clamp it to that range",
+        );
+    }
+}
+
+/// Advise about an opinion value that is far beyond the -100 to 100 that a character's total
+/// opinion of someone is kept in. The game allows it, but it is usually a typo.
+pub fn check_opinion_value(token: &Token) {
+    if let Ok(value) = token.as_str().parse::<i64>() {
+        if !(-400..=400).contains(&value) {
+            let msg = format!("opinion value {value} is very large");
+            let info =
+                "total opinion is capped at 100 either way, so this drowns out everything else";
+            advice_info(token, ErrorKey::Range, &msg, info);
+        }
+    }
+}
+
+/// Check the argument of a prefix like `title:k_france`. Prefixes whose argument is a name
+/// chosen by the script, like `flag:` and `scope:`, accept anything.
 pub fn validate_prefix_reference(prefix: &Token, arg: &Token, data: &Everything) {
-    // TODO there are more to match
     match prefix.as_str() {
+        "activity_type" => data.verify_exists(Item::Activity, arg),
+        "aptitude" | "court_position" => data.verify_exists(Item::CourtPosition, arg),
+        "array_define" | "define" => data.defines.verify_exists(arg),
         "character" => data.verify_exists(Item::Character, arg),
+        "council_task" | "cp" => data.verify_exists(Item::CouncilPosition, arg),
+        "culture" => data.verify_exists(Item::Culture, arg),
+        "decision" => data.verify_exists(Item::Decision, arg),
         "dynasty" => data.verify_exists(Item::Dynasty, arg),
         "event_id" => data.verify_exists(Item::Event, arg),
         "faith" => data.verify_exists(Item::Faith, arg),
         "house" => data.verify_exists(Item::House, arg),
-        "province" => data.verify_exists(Item::Province, arg),
+        "province" => {
+            if arg.as_str().parse::<u32>().is_ok() {
+                data.verify_exists(Item::Province, arg);
+                data.provinces.verify_not_water(arg.as_str(), arg);
+            } else {
+                error(arg, ErrorKey::Validation, "expected a province id number");
+            }
+        }
         "religion" => data.verify_exists(Item::Religion, arg),
+        "struggle" => data.verify_exists(Item::Struggle, arg),
         "title" => data.verify_exists(Item::Title, arg),
+        "vassal_contract_obligation_level" => data.verify_exists(Item::VassalObligation, arg),
         &_ => (),
     }
 }
@@ -204,6 +221,7 @@ pub fn validate_prefix_reference_token(token: &Token, data: &Everything, wanted:
 /// This checks the fields that are only used in iterators.
 /// It does not check "limit" because that is shared with the if/else blocks.
 /// Returns true iff the iterator took care of its own tooltips
+#[allow(clippy::too_many_lines)]
 pub fn validate_iterator_fields(
     list_type: ListType,
     block: &Block,
@@ -237,6 +255,7 @@ pub fn validate_iterator_fields(
         }
     });
 
+    // Like `weight` below, `order_by` is calculated for each item in the list
     if let Some(bv) = vd.field("order_by") {
         if list_type == ListType::Ordered {
             ScriptValue::validate_bv(bv, data, sc);
@@ -249,11 +268,22 @@ pub fn validate_iterator_fields(
         }
     }
 
+    if list_type == ListType::Ordered
+        && block.get_key("order_by").is_none()
+        && block.get_key("position").is_none()
+    {
+        advice_info(
+            block,
+            ErrorKey::Logic,
+            "ordered list without `order_by` or `position`",
+            "without them, which item gets picked is arbitrary",
+        );
+    }
+
+    // Negative positions count from the end of the list
     if let Some(token) = vd.field_value("position") {
         if list_type == ListType::Ordered {
-            if token.as_str().parse::<i32>().is_err() {
-                warn(token, ErrorKey::Validation, "expected an integer");
-            }
+            token.expect_integer_at(ErrorLevel::Warning);
         } else {
             warn(
                 block.get_key("position").unwrap(),
@@ -263,11 +293,9 @@ pub fn validate_iterator_fields(
         }
     }
 
-    if let Some(token) = vd.field_value("min") {
+    if let Some(bv) = vd.field("min") {
         if list_type == ListType::Ordered {
-            if token.as_str().parse::<i32>().is_err() {
-                warn(token, ErrorKey::Validation, "expected an integer");
-            }
+            validate_outer_script_value(block.get_key("min").unwrap(), bv, data, sc);
         } else {
             warn(
                 block.get_key("min").unwrap(),
@@ -279,7 +307,7 @@ pub fn validate_iterator_fields(
 
     if let Some(bv) = vd.field("max") {
         if list_type == ListType::Ordered {
-            ScriptValue::validate_bv(bv, data, sc);
+            validate_outer_script_value(block.get_key("max").unwrap(), bv, data, sc);
         } else {
             warn(
                 block.get_key("max").unwrap(),
@@ -291,9 +319,7 @@ pub fn validate_iterator_fields(
 
     if let Some(token) = vd.field_value("check_range_bounds") {
         if list_type == ListType::Ordered {
-            if !(token.is("yes") || token.is("no")) {
-                warn(token, ErrorKey::Validation, "expected yes or no");
-            }
+            token.expect_bool_at(ErrorLevel::Warning);
         } else {
             warn(
                 block.get_key("check_range_bounds").unwrap(),
@@ -303,9 +329,11 @@ pub fn validate_iterator_fields(
         }
     }
 
-    if let Some(_b) = vd.field_block("weight") {
+    // The weight is calculated for each item in the list, and the caller has already
+    // opened the iterator's scope in `sc`, so its triggers are checked against the items.
+    if let Some(bv) = vd.field("weight") {
         if list_type == ListType::Random {
-            // TODO
+            validate_ai_chance(bv, data, sc);
         } else {
             warn(
                 block.get_key("weight").unwrap(),
@@ -317,6 +345,21 @@ pub fn validate_iterator_fields(
     has_tooltip
 }
 
+/// The `min` and `max` of an ordered list are calculated once, before going through the list,
+/// so they are in the scope outside the list rather than in the iterator's scope that the
+/// caller has opened in `sc`.
+fn validate_outer_script_value(
+    key: &Token,
+    bv: &BlockOrValue,
+    data: &Everything,
+    sc: &mut ScopeContext,
+) {
+    sc.open_builder();
+    sc.replace_prev(key);
+    ScriptValue::validate_bv(bv, data, sc);
+    sc.close();
+}
+
 /// This checks the special fields for certain iterators, like `type =` in `every_relation`.
 /// It doesn't check the generic ones like `limit` or the ordering ones for `ordered_*`.
 pub fn validate_inside_iterator(
@@ -502,17 +545,296 @@ pub fn validate_inside_iterator(
     }
 }
 
+/// The fields of a `cost` block. They can all be script values, and the game shows the player
+/// how each one is calculated, so the named script values used here need localization.
+/// Values that are never shown, like `ai_chance`, are not checked for that.
+const COST_FIELDS: &[&str] = &["gold", "prestige", "piety"];
+
 pub fn validate_cost(block: &Block, data: &Everything, sc: &mut ScopeContext) {
     let mut vd = Validator::new(block, data);
-    // These can all be script values
-    vd.field_validated_bv("gold", |bv, data| {
+    for field in COST_FIELDS {
+        vd.field_validated_bv(field, |bv, data| {
+            ScriptValue::validate_bv(bv, data, sc);
+            if let BlockOrValue::Token(token) = bv {
+                data.scriptvalues.verify_shown(token, data);
+            }
+        });
+    }
+    vd.field_bool("round");
+}
+
+/// Warn about a number that is above 100 in a field that is a probability from 0 to 1, such as
+/// a chance. It was probably written as a percentage.
+pub fn check_fraction_hint(bv: &BlockOrValue) {
+    if let BlockOrValue::Token(t) = bv {
+        if let Ok(value) = t.as_str().parse::<f64>() {
+            if value > 100.0 {
+                let msg = "value is above 100, but this field is usually from 0 to 1";
+                let info = format!("if this is a percentage, write {} instead", value / 100.0);
+                warn_info(t, ErrorKey::Range, msg, &info);
+            }
+        }
+    }
+}
+
+/// Whether a target is made only of scopes that always exist, like `root` or `prev.prev`
+pub fn always_exists(target: &Token) -> bool {
+    target.split('.').iter().all(|part| {
+        ["root", "this", "prev", "ROOT", "THIS", "PREV"]
+            .iter()
+            .any(|name| part.is(name))
+    })
+}
+
+/// Warn about `target ?= { ... }` where the target is a scope that always exists, like `root`.
+/// The `?=` only skips the block when the target does not exist, so there it does nothing.
+pub fn check_qeq_target(key: &Token) {
+    if always_exists(key) {
+        let msg = format!("`{key}` always exists, so `?=` does nothing here");
+        warn_info(key, ErrorKey::Logic, &msg, "use `=` instead");
+    }
+}
+
+/// Validate an `ai_chance`, `ai_will_do`, or `weight` field. It can be a simple value or a block
+/// with a base value and modifiers.
+pub fn validate_ai_chance(bv: &BlockOrValue, data: &Everything, sc: &mut ScopeContext) {
+    match bv {
+        BlockOrValue::Token(t) => ScriptValue::validate_value(t, data, sc),
+        BlockOrValue::Block(b) => validate_modifiers_with_base(b, data, sc),
+    }
+}
+
+/// Validate a block that starts with a `base` value and then adjusts it with modifiers.
+pub fn validate_modifiers_with_base(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+
+    vd.field_validated_bv("base", |bv, data| {
         ScriptValue::validate_bv(bv, data, sc);
     });
-    vd.field_validated_bv("prestige", |bv, data| {
+    vd.field_validated_bv("value", |bv, data| {
         ScriptValue::validate_bv(bv, data, sc);
     });
-    vd.field_validated_bv("piety", |bv, data| {
+    if let (Some(base), Some(value)) = (block.get_key("base"), block.get_key("value")) {
+        warn2(
+            value,
+            ErrorKey::Conflict,
+            "`value` and `base` both set the starting value",
+            base,
+            "the other one is here",
+        );
+    } else {
+        // The adjustments are applied in order, and the starting value replaces what came before
+        vd.field_order(&["base", "value"]);
+    }
+    vd.field_validated_bvs("add", |bv, data| {
         ScriptValue::validate_bv(bv, data, sc);
     });
-    vd.field_bool("round");
+    vd.field_validated_bvs("factor", |bv, data| {
+        ScriptValue::validate_bv(bv, data, sc);
+    });
+    vd.field_script_value("min", sc);
+    vd.field_script_value("max", sc);
+    validate_modifiers(&mut vd, data, sc);
+}
+
+/// Validate the weight adjustments that can appear in `ai_will_do`, `ai_chance`, `weight`
+/// and similar blocks, as well as in scripted modifiers. Any field not recognized here
+/// is looked up as a scripted modifier, so this should be called after the caller has
+/// validated its own fields.
+pub fn validate_modifiers(vd: &mut Validator, data: &Everything, sc: &mut ScopeContext) {
+    vd.field_validated_blocks("modifier", |b, data| {
+        validate_trigger(Caller::Modifier, b, data, sc, false);
+    });
+    vd.field_validated_blocks("compare_modifier", |b, data| {
+        validate_compare_modifier(b, data, sc);
+    });
+    vd.field_validated_blocks("opinion_modifier", |b, data| {
+        validate_opinion_modifier(b, data, sc);
+    });
+    vd.field_validated_blocks("ai_value_modifier", |b, data| {
+        validate_ai_value_modifier(b, data, sc);
+    });
+
+    'outer: for (key, bv) in vd.unknown_keys() {
+        if let Some(modifier) = data.scripted_modifiers.get(key.as_str()) {
+            match bv {
+                BlockOrValue::Token(token) => {
+                    if !token.is("yes") {
+                        warn(token, ErrorKey::Validation, "expected yes");
+                    }
+                    if !modifier.macro_parms().is_empty() {
+                        error(token, ErrorKey::Macro, "expected macro arguments");
+                    }
+                    modifier.validate_call(key, data, sc);
+                }
+                BlockOrValue::Block(block) => {
+                    let parms = modifier.macro_parms();
+                    if parms.is_empty() {
+                        error(
+                            block,
+                            ErrorKey::Macro,
+                            "scripted modifier does not need macro arguments",
+                        );
+                    } else {
+                        let mut vec = Vec::new();
+                        let mut vd = Validator::new(block, data);
+                        for parm in &parms {
+                            vd.req_field(parm);
+                            if let Some(token) = vd.field_value(parm.as_str()) {
+                                vec.push(token.clone());
+                            } else {
+                                continue 'outer;
+                            }
+                        }
+                        let args: Vec<_> = parms.into_iter().zip(vec).collect();
+                        modifier.validate_macro_expansion(key, &args, data, sc);
+                    }
+                }
+            }
+        } else {
+            let msg = format!("unknown field `{key}`");
+            warn(key, ErrorKey::Validation, &msg);
+        }
+    }
+}
+
+fn validate_compare_modifier(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+
+    vd.field_validated_block("trigger", |b, data| {
+        validate_normal_trigger(b, data, sc, false);
+    });
+    vd.field_validated_bv("desc", |bv, data| validate_desc(bv, data, sc));
+    if let Some(token) = vd.field_value("target") {
+        validate_target(token, data, sc, Scopes::non_primitive());
+    }
+    vd.req_field("value");
+    match block.get_field_value("target") {
+        // The scope type of the target isn't known here, so only the value's references are
+        // checked, not its scopes.
+        Some(target) => {
+            sc.open_scope(Scopes::non_primitive(), target.clone());
+            vd.field_script_value("value", sc);
+            sc.close();
+        }
+        None => {
+            vd.field_script_value("value", sc);
+        }
+    }
+    vd.field_script_value("multiplier", sc);
+    vd.field_script_value("step", sc);
+    vd.field_script_value("offset", sc);
+    vd.field_script_value("min", sc);
+    vd.field_script_value("max", sc);
+}
+
+fn validate_opinion_modifier(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+
+    vd.field_validated_block("trigger", |b, data| {
+        validate_normal_trigger(b, data, sc, false);
+    });
+    vd.field_validated_bv("desc", |bv, data| validate_desc(bv, data, sc));
+    if let Some(token) = vd.field_value("who") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    vd.req_field("opinion_target");
+    if let Some(token) = vd.field_value("opinion_target") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    vd.field_script_value("multiplier", sc);
+    vd.field_script_value("step", sc);
+    vd.field_script_value("min", sc);
+    vd.field_script_value("max", sc);
+}
+
+/// The AI personality values that `ai_value_modifier` can scale the weight by
+const AI_VALUES: &[&str] = &[
+    "ai_boldness",
+    "ai_compassion",
+    "ai_energy",
+    "ai_greed",
+    "ai_honor",
+    "ai_rationality",
+    "ai_sociability",
+    "ai_vengefulness",
+    "ai_zeal",
+];
+
+fn validate_ai_value_modifier(block: &Block, data: &Everything, sc: &mut ScopeContext) {
+    let mut vd = Validator::new(block, data);
+
+    vd.field_validated_block("trigger", |b, data| {
+        validate_normal_trigger(b, data, sc, false);
+    });
+    vd.field_validated_bv("desc", |bv, data| validate_desc(bv, data, sc));
+    // The personality values are taken from `who`, which is the current character by default
+    if let Some(token) = vd.field_value("who") {
+        validate_target(token, data, sc, Scopes::Character);
+    }
+    for field in AI_VALUES {
+        vd.field_script_value(field, sc);
+    }
+    vd.field_script_value("min", sc);
+    vd.field_script_value("max", sc);
+    for (key, _) in vd.unknown_keys() {
+        let msg = format!("unknown ai value `{key}`");
+        if let Some(close) = closest_key(key.as_str(), AI_VALUES.iter().copied()) {
+            let info = format!("did you mean `{close}`?");
+            warn_info(key, ErrorKey::Validation, &msg, &info);
+        } else {
+            let info = format!("the ai values are {}", AI_VALUES.join(", "));
+            warn_info(key, ErrorKey::Validation, &msg, &info);
+        }
+    }
+}
+
+/// Check the arguments of a call to a scripted effect or trigger that has macro parameters,
+/// and return them for expanding the macro. Returns `None` if a parameter that has no
+/// default is missing.
+pub fn validate_macro_args(
+    what: &str,
+    key: &Token,
+    block: &Block,
+    data: &Everything,
+    parms: &[String],
+    required: &[String],
+) -> Option<Vec<(String, Token)>> {
+    let mut vd = Validator::new(block, data);
+    let mut args = Vec::new();
+    let mut missing = false;
+    for parm in parms {
+        if required.contains(parm) && !vd.req_field(parm) {
+            missing = true;
+        }
+        if let Some(token) = vd.field_value(parm) {
+            args.push((parm.clone(), token.clone()));
+        }
+    }
+    vd.no_warn_remaining();
+    for (k, _, bv) in block.iter_items() {
+        match k {
+            Some(k) => {
+                if !parms.iter().any(|parm| k.is(parm)) {
+                    let msg = format!("{what} `{key}` has no parameter `{k}`");
+                    if let Some(close) = closest_key(k.as_str(), parms.iter().map(String::as_str)) {
+                        let info = format!("did you mean `{close}`?");
+                        warn_info(k, ErrorKey::Macro, &msg, &info);
+                    } else {
+                        warn(k, ErrorKey::Macro, &msg);
+                    }
+                }
+            }
+            None => warn(
+                bv,
+                ErrorKey::Macro,
+                "found loose value, expected only `key =`",
+            ),
+        }
+    }
+    if missing {
+        None
+    } else {
+        Some(args)
+    }
 }
@@ -0,0 +1,51 @@
+use std::fmt::{Display, Formatter};
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// A version of the game, by its major and minor number. The patch number is left out
+/// because script commands don't change in patches.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameVersion {
+    major: u16,
+    minor: u16,
+}
+
+impl GameVersion {
+    /// The version of the game that the tables of effects, triggers and links are for
+    pub const SUPPORTED: GameVersion = GameVersion::new(1, 7);
+
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parse a version like `1.7.0` or `1.7.*`
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some(Self::new(major, minor))
+    }
+
+    /// Find the version of the game installed at `vanilla_root`, from the launcher's settings
+    /// file next to the `game` folder. It has a line like `"rawVersion": "1.7.0",`.
+    pub fn detect(vanilla_root: &Path) -> Option<Self> {
+        let path = vanilla_root
+            .parent()?
+            .join("launcher")
+            .join("launcher-settings.json");
+        let content = read_to_string(path).ok()?;
+        let rest = &content[content.find("\"rawVersion\"")? + "\"rawVersion\"".len()..];
+        let value = rest
+            .trim_start()
+            .strip_prefix(':')?
+            .trim_start()
+            .strip_prefix('"')?;
+        Self::parse(&value[..value.find('"')?])
+    }
+}
+
+impl Display for GameVersion {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
@@ -0,0 +1,5 @@
+version = "1.0"
+name = "Base mod test"
+path = "base"
+replace_path = "events"
+supported_version = "1.9.*"
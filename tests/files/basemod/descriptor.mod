@@ -0,0 +1,5 @@
+version = "1.0"
+name = "Submod test"
+path = "mod"
+replace_path = "common/scripted_effects"
+supported_version = "1.9.*"
@@ -0,0 +1,2 @@
+name = "Missing"
+path = "mod/missing"
@@ -0,0 +1,2 @@
+name = "Moved"
+path = "mod/moved_away"
@@ -0,0 +1,2 @@
+name = "Slashes"
+path = "mod\variant\"
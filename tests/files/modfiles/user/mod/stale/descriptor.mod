@@ -0,0 +1,2 @@
+name = "Stale"
+path = "mod/somewhere_else"
@@ -0,0 +1,2 @@
+name = "Variant"
+path = "mod/variant"
@@ -0,0 +1,2 @@
+name = "Zipped"
+archive = "mod/zipped.zip"
@@ -0,0 +1,6 @@
+version = "1.0"
+name = "Replace path test"
+path = "mod"
+replace_path = "events"
+replace_path = "evnets"
+supported_version = "1.9.*"
@@ -0,0 +1,5 @@
+version = "1.0"
+name = "Total conversion test"
+path = "mod"
+replace_path = "common/landed_titles"
+supported_version = "1.9.*"
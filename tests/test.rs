@@ -1,25 +1,63 @@
 use lazy_static::lazy_static;
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Mutex;
 
-use ck3_tiger::errors::{log_to, set_mod_root, set_vanilla_root, take_log_to};
+use ck3_tiger::audit::audit_tables;
+use ck3_tiger::errorkey::ErrorKey;
+use ck3_tiger::errors::{
+    collect_reports, count_at_level, log_policy_summary, log_summary, log_to, set_base_mod_root,
+    set_mod_root, set_vanilla_root, show_vanilla, start_mod_run, take_log_to, take_reports,
+    ErrorLevel,
+};
 use ck3_tiger::everything::Everything;
+use ck3_tiger::html::html_report;
+use ck3_tiger::modfile::ModFile;
+use ck3_tiger::query::{dump_filelist, find_references, list_items, parse_find, where_item};
+use strum::IntoEnumIterator;
 
 lazy_static! {
     static ref TEST_MUTEX: Mutex<()> = Mutex::new(());
 }
 
 fn check_mod_helper(modname: &str) -> String {
+    check_helper(
+        "tests/files/ck3",
+        &format!("tests/files/{}", modname),
+        &[],
+        &[],
+        true,
+    )
+}
+
+fn check_helper(
+    vanilla_root: &str,
+    mod_root: &str,
+    no_dlc: &[String],
+    profiles: &[&str],
+    follow_symlinks: bool,
+) -> String {
     let _guard = TEST_MUTEX.lock().unwrap();
 
-    let vanilla_root = PathBuf::from("tests/files/ck3");
-    let mod_root = PathBuf::from(format!("tests/files/{}", modname));
+    let vanilla_root = PathBuf::from(vanilla_root);
+    let mod_root = PathBuf::from(mod_root);
 
     set_vanilla_root(vanilla_root.clone());
     set_mod_root(mod_root.clone());
     log_to(Box::new(Vec::new()));
 
-    let mut everything = Everything::new(&vanilla_root, &mod_root, Vec::new()).unwrap();
+    let mut everything = Everything::new(
+        &vanilla_root,
+        Some(&mod_root),
+        Vec::new(),
+        no_dlc,
+        follow_symlinks,
+    )
+    .unwrap();
+    for profile in profiles {
+        everything.add_profile(profile).unwrap();
+    }
     everything.load_all();
     everything.validate_all();
 
@@ -72,3 +110,2847 @@ fn test_mod_2() {
     assert!(errors.contains("interaction.txt:3:18: ERROR: referenced file does not exist"));
     assert!(errors.contains("interaction.txt:2:12: ERROR: file gfx/interface/icons/character_interactions/missing_icon.dds does not exist"));
 }
+
+#[test]
+fn test_mod_3() {
+    let errors = check_mod_helper("mod3");
+
+    // Three levels of desc nesting: first_valid > triggered_desc > random_valid > triggered_desc
+    assert!(errors.contains(
+        "line 14 \t\t\t\t\t\t\tdesc = desc_test_level_three\nline 14 \t\t\t\t\t\t\t       ^^^^^^^^^^^^^^^^^^^^^\nERROR (missing-localization): missing english localization key desc_test_level_three\n"
+    ));
+    assert!(!errors.contains("localization key desc_test.0001.desc"));
+    assert!(errors.contains(
+        "line 7 \t\tfirst_valid = {\nline 7 \t\t^^^^^^^^^^^\nWARNING (validation): `first_valid` has no unconditional entry\n"
+    ));
+    assert!(!errors
+        .contains("line 11 \t\t\t\t\trandom_valid = {\nline 11 \t\t\t\t\t^^^^^^^^^^^^\nWARNING"));
+    assert!(errors.contains(
+        "line 30 \tdesc = { }\nline 30 \t       ^\nWARNING (validation): empty description block\n"
+    ));
+}
+
+#[test]
+fn test_mod_4() {
+    let errors = check_mod_helper("mod4");
+
+    // Each of these parse errors should be reported at the right place...
+    assert!(errors.contains(
+        "line 5 \tdesc = \"some \"quoted\" text\"\nline 5 \t             ^\nERROR (parse-error): quoted string ends here but the text continues\n"
+    ));
+    assert!(errors.contains(
+        "line 7 \t\tname = parse_test.0001.a;\nline 7 \t\t                        ^\nWARNING (parse-error): unexpected semicolon\n"
+    ));
+    assert!(errors.contains("line 10 }\nline 10 ^\nERROR (parse-error): Unexpected }\n"));
+    assert!(errors.contains(
+        "line 15 \toption\nline 15 \t^^^^^^\nERROR (parse-error): missing `=` between key and block\n"
+    ));
+
+    // ...and the rest of the file should still be validated.
+    assert!(!errors.contains("Could not parse file"));
+    assert!(errors.contains("missing english localization key parse_test_missing_desc_2"));
+    assert!(errors.contains("missing english localization key parse_test_missing_desc_3"));
+    assert!(!errors.contains("required field `option` missing"));
+}
+
+#[test]
+fn test_replace_path() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/replace/ck3");
+    let modfile = ModFile::read(&PathBuf::from("tests/files/replace/descriptor.mod")).unwrap();
+    let mod_root = modfile.modpath();
+
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+
+    let mut everything = Everything::new(
+        &vanilla_root,
+        Some(&mod_root),
+        modfile.replace_paths(),
+        &[],
+        true,
+    )
+    .unwrap();
+    everything.load_all();
+    everything.validate_all();
+
+    let errors = (*take_log_to()).get_logs().unwrap();
+    eprint!("{}", &errors);
+
+    // Only events/replaced.txt is excluded; events/nested/nested.txt is in a subdirectory
+    assert!(errors.contains("INFO (packaging): replace_path excludes 1 vanilla files\n"));
+    assert!(errors.contains(
+        "WARNING (packaging): replace_path names a directory that does not exist in vanilla\n"
+    ));
+}
+
+#[test]
+fn test_dlc() {
+    let errors = check_helper("tests/files/dlc/ck3", "tests/files/dlc/mod", &[], &[], true);
+    assert!(!errors.contains("base_test.0001"));
+    assert!(!errors.contains("dlc_test.0001"));
+
+    let errors = check_helper(
+        "tests/files/dlc/ck3",
+        "tests/files/dlc/mod",
+        &["dlc001".to_string()],
+        &[],
+        true,
+    );
+    assert!(!errors.contains("base_test.0001"));
+    assert!(errors.contains("dlc_test.0001"));
+}
+
+#[test]
+fn test_mod_5() {
+    let errors = check_mod_helper("mod5");
+
+    assert!(errors.contains("[MOD] file event/misplaced.txt\nERROR (filename): the game does not load files from `event`\n  did you mean `events`?\n"));
+    assert!(errors.contains("[MOD] file events/backup.txt.bak\nWARNING (filename): the game only loads .txt files from `events`\n"));
+    assert!(errors.contains(
+        "[MOD] file notes/todo.md\nWARNING (filename): the game does not load files from `notes`\n"
+    ));
+    assert!(errors.contains("[MOD] file common/on_actions/misplaced.txt\nERROR (filename): file in unexpected directory\n  did you mean `common/on_action`?\n"));
+    assert!(!errors.contains("README.md"));
+    assert!(!errors.contains("source_art"));
+}
+
+#[test]
+fn test_mod_6() {
+    let errors = check_mod_helper("mod6");
+
+    assert!(errors.contains(
+        "ERROR (missing-item): lifestyle diplomacy_lifestlye not defined in common/lifestyles/\n  did you mean `diplomacy_lifestyle`?\n"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-item): lifestyle learning_lifestyle not defined in common/lifestyles/\n\n"
+    ));
+
+    // A `limit` directly in an interaction's effect block
+    assert!(errors.contains(
+        "line 3 \t\tlimit = { always = yes }\nline 3 \t\t^^^^^\nWARNING (validation): `limit` can only be used in if/else_if or lists\n"
+    ));
+
+    // The declared fields are accepted, and a misspelled one is reported
+    assert!(errors.contains(
+        "line 25 \tai_acept = {\nline 25 \t^^^^^^^^\nWARNING (validation): unknown field `ai_acept`\n"
+    ));
+    for line in 9..25 {
+        assert!(!errors.contains(&format!("line {} ", line)));
+    }
+}
+
+#[test]
+fn test_mod_7() {
+    let errors = check_mod_helper("mod7");
+
+    assert!(errors.contains(
+        "line 9 \tcooldown = { }\nline 9 \t           ^\nERROR (validation): required one of `days`, `months`, `years` missing\n"
+    ));
+    assert!(errors.contains(
+        "line 15 \t\t\tyears = 1\nline 15 \t\t\t^^^^^\nWARNING (conflict): only one of `days`, `weeks`, `months`, `years` can be used\n"
+    ));
+    assert!(errors.contains("`days` is already set here\n"));
+    // The game doesn't care where the name of an option is
+    assert!(!errors.contains("line 21 "));
+
+    // Just one of the fields is fine
+    assert!(!errors.contains("line 33 "));
+    assert!(!errors.contains("line 48 "));
+    // A repeated field is a duplicate, not a conflict with itself
+    assert!(errors.contains(
+        "line 37 \t\t\tmonths = 1\nline 37 \t\t\t^^^^^^\nWARNING (duplicate): `months` is redefined in a following line\n"
+    ));
+    assert!(!errors
+        .contains("only one of `days`, `weeks`, `months`, `years` can be used\n  --> `months`"));
+    assert!(errors.contains(
+        "line 40 \t\ttrigger_event = {\nline 40 \t\t                ^\nERROR (validation): required one of `id`, `on_action` missing\n"
+    ));
+    assert_eq!(
+        errors
+            .matches("WARNING (conflict): only one of `id`, `on_action` can be used\n  --> `id` is already set here\n")
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_mod_8() {
+    let errors = check_mod_helper("mod8");
+
+    assert!(errors.contains(
+        "line 12 \t\t\tlimit < { always = yes }\nline 12 \t\t\t^^^^^\nERROR (validation): expected `limit =`, found `<`\n"
+    ));
+    assert!(errors.contains(
+        "line 16 \t\thas_trait > brave\nline 16 \t\t^^^^^^^^^\nERROR (validation): expected `has_trait =`, found `>`\n"
+    ));
+    assert!(!errors.contains("line 15 "));
+    assert!(!errors.contains("line 20 "));
+    assert!(errors.contains(
+        "line 21 \t\tadd_gold ?= 10\nline 21 \t\t^^^^^^^^\nERROR (validation): `add_gold ?=` does nothing here\n"
+    ));
+    assert!(errors.contains(
+        "line 22 \t\tadd_prestige < 10\nline 22 \t\t^^^^^^^^^^^^\nERROR (validation): expected `add_prestige =`, found `<`\n"
+    ));
+}
+
+#[test]
+fn test_mod_9() {
+    let errors = check_mod_helper("mod9");
+
+    // Precedence, parentheses and unary minus all give values in range
+    assert!(!errors.contains("line 14 "));
+    assert!(!errors.contains("line 15 "));
+    assert!(!errors.contains("line 16 "));
+    assert!(errors.contains(
+        "line 17 \t\tany_child = { percent = @[1 + one * 2] }\nline 17 \t\t                        ^\nWARNING (range): expected a value from 0 to 1\n"
+    ));
+    assert!(errors.contains(
+        "line 18 \t\tany_child = { percent = @[1 / zero] }\nline 18 \t\t                            ^\nERROR (parse-error): division by zero in calculation\n"
+    ));
+    assert!(errors.contains(
+        "line 19 \t\tany_child = { percent = @undefined }\nline 19 \t\t                        ^^^^^^^^^^\nERROR (parse-error): `@undefined` is not defined in this file\n"
+    ));
+    assert!(errors.contains(
+        "line 20 \t\tany_child = { percent = @[half * missing] }\nline 20 \t\t                                 ^^^^^^^\nERROR (parse-error): `@missing` is not defined in this file\n"
+    ));
+}
+
+#[test]
+fn test_mod_10() {
+    let errors = check_mod_helper("mod10");
+
+    assert!(!errors.contains("line 10 "));
+    assert!(!errors.contains("line 11 "));
+    assert!(errors.contains(
+        "line 12 \t\thas_dlc = royal_court\nline 12 \t\t          ^^^^^^^^^^^\nWARNING (validation): `royal_court` is a feature flag, not a DLC name\n  use `has_dlc_feature = royal_court` or `has_dlc = \"Royal Court\"`\n"
+    ));
+    assert!(
+        errors.contains("WARNING (validation): `Royal Court` is a DLC name, not a feature flag\n")
+    );
+    assert!(errors
+        .contains("ERROR (validation): unknown DLC `Royal Cort`\n  did you mean `Royal Court`?\n"));
+    assert!(!errors.contains("line 15 "));
+    assert!(errors.contains("WARNING (logic): `current_date =` is only true on that exact day\n"));
+    assert!(errors.contains(
+        "line 17 \t\tgame_start_date < 1066.2.30\nline 17 \t\t                  ^^^^^^^^^\nERROR (range): day must be from 1 to 28\n"
+    ));
+}
+
+#[test]
+fn test_mod_11() {
+    let errors = check_mod_helper("mod11");
+
+    assert!(!errors.contains("line 10 "));
+    assert!(!errors.contains("line 11 "));
+    assert!(errors.contains(
+        "line 12 \t\thas_character_flag = seen_flga\nline 12 \t\t                     ^^^^^^^^^\nWARNING (unset-flag): character flag `seen_flga` is never set with `add_character_flag`\n  did you mean `seen_flag`?\n"
+    ));
+    assert!(errors.contains(
+        "WARNING (unset-flag): character flag `nowhere_flag` is never set with `add_character_flag`\n\n"
+    ));
+
+    // Each family of flags is its own namespace
+    assert!(!errors.contains("line 8 "));
+    assert!(!errors.contains("line 11 "));
+    assert!(!errors.contains("line 15 "));
+    assert!(!errors.contains("line 19 "));
+    assert!(!errors.contains("line 23 "));
+    assert!(errors.contains(
+        "WARNING (unset-flag): realm flag `seen_flag` is never set with `add_realm_flag`\n"
+    ));
+    assert!(errors.contains(
+        "WARNING (unset-flag): county flag `realm_flag` is never set with `add_county_flag`\n"
+    ));
+    assert!(errors.contains(
+        "WARNING (unset-flag): province flag `county_flag` is never set with `add_province_flag`\n"
+    ));
+    assert!(errors.contains(
+        "WARNING (unset-flag): culture flag `province_flag` is never set with `add_culture_flag`\n"
+    ));
+    assert!(errors.contains(
+        "WARNING (unset-flag): faith flag `culture_flag` is never set with `add_faith_flag`\n"
+    ));
+    assert!(errors.contains("line 26 \t\thas_county_flag = county_flag\nline 26 \t\t^^^^^^^^^^^^^^^\nWARNING (scopes): `has_county_flag` requires landed title scope but the scope here is character\n"));
+}
+
+#[test]
+fn test_mod_12() {
+    let errors = check_mod_helper("mod12");
+
+    assert!(errors.contains(
+        "line 11 \t\thidden_effect = {\nline 11 \t\t^^^^^^^^^^^^^\nADVICE (tooltip): all the effects of this option are hidden, so it has no tooltip\n"
+    ));
+    assert!(errors.contains(
+        "line 18 \t\tcustom_tooltip = tooltips_gold_tt\nline 18 \t\t^^^^^^^^^^^^^^\nADVICE (tooltip): this text is shown in addition to the tooltips of the effects here\n"
+    ));
+    assert!(errors.contains(
+        "line 24 \t\trandom_list = {\nline 24 \t\t              ^\nADVICE (tooltip): the tooltip will list every outcome with its chance\n"
+    ));
+    assert!(errors.contains(
+        "line 36 \t\tsend_interface_message = {\nline 36 \t\t                         ^\nADVICE (tooltip): the effects in this message will also be shown in the surrounding tooltip\n"
+    ));
+    // custom_tooltip with the real effects hidden is the right way to do it
+    assert!(!errors.contains("line 33 "));
+}
+
+#[test]
+fn test_mod_13() {
+    let errors = check_mod_helper("mod13");
+
+    assert!(errors.contains(
+        "line 13 \t\t\tage = 25\nline 13 \t\t\t^^^\nWARNING (conflict): `age` has no effect when `template` is used\n"
+    ));
+    assert!(!errors.contains("line 12 "));
+    assert!(!errors.contains("line 14 "));
+    assert!(errors.contains(
+        "ERROR (missing-item): character template test_knight_tempalte not defined in common/scripted_character_templates/\n  did you mean `test_knight_template`?\n"
+    ));
+    assert!(errors.contains(
+        "line 20 \t\t\tage = { 16 }\nline 20 \t\t\t      ^\nERROR (validation): expected 2 integers\n"
+    ));
+    assert!(errors.contains(
+        "line 7 \ttrait = bravve\nline 7 \t        ^^^^^^\nERROR (missing-item): trait bravve not defined in common/traits/\n"
+    ));
+    // after_creation is in the new character's scope, so add_gold works there
+    assert!(!errors.contains("line 24 "));
+    // The trait weights in random_traits_list are weight blocks
+    assert!(!errors.contains("line 10 "));
+    assert!(errors.contains(
+        "line 11 \t\tcraven = { bsae = 5 }\nline 11 \t\t           ^^^^\nWARNING (validation): unknown field `bsae`\n"
+    ));
+}
+
+#[test]
+fn test_mod_14() {
+    let errors = check_mod_helper("mod14");
+
+    assert!(!errors.contains("line 1"));
+    assert!(errors.contains(
+        "line 25 \t\tspawn_army = {\nline 25 \t\t             ^\nWARNING (logic): this army will be empty\n"
+    ));
+    assert!(errors.contains(
+        "line 27 \t\t\twar_keep_on_attacker_victory = yes\nline 27 \t\t\t^^^^^^^^^^^^^^^^^^^^^^^^^^^^\nWARNING (validation): `war_keep_on_attacker_victory` does nothing without `war`\n"
+    ));
+    assert!(errors.contains(
+        "line 30 \t\t\tmen_at_arms = {\nline 30 \t\t\t              ^\nERROR (validation): required one of `stacks`, `men` missing\n"
+    ));
+    assert!(errors.contains(
+        "line 33 \t\t\tlocation = root\nline 33 \t\t\t           ^^^^\nWARNING (scopes): `root` produces character but expected province\n"
+    ));
+}
+
+#[test]
+fn test_mod_15() {
+    let errors = check_mod_helper("mod15");
+
+    assert!(errors.contains(
+        "line 22 \t\t\tlevel = test_levies_high\nline 22 \t\t\t        ^^^^^^^^^^^^^^^^\nERROR (missing-item): `test_levies_high` is not an obligation level of `test_taxes`\n  it belongs to `test_levies`\n"
+    ));
+    assert!(!errors.contains("line 18 "));
+    assert!(errors
+        .contains("ERROR (range): `test_taxes` has only 2 obligation levels, numbered from 0\n"));
+    assert!(errors.contains(
+        "ERROR (missing-item): vassal obligation test_taxs not defined in common/vassal_contracts/\n  did you mean `test_taxes`?\n"
+    ));
+    assert!(!errors.contains("line 10 "));
+    assert!(errors.contains(
+        "ERROR (missing-item): vassal contract flag test_high_taxes not defined in common/vassal_contracts/\n"
+    ));
+    assert!(errors.contains(
+        "line 22 \tobligation_levels = {\nline 22 \t                    ^\nERROR (validation): only one obligation level can be the default\n"
+    ));
+}
+
+#[test]
+fn test_mod_16() {
+    let errors = check_mod_helper("mod16");
+
+    assert!(errors.contains(
+        "line 16 \t\ttest_spearman = 2\nline 16 \t\t^^^^^^^^^^^^^\nERROR (missing-item): men at arms test_spearman not defined in common/men_at_arms_types/\n  did you mean `test_spearmen`?\n"
+    ));
+    assert!(!errors.contains("common/mercenary_companies/test_mercs.txt\nline 4 "));
+    assert!(!errors.contains("file in unexpected directory"));
+    assert!(errors.contains(
+        "line 15 \t\tcreate_holy_order = {\nline 15 \t\t                    ^\nERROR (validation): required field `capital` missing\n"
+    ));
+    assert!(errors.contains(
+        "line 16 \t\t\tleader = root.primary_title\nline 16 \t\t\t              ^^^^^^^^^^^^^\nWARNING (scopes): `primary_title` produces landed title but expected character\n"
+    ));
+    assert!(!errors.contains("line 10 "));
+}
+
+#[test]
+fn test_mod_17() {
+    let errors = check_mod_helper("mod17");
+
+    assert!(errors.contains(
+        "line 19 guest = {\nline 19 ^^^^^\nWARNING (validation): unknown section `guest`\n  expected one of courtiers, guests\n"
+    ));
+    assert!(errors.contains(
+        "[MOD] file common/courtier_guest_management/00_management.txt\nWARNING (validation): missing section `guests`\n  the game requires this section and will log errors without it\n"
+    ));
+    assert!(errors.contains(
+        "line 31 \t\thas_title = yes\nline 31 \t\t            ^^^\nWARNING (scopes): `yes` produces bool but expected landed title\n"
+    ));
+    assert!(!errors.contains("line 26 "));
+    assert!(!errors.contains("line 10 "));
+}
+
+#[test]
+fn test_mod_18() {
+    let errors = check_mod_helper("mod18");
+
+    assert!(errors.contains(
+        "line 10 \t\tdivorce = scope:spouse\nline 10 \t\t^^^^^^^\nADVICE (guard): `divorce` is not guarded by a `limit`\n  this can cause errors if its preconditions are not met; guard it with `is_married` or `is_spouse_of` or `is_consort_of` unless the caller already does\n"
+    ));
+    assert!(!errors.contains("line 15 "));
+    assert!(!errors.contains("line 21 \t\t\tremove_trait = brave\nline 21 \t\t\t^^^^^^^^^^^^\n"));
+    assert!(errors.contains(
+        "line 23 \t\tremove_trait = brave\nline 23 \t\t^^^^^^^^^^^^\nADVICE (guard): `remove_trait` is not guarded by a `limit`\n"
+    ));
+    assert!(!errors.contains("make_pregnant"));
+}
+
+#[test]
+fn test_mod_19() {
+    let errors = check_mod_helper("mod19");
+
+    assert!(errors.contains(
+        "line 8 \thidden = true\nline 8 \t         ^^^^\nERROR (validation): expected yes or no, found `true`\n  CK3 requires lowercase yes or no, and treats anything else as no\n"
+    ));
+    assert!(errors.contains(
+        "line 11 \t\tis_adult = Yes\nline 11 \t\t           ^^^\nERROR (validation): expected yes or no, found `Yes`\n"
+    ));
+    assert!(errors.contains(
+        "line 12 \t\tis_married != yes\nline 12 \t\t^^^^^^^^^^\nWARNING (logic): `is_married != yes` works, but is usually a mistake\n  if it is intended, `is_married = no` is clearer\n"
+    ));
+    assert!(!errors.contains("line 13 "));
+    assert!(!errors.contains("line 18 "));
+    assert!(errors.contains(
+        "line 19 \t\tset_to_lowborn = true\nline 19 \t\t                 ^^^^\nERROR (validation): expected yes or no, found `true`\n"
+    ));
+}
+
+#[test]
+fn test_mod_20() {
+    let errors = check_mod_helper("mod20");
+
+    assert!(errors.contains(
+        "line 11 \t\t\tadd_gold = 10\nline 11 \t\t\t^^^^^^^^\nWARNING (scopes): `add_gold` requires character scope but the scope here is province\n  --> scope became province because of `primary_title.holder.location` here\n    [MOD] file events/scopes.txt\n    line 10 \t\tprimary_title.holder.location = {\n    line 10 \t\t^^^^^^^^^^^^^^^^^^^^^^^^^^^^^\n"
+    ));
+    assert!(!errors.contains("line 14 "));
+}
+
+#[test]
+fn test_mod_21() {
+    let errors = check_mod_helper("mod21");
+
+    assert!(errors.contains(
+        "line 10 \t\tprev = {\nline 10 \t\t^^^^\nWARNING (scopes): there is no previous scope here\n"
+    ));
+    assert!(errors.contains(
+        "line 31 \t\t\t\t\tprev.prev.prev.prev = {\nline 31 \t\t\t\t\t               ^^^^\nWARNING (scopes): there is no previous scope here\n"
+    ));
+    assert!(!errors.contains("makes no sense except as first part"));
+    for line in [17, 20, 23, 26, 29, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50] {
+        assert!(!errors.contains(&format!("line {} ", line)));
+    }
+    assert!(errors.contains(
+        "line 35 \t\t\t\t\t\tadd_gold = 10\nline 35 \t\t\t\t\t\t^^^^^^^^\nWARNING (scopes): `add_gold` requires character scope but the scope here is landed title\n"
+    ));
+    assert!(errors.contains(
+        "line 38 \t\t\t\t\t\tset_title_name = prev.0001.t\nline 38 \t\t\t\t\t\t^^^^^^^^^^^^^^\nWARNING (scopes): `set_title_name` requires landed title scope but the scope here is character\n"
+    ));
+}
+
+#[test]
+fn test_mod_22() {
+    let errors = check_mod_helper("mod22");
+
+    assert!(!errors.contains(".tiger"));
+    // test_on.0001 is fired from a county on_action, through another on_action
+    assert!(!errors.contains("line 8 "));
+    assert!(errors.contains(
+        "line 10 \t\t\tadd_gold = 10\nline 10 \t\t\t^^^^^^^^\nWARNING (scopes): `add_gold` requires character scope but the scope here is faith\n"
+    ));
+    assert!(!errors.contains("line 19 "));
+    assert!(!errors.contains("line 32 "));
+    assert!(errors.contains(
+        "line 35 \t\tadd_gold = 10\nline 35 \t\t^^^^^^^^\nWARNING (scopes): `add_gold` requires character scope but the scope here is landed title\n  --> scope became landed title because of `test_on.0003` here\n    [MOD] file common/on_action/test_on_actions.txt\n    line 28 \t\ttest_on.0003\n    line 28 \t\t^^^^^^^^^^^^\n"
+    ));
+    assert!(errors.contains(
+        "line 22 \t\t10 = test_on.0009\nline 22 \t\t     ^^^^^^^^^^^^\nERROR (missing-item): event test_on.0009 not defined in events/\n"
+    ));
+}
+
+#[test]
+fn test_mod_23() {
+    let errors = check_helper("tests/files/ck3", "tests/files/mod23", &[], &["agot"], true);
+
+    assert!(!errors.contains("file in unexpected directory"));
+    assert!(!errors.contains("test_spearmen_flavor"));
+    assert!(errors.contains("line 5 test_riders = {\nline 5 ^^^^^^^^^^^\nERROR (missing-localization): missing english localization key test_riders_flavor\n"));
+    assert!(errors.contains("line 9 meraxes = {\nline 9           ^\nERROR (validation): required field `trait` missing\n"));
+    assert!(
+        errors.contains("ERROR (missing-localization): missing english localization key meraxes\n")
+    );
+    assert!(!errors.contains("missing english localization key balerion"));
+
+    // Without the profile, the framework folder is unknown and the extra checks are not done
+    let errors = check_mod_helper("mod23");
+    assert!(errors.contains("ERROR (filename): file in unexpected directory\n"));
+    assert!(!errors.contains("test_riders_flavor"));
+    assert!(!errors.contains("meraxes"));
+}
+
+#[test]
+fn test_mod_24() {
+    let errors = check_mod_helper("mod24");
+
+    assert!(errors.contains(
+        "line 4 \ttest_too_bright = rgb { 300 0 0 }\nline 4 \t                        ^^^\nWARNING (range): color values should be between 0 and 255\n"
+    ));
+    assert!(errors.contains(
+        "line 2 \tcolor = hsv360 { 400 50 50 }\nline 2 \t                 ^^^\nWARNING (range): hue should be between 0 and 360\n"
+    ));
+    assert!(errors.contains(
+        "line 3 \tcolor2 = hsv { 0.5 1.5 1 }\nline 3 \t                   ^^^\nWARNING (range): color values should be between 0.0 and 1.0\n"
+    ));
+    assert!(errors.contains("line 5 \t\tcolor = { 10 20 }\nline 5 \t\t        ^\nERROR (validation): expected 3 color values\n"));
+    assert!(errors.contains(
+        "ERROR (missing-item): named color test_blue not defined in common/named_colors/\n"
+    ));
+    assert!(!errors.contains("test_red not defined"));
+    assert!(!errors.contains("test_teal not defined"));
+    assert!(errors.contains(
+        "ADVICE (conflict): faith test_faith_two has the same color as test_faith_one\n"
+    ));
+    assert!(!errors.contains("has the same color as test_faith_three"));
+}
+
+#[test]
+fn test_mod_25() {
+    let errors = check_mod_helper("mod25");
+
+    assert!(!errors.contains("Unrecognized character"));
+    assert!(errors.contains(
+        "line 10 \t\tadd_gold = 10%\nline 10 \t\t           ^^^\nERROR (validation): percent signs are not valid\n  write 0.1 for 10%\n"
+    ));
+    assert!(errors.contains(
+        "line 11 \t\tadd_prestige = 1,000\nline 11 \t\t               ^^^^^\nERROR (validation): thousands separators are not valid\n  write 1000 for 1,000\n"
+    ));
+    assert!(errors.contains("line 12 \t\tadd_piety = 0.12345678\nline 12 \t\t            ^^^^^^^^^^\nWARNING (range): too many decimal places\n"));
+    assert!(!errors.contains("line 13 "));
+    // A factor multiplies the weight, so large values are normal
+    assert!(!errors.contains("line 20 "));
+    assert!(!errors.contains("line 22 "));
+    assert!(!errors.contains("line 18 "));
+    assert!(!errors.contains("line 26 "));
+    assert!(errors.contains("line 35 \t\t\tbase = 5\nline 35 \t\t\t^^^^\nWARNING (validation): `base` should come before `add`\n  --> `add` is here\n"));
+}
+
+#[test]
+fn test_mod_26() {
+    let errors = check_mod_helper("mod26");
+
+    assert!(!errors.contains("line 11 "));
+    assert!(errors.contains(
+        "line 12 \t\t\tset_title_name = names_no_adj\nline 12 \t\t\t                 ^^^^^^^^^^^^\nERROR (missing-localization): missing english localization key names_no_adj_adj\n"
+    ));
+    assert!(!errors.contains("line 16 "));
+    assert!(errors.contains("line 17 \t\t\t\ttitle = root\nline 17 \t\t\t\t        ^^^^\nWARNING (scopes): `root` produces character but expected landed title\n"));
+    assert!(errors.contains("line 18 \t\t\t\tarticle = Yes\nline 18 \t\t\t\t          ^^^\nERROR (validation): expected yes or no, found `Yes`\n"));
+    assert!(!errors.contains("line 19 "));
+    assert!(errors.contains("line 24 \t\t\t\tname = { names_good }\nline 24 \t\t\t\t       ^\nERROR (validation): expected a localization key for the new name, found a block\n"));
+    assert!(errors.contains("line 27 \t\tset_title_name = names_good\nline 27 \t\t^^^^^^^^^^^^^^\nWARNING (scopes): `set_title_name` requires landed title scope but the scope here is character\n"));
+}
+
+#[test]
+fn test_mod_27() {
+    let errors = check_mod_helper("mod27");
+
+    assert!(errors
+        .contains("  --> the other dynasty is here\n    [MOD] file common/dynasties/test_dynasties.txt\n    line 10 9001 = {\n    line 10 ^^^^\n"));
+    assert!(errors.contains("line 8 \tdynasty = 9009\nline 8 \t          ^^^^\nERROR (missing-item): dynasty 9009 not defined in common/dynasties/\n\n"));
+    assert!(errors.contains("line 13 \t\tdynasty:9005 = {\nline 13 \t\t        ^^^^\nERROR (missing-item): dynasty 9005 not defined in common/dynasties/\n"));
+    assert!(errors.contains("line 19 \t\thouse:house_missing = {\nline 19 \t\t      ^^^^^^^^^^^^^\nERROR (missing-item): house house_missing not defined in common/dynasty_houses/\n"));
+    assert!(!errors.contains("line 10 \t\tdynasty:9001"));
+    assert!(!errors.contains("line 16 \t\thouse:house_test"));
+    assert!(!errors.contains("line 4 \tdynasty_house = house_test"));
+    assert!(errors.contains("line 10 \tdynasty_house = house_test\nline 10 \t                ^^^^^^^^^^\nWARNING (conflict): house house_test belongs to dynasty 9001, not 9002\n"));
+    assert!(errors.contains("line 15 \tdynasty = 9003\nline 15 \t          ^^^^\nERROR (missing-item): dynasty 9003 not defined in common/dynasties/\n"));
+    assert!(errors.contains("ERROR (missing-item): house house_tset not defined in common/dynasty_houses/\n  did you mean `house_test`?\n"));
+}
+
+#[test]
+fn test_mod_28() {
+    let errors = check_mod_helper("mod28");
+
+    assert!(errors.contains("line 3 \tbuildings = { test_farms_01 test_wonder_01 }\nline 3 \t                            ^^^^^^^^^^^^^^\nWARNING (validation): `test_wonder_01` is not a regular building\n"));
+    assert!(errors.contains("  --> it is a special building\n"));
+    assert!(errors.contains("line 5 \tduchy_capital_building = test_farms_01\nline 5 \t                         ^^^^^^^^^^^^^\nWARNING (validation): `test_farms_01` is not a duchy capital building\n  --> it is a regular building\n    [MOD] file common/buildings/test_buildings.txt\n    line 1 test_farms_01 = {\n    line 1 ^^^^^^^^^^^^^\n"));
+    assert!(!errors.contains("line 4 "));
+    assert!(!errors.contains("line 7 \t\tspecial_building"));
+    assert!(errors.contains("line 12 \tholding = castel_holding\n"));
+    assert!(errors.contains("line 13 \tspecial_building = test_wonder_01\nline 13 \t                   ^^^^^^^^^^^^^^\nWARNING (validation): province has no `special_building_slot = test_wonder_01`\n"));
+    assert!(errors.contains("line 14 \tspecial_building_slot = test_capital_01\nline 14 \t                        ^^^^^^^^^^^^^^^\nWARNING (validation): `test_capital_01` is not a special building\n"));
+    assert!(!errors.contains("line 15 "));
+    // Province 1 is the capital of d_test, province 3 is not
+    assert_eq!(errors.matches("is not the capital of a duchy").count(), 1);
+    assert!(errors.contains("line 20 \tduchy_capital_building = test_capital_01\nline 20 \t                         ^^^^^^^^^^^^^^^\nWARNING (validation): province is not the capital of a duchy\n"));
+    assert!(errors.contains(
+        "ERROR (missing-item): building test_farms_02 not defined in common/buildings/\n"
+    ));
+}
+
+#[test]
+fn test_vanilla_only() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/ck3");
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(PathBuf::new());
+    show_vanilla(true);
+    log_to(Box::new(Vec::new()));
+
+    let mut everything = Everything::new(&vanilla_root, None, Vec::new(), &[], true).unwrap();
+    everything.load_all();
+    everything.validate_all();
+    log_summary();
+    show_vanilla(false);
+
+    let errors = (*take_log_to()).get_logs().unwrap();
+    eprint!("{}", &errors);
+
+    assert!(errors.contains(
+        "[CK3] file events/non-dup.txt
+line 3 non-dup.0001 = {
+line 3                ^
+ERROR (validation): required field `option` missing
+"
+    ));
+    assert!(errors.contains(
+        "Summary:
+  ERROR (validation): 1
+  ADVICE (encoding): 1
+  total: 2
+"
+    ));
+}
+
+#[test]
+fn test_mod_29() {
+    let errors = check_mod_helper("mod29");
+
+    assert!(errors.contains(
+        "[MOD] file common/on_action/loop
+ERROR (read-error): could not read path
+  it links back to tests/files/mod29
+"
+    ));
+    assert!(errors.contains(
+        "[MOD] file events/missing.txt
+ERROR (read-error): could not read path
+  No such file or directory (os error 2)
+"
+    ));
+    // The rest of the mod is still validated
+    assert!(errors.contains(
+        "[MOD] file events/after.txt
+line 3 after.0001 = {
+line 3              ^
+ERROR (validation): required field `option` missing
+"
+    ));
+}
+
+#[test]
+fn test_mod_30() {
+    let errors = check_helper("tests/files/ck3", "tests/files/mod30", &[], &[], true);
+
+    assert!(errors.contains(
+        "[MOD] file events/self
+ERROR (read-error): could not read path
+"
+    ));
+    assert!(errors.contains(
+        "[MOD] file events/shared
+WARNING (duplicate): symlinked directory was already scanned
+  its files were loaded as events/framework
+"
+    ));
+    assert!(!errors.contains("events/shared/framework.txt"));
+    assert!(errors.contains("[MOD] file events/framework/framework.txt\n"));
+
+    let errors = check_helper("tests/files/ck3", "tests/files/mod30", &[], &[], false);
+    assert!(!errors.contains("events/self"));
+    assert!(!errors.contains("events/shared"));
+    assert!(errors.contains("[MOD] file events/framework/framework.txt\n"));
+}
+
+#[test]
+fn test_mod_31() {
+    let errors = check_mod_helper("mod31");
+
+    // Tabs and non-ASCII characters each count as one column
+    assert!(errors.contains(
+        "line 2  bad_concept:0 \"Ça été\t[nonexistent_concept|E] 日本 [other_missing|E]\"
+line 2                       \t ^^^^^^^^^^^^^^^^^^^
+ERROR (missing-item): game concept nonexistent_concept not defined in common/game_concepts/
+"
+    ));
+    assert!(errors.contains(
+        "line 2  bad_concept:0 \"Ça été\t[nonexistent_concept|E] 日本 [other_missing|E]\"
+line 2                       \t                              ^^^^^^^^^^^^^
+ERROR (missing-item): game concept other_missing not defined in common/game_concepts/
+"
+    ));
+    assert!(errors.contains(
+        "line 8 \timmediate = {\tadd_gold = 日本 }
+line 8 \t             \t           ^^^^
+"
+    ));
+    // Quoted strings are underlined including their quotes
+    assert!(errors.contains(
+        "line 5 \ttitle = \"ça\t日本\"
+line 5 \t        ^^^^^^^^^
+"
+    ));
+    // The part of an unclosed string on the next line is reported where it is
+    assert!(errors.contains(
+        "line 6 \tdesc = \"multi
+line 6 \t       ^^^^^^
+WARNING (parse-error): Quoted string not closed
+"
+    ));
+    assert!(errors.contains(
+        "line 7 line\" option = { name = nonexistent_loc_key }
+line 7 ^^^^^
+ERROR (missing-localization): missing english localization key line
+"
+    ));
+}
+
+#[test]
+fn test_errorkey_codes() {
+    let mut seen = HashSet::new();
+    for key in ErrorKey::iter() {
+        let code = key.as_str();
+        assert!(seen.insert(code), "duplicate code {}", code);
+        assert_eq!(ErrorKey::from_str(code), Ok(key));
+        assert!(!key.description().is_empty());
+        assert!(!key.explanation().is_empty());
+    }
+}
+
+#[test]
+fn test_mod_32() {
+    let errors = check_mod_helper("mod32");
+
+    // Single-valued fields that are repeated are reported at both places
+    assert!(errors.contains(
+        "line 6 \tdesc = dup_desc_a
+line 6 \t^^^^
+WARNING (duplicate): `desc` is redefined in a following line
+  --> the other one is here
+    [MOD] file events/dup.txt
+    line 7 \tdesc = dup_desc_b
+    line 7 \t^^^^
+"
+    ));
+    assert!(errors.contains("WARNING (duplicate): `immediate` is redefined in a following line\n"));
+    assert!(errors.contains("WARNING (duplicate): `is_shown` is redefined in a following line\n"));
+    assert!(errors
+        .contains("WARNING (duplicate): `ai_check_interval` is redefined in a following line\n"));
+    assert!(errors.contains(
+        "[MOD] file common/character_interactions/dup.txt
+line 3 \tdesc = dup_desc_a
+line 3 \t^^^^
+WARNING (duplicate): `desc` is redefined in a following line
+"
+    ));
+    assert!(errors.contains("WARNING (duplicate): `ai_accept` is redefined in a following line\n"));
+    // Fields that can be repeated are not reported
+    assert!(!errors.contains("`option` is redefined"));
+    assert!(!errors.contains("`cost` is redefined"));
+}
+
+#[test]
+fn test_mod_33() {
+    let errors = check_mod_helper("mod33");
+
+    assert!(errors.contains(
+        "line 7 \ttrigger = { }
+line 7 \t^^^^^^^
+ADVICE (empty-block): empty `trigger` does nothing
+"
+    ));
+    assert!(errors.contains(
+        "line 10 \t\t\tlimit = { is_adult = yes }
+line 10 \t\t\t^^^^^
+ADVICE (empty-block): this `limit` has no effects after it
+"
+    ));
+    assert!(errors.contains(
+        "line 12 \t\thidden_effect = { }
+line 12 \t\t                ^
+ADVICE (empty-block): empty `hidden_effect` does nothing
+"
+    ));
+    assert!(errors.contains(
+        "line 17 \toption = {
+line 17 \t         ^
+ADVICE (empty-block): option has no name, no effects, and no ai_chance
+"
+    ));
+    assert!(!errors.contains("line 14 "));
+}
+
+#[test]
+fn test_where() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/where/ck3");
+    let mod_root = PathBuf::from("tests/files/where/mod");
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+
+    let mut everything =
+        Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+    everything.load_all();
+    let found = where_item(&everything, "decision:my_decision").unwrap();
+    let listed = list_items(&everything, "decision").unwrap();
+    let missing = where_item(&everything, "decision:no_decision").unwrap();
+    let bad_type = where_item(&everything, "nonsense:my_decision");
+    take_log_to();
+
+    eprint!("{}{}{}", found, listed, missing);
+    assert_eq!(
+        found,
+        "decision:my_decision:
+  CK3 common/decisions/00_decisions.txt:1:1 (file replaced by DLC file)
+  DLC common/decisions/00_decisions.txt:2:1
+  MOD common/decisions/zz_decisions.txt:2:1 (used)
+"
+    );
+    assert_eq!(
+        listed,
+        "my_decision MOD common/decisions/zz_decisions.txt:2:1
+other_decision CK3 common/decisions/10_more.txt:1:1
+vanilla_decision DLC common/decisions/00_decisions.txt:5:1
+"
+    );
+    assert_eq!(missing, "decision:no_decision:\n  not defined anywhere\n");
+    assert!(bad_type.is_err());
+}
+
+#[test]
+fn test_dump_filelist() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/where/ck3");
+    let mod_root = PathBuf::from("tests/files/where/mod");
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+    let everything =
+        Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+    let overridden = dump_filelist(&everything, Some("common/decisions"));
+    take_log_to();
+
+    let vanilla_root = PathBuf::from("tests/files/replace/ck3");
+    let modfile = ModFile::read(&PathBuf::from("tests/files/replace/descriptor.mod")).unwrap();
+    let mod_root = modfile.modpath();
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+    let everything = Everything::new(
+        &vanilla_root,
+        Some(&mod_root),
+        modfile.replace_paths(),
+        &[],
+        true,
+    )
+    .unwrap();
+    let excluded = dump_filelist(&everything, Some("events"));
+    take_log_to();
+
+    eprint!("{}{}", overridden, excluded);
+    assert_eq!(
+        overridden,
+        "CK3 common/decisions/00_decisions.txt (overridden by DLC file)
+DLC common/decisions/00_decisions.txt
+CK3 common/decisions/10_more.txt
+MOD common/decisions/zz_decisions.txt
+"
+    );
+    assert_eq!(
+        excluded,
+        "MOD events/modded.txt
+CK3 events/nested/nested.txt
+CK3 events/replaced.txt (excluded by replace_path events)
+"
+    );
+}
+
+#[test]
+fn test_mod_34() {
+    let errors = check_mod_helper("mod34");
+
+    assert!(errors.contains(
+        "line 9 \t\tadd_internal_flag = risky
+line 9 \t\t                    ^^^^^
+ERROR (validation): expected one of special, dangerous
+"
+    ));
+    assert!(errors.contains(
+        "line 11 \t\treply = { name = options_ok }
+line 11 \t\t^^^^^
+ADVICE (unneeded): only needed for letter_event
+"
+    ));
+    assert!(errors.contains(
+        "line 3 options.0001 = {
+line 3 ^^^^^^^^^^^^
+WARNING (logic): none of the options of this event can ever be chosen
+"
+    ));
+    assert!(errors.contains(
+        "line 31 \t\t\tskill = juggling
+line 31 \t\t\t        ^^^^^^^^
+ERROR (missing-item): skill juggling not defined"
+    ));
+    assert!(errors.contains(
+        "line 45 \tsender = 42
+line 45 \t         ^^
+WARNING (scopes): expected character
+"
+    ));
+    assert!(!errors.contains("line 20 "));
+    assert!(!errors.contains("line 24 "));
+}
+
+#[test]
+fn test_travel() {
+    let errors = check_helper(
+        "tests/files/travel/ck3",
+        "tests/files/travel/mod",
+        &[],
+        &[],
+        true,
+    );
+
+    assert!(errors.contains(
+        "line 6 \t\tscope:travel_plan = { add_travel_option = fast_option }
+line 6 \t\t                                          ^^^^^^^^^^^
+ERROR (missing-item): travel option fast_option not defined in common/travel/travel_options/
+"
+    ));
+    assert!(errors.contains(
+        "line 8 \ttravel_plan_modifier = { travel_safety = 5 travel_luck = 1 }
+line 8 \t                                           ^^^^^^^^^^^
+WARNING (validation): unknown modifier `travel_luck`
+"
+    ));
+    assert!(errors.contains(
+        "line 9 \t\t\ton_arrival_event = travel.9999
+line 9 \t\t\t                   ^^^^^^^^^^^
+ERROR (missing-item): event travel.9999 not defined in events/
+"
+    ));
+    assert!(errors.contains("terrain swamp not defined"));
+    assert!(!errors.contains("unknown token"));
+    assert!(!errors.contains("file in unexpected directory"));
+
+    // A game from before the travel system doesn't have its effects and triggers
+    let errors = check_helper("tests/files/ck3", "tests/files/travel/mod", &[], &[], true);
+    assert!(errors.contains(
+        "line 6 \t\tstart_travel_plan = {
+line 6 \t\t^^^^^^^^^^^^^^^^^
+ERROR (validation): unknown token `start_travel_plan`
+"
+    ));
+    assert!(errors.contains("unknown token `has_travel_option`"));
+}
+
+#[test]
+fn test_mod_35() {
+    let errors = check_mod_helper("mod35");
+
+    assert!(errors.contains("WARNING (config): unknown limit\n"));
+    assert!(errors.contains("ERROR (config): expected a whole number\n"));
+    assert!(errors.contains(
+        "line 22 \t\thidden_effect = {
+line 22 \t\t                ^
+ADVICE (deep-nesting): effects and triggers are nested more than 15 deep here
+"
+    ));
+    assert!(!errors.contains("line 21 \t\thidden_effect"));
+    assert!(errors.contains(
+        "line 41 \t\trandom_list = {
+line 41 \t\t              ^
+ADVICE (long-random-list): random_list has 4 entries
+  more than 3 is hard to maintain; consider a scripted list or a script value
+"
+    ));
+    assert!(errors.contains(
+        "line 3 limits.0001 = {
+line 3 ^^^^^^^^^^^
+ADVICE (too-many-options): event has 6 options
+  only 5 fit in the event window, the rest will overflow it
+"
+    ));
+    assert!(errors.contains(
+        "ADVICE (long-localization): localization value is 61 characters long
+  the game cuts off values longer than 40 characters
+"
+    ));
+}
+
+#[test]
+fn test_defines() {
+    let errors = check_helper(
+        "tests/files/defines/ck3",
+        "tests/files/defines/mod",
+        &[],
+        &[],
+        true,
+    );
+
+    assert!(errors.contains(
+        "line 3 \tEND_DATA = \"1500.1.1\"
+line 3 \t^^^^^^^^
+ERROR (validation): unknown define `NGame.END_DATA`
+  the game ignores defines it doesn't know; did you mean `END_DATE`?
+"
+    ));
+    assert!(errors.contains(
+        "line 6 NCharater = {
+line 6 ^^^^^^^^^
+ERROR (validation): unknown define group `NCharater`
+  did you mean `NCharacter`?
+"
+    ));
+    assert!(errors.contains(
+        "line 10 NDefines.NCharacter.MAX_AGE = \"old\"
+line 10                               ^^^^^
+ERROR (validation): expected a number but found a string
+  --> the game's define is here
+    [CK3] file common/defines/00_defines.txt
+    line 7 \tMAX_AGE = 100
+"
+    ));
+    assert!(errors.contains(
+        "line 11                                           ^^^^
+ERROR (validation): expected a list of numbers
+"
+    ));
+    assert!(errors.contains(
+        "line 12                                       ^^^^^
+ERROR (validation): define lists can only contain values
+"
+    ));
+    assert!(!errors.contains("line 2 "));
+}
+
+#[test]
+fn test_mod_36() {
+    let errors = check_mod_helper("mod36");
+
+    assert!(errors.contains(
+        "line 4 \t\tgold = bare_cost_value
+line 4 \t\t       ^^^^^^^^^^^^^^^
+WARNING (missing-localization): script value `bare_cost_value` is shown to the player but has no localization
+  give its parts a `desc`, or add a localization key `bare_cost_value`, so the player doesn't see its raw name
+"
+    ));
+    assert!(!errors.contains("script value `described_cost_value`"));
+    assert!(!errors.contains("script value `localized_cost_value`"));
+    // ai_will_do is never shown to the player
+    assert!(!errors.contains("line 15"));
+}
+
+#[test]
+fn test_several_mods() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/ck3");
+    set_vanilla_root(vanilla_root.clone());
+    log_to(Box::new(Vec::new()));
+    Everything::cache_game_files();
+
+    let mut advice = Vec::new();
+    for name in ["first", "second"] {
+        let mod_root = PathBuf::from(format!("tests/files/several/{}", name));
+        start_mod_run(mod_root.clone());
+        let mut everything =
+            Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+        everything.load_all();
+        everything.validate_all();
+        advice.push(count_at_level(ErrorLevel::Advice));
+    }
+
+    let errors = (*take_log_to()).get_logs().unwrap();
+    eprint!("{}", &errors);
+    // The first mod's config ignores the BOM advice, and that must not carry over
+    assert_eq!(errors.matches("file starts with a UTF-8 BOM").count(), 1);
+    assert_eq!(advice[0], 0);
+    assert!(advice[1] > 0);
+}
+
+#[test]
+fn test_audit_tables() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/ck3");
+    set_vanilla_root(vanilla_root.clone());
+    log_to(Box::new(Vec::new()));
+    let mut everything = Everything::new(&vanilla_root, None, Vec::new(), &[], true).unwrap();
+    everything.load_all();
+    let audit = audit_tables(&everything, &PathBuf::from("tests/files/audit")).unwrap();
+    let _ = take_log_to();
+    eprint!("{}", audit);
+
+    assert!(audit.contains(
+        "Effects in effects.log but not in SCOPE_EFFECT:
+    (Character | LandedTitle, \"add_brand_new_thing\", Unchecked),
+"
+    ));
+    assert!(audit.contains(
+        "    every_vassal: scopes Character in the tables, Character | Province in effects.log\n"
+    ));
+    assert!(audit.contains(
+        "Triggers in triggers.log but not in SCOPE_TRIGGER_TARGET:
+    (Character, \"is_brand_new_friend_of\", Character),
+"
+    ));
+    assert!(audit.contains(
+        "Triggers in triggers.log but not in SCOPE_VALUE:
+    (Faith, \"brand_new_count\"),
+"
+    ));
+    assert!(audit.contains(
+        "Triggers in triggers.log but not in SCOPE_TRIGGER_BOOL:
+    (Character, \"is_brand_new\"),
+"
+    ));
+    assert!(audit.contains(
+        "Links in event_targets.log but not in SCOPE_TO_SCOPE:
+    (Character, \"brand_new_link\", Faith),
+"
+    ));
+    assert!(audit.contains("Scopes in the logs that are not in `Scopes`:\n    mystery_scope\n"));
+    // Known effects, triggers and links that match their tables are not mentioned
+    assert!(!audit.contains("add_gold"));
+    assert!(!audit.contains("\"age\""));
+    assert!(!audit.contains("exists"));
+    assert!(!audit.contains("    liege\n"));
+    assert!(audit.contains("    liege_or_court_owner\n"));
+}
+
+#[test]
+fn test_mod_37() {
+    let errors = check_mod_helper("mod37");
+
+    // A long chain of links through many different scopes
+    assert!(!errors.contains("line 9 "));
+    assert!(!errors.contains("line 10 "));
+    assert!(errors.contains(
+        "line 11 \t\texists = primary_heir.mothr
+line 11 \t\t                      ^^^^^
+ERROR (validation): unknown token `mothr`
+  did you mean `mother`?
+"
+    ));
+    assert!(errors.contains(
+        "line 12 \t\texists = tite:k_france
+line 12 \t\t         ^^^^^^^^^^^^^
+ERROR (validation): unknown prefix `tite:`
+  did you mean `title:`?
+"
+    ));
+    assert!(errors.contains(
+        "line 13 \t\texists = province:abc
+line 13 \t\t                  ^^^
+ERROR (validation): expected a province id number
+"
+    ));
+    assert!(!errors.contains("line 14 "));
+}
+
+#[test]
+fn test_mod_38() {
+    let errors = check_mod_helper("mod38");
+
+    // Script values are fine at the end of a chain where a value is expected
+    assert!(!errors.contains("line 9 "));
+    assert!(!errors.contains("line 13 "));
+    assert!(errors.contains(
+        "line 10 \t\tis_child_of = my_value.liege
+line 10 \t\t              ^^^^^^^^
+WARNING (scopes): script value `my_value` can only be the last part of a chain
+  a script value is a number, so it has no scopes to go to from there
+"
+    ));
+    assert!(errors.contains(
+        "line 14 \t\tliege.my_value = {
+line 14 \t\t      ^^^^^^^^
+WARNING (scopes): script value `my_value` is a number, not a scope
+  effects can only be run in a scope like a character or a title
+"
+    ));
+    assert!(errors.contains(
+        "line 17 \t\tmy_value.liege = {
+line 17 \t\t^^^^^^^^
+WARNING (scopes): script value `my_value` can only be the last part of a chain
+"
+    ));
+    // Scripted triggers in weight modifiers
+    assert!(!errors.contains("my_trigger"));
+}
+
+#[test]
+fn test_mod_39() {
+    let errors = check_mod_helper("mod39");
+
+    // Negative positions and script values for min and max are fine
+    assert!(!errors.contains("line 11 "));
+    assert!(!errors.contains("line 12 "));
+    assert!(!errors.contains("line 18 "));
+    assert!(errors.contains(
+        "line 19 \t\t\tposition = last
+line 19 \t\t\t           ^^^^
+WARNING (validation): expected integer
+"
+    ));
+    assert!(errors.contains(
+        "line 22 \t\tordered_vassal = {
+line 22 \t\t                 ^
+ADVICE (logic): ordered list without `order_by` or `position`
+  without them, which item gets picked is arbitrary
+"
+    ));
+    assert!(!errors.contains("line 9 "));
+}
+
+#[test]
+fn test_mod_40() {
+    let errors = check_mod_helper("mod40");
+
+    // The weight is for each province, not for the character who owns the realm
+    assert!(errors.contains(
+        "line 14 \t\t\t\t\tis_adult = yes
+line 14 \t\t\t\t\t^^^^^^^^
+WARNING (scopes): `is_adult` requires character scope but the scope here is province
+  --> scope became province because of `random_realm_province` here
+    [MOD] file events/weights.txt
+    line 9 \t\trandom_realm_province = {
+    line 9 \t\t^^^^^^^^^^^^^^^^^^^^^
+"
+    ));
+    assert!(!errors.contains("is_coastal"));
+    assert!(!errors.contains("rich_province_modifier"));
+    assert!(!errors.contains("modifiers.txt"));
+}
+
+#[test]
+fn test_mod_41() {
+    let errors = check_mod_helper("mod41");
+
+    // The cases of `trigger = has_trait` are traits
+    assert!(errors.contains(
+        "line 19 \t\t\tcrave = { add_gold = 5 }
+line 19 \t\t\t^^^^^
+ERROR (missing-item): trait crave not defined in common/traits/
+"
+    ));
+    assert!(!errors.contains("line 18 "));
+    assert!(!errors.contains("line 20 "));
+
+    // The cases of a yes/no trigger are yes and no
+    assert!(errors.contains(
+        "line 12 \t\t\tmaybe = { age > 30 }
+line 12 \t\t\t^^^^^
+ERROR (validation): expected yes or no
+"
+    ));
+    assert!(!errors.contains("line 11 "));
+
+    assert!(errors.contains(
+        "line 23 \t\t\ttrigger = my_scripted_trigger
+line 23 \t\t\t          ^^^^^^^^^^^^^^^^^^^
+WARNING (validation): `my_scripted_trigger` can't be the trigger of a `switch`
+  it needs a trigger that compares to a value, like `has_trait`
+"
+    ));
+
+    // The modifiers of `random` are checked like triggers
+    assert!(errors.contains(
+        "line 30 \t\t\t\tis_coastal = yes
+line 30 \t\t\t\t^^^^^^^^^^
+WARNING (scopes): `is_coastal` requires province scope but the scope here is character
+"
+    ));
+    assert!(!errors.contains("chance"));
+    assert!(!errors.contains("add_prestige"));
+}
+
+#[test]
+fn test_mod_42() {
+    let errors = check_mod_helper("mod42");
+
+    // The change in `immediate` is resolved, and the one in the scripted effect may be
+    // resolved by its caller
+    assert!(!errors.contains("`scope:change`"));
+    assert!(!errors.contains("effects.txt"));
+
+    assert!(errors.contains(
+        "line 25 \t\t\tsave_scope_as = forgotten
+line 25 \t\t\t                ^^^^^^^^^
+WARNING (logic): title and vassal change `scope:forgotten` is never resolved
+  without `resolve_title_and_vassal_change = scope:forgotten` the changes are left half done, which can corrupt the realms involved
+"
+    ));
+    assert!(errors.contains(
+        "line 24 \t\t\ttype = stolen
+line 24 \t\t\t       ^^^^^^
+ERROR (validation): expected one of abdication, conquest,"
+    ));
+    assert!(errors.contains(
+        "line 31 \t\tset_realm_capital = title:d_france
+line 31 \t\t                          ^^^^^^^^
+WARNING (validation): `set_realm_capital` needs a county, but `d_france` is a duchy
+"
+    ));
+}
+
+#[test]
+fn test_mod_43() {
+    let errors = check_mod_helper("mod43");
+
+    assert!(errors.contains(
+        "line 2  yes_button:0 \"Sure\"
+line 2  ^^^^^^^^^^
+ADVICE (localization-override): `yes_button` replaces the game's localization for this key
+  if that's intended, put it in a `replace/` folder; otherwise give it a name of its own
+  --> the game's key is here
+    [CK3] file localization/english/ui_l_english.yml
+    line 2  yes_button:0 \"Yes\"
+    line 2  ^^^^^^^^^^
+"
+    ));
+    // Overridden in a replace/ folder, by a prefix in the config, or used by the mod's event
+    assert!(!errors.contains("no_button"));
+    assert!(!errors.contains("tooltip_gold"));
+    assert!(!errors.contains("vanilla_event_title"));
+}
+
+#[test]
+fn test_total_conversion() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/tc/ck3");
+    let modfile = ModFile::read(&PathBuf::from("tests/files/tc/descriptor.mod")).unwrap();
+    let mod_root = modfile.modpath();
+
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+
+    // Replacing the landed titles makes it a total conversion
+    let mut everything = Everything::new(
+        &vanilla_root,
+        Some(&mod_root),
+        modfile.replace_paths(),
+        &[],
+        true,
+    )
+    .unwrap();
+    assert!(everything.is_total_conversion());
+    everything.load_all();
+    everything.validate_all();
+
+    let errors = (*take_log_to()).get_logs().unwrap();
+    eprint!("{}", &errors);
+    assert!(errors.contains(
+        "ERROR (missing-item): title k_vanilla not defined in common/landed_titles/
+  the mod replaces the game's common/landed_titles/, so only its own are defined
+"
+    ));
+    assert!(!errors.contains("k_mine"));
+
+    // Without the replace_path, it can be asked for
+    log_to(Box::new(Vec::new()));
+    let mut everything =
+        Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+    assert!(!everything.is_total_conversion());
+    everything.set_total_conversion();
+    everything.load_all();
+    everything.validate_all();
+
+    let errors = (*take_log_to()).get_logs().unwrap();
+    eprint!("{}", &errors);
+    assert!(errors.contains(
+        "ERROR (missing-item): title k_vanilla not defined in common/landed_titles/
+  the mod replaces the game's common/landed_titles/, so only its own are defined
+"
+    ));
+}
+
+#[test]
+fn test_file_policies() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/ck3");
+    let mod_root = PathBuf::from("tests/files/mod44");
+
+    set_vanilla_root(vanilla_root.clone());
+    start_mod_run(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+
+    let mut everything =
+        Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+    everything.load_all();
+    everything.validate_all();
+    log_policy_summary();
+    // Don't let the policies apply to the other tests
+    start_mod_run(PathBuf::new());
+
+    let errors = (*take_log_to()).get_logs().unwrap();
+    eprint!("{}", &errors);
+
+    // The framework's missing localization is ignored, but its other reports are not
+    assert!(!errors.contains("library_title"));
+    assert!(errors.contains("[MOD] file events/framework/library.txt"));
+    assert!(errors.contains("WARNING (validation): unknown field `unknown_field`"));
+    // Under wip only errors are shown, and the framework's ignored key still applies
+    assert!(errors.contains(
+        "[MOD] file events/framework/wip/draft.txt
+line 3 draft.0001 = {
+line 3              ^
+ERROR (validation): required field `option` missing
+"
+    ));
+    assert!(!errors.contains("draft_title"));
+    assert_eq!(errors.matches("unknown field").count(), 2);
+    // Outside the policies everything is shown
+    assert!(errors.contains("missing russian localization key main_title"));
+    // The russian localization is left out entirely
+    assert!(!errors.contains("policy_l_russian"));
+    assert!(errors.contains(
+        "Left out by the file policies:
+  events/framework: 4
+  events/framework/wip: 1
+  localization/russian: 1
+"
+    ));
+}
+
+#[test]
+fn test_modfile_paths() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let user_dir = PathBuf::from("tests/files/modfiles/user");
+    let variant = user_dir.join("mod/variant");
+
+    // The launcher's .mod files have paths relative to the user directory
+    let modfile = ModFile::read(&user_dir.join("mod/variant.mod")).unwrap();
+    assert_eq!(modfile.locate_modpath().unwrap(), variant);
+
+    // Backslashes and a trailing slash
+    let modfile = ModFile::read(&user_dir.join("mod/slashes.mod")).unwrap();
+    assert_eq!(modfile.locate_modpath().unwrap(), variant);
+
+    // An absolute path
+    let absolute = variant.canonicalize().unwrap();
+    let pathname = std::env::temp_dir().join("ck3-tiger-absolute.mod");
+    std::fs::write(
+        &pathname,
+        format!("name = \"Absolute\"\npath = \"{}\"\n", absolute.display()),
+    )
+    .unwrap();
+    let modfile = ModFile::read(&pathname).unwrap();
+    std::fs::remove_file(&pathname).unwrap();
+    assert_eq!(modfile.locate_modpath().unwrap(), absolute);
+
+    // Archives can't be checked
+    let modfile = ModFile::read(&user_dir.join("mod/zipped.mod")).unwrap();
+    let err = modfile.locate_modpath().unwrap_err().to_string();
+    assert!(err.contains("Archives are not supported; please extract it first"));
+
+    // A path that isn't there
+    let modfile = ModFile::read(&user_dir.join("mod/missing.mod")).unwrap();
+    let err = modfile.locate_modpath().unwrap_err().to_string();
+    assert!(err.starts_with("Cannot find mod directory mod/missing, from `path` on line 2 of"));
+    assert!(err.contains("Looked in: "));
+    assert!(err.contains("mod/missing,"));
+
+    // A launcher .mod file with a stale path falls back to the directory with its name
+    let modfile = ModFile::read(&user_dir.join("mod/moved.mod")).unwrap();
+    assert_eq!(
+        modfile.locate_modpath().unwrap(),
+        user_dir.join("mod/moved")
+    );
+
+    // A descriptor.mod in the mod directory is used even if its path is out of date
+    let stale = user_dir.join("mod/stale");
+    let modfile = ModFile::locate(&stale, None).unwrap();
+    assert_eq!(modfile.name().unwrap().as_str(), "Stale");
+    assert_eq!(modfile.locate_modpath().unwrap(), stale);
+
+    // A mod directory needs a descriptor.mod
+    let err = ModFile::locate(&user_dir.join("mod/nodescriptor"), None)
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("No descriptor.mod in mod directory"));
+
+    // Without a descriptor.mod, a single other .mod file is used
+    let single = user_dir.join("mod/single");
+    let modfile = ModFile::locate(&single, None).unwrap();
+    assert_eq!(modfile.name().unwrap().as_str(), "Single");
+    assert_eq!(modfile.locate_modpath().unwrap(), single);
+
+    // Steam workshop mods by number
+    let steamapps = PathBuf::from("tests/files/modfiles/steamapps");
+    let modfile = ModFile::locate(&PathBuf::from("12345"), Some(&steamapps)).unwrap();
+    assert_eq!(modfile.name().unwrap().as_str(), "Workshop");
+    assert_eq!(
+        modfile.locate_modpath().unwrap(),
+        steamapps.join("workshop/content/1158310/12345")
+    );
+    let err = ModFile::locate(&PathBuf::from("67890"), Some(&steamapps))
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("Steam workshop mod 67890 is not downloaded"));
+}
+
+#[test]
+fn test_game_concepts() {
+    let errors = check_mod_helper("mod45");
+
+    assert!(errors
+        .contains("ERROR (missing-item): game concept glory not defined in common/game_concepts/"));
+    assert!(errors
+        .contains("ERROR (missing-item): game concept honor not defined in common/game_concepts/"));
+    assert!(!errors.contains("game concept fame"));
+    assert!(!errors.contains("game concept renown"));
+    assert!(errors.contains(
+        "line 6 \talias = { devotion renown prestige }
+line 6 \t                          ^^^^^^^^
+WARNING (name-conflict): alias `prestige` is also the name of a game concept
+  --> that concept is here
+    [MOD] file common/game_concepts/concepts.txt
+    line 1 prestige = {
+    line 1 ^^^^^^^^
+"
+    ));
+    assert!(errors.contains(
+        "line 2 \talias = { fame renown }
+line 2 \t               ^^^^^^
+WARNING (name-conflict): alias `renown` belongs to more than one game concept
+  --> the other alias is here
+    [MOD] file common/game_concepts/concepts.txt
+    line 6 \talias = { devotion renown prestige }
+    line 6 \t                   ^^^^^^
+"
+    ));
+}
+
+#[test]
+fn test_court_scene() {
+    let errors = check_mod_helper("mod46");
+
+    // Slot names are unique
+    assert!(
+        errors.contains("WARNING (duplicate): artifact slot is redefined by another artifact slot")
+    );
+    // Environments and artifact assets are entities from the asset files
+    assert!(!errors.contains("entity throne_room_entity"));
+    assert!(!errors.contains("entity goblet_entity"));
+    assert!(errors.contains(
+        "ERROR (missing-item): entity throne_room_lights_entity not defined in gfx/models/"
+    ));
+    assert!(
+        errors.contains("ERROR (missing-item): entity banner_entity not defined in gfx/models/")
+    );
+    // Slots and cameras are cross-checked
+    assert!(errors.contains("ERROR (missing-item): court scene character slot spouse not defined in gfx/court_scene/character_slots/"));
+    assert!(errors.contains("ERROR (missing-item): court scene camera court_closeup_camera not defined in gfx/court_scene/camera_settings/"));
+    assert!(errors.contains(
+        "line 12 \tcourt_slot = wall_big_01
+line 12 \t             ^^^^^^^^^^^
+ERROR (missing-item): court scene artifact slot wall_big_01 not defined in gfx/court_scene/artifact_slots/
+  did you mean `wall_big_1`?
+"
+    ));
+    assert!(!errors.contains("pedestal_1 not defined"));
+    // The fields are declared, so typos are reported
+    assert!(errors.contains("line 13 \ticon_size = 2\nline 13 \t^^^^^^^^^\nWARNING (validation): unknown field `icon_size`\n"));
+    assert!(errors.contains("line 10 \tpostion = { 2 0 1 }\nline 10 \t^^^^^^^\nWARNING (validation): unknown field `postion`\n"));
+    assert!(errors.contains("line 9 \tposition = { 2 0 }\nline 9 \t           ^\nWARNING (validation): expected 3 coordinates, found 2\n"));
+    assert!(!errors.contains("line 3 \tposition"));
+    assert!(!errors.contains("line 9 \t\ttrigger"));
+}
+
+#[test]
+fn test_icons() {
+    let errors = check_mod_helper("mod47");
+
+    // The icon name is looked for in the trait icons folder, with .dds added
+    assert!(!errors.contains("brave.dds"));
+    assert!(!errors.contains("calm_icon.dds does not exist"));
+    assert!(errors.contains(
+        "line 12 \ticon = shy_icon
+line 12 \t       ^^^^^^^^
+ERROR (missing-file): file gfx/interface/icons/traits/shy_icon.dds does not exist
+"
+    ));
+    assert!(errors.contains("WARNING (file-case): path exists but with different case: expected gfx/interface/icons/traits/calm_icon.dds, found gfx/interface/icons/traits/Calm_Icon.dds"));
+    // The file must really be a DDS image
+    assert!(errors.contains(
+        "ERROR (image-format): gfx/interface/icons/traits/craven.dds is not a DDS file
+  it is a PNG image with a .dds name, which some graphics cards can't show
+"
+    ));
+}
+
+#[test]
+fn test_near_miss_localization() {
+    let errors = check_mod_helper("mod48");
+
+    assert!(errors.contains(
+        "ERROR (missing-localization): missing english localization key  near_title
+  the key has spaces around it; without them, it would be found
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-localization): missing english localization key near_\u{200b}desc
+  the key contains the invisible character U+200B; without it, it would be found
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-localization): missing english localization key Near_Option
+  the localization key is `near_option`, with different case
+"
+    ));
+    // An invisible character in the localization file's key is pointed out where it is
+    assert!(errors
+        .contains("ERROR (localization): Unexpected invisible character U+200B, expected `:`"));
+}
+
+#[test]
+fn test_trait_xp() {
+    let errors = check_mod_helper("mod49");
+
+    assert!(errors.contains(
+        "ERROR (missing-item): trait `xp_several` has no track `hunters`
+  did you mean `hunter`?
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-item): trait `xp_several` has no track `falconers`
+  did you mean `falconer`?
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (validation): trait `xp_several` has several tracks
+  say which one with `track =`
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (validation): trait `xp_single` has only one track
+  leave out the `track`
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (validation): trait `xp_none` has no XP track
+  the game ignores XP for traits without a `track` or `tracks`
+"
+    ));
+    assert!(errors.contains(
+        "line 18 \t\t\t150 = {
+line 18 \t\t\t^^^
+WARNING (range): trait XP goes from 0 to 100
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (validation): XP thresholds should be in increasing order
+  --> the previous threshold is here
+    [MOD] file common/traits/xp_traits.txt
+    line 23 \t\t\t80 = {
+    line 23 \t\t\t^^
+"
+    ));
+    // The correct uses in xp_good_effect are not reported
+    assert!(!errors.contains("line 2 \tadd_trait_xp"));
+}
+
+#[test]
+fn test_pool_selectors() {
+    let errors = check_mod_helper("mod50");
+
+    assert!(errors.contains(
+        "ERROR (missing-item): pool character selector knigt_selector not defined in common/pool_character_selectors/
+  did you mean `knight_selector`?
+"
+    ));
+    // The selector's trigger knows about scope:province and scope:employer
+    assert!(errors.contains(
+        "line 22 \t\tlocation = scope:employer
+line 22 \t\t           ^^^^^^^^^^^^^^
+WARNING (scopes): `scope:employer` produces character but expected province
+"
+    ));
+    assert!(!errors.contains("line 4 \t\tlocation = scope:province"));
+    assert!(errors.contains("WARNING (range): `min` age is higher than `max`"));
+    assert!(errors.contains("ERROR (validation): expected one of male, female, any"));
+}
+
+#[test]
+fn test_combat() {
+    let errors = check_mod_helper("mod51");
+
+    assert!(errors.contains(
+        "ERROR (missing-item): combat effect river_crosing_effect not defined in common/combat_effects/
+  did you mean `river_crossing_effect`?
+"
+    ));
+    assert!(errors.contains(
+        "line 3 \tadvantage = lots
+line 3 \t            ^^^^
+ERROR (validation): expected number
+"
+    ));
+    assert!(
+        errors.contains("ERROR (validation): expected one of skirmish, main, pursuit, aftermath")
+    );
+    // Combat phase events run in the scope of a combat side
+    assert!(errors.contains(
+        "WARNING (scopes): `is_adult` requires character scope but the scope here is combat side"
+    ));
+    assert!(!errors.contains("line 6 \t\thas_combat_effect"));
+    assert!(errors.contains(
+        "line 9 \t\tbattle_event = {
+line 9 \t\t               ^
+ERROR (validation): required field `key` missing
+"
+    ));
+}
+
+#[test]
+fn test_iterator_scopes() {
+    let errors = check_mod_helper("mod52");
+
+    // `order_by` and `weight` are calculated for each province in the list
+    assert!(errors.contains(
+        "line 7 \t\t\t\t\tis_adult = yes
+line 7 \t\t\t\t\t^^^^^^^^
+WARNING (scopes): `is_adult` requires character scope but the scope here is province
+"
+    ));
+    assert!(errors.contains(
+        "line 22 \t\t\t\tis_adult = yes
+line 22 \t\t\t\t^^^^^^^^
+WARNING (scopes): `is_adult` requires character scope but the scope here is province
+  --> scope became province because of `random_realm_province` here
+    [MOD] file common/scripted_effects/ordered.txt
+    line 17 \trandom_realm_province = {
+    line 17 \t^^^^^^^^^^^^^^^^^^^^^
+"
+    ));
+    assert!(errors.contains(
+        "line 5 \t\t\tvalue = prowess
+line 5 \t\t\t        ^^^^^^^
+WARNING (scopes): `prowess` requires character scope but the scope here is province
+"
+    ));
+    // `max` is calculated once, in the character scope outside the list
+    assert!(!errors.contains("line 13 "));
+    assert!(!errors.contains("line 15 "));
+}
+
+#[test]
+fn test_ai_modifiers() {
+    let errors = check_mod_helper("mod53");
+
+    assert!(errors.contains(
+        "WARNING (validation): unknown ai value `ai_boldnes`
+  did you mean `ai_boldness`?
+"
+    ));
+    assert!(errors.contains(
+        "line 13 \t\t\twho = capital_province
+line 13 \t\t\t      ^^^^^^^^^^^^^^^^
+WARNING (scopes): `capital_province` produces province but expected character
+"
+    ));
+    assert!(errors.contains(
+        "line 16 \t\tcompare_modifier = {
+line 16 \t\t                   ^
+ERROR (validation): required field `value` missing
+"
+    ));
+    assert!(!errors.contains("line 20 "));
+    assert!(errors.contains(
+        "line 25 \t\t\topinion_target = capital_province
+line 25 \t\t\t                 ^^^^^^^^^^^^^^^^
+WARNING (scopes): `capital_province` produces province but expected character
+"
+    ));
+}
+
+#[test]
+fn test_scripted_cycles() {
+    let errors = check_mod_helper("mod54");
+
+    // The cycle goes through a macro call and an `if`, and is reported once
+    assert!(errors.contains(
+        "line 17 \t\tcycle_a_effect = yes
+line 17 \t\t^^^^^^^^^^^^^^
+ERROR (crash): scripted effect `cycle_a_effect` calls itself in a cycle
+  the game hangs or crashes on cycle_a_effect -> cycle_b_effect -> cycle_c_effect -> cycle_a_effect
+"
+    ));
+    assert_eq!(errors.matches("calls itself in a cycle").count(), 1);
+    assert!(errors.contains("ERROR (crash): scripted effect `self_effect` calls itself\n"));
+    assert!(errors.contains("ERROR (crash): scripted trigger `self_trigger` calls itself\n"));
+    // Only the effect that first goes over the limit is reported, not the ones calling it
+    assert!(errors.contains(
+        "ADVICE (tooltip): scripted effect `big_effect` expands to 30 fields when all its calls are filled in"
+    ));
+    assert!(!errors.contains("`bigger_effect` expands"));
+}
+
+#[test]
+fn test_event_scope_names() {
+    let errors = check_mod_helper("mod55");
+
+    // Temporary scopes are not passed on to the events that are fired
+    assert!(errors.contains(
+        "line 12 \t\ttrigger_event = scopetest.2
+line 12 \t\t                ^^^^^^^^^^^
+WARNING (scopes): event `scopetest.2` uses `scope:temp`, which is not set when it is fired here
+  --> it is used here
+    [MOD] file events/scopetest.txt
+    line 29 \t\tscope:temp = {
+    line 29 \t\t^^^^^^^^^^
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (scopes): event `scopetest.2` uses `scope:enemy`, which is not set when it is fired here"
+    ));
+    // Saved scopes are passed along a chain of events, but scope:rival is never saved
+    assert!(errors.contains(
+        "line 53 \t\ttrigger_event = scopetest.4
+line 53 \t\t                ^^^^^^^^^^^
+WARNING (scopes): event `scopetest.4` uses `scope:rival`, which is not set when it is fired here
+"
+    ));
+    assert!(!errors.contains("`scope:friend`"));
+    // scope:mother comes from the on_action that fires scopetest.1
+    assert!(!errors.contains("`scope:mother`"));
+    // Scopes that are checked for existence first are optional
+    assert!(!errors.contains("`scope:maybe`"));
+    assert!(!errors.contains("`scope:perhaps`"));
+    // scopetest.5 is fired from a scripted effect, so anything may be set
+    assert!(!errors.contains("`scope:anything`"));
+}
+
+#[test]
+fn test_province_refs() {
+    let errors = check_mod_helper("mod56");
+
+    assert!(errors.contains(
+        "line 9 \t\tprovince:2 = {
+line 9 \t\t         ^
+WARNING (validation): province 2 is a sea zone
+  most effects and triggers only work on land provinces
+"
+    ));
+    assert!(errors.contains(
+        "line 12 \t\tprovince:9 = {
+line 12 \t\t         ^
+ERROR (missing-item): province 9 not defined in map_data/definition.csv
+"
+    ));
+    // Armies can't be spawned in impassable provinces
+    assert!(errors.contains(
+        "line 17 \t\t\tlocation = province:3
+line 17 \t\t\t           ^^^^^^^^^^
+WARNING (validation): province 3 is an impassable mountain
+  this needs a land province that can be entered
+"
+    ));
+    assert!(!errors.contains("province 1 "));
+}
+
+#[test]
+fn test_macro_args() {
+    let errors = check_mod_helper("mod57");
+
+    // The arguments are checked where the macro puts them
+    assert!(errors.contains("ERROR (missing-item): trait macro_wise not defined in common/traits/"));
+    assert!(
+        errors.contains("ERROR (missing-item): trait macro_coward not defined in common/traits/")
+    );
+    assert!(errors.contains(
+        "line 13 \t\tgive_trait_effect = { TRAT = macro_brave AMOUNT = 20 }
+line 13 \t\t                    ^
+ERROR (validation): required field `TRAIT` missing
+"
+    ));
+    assert!(errors.contains(
+        "line 13 \t\t                      ^^^^
+WARNING (macro): scripted effect `give_trait_effect` has no parameter `TRAT`
+  did you mean `TRAIT`?
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (macro): scripted effect `give_trait_effect` has no parameter `COLOR`\n"
+    ));
+    // AMOUNT has a default, so it can be left out
+    assert!(!errors.contains("`AMOUNT`"));
+    assert!(!errors.contains("macro_brave not defined"));
+}
+
+#[test]
+fn test_languages() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/ck3");
+    let mod_root = PathBuf::from("tests/files/mod58");
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+
+    let mut everything =
+        Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+    everything.add_translation("german").unwrap();
+    assert!(everything.add_translation("klingon").is_err());
+    everything.load_all();
+    everything.validate_all();
+    let summary = everything.missing_translations(false);
+    let details = everything.missing_translations(true);
+    let errors = (*take_log_to()).get_logs().unwrap();
+
+    eprint!("{}{}{}", errors, summary, details);
+    // Keys are checked in english, and the german ones are only in the summary
+    assert!(errors.contains(
+        "line 2 \tdesc = langtest_german_only
+line 2 \t       ^^^^^^^^^^^^^^^^^^^^
+ERROR (missing-localization): missing english localization key langtest_german_only
+"
+    ));
+    assert!(!errors.contains("german localization key"));
+    assert_eq!(
+        summary,
+        "Missing german translations of english: 4 keys
+  localization/english/langtest_l_english.yml: 3
+  localization/english/other_l_english.yml: 1
+
+"
+    );
+    assert_eq!(
+        details,
+        "Missing german translations of english: 4 keys
+  localization/english/langtest_l_english.yml: 3
+    line 4 langtest_decision_tooltip
+    line 5 langtest_decision_confirm
+    line 6 langtest_extra
+  localization/english/other_l_english.yml: 1
+    line 2 langtest_other
+
+"
+    );
+}
+
+#[test]
+fn test_global_scopes() {
+    let errors = check_mod_helper("mod59");
+
+    // The game start on_actions have no scope, and neither do the on_actions they call
+    assert!(errors.contains(
+        "line 4 \t\tadd_gold = 100
+line 4 \t\t^^^^^^^^
+WARNING (scopes): `add_gold` requires character scope but the scope here is none
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (scopes): `add_piety` requires character scope but the scope here is none"
+    ));
+    assert!(errors
+        .contains("WARNING (scopes): `is_ai` requires character scope but the scope here is none"));
+    // Iterators give the effects a character to work on
+    assert!(!errors.contains("`add_prestige` requires"));
+    // Achievements are checked for the player character
+    assert!(errors.contains(
+        "line 10 broken_achievement = {
+line 10 ^^^^^^^^^^^^^^^^^^
+WARNING (validation): achievement has `happened` but no `possible`
+"
+    ));
+    assert!(errors.contains("ERROR (validation): unknown token `has_nonexistent_thing`"));
+    assert!(!errors.contains("`gold` requires"));
+}
+
+#[test]
+fn test_scanned_assets() {
+    let errors = check_mod_helper("mod60");
+
+    // The game's asset files are scanned for the names of their entities
+    assert!(!errors.contains("scan_sword_entity"));
+    assert!(!errors.contains("scan_shield_entity"));
+    // Names deeper inside the entities don't count
+    assert!(errors.contains(
+        "line 10 \tasset = scan_nested_entity
+line 10 \t        ^^^^^^^^^^^^^^^^^^
+ERROR (missing-item): entity scan_nested_entity not defined in gfx/models/
+"
+    ));
+    assert!(errors.contains("ERROR (missing-item): entity idle not defined in gfx/models/"));
+}
+
+#[test]
+fn test_line_endings() {
+    let errors = check_mod_helper("mod61");
+
+    // The same file with CRLF and with LF line endings gets the same reports
+    let reports = |file: &str| {
+        errors
+            .split("\n\n")
+            .filter(|report| report.contains(file))
+            .map(|report| {
+                report
+                    .replace("events/lf.txt", "events/crlf.txt")
+                    .replace("lfxx", "crlf")
+            })
+            .map(|report| report[report.find("[MOD]").unwrap()..].to_string())
+            .collect::<Vec<_>>()
+    };
+    let crlf = reports("events/crlf.txt");
+    assert!(crlf.len() >= 5);
+    assert_eq!(crlf, reports("events/lf.txt"));
+    assert!(errors.contains(
+        "line 11 \" unknown_effect = yes }
+line 11   ^^^^^^^^^^^^^^
+ERROR (validation): unknown token `unknown_effect`
+"
+    ));
+
+    assert!(errors.contains(
+        "[MOD] file events/archive.txt
+ERROR (parse-error): file contains null bytes
+"
+    ));
+    assert!(errors.contains(
+        "line 3 bomtest.1 = {
+line 3 ^
+ADVICE (encoding): UTF-8 BOM in the middle of the file
+"
+    ));
+    assert!(errors.contains(
+        "line 5 \t                            ^
+ERROR (parse-error): Unrecognized control character U+0001
+"
+    ));
+}
+
+#[test]
+fn test_suggestions() {
+    let errors = check_mod_helper("mod62");
+
+    assert!(errors.contains(
+        "WARNING (config): unknown error key `missing-itme`
+  did you mean `missing-item`?
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (casing): dynasty Suggest_Dynasty not defined in common/dynasties/
+  it is `suggest_dynasty`, with different case
+"
+    ));
+    // Swapped letters count as one typo
+    assert!(errors.contains(
+        "ERROR (validation): unknown token `add_glod`
+  did you mean `add_gold`?
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-localization): missing english localization key suggest_event_titel
+  did you mean `suggest_event_title`?
+"
+    ));
+    // Iterators with a wrong or missing prefix get the prefix that works there
+    assert!(errors.contains(
+        "ERROR (validation): unknown token `each_vassal`
+  did you mean `every_vassal`?
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (validation): unknown token `vassal`
+  did you mean `any_vassal`?
+"
+    ));
+}
+
+#[test]
+fn test_temple_holdings() {
+    let errors = check_mod_helper("mod63");
+
+    assert!(errors.contains(
+        "line 10 \tholding = church_holding
+line 10 \t          ^^^^^^^^^^^^^^
+INFO (history): temple holding in c_templeton, whose faith temple_faith_none has no theocracy doctrine
+  the faith needs one of doctrine_theocracy_lay_clergy or doctrine_theocracy_temporal to decide whether the liege or the faith controls its temples
+  --> the county's faith is set here
+    [MOD] file history/provinces/temple_provinces.txt
+    line 3 \treligion = temple_faith_none
+"
+    ));
+    // The later faith has a theocracy doctrine
+    assert!(!errors.contains("faith temple_faith_lay has no"));
+    assert!(!errors.contains("line 11 "));
+    assert!(errors.contains(
+        "line 14 \t\t\tlease_out_to = scope:order
+line 14 \t\t\t^^^^^^^^^^^^
+ADVICE (validation): `b_fortress` has no temple holding in its province history
+"
+    ));
+    assert!(errors.contains(
+        "line 17 \t\t\tlease_out_to = scope:order
+line 17 \t\t\t^^^^^^^^^^^^
+WARNING (validation): `lease_out_to` needs a barony, but `c_templeton` is a county
+"
+    ));
+}
+
+#[test]
+fn test_dna() {
+    let errors = check_mod_helper("mod64");
+
+    assert!(errors.contains(
+        "line 8 \tdna = dna_missing
+line 8 \t      ^^^^^^^^^^^
+ERROR (missing-item): dna dna_missing not defined in common/dna_data/
+"
+    ));
+    assert!(errors.contains(
+        "line 28 \t\t\t\t1 = male_hair_missing
+line 28 \t\t\t\t    ^^^^^^^^^^^^^^^^^
+ERROR (missing-item): accessory male_hair_missing not defined in gfx/portraits/accessories/
+"
+    ));
+    assert!(!errors.contains("male_hair_short"));
+    assert!(errors.contains(
+        "line 15 \t\t\thair_color = { 13 300 13 245 }
+line 15 \t\t\t                  ^^^
+ERROR (range): expected a number from 0 to 255
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-item): gene gene_chin_height has no template `chin_width_pos`\n"
+    ));
+    // The unknown gene's values are not checked further
+    assert!(errors.contains(
+        "line 17 \t\t\tgene_nose_size = { \"nose_size_pos\" 10 \"nose_size_pos\" 10 }
+line 17 \t\t\t^^^^^^^^^^^^^^
+ERROR (missing-item): gene gene_nose_size not defined in common/genes/
+"
+    ));
+    assert!(errors.contains(
+        "line 18 \t\t\thairstyles = { \"scripted_hairstyles\" 42 }
+line 18 \t\t\t             ^
+ERROR (validation): expected 4 values
+  a template and its strength, twice
+"
+    ));
+    assert!(errors.contains(
+        "[MOD] file common/bookmark_portraits/dna_bookmark.txt
+line 6 \t\thair_color = { 13 245 13 -1 }
+line 6 \t\t                         ^^
+ERROR (range): expected a number from 0 to 255
+"
+    ));
+    assert!(!errors.contains("dna_good"));
+}
+
+#[test]
+fn test_cultural_names() {
+    let errors = check_mod_helper("mod65");
+
+    assert!(errors.contains(
+        "line 7 \t\tname_list_missing = cn_cnland_folk
+line 7 \t\t^^^^^^^^^^^^^^^^^
+ERROR (missing-item): name list name_list_missing not defined in common/culture/name_lists/
+"
+    ));
+    assert!(errors.contains(
+        "line 4 \tlandless = maybe
+line 4 \t           ^^^^^
+ERROR (validation): expected yes or no
+"
+    ));
+    assert!(errors.contains(
+        "line 11 \t\tdestroy_if_invalid_heir = { yes }
+line 11 \t\t                          ^
+ERROR (validation): expected value, found block
+"
+    ));
+    assert!(!errors.contains("definite_form"));
+    assert!(!errors.contains("ruler_uses_title_name"));
+    assert!(errors.contains(
+        "line 6  cn_cnland_flok:0 \"Flokland\"
+line 6  ^^^^^^^^^^^^^^
+WARNING (localization): cultural name `cn_cnland_flok` is not used by any title
+  check the `cultural_names` of the title it's meant for
+"
+    ));
+    assert!(!errors.contains("`cn_cnland_folk`"));
+    assert!(!errors.contains("`cn_cnland_count`"));
+}
+
+#[test]
+fn test_faith_icons() {
+    let errors = check_mod_helper("mod66");
+
+    assert!(errors.contains(
+        "line 18 \t\t\treformed_icon = icon_faith_star
+line 18 \t\t\t                ^^^^^^^^^^^^^^^
+WARNING (missing-file): icon `icon_faith_star` is only in gfx/interface/icons/faith/small/icon_faith_star.dds
+  the game looks for it in gfx/interface/icons/faith/icon_faith_star.dds
+"
+    ));
+    assert!(errors.contains(
+        "line 23 \t\t\ticon = {
+line 23 \t\t\t       ^
+ERROR (validation): required field `icon` missing
+"
+    ));
+    assert!(errors.contains(
+        "line 17 \t\ticon = tenet_icon_nowhere
+line 17 \t\t       ^^^^^^^^^^^^^^^^^^
+ERROR (missing-file): file gfx/interface/icons/faith_doctrines/tenet_icon_nowhere.dds does not exist
+"
+    ));
+    // Without an `icon`, the doctrine's own name is used
+    assert!(errors.contains(
+        "line 19 \ttenet_icon_unnamed = {
+line 19 \t^^^^^^^^^^^^^^^^^^
+ERROR (missing-file): file gfx/interface/icons/faith_doctrines/tenet_icon_unnamed.dds does not exist
+"
+    ));
+    assert!(!errors.contains("tenet_icon_default.dds"));
+    assert!(!errors.contains("icon_faith_default.dds"));
+    assert!(!errors.contains("icon_faith_sun"));
+    assert!(!errors.contains("icon_faith_moon"));
+    assert!(!errors.contains("icon_faith_missing.dds"));
+    assert!(!errors.contains("doctrine tenet_icon_sunlight not defined"));
+    // The costs, triggers and modifiers of doctrines are checked, in the faith's scope
+    assert!(errors.contains("line 14 \t\t\tis_adult = yes\nline 14 \t\t\t^^^^^^^^\nWARNING (scopes): `is_adult` requires character scope but the scope here is faith\n"));
+    assert!(errors.contains("line 19 \t\tpiety_cots = 50\nline 19 \t\t^^^^^^^^^^\nWARNING (validation): unknown field `piety_cots`\n"));
+    for line in [7, 11, 16] {
+        assert!(!errors.contains(&format!("field_doctrines.txt\nline {line} ")));
+    }
+}
+
+#[test]
+fn test_deprecated() {
+    let errors = check_mod_helper("mod67");
+
+    assert!(errors.contains(
+        "line 18 \t\tchange_focus = diplomacy_focus
+line 18 \t\t^^^^^^^^^^^^
+ERROR (deprecated): `change_focus` was removed in 1.5, and you are targeting 1.7
+  use `set_focus` instead
+"
+    ));
+    assert!(errors.contains(
+        "line 10 \t\thas_focus_type = diplomacy_focus
+line 10 \t\t^^^^^^^^^^^^^^
+WARNING (deprecated): `has_focus_type` was renamed to `has_focus` in 1.5
+  the old name still works in 1.7, but may be removed in a later version
+"
+    ));
+    assert!(errors.contains(
+        "line 11 \t\thas_doctrine_parameter_value = yes
+line 11 \t\t^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+ERROR (deprecated): `has_doctrine_parameter_value` was removed in 1.6, and you are targeting 1.7
+  use `has_doctrine_parameter` instead
+"
+    ));
+    // Links are recognized alone, at the start of a chain, and further along it
+    for line in ["line 12 \t\t^", "line 19 \t\t^", "line 22 \t\t      ^"] {
+        assert!(errors.contains(&format!(
+            "{}^^^^^^^^^^^^
+WARNING (deprecated): `realm_capital` was renamed to `capital_province` in 1.6
+",
+            line
+        )));
+    }
+    assert!(!errors.contains("unknown token"));
+}
+
+#[test]
+fn test_activity_guests() {
+    let errors = check_mod_helper("mod68");
+
+    assert!(errors.contains(
+        "line 30 \t\t\t\tscope:special_guest_guardian = {
+line 30 \t\t\t\t^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+ERROR (missing-item): special guest `guardian` is not declared in activity activity_guest_feast
+  special guests are declared in the `special_guests` block of the activity
+"
+    ));
+    assert!(errors.contains(
+        "line 45 \t\t\t\t\tscope:special_guest_honore ?= {
+line 45 \t\t\t\t\t^^^^^^^^^^^^^^^^^^^^^^^^^^
+ERROR (missing-item): special guest `honore` is not declared in activity activity_guest_feast
+  did you mean `honoree`?
+"
+    ));
+    assert!(errors.contains(
+        "line 16 \t\t\tcontribution = lots_of_gold
+line 16 \t\t\t               ^^^^^^^^^^^^
+ERROR (missing-item): script value lots_of_gold not defined in common/script_values/
+"
+    ));
+    assert!(!errors.contains("`honoree` is not declared"));
+    assert!(!errors.contains("scope:host"));
+
+    // The on_actions that the activity runs, and the ones they call, are checked too
+    assert!(errors.contains(
+        "line 14 \t\tscope:special_guest_toastmaster = {
+line 14 \t\t^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+ERROR (missing-item): special guest `toastmaster` is not declared in activity activity_guest_feast
+  --> the activity runs the on_action here
+    [MOD] file common/activities/guest_activities.txt
+    line 55 \t\t\ton_action = feast_complete
+"
+    ));
+    assert!(errors.contains(
+        "line 63 \tmax_guest_count = 10\nline 63 \t^^^^^^^^^^^^^^^\nWARNING (validation): unknown field `max_guest_count`\n"
+    ));
+}
+
+#[test]
+fn test_council_tasks() {
+    let errors = check_mod_helper("mod69");
+
+    assert!(errors.contains(
+        "line 14 \t\tis_adult = yes
+line 14 \t\t^^^^^^^^
+WARNING (scopes): `is_adult` requires character scope but the scope here is landed title
+"
+    ));
+    assert!(errors.contains(
+        "line 37 \tcounty_target = realm
+line 37 \t^^^^^^^^^^^^^
+WARNING (validation): `county_target` is only used by tasks of task_type_county
+"
+    ));
+    assert!(errors.contains(
+        "line 41 \t\tadd = development_level
+line 41 \t\t      ^^^^^^^^^^^^^^^^^
+WARNING (scopes): `development_level` requires landed title scope but the scope here is character
+"
+    ));
+    // The county owner and the councillor are known in the county task
+    assert!(!errors.contains("line 10 "));
+    assert!(!errors.contains("line 18 "));
+    assert!(!errors.contains("line 24 "));
+    assert!(!errors.contains("line 25 "));
+    assert!(!errors.contains("line 30 "));
+    // All the fields are declared
+    assert!(errors.contains("line 45 \tskil = stewardship\nline 45 \t^^^^\nWARNING (validation): unknown field `skil`\n"));
+    assert!(!errors.contains("line 43 "));
+    assert!(!errors.contains("line 44 "));
+}
+
+#[test]
+fn test_trait_opposites() {
+    let errors = check_mod_helper("mod70");
+
+    assert!(errors.contains(
+        "line 4 \tadd_trait = test_bold
+line 4 \t            ^^^^^^^^^
+WARNING (conflict): trait `test_bold` is an opposite of `test_timid`, so the character will not get it
+"
+    ));
+    assert!(errors.contains(
+        "line 5 \ttrait = test_timid
+line 5 \t        ^^^^^^^^^^
+WARNING (conflict): trait `test_timid` is an opposite of `test_bold`, so the character will not get it
+"
+    ));
+    // Only traits added in the same block are compared
+    assert!(!errors.contains("line 7 "));
+    assert!(errors.contains(
+        "line 3 \thas_trait_with_flag = test_flag_darring
+line 3 \t                      ^^^^^^^^^^^^^^^^^
+ERROR (missing-item): trait flag test_flag_darring not defined in common/traits/
+"
+    ));
+    assert!(!errors.contains("test_flag_daring not defined"));
+    assert!(errors.contains(
+        "WARNING (unset-flag): character flag `test_flag_shy` is never set with `add_character_flag`
+  `test_flag_shy` is a trait flag; check it with `has_trait_with_flag`
+"
+    ));
+}
+
+#[test]
+fn test_qeq_targets() {
+    let errors = check_mod_helper("mod71");
+
+    assert!(errors.contains(
+        "line 2 \troot ?= {
+line 2 \t^^^^
+WARNING (logic): `root` always exists, so `?=` does nothing here
+  use `=` instead
+"
+    ));
+    assert!(errors.contains(
+        "line 2 \tthis ?= {
+line 2 \t^^^^
+WARNING (logic): `this` always exists, so `?=` does nothing here
+"
+    ));
+    assert!(!errors.contains("`liege` always exists"));
+    assert!(!errors.contains("`liege.liege` always exists"));
+    // `?=` guards the block as if it checked that the target exists
+    assert!(!errors.contains(
+        "line 6 \t\tset_relation_friend = { target = root reason = test_reason }
+line 6 \t\t^^^^^^^^^^^^^^^^^^^
+ADVICE (guard)"
+    ));
+    assert!(errors.contains(
+        "line 9 \t\tset_relation_friend = { target = root reason = test_reason }
+line 9 \t\t^^^^^^^^^^^^^^^^^^^
+ADVICE (guard): `set_relation_friend` is not guarded by a `limit`
+"
+    ));
+}
+
+#[test]
+fn test_html_report() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/ck3");
+    let mod_root = PathBuf::from("tests/files/mod71");
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+    collect_reports(true);
+
+    let mut everything =
+        Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+    everything.load_all();
+    everything.validate_all();
+    let reports = take_reports();
+    collect_reports(false);
+    take_log_to();
+
+    let html = html_report(&[("mod71".to_string(), reports)]);
+    assert!(html.contains(
+        "<label><input type=\"checkbox\" class=\"level\" value=\"warning\" checked> WARNING (2)</label>"
+    ));
+    assert!(html.contains("<option value=\"logic\">logic (2)</option>"));
+    assert!(html.contains(
+        "<details class=\"file\" open data-path=\"common/scripted_triggers/test_triggers.txt\">"
+    ));
+    assert!(html.contains(
+        "<div class=\"report warning\" data-level=\"warning\" data-key=\"logic\">
+<div><span class=\"level\">WARNING</span> <span class=\"key\">(logic)</span> `this` always exists, so `?=` does nothing here</div>
+<pre>line 2 \tthis ?= {
+line 2 \t^^^^</pre>
+<div class=\"info\">use `=` instead</div>
+</div>
+"
+    ));
+    assert!(!html.contains("left out to keep this page usable"));
+}
+
+#[test]
+fn test_accolade_and_activity_type_scopes() {
+    let errors = check_mod_helper("mod72");
+
+    // The accolade iterator, trigger, value and link all work in accolade scope
+    assert!(!errors.contains("line 4 "));
+    assert!(!errors.contains("line 5 "));
+    assert!(!errors.contains("line 6 "));
+    assert!(errors.contains(
+        "line 8 \taccolade_rank >= 2
+line 8 \t^^^^^^^^^^^^^
+WARNING (scopes): `accolade_rank` requires accolade scope but the scope here is character
+"
+    ));
+    assert!(errors.contains(
+        "line 11 \tactivity_type:missing_activity = {
+line 11 \t              ^^^^^^^^^^^^^^^^
+ERROR (missing-item): activity missing_activity not defined in common/activities/
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (scopes): `is_adult` requires character scope but the scope here is activity type
+"
+    ));
+    // Decision and activity type targets are accepted where the triggers take those items
+    assert!(errors.contains(
+        "line 16 \tis_decision_on_cooldown = decision:missing_decision
+line 16 \t                                   ^^^^^^^^^^^^^^^^
+ERROR (missing-item): decision missing_decision not defined in common/decisions/
+"
+    ));
+    assert!(errors.contains(
+        "line 17 \tcan_execute_decision = root.primary_title
+line 17 \t                            ^^^^^^^^^^^^^
+WARNING (scopes): `primary_title` produces landed title but expected decision
+"
+    ));
+    assert!(errors.contains("line 18 \tin_activity_type = activity_type:missing_activity\n"));
+    assert!(errors.contains("line 28 \t\thas_activity_type = activity_type:missing_activity\n"));
+    // The accolade_type link compares to accolade types
+    assert!(!errors.contains("line 22 "));
+    assert!(errors.contains(
+        "line 23 \t\taccolade_type = root.primary_title
+line 23 \t\t                     ^^^^^^^^^^^^^
+WARNING (scopes): `primary_title` produces landed title but expected accolade type
+"
+    ));
+    assert!(errors.contains(
+        "line 29 \t\tactivity_type = { is_adult = yes }
+line 29 \t\t                  ^^^^^^^^
+WARNING (scopes): `is_adult` requires character scope but the scope here is activity type
+"
+    ));
+}
+
+#[test]
+fn test_interaction_loc_chains() {
+    let errors = check_mod_helper("mod73");
+
+    assert!(errors.contains(
+        "ERROR (missing-localization): missing english localization for the tooltip for a yes answer of interaction test_ask_interaction
+  the game tries these keys in order: `test_ask_interaction_pre_answer_yes`, `INTERACTION_PRE_ANSWER_YES`
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-localization): missing english localization for the send options heading of interaction test_ask_interaction
+"
+    ));
+    assert!(errors.contains(
+        "line 13 \t\tflag = bribe
+line 13 \t\t       ^^^^^
+ERROR (missing-localization): missing english localization for send option bribe
+  the game tries these keys in order: `test_ask_interaction_bribe`, `bribe`
+"
+    ));
+    // These fall back to a key that exists, or are set by their field
+    assert!(!errors.contains("for the notification of"));
+    assert!(!errors.contains("for the accept button of"));
+    assert!(!errors.contains("for the decline button of"));
+    assert!(!errors.contains("for the tooltip for a no answer of"));
+    assert!(!errors.contains("for the prompt of"));
+    assert!(!errors.contains("for send option gift"));
+    // ai_accept is evaluated with the recipient as root
+    assert!(!errors.contains("`has_trait` requires character scope"));
+}
+
+#[test]
+fn test_find_references() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/ck3");
+    let mod_root = PathBuf::from("tests/files/mod74");
+    set_vanilla_root(vanilla_root.clone());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+    let mut everything =
+        Everything::new(&vanilla_root, Some(&mod_root), Vec::new(), &[], true).unwrap();
+    everything.load_all();
+    everything
+        .references
+        .add_query(parse_find("trait:test_brave", false).unwrap());
+    everything
+        .references
+        .add_query(parse_find("trait:^test_education_", true).unwrap());
+    everything
+        .references
+        .add_query(parse_find("trait:test_coward", false).unwrap());
+    everything.validate_all();
+    let found = find_references(&everything);
+    take_log_to();
+
+    eprint!("{}", found);
+    assert_eq!(
+        found,
+        "trait:test_brave:
+  MOD common/scripted_effects/test_effects.txt:2:14 in test_effect
+  MOD common/scripted_triggers/test_triggers.txt:2:14 in test_trigger
+  MOD history/characters/test.txt:3:10 in 100001
+trait:^test_education_:
+  MOD common/scripted_effects/test_effects.txt:3:14 test_education_1 in test_effect
+  MOD common/scripted_effects/test_effects.txt:8:17 test_education_2 in test_other_effect
+trait:test_coward:
+  not referenced anywhere
+"
+    );
+}
+
+#[test]
+fn test_interaction_categories() {
+    let errors = check_mod_helper("mod75");
+
+    assert!(errors.contains(
+        "line 6 interaction_category_test = {
+line 6 ^^^^^^^^^^^^^^^^^^^^^^^^^
+ERROR (duplicate): index duplicates the index of interaction_category_friendly
+"
+    ));
+    assert!(errors.contains(
+        "line 11                                  ^
+ERROR (validation): required field `index` missing
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-localization): missing english localization key interaction_category_test
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (conflict): more than one interaction category has `default = yes`
+  --> the other one is here
+"
+    ));
+    assert!(errors.contains(
+        "line 2 \tcategory = interaction_category_hostile
+line 2 \t           ^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+WARNING (missing-item): interaction category interaction_category_hostile is only defined in a file that the mod overrides, so this interaction will not be in the character menu
+  --> the overridden definition is here
+    [CK3] file common/character_interaction_categories/00_interaction_categories.txt
+"
+    ));
+    assert!(errors.contains(
+        "ERROR (missing-item): interaction category interaction_category_freindly not defined in common/character_interaction_categories/
+  did you mean `interaction_category_friendly`?
+"
+    ));
+}
+
+#[test]
+fn test_constant_triggers() {
+    let errors = check_mod_helper("mod76");
+
+    assert!(errors.contains(
+        "line 2 \tOR = { }
+line 2 \t^^
+WARNING (logic): `OR = { }` is empty, so it is always false
+  there is no trigger in it that could succeed
+"
+    ));
+    assert!(errors.contains(
+        "line 5 \tAND = { }
+line 5 \t^^^
+WARNING (logic): `AND = { }` is empty, so it is always true
+  there is no trigger in it that could fail
+"
+    ));
+    assert!(errors.contains("WARNING (logic): `NOT = { always = yes }` is always false\n"));
+    assert!(errors.contains(
+        "WARNING (logic): `NAND` with only one trigger in it is the same as `NOT`, which may not be what was intended
+  use `NOT` if that is intended
+"
+    ));
+    assert!(errors.contains(
+        "line 16 \tNOT = {
+line 16 \t^^^
+WARNING (logic): `NOT = { NOT = { ... } }` cancels itself out
+"
+    ));
+    assert!(errors.contains(
+        "WARNING (logic): `calc_true_if` needs 3 of its triggers to be true, but it only has 2, so it is always false\n"
+    ));
+    assert!(!errors.contains("line 30 "));
+    assert!(errors.contains(
+        "line 37 \tgold > gold
+line 37 \t^^^^
+WARNING (logic): `gold` is compared to itself, so this is always false
+"
+    ));
+    assert!(errors.contains("WARNING (logic): `root` always exists, so this is always true\n"));
+    assert!(!errors.contains("line 44 "));
+    assert!(!errors.contains("line 47 "));
+}
+
+#[test]
+fn test_ignored_limit_and_trigger() {
+    let errors = check_mod_helper("mod77");
+
+    assert!(errors.contains(
+        "line 9 \t\tlimit = { is_adult = yes }
+line 9 \t\t^^^^^
+WARNING (validation): `limit` can only be used in if/else_if or lists, not directly in `immediate`
+  to make effects conditional, use `if = { limit = { ... } ... }`
+"
+    ));
+    // An option's `limit` does nothing, though its `trigger` is fine
+    assert!(errors.contains(
+        "line 14 \t\tlimit = { is_adult = yes }
+line 14 \t\t^^^^^
+WARNING (validation): `limit` can only be used in if/else_if or lists, not directly in `option`
+"
+    ));
+    assert!(!errors.contains("line 19 "));
+    assert!(errors.contains(
+        "line 21 \t\t\tlimit = { is_adult = yes }
+line 21 \t\t\t^^^^^
+WARNING (validation): `limit` can only be used in if/else_if or lists, not directly in `hidden_effect`
+"
+    ));
+    assert!(errors.contains(
+        "line 25 \t\t\ttrigger = { is_adult = yes }
+line 25 \t\t\t^^^^^^^
+WARNING (validation): `trigger` does nothing in `hidden_effect`
+  to make effects conditional, use `if = { limit = { ... } ... }`
+"
+    ));
+    assert!(!errors.contains("line 32 "));
+    assert!(errors.contains("WARNING (validation): `trigger` does nothing in `random`\n"));
+    assert!(errors.contains(
+        "WARNING (validation): `limit` can only be used in if/else_if or lists, not directly in `effect`\n"
+    ));
+    assert!(errors.contains("WARNING (validation): `trigger` does nothing in an effect block\n"));
+    assert!(!errors.contains("unknown token `trigger`"));
+}
+
+#[test]
+fn test_base_mod() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let vanilla_root = PathBuf::from("tests/files/basemod/ck3");
+    let base = ModFile::read(&PathBuf::from("tests/files/basemod/base.mod")).unwrap();
+    let modfile = ModFile::read(&PathBuf::from("tests/files/basemod/descriptor.mod")).unwrap();
+    let mod_root = modfile.modpath();
+    set_vanilla_root(vanilla_root.clone());
+    set_base_mod_root(base.modpath());
+    set_mod_root(mod_root.clone());
+    log_to(Box::new(Vec::new()));
+    let mut everything = Everything::new_with_base_mod(
+        &vanilla_root,
+        Some((base.modpath(), base.replace_paths())),
+        Some(&mod_root),
+        modfile.replace_paths(),
+        &[],
+        true,
+    )
+    .unwrap();
+    let files = dump_filelist(&everything, Some("events"));
+    let effects = dump_filelist(&everything, Some("common/scripted_effects"));
+    everything.load_all();
+    let found = where_item(&everything, "decision:my_decision").unwrap();
+    everything.validate_all();
+    let errors = (*take_log_to()).get_logs().unwrap();
+
+    eprint!("{}{}{}{}", files, effects, found, errors);
+    // The base mod's replace_path applies to vanilla only
+    assert_eq!(
+        files,
+        "BASE events/base.txt
+MOD events/submod.txt
+CK3 events/vanilla.txt (excluded by replace_path events)
+"
+    );
+    // The mod's replace_path applies to both
+    assert_eq!(
+        effects,
+        "CK3 common/scripted_effects/00_effects.txt (excluded by replace_path common/scripted_effects)
+BASE common/scripted_effects/base_effects.txt (excluded by replace_path common/scripted_effects)
+"
+    );
+    assert_eq!(
+        found,
+        "decision:my_decision:
+  CK3 common/decisions/00_decisions.txt:1:1 (file replaced by BASE file)
+  BASE common/decisions/00_decisions.txt:2:1
+  MOD common/decisions/zz_decisions.txt:2:1 (used)
+"
+    );
+    assert!(!errors.contains("event base.0001 not defined"));
+    assert!(errors.contains("event vanilla.0001 not defined"));
+    assert!(errors.contains("unknown token `base_effect`"));
+    // Reports about the base mod's own files are hidden like vanilla's
+    assert!(!errors.contains("nonsense_field"));
+    assert!(!errors.contains("base.mod"));
+    assert!(errors.contains("replace_path excludes 2 vanilla and base mod files"));
+}
+
+#[test]
+fn test_scripted_rules() {
+    let errors = check_mod_helper("mod78");
+
+    assert!(errors.contains(
+        "line 14 can_divorce = {\nline 14 ^^^^^^^^^^^\nERROR (validation): unknown scripted rule\n"
+    ));
+    // Each rule has its own named scopes
+    assert!(
+        errors.contains("`is_female` requires character scope but the scope here is landed title")
+    );
+    assert!(!errors.contains("scope:spouse"));
+    // The file overrides the game's, so the rules it leaves out are empty
+    assert!(errors.contains("WARNING (missing-item): this file replaces the game's scripted rules but leaves out can_be_betrothed, can_be_commander,"));
+    assert!(!errors.contains("leaves out can_marry"));
+    assert!(!errors.contains(", can_marry,"));
+    assert!(!errors.contains("is_valid_heir,"));
+}
+
+#[test]
+fn test_important_action_arguments() {
+    let errors = check_mod_helper("mod79");
+
+    assert!(errors.contains("line 9 \t\t\tactor = primary_title\nline 9 \t\t\t        ^^^^^^^^^^^^^\nWARNING (scopes): `actor` is landed title but the localization needs character\n  --> because it calls `GetFirstName` on it here\n"));
+    assert!(errors.contains("WARNING (scopes): `landed_title` is character but the localization needs landed title\n  --> because it calls `GetNameNoTier` on it here\n"));
+    // `liege` is a character, as `GetSheHe` needs
+    assert!(!errors.contains("`recipient`"));
+    // The action's own call passes the right types
+    assert!(!errors.contains("file common/important_actions/test_actions.txt"));
+    assert!(errors.contains("important action action_no_such_thing not defined"));
+    // The fields of important actions are declared
+    assert!(errors.contains("line 6 \tcombine_in_one = yes\nline 6 \t^^^^^^^^^^^^^^\nWARNING (validation): unknown field `combine_in_one`\n"));
+    for line in 2..6 {
+        assert!(!errors.contains(&format!("field_actions.txt\nline {line} ")));
+    }
+}
+
+#[test]
+fn test_indexed_game_items() {
+    let errors = check_helper(
+        "tests/files/index/ck3",
+        "tests/files/index/mod",
+        &[],
+        &[],
+        true,
+    );
+
+    // The game's items are only indexed, but can still be looked up and read on demand
+    assert!(!errors.contains("dna_first"));
+    assert!(!errors.contains("house_a not defined"));
+    assert!(errors.contains("WARNING (conflict): house house_b belongs to dynasty dynasty_2, not dynasty_1\n  --> the house's dynasty is set here\n    [CK3] file common/dynasty_houses/00_houses.txt\n    line 8 \tdynasty = dynasty_2\n"));
+    assert!(
+        errors.contains("ERROR (missing-item): dna dna_second not defined in common/dna_data/\n")
+    );
+    assert!(errors.contains("ERROR (missing-item): house house_c not defined in common/dynasty_houses/\n  did you mean `house_a`?\n"));
+}
+
+#[test]
+fn test_opinion_values() {
+    let errors = check_mod_helper("mod80");
+
+    assert!(!errors.contains("line 11 "));
+    assert!(errors.contains("line 17 \t\t\topinion = 1000\nline 17 \t\t\t          ^^^^\nADVICE (range): opinion value 1000 is very large\n"));
+    assert!(errors.contains("line 20 \t\t\ttarget = root.capital_province\nline 20 \t\t\t              ^^^^^^^^^^^^^^^^\nWARNING (scopes): `capital_province` produces province but expected character\n"));
+    // A miscounted calc_true_if is suspicious but not fatal
+    assert!(errors.contains("line 33 \t\t\tamount = two\nline 33 \t\t\t         ^^^\nWARNING (validation): expected integer\n"));
+}